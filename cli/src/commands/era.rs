@@ -0,0 +1,334 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::LedgerArgs;
+use snarkvm::{
+    ledger::{
+        block::Block,
+        store::{helpers::rocksdb::ConsensusDB, ConsensusStore},
+    },
+    prelude::{FromBytes, Ledger, Network, Testnet3, ToBytes},
+};
+
+use aleo_std::StorageMode;
+use anyhow::{bail, ensure, Result};
+use clap::Parser;
+use colored::Colorize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+/// The magic bytes at the start of an era file.
+const ERA_MAGIC: &[u8; 8] = b"SNARKERA";
+/// The version of the era file format.
+const ERA_VERSION: u16 = 1;
+/// The size of the checksum at the end of an era file.
+const ERA_CHECKSUM_SIZE: u64 = 32;
+
+/// Commands to export and import the era files of the chain, which contain a contiguous range of blocks,
+/// so that the history of the chain can be distributed outside of the peer-to-peer network.
+#[derive(Debug, Parser)]
+pub enum Era {
+    /// Exports a range of blocks from the ledger of a stopped node into an era file
+    Export {
+        #[clap(flatten)]
+        ledger: LedgerArgs,
+        /// Specify the height of the first block to export
+        #[clap(long = "start")]
+        start: u32,
+        /// Specify the height after the last block to export
+        #[clap(long = "end")]
+        end: u32,
+        /// Specify the path of the era file to create
+        #[clap(long = "output")]
+        output: PathBuf,
+    },
+    /// Imports the blocks of an era file into the ledger of a stopped node
+    Import {
+        #[clap(flatten)]
+        ledger: LedgerArgs,
+        /// Specify the path of the era file to import
+        #[clap(long = "input")]
+        input: PathBuf,
+    },
+}
+
+impl Era {
+    /// Exports or imports an era file.
+    pub fn parse(self) -> Result<String> {
+        match self {
+            Self::Export { ledger, start, end, output } => {
+                ensure!(start < end, "The block range {start}..{end} is empty");
+                ensure!(!output.exists(), "The era file \"{}\" already exists", output.display());
+                let storage_mode = ledger.storage_mode();
+                let ledger_dir = ledger.ledger_dir();
+                match ledger.network {
+                    Testnet3::ID => export_era::<Testnet3>(storage_mode, start, end, &output)?,
+                    _ => bail!("Unsupported network ID {}", ledger.network),
+                };
+                let path_string = format!("(from \"{}\")", ledger_dir.display()).dimmed();
+                Ok(format!("✅ Exported the blocks {start} to {} to \"{}\" {path_string}", end - 1, output.display()))
+            }
+            Self::Import { ledger, input } => {
+                let storage_mode = ledger.storage_mode();
+                let ledger_dir = ledger.ledger_dir();
+                let latest_height = match ledger.network {
+                    Testnet3::ID => import_era::<Testnet3>(storage_mode, ledger.dev.is_some(), &ledger_dir, &input)?,
+                    _ => bail!("Unsupported network ID {}", ledger.network),
+                };
+                let path_string = format!("(in \"{}\")", ledger_dir.display()).dimmed();
+                Ok(format!("✅ Imported \"{}\" up to block {latest_height} {path_string}", input.display()))
+            }
+        }
+    }
+}
+
+/// Writes the blocks in the given range of the ledger into a new era file.
+fn export_era<N: Network>(storage_mode: StorageMode, start: u32, end: u32, output: &Path) -> Result<()> {
+    let store = ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode)?;
+    let block_store = store.block_store();
+    let Some(latest_height) = block_store.max_height() else {
+        bail!("No ledger was found");
+    };
+    ensure!(end <= latest_height + 1, "The ledger ends at block {latest_height}, below block {}", end - 1);
+
+    let blocks = (start..end).map(|height| {
+        let Some(hash) = block_store.get_block_hash(height)? else {
+            bail!("Block {height} is missing from the ledger");
+        };
+        let Some(block) = block_store.get_block(&hash)? else {
+            bail!("Block {height} ('{hash}') is indexed, but its contents are missing from the ledger");
+        };
+        block.to_bytes_le()
+    });
+
+    let writer = EraWriter::new(BufWriter::new(fs::File::create(output)?));
+    if let Err(error) = write_era(writer, N::ID, start, end - start, blocks) {
+        let _ = fs::remove_file(output);
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Imports the blocks of the given era file into the ledger, and returns the height of the latest block.
+///
+/// The blocks that are already in the ledger are skipped, as long as they match the ledger, and the other blocks
+/// are checked and added in order. If there is no ledger yet, it is created from the genesis block of the network,
+/// or in development mode, from the first block of the era file.
+fn import_era<N: Network>(storage_mode: StorageMode, is_dev: bool, ledger_dir: &Path, input: &Path) -> Result<u32> {
+    let mut reader = BufReader::new(fs::File::open(input)?);
+    // Verify the checksum before reading any block.
+    verify_era_checksum(&mut reader)?;
+    reader.rewind()?;
+
+    let genesis = match ledger_dir.exists() {
+        // Load the genesis block of the ledger.
+        true => {
+            let store = ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode.clone())?;
+            let Some(hash) = store.block_store().get_block_hash(0)? else {
+                bail!("The ledger in \"{}\" has no genesis block", ledger_dir.display());
+            };
+            let Some(genesis) = store.block_store().get_block(&hash)? else {
+                bail!("The ledger in \"{}\" has no genesis block", ledger_dir.display());
+            };
+            genesis
+        }
+        // Start the ledger from the first block of the era file.
+        false if is_dev => {
+            let (start, _) = read_era_header(&mut reader, N::ID)?;
+            ensure!(start == 0, "A development ledger can only be created from an era file starting at block 0");
+            let genesis = Block::read_le(&read_era_block(&mut reader)?[..])?;
+            reader.rewind()?;
+            genesis
+        }
+        // Start the ledger from the genesis block of the network.
+        false => Block::from_bytes_le(N::genesis_bytes())?,
+    };
+    let ledger = Ledger::<N, ConsensusDB<N>>::load(genesis, storage_mode)?;
+
+    let (start, num_blocks) = read_era_header(&mut reader, N::ID)?;
+    ensure!(
+        start <= ledger.latest_height() + 1,
+        "The era file starts at block {start}, but the ledger ends at block {}",
+        ledger.latest_height()
+    );
+    let rng = &mut rand::thread_rng();
+    for height in start..start.saturating_add(num_blocks) {
+        let block = Block::<N>::read_le(&read_era_block(&mut reader)?[..])?;
+        ensure!(block.height() == height, "The era file contains block {} in place of block {height}", block.height());
+
+        if height <= ledger.latest_height() {
+            // Ensure the block matches the ledger.
+            let hash = ledger.get_hash(height)?;
+            ensure!(block.hash() == hash, "Block {height} ('{}') does not match the ledger ('{hash}')", block.hash());
+            continue;
+        }
+        ledger.check_next_block(&block, rng)?;
+        ledger.advance_to_next_block(&block)?;
+
+        if height % 10_000 == 0 {
+            println!("Imported the blocks {start} to {height}...");
+        }
+    }
+    // Ensure there is nothing after the last block.
+    let mut checksum = [0u8; ERA_CHECKSUM_SIZE as usize];
+    reader.read_exact(&mut checksum)?;
+    ensure!(reader.read(&mut [0u8; 1])? == 0, "The era file contains unexpected trailing bytes");
+
+    Ok(ledger.latest_height())
+}
+
+/// A writer that computes the checksum of the bytes written to it.
+struct EraWriter<W: Write> {
+    writer: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> EraWriter<W> {
+    /// Initializes a new era writer.
+    fn new(writer: W) -> Self {
+        Self { writer, hasher: Sha256::new() }
+    }
+
+    /// Writes the given bytes, and adds them to the checksum.
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        self.hasher.update(bytes);
+        Ok(self.writer.write_all(bytes)?)
+    }
+
+    /// Writes the checksum of the bytes written so far, and flushes the writer.
+    fn finish(mut self) -> Result<()> {
+        self.writer.write_all(&self.hasher.finalize())?;
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Writes an era file with the given blocks, which are serialized in order.
+///
+/// The era file consists of a header (the magic bytes, the format version, the network ID, the height of the
+/// first block, and the number of blocks), followed by each block prefixed with its length, and ends with
+/// the SHA-256 checksum of all the preceding bytes.
+fn write_era<W: Write>(
+    mut writer: EraWriter<W>,
+    network: u16,
+    start: u32,
+    num_blocks: u32,
+    blocks: impl Iterator<Item = Result<Vec<u8>>>,
+) -> Result<()> {
+    writer.write_all(ERA_MAGIC)?;
+    writer.write_all(&ERA_VERSION.to_le_bytes())?;
+    writer.write_all(&network.to_le_bytes())?;
+    writer.write_all(&start.to_le_bytes())?;
+    writer.write_all(&num_blocks.to_le_bytes())?;
+    let mut num_written = 0u32;
+    for block in blocks {
+        let block = block?;
+        writer.write_all(&u32::try_from(block.len())?.to_le_bytes())?;
+        writer.write_all(&block)?;
+        num_written += 1;
+    }
+    ensure!(num_written == num_blocks, "Expected {num_blocks} blocks, found {num_written} blocks");
+    writer.finish()
+}
+
+/// Ensures the checksum at the end of the given era file matches its contents.
+fn verify_era_checksum<R: Read + Seek>(reader: &mut R) -> Result<()> {
+    let size = reader.seek(io::SeekFrom::End(0))?;
+    ensure!(size >= ERA_CHECKSUM_SIZE, "The era file is truncated");
+    reader.rewind()?;
+
+    let mut hasher = Sha256::new();
+    let mut contents = reader.take(size - ERA_CHECKSUM_SIZE);
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let num_bytes = contents.read(&mut buffer)?;
+        if num_bytes == 0 {
+            break;
+        }
+        hasher.update(&buffer[..num_bytes]);
+    }
+    let mut checksum = [0u8; ERA_CHECKSUM_SIZE as usize];
+    reader.read_exact(&mut checksum)?;
+    ensure!(hasher.finalize()[..] == checksum[..], "The checksum of the era file does not match its contents");
+    Ok(())
+}
+
+/// Reads the header of an era file for the given network, and returns the height of its first block
+/// and its number of blocks.
+fn read_era_header<R: Read>(reader: &mut R, network: u16) -> Result<(u32, u32)> {
+    let magic: [u8; 8] = read_array(reader)?;
+    ensure!(&magic == ERA_MAGIC, "The file is not an era file");
+    let version = u16::from_le_bytes(read_array(reader)?);
+    ensure!(version == ERA_VERSION, "The era file format version {version} is not supported");
+    let era_network = u16::from_le_bytes(read_array(reader)?);
+    ensure!(era_network == network, "The era file is for network {era_network}, not network {network}");
+    let start = u32::from_le_bytes(read_array(reader)?);
+    let num_blocks = u32::from_le_bytes(read_array(reader)?);
+    Ok((start, num_blocks))
+}
+
+/// Reads the next serialized block of an era file.
+fn read_era_block<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let size = u32::from_le_bytes(read_array(reader)?) as u64;
+    let mut block = Vec::new();
+    let num_bytes = reader.take(size).read_to_end(&mut block)?;
+    ensure!(num_bytes as u64 == size, "The era file is truncated");
+    Ok(block)
+}
+
+/// Reads an array of bytes from the given reader.
+fn read_array<R: Read, const SIZE: usize>(reader: &mut R) -> Result<[u8; SIZE]> {
+    let mut bytes = [0u8; SIZE];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_era_roundtrip() {
+        let blocks = vec![vec![1u8; 10], vec![], vec![3u8; 1000]];
+        let mut bytes = Vec::new();
+        write_era(EraWriter::new(&mut bytes), 3, 7, 3, blocks.clone().into_iter().map(Ok)).unwrap();
+
+        let mut reader = io::Cursor::new(&bytes);
+        verify_era_checksum(&mut reader).unwrap();
+        reader.rewind().unwrap();
+        assert!(read_era_header(&mut reader, 4).is_err());
+        reader.rewind().unwrap();
+        assert_eq!(read_era_header(&mut reader, 3).unwrap(), (7, 3));
+        for block in &blocks {
+            assert_eq!(&read_era_block(&mut reader).unwrap(), block);
+        }
+
+        // A corrupted era file is rejected.
+        let mut corrupted = bytes.clone();
+        corrupted[30] ^= 1;
+        assert!(verify_era_checksum(&mut io::Cursor::new(&corrupted)).is_err());
+        // A truncated era file is rejected.
+        assert!(verify_era_checksum(&mut io::Cursor::new(&bytes[..bytes.len() - 1])).is_err());
+        assert!(verify_era_checksum(&mut io::Cursor::new(&bytes[..10])).is_err());
+    }
+
+    #[test]
+    fn test_era_block_count_mismatch() {
+        let blocks = vec![Ok(vec![1u8; 10])];
+        assert!(write_era(EraWriter::new(Vec::new()), 3, 0, 2, blocks.into_iter()).is_err());
+    }
+}
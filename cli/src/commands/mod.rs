@@ -21,15 +21,26 @@ pub use clean::*;
 mod developer;
 pub use developer::*;
 
+mod era;
+pub use era::*;
+
+mod snapshot;
+pub use snapshot::*;
+
 mod start;
 pub use start::*;
 
+mod storage;
+pub use storage::*;
+
 mod update;
 pub use update::*;
 
+use aleo_std::StorageMode;
 use anstyle::{AnsiColor, Color, Style};
 use anyhow::Result;
-use clap::{builder::Styles, Parser};
+use clap::{builder::Styles, Args, Parser};
+use std::path::PathBuf;
 
 const HEADER_COLOR: Option<Color> = Some(Color::Ansi(AnsiColor::Yellow));
 const LITERAL_COLOR: Option<Color> = Some(Color::Ansi(AnsiColor::Green));
@@ -57,12 +68,47 @@ pub enum Command {
     Clean(Clean),
     #[clap(subcommand)]
     Developer(Developer),
+    #[clap(subcommand)]
+    Era(Era),
+    #[clap(subcommand)]
+    Snapshot(Snapshot),
     #[clap(name = "start")]
     Start(Box<Start>),
+    #[clap(subcommand)]
+    Storage(Storage),
     #[clap(name = "update")]
     Update(Update),
 }
 
+/// The arguments which locate the ledger of a node in storage.
+#[derive(Debug, Args)]
+pub struct LedgerArgs {
+    /// Specify the network of the ledger
+    #[clap(default_value = "3", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path to a directory containing the ledger
+    #[clap(long = "path")]
+    pub path: Option<PathBuf>,
+}
+
+impl LedgerArgs {
+    /// Returns the storage mode of the ledger.
+    pub fn storage_mode(&self) -> StorageMode {
+        match &self.path {
+            Some(path) => StorageMode::Custom(path.clone()),
+            None => StorageMode::from(self.dev),
+        }
+    }
+
+    /// Returns the directory of the ledger.
+    pub fn ledger_dir(&self) -> PathBuf {
+        aleo_std::aleo_ledger_dir(self.network, self.storage_mode())
+    }
+}
+
 impl Command {
     /// Parses the command.
     pub fn parse(self) -> Result<String> {
@@ -70,7 +116,10 @@ impl Command {
             Self::Account(command) => command.parse(),
             Self::Clean(command) => command.parse(),
             Self::Developer(command) => command.parse(),
+            Self::Era(command) => command.parse(),
+            Self::Snapshot(command) => command.parse(),
             Self::Start(command) => command.parse(),
+            Self::Storage(command) => command.parse(),
             Self::Update(command) => command.parse(),
         }
     }
@@ -0,0 +1,406 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::LedgerArgs;
+use snarkvm::{
+    ledger::{
+        block::Block,
+        store::{helpers::rocksdb::ConsensusDB, ConsensusStore},
+    },
+    prelude::{FromBytes, Ledger, Network, Testnet3, ToBytes},
+};
+
+use aleo_std::StorageMode;
+use anyhow::{bail, ensure, Result};
+use clap::Parser;
+use colored::Colorize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The magic bytes at the start of a snapshot file.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"SNARKSNP";
+/// The version of the snapshot format.
+const SNAPSHOT_VERSION: u16 = 2;
+/// The size of the checksum at the end of a snapshot file.
+const SNAPSHOT_CHECKSUM_SIZE: usize = 32;
+
+/// Commands to export and import the snapshots of the ledger, which bootstrap a new node
+/// from the ledger of another node, before it syncs the subsequent blocks from its peers.
+#[derive(Debug, Parser)]
+pub enum Snapshot {
+    /// Exports the ledger of a stopped node into a snapshot file
+    Export {
+        #[clap(flatten)]
+        ledger: LedgerArgs,
+        /// Specify the path of the snapshot file to create
+        #[clap(long = "output")]
+        output: PathBuf,
+    },
+    /// Imports a snapshot file as the ledger of a new node
+    Import {
+        #[clap(flatten)]
+        ledger: LedgerArgs,
+        /// Specify the path of the snapshot file to import
+        #[clap(long = "input")]
+        input: PathBuf,
+    },
+}
+
+impl Snapshot {
+    /// Exports or imports the snapshot of the ledger.
+    pub fn parse(self) -> Result<String> {
+        match self {
+            Self::Export { ledger, output } => {
+                let storage_mode = ledger.storage_mode();
+                let ledger_dir = ledger.ledger_dir();
+                ensure!(ledger_dir.is_dir(), "No ledger was found in \"{}\"", ledger_dir.display());
+                ensure!(!output.exists(), "The snapshot file \"{}\" already exists", output.display());
+                // Record the latest block height and state root of the ledger in the snapshot.
+                let header = match ledger.network {
+                    Testnet3::ID => snapshot_header::<Testnet3>(storage_mode)?,
+                    _ => bail!("Unsupported network ID {}", ledger.network),
+                };
+                let num_files = export_snapshot(&ledger_dir, &header, &output)?;
+                let path_string = format!("(from \"{}\")", ledger_dir.display()).dimmed();
+                Ok(format!(
+                    "✅ Exported {num_files} files to the snapshot \"{}\" at block {} {path_string}",
+                    output.display(),
+                    header.height
+                ))
+            }
+            Self::Import { ledger, input } => {
+                let storage_mode = ledger.storage_mode();
+                let ledger_dir = ledger.ledger_dir();
+                let verify_snapshot = match ledger.network {
+                    Testnet3::ID => verify_snapshot::<Testnet3>,
+                    _ => bail!("Unsupported network ID {}", ledger.network),
+                };
+                let header = import_snapshot(&input, ledger.network, &ledger_dir)?;
+                // Ensure the imported ledger matches the latest block height and state root of the snapshot.
+                if let Err(error) = verify_snapshot(storage_mode, &header) {
+                    let _ = fs::remove_dir_all(&ledger_dir);
+                    bail!("The imported ledger does not match the snapshot - {error}");
+                }
+                let path_string = format!("(in \"{}\")", ledger_dir.display()).dimmed();
+                Ok(format!(
+                    "✅ Imported the snapshot \"{}\" at block {} {path_string}\n\nStart the node to sync the \
+                     subsequent blocks from its peers.",
+                    input.display(),
+                    header.height
+                ))
+            }
+        }
+    }
+}
+
+/// The header of a snapshot, which identifies the state of the ledger it contains.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SnapshotHeader {
+    /// The network ID of the ledger.
+    network: u16,
+    /// The height of the latest block of the ledger.
+    height: u32,
+    /// The latest state root of the ledger.
+    state_root: String,
+}
+
+/// Loads the ledger in the given storage, from the genesis block it contains.
+fn load_ledger<N: Network>(storage_mode: StorageMode) -> Result<Ledger<N, ConsensusDB<N>>> {
+    let store = ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode.clone())?;
+    let Some(hash) = store.block_store().get_block_hash(0)? else {
+        bail!("The ledger has no genesis block");
+    };
+    let Some(genesis) = store.block_store().get_block(&hash)? else {
+        bail!("The ledger has no genesis block");
+    };
+    Ledger::<N, ConsensusDB<N>>::load(genesis, storage_mode)
+}
+
+/// Returns the header of a snapshot of the ledger in the given storage.
+fn snapshot_header<N: Network>(storage_mode: StorageMode) -> Result<SnapshotHeader> {
+    let ledger = load_ledger::<N>(storage_mode)?;
+    Ok(SnapshotHeader {
+        network: N::ID,
+        height: ledger.latest_height(),
+        state_root: ledger.latest_state_root().to_string(),
+    })
+}
+
+/// Ensures the ledger in the given storage matches the header of the snapshot it was imported from, i.e. its latest
+/// block is intact and at the height of the snapshot, and its state root is the state root of the snapshot.
+fn verify_snapshot<N: Network>(storage_mode: StorageMode, header: &SnapshotHeader) -> Result<()> {
+    let ledger = load_ledger::<N>(storage_mode)?;
+    let latest_block = ledger.latest_block();
+    let (height, hash) = (latest_block.height(), latest_block.hash());
+    ensure!(height == header.height, "The ledger ends at block {height}, instead of block {}", header.height);
+    // Recompute the hash of the latest block, which is verified as the block is deserialized.
+    if let Err(error) = Block::<N>::read_le(&latest_block.to_bytes_le()?[..]) {
+        bail!("Block {height} ('{hash}') does not match its hash - {error}");
+    }
+    ensure!(hash == ledger.get_hash(height)?, "Block {height} ('{hash}') is not stored under its hash");
+    let state_root = ledger.latest_state_root().to_string();
+    ensure!(state_root == header.state_root, "The state root is '{state_root}', instead of '{}'", header.state_root);
+    Ok(())
+}
+
+/// A reader or writer that computes the checksum of the bytes read from it or written to it.
+struct Checksummed<T> {
+    inner: T,
+    hasher: Sha256,
+}
+
+impl<T> Checksummed<T> {
+    /// Initializes a new checksummed reader or writer.
+    fn new(inner: T) -> Self {
+        Self { inner, hasher: Sha256::new() }
+    }
+
+    /// Returns the inner reader or writer, along with the checksum of the bytes so far.
+    fn finish(self) -> (T, [u8; SNAPSHOT_CHECKSUM_SIZE]) {
+        (self.inner, self.hasher.finalize().into())
+    }
+}
+
+impl<R: Read> Read for Checksummed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let num_bytes = self.inner.read(buf)?;
+        self.hasher.update(&buf[..num_bytes]);
+        Ok(num_bytes)
+    }
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.inner.write(buf)?;
+        self.hasher.update(&buf[..num_bytes]);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes the files of the given ledger directory into a new snapshot file, and returns the number of files.
+///
+/// The snapshot consists of a header (the magic bytes, the format version, the network ID, the latest block height,
+/// the latest state root, and the number of files), followed by each file as its relative path and its contents,
+/// both prefixed with their length, and ends with the SHA-256 checksum of all the preceding bytes.
+/// The node must be stopped, so that the ledger does not change during the export.
+fn export_snapshot(ledger_dir: &Path, header: &SnapshotHeader, output: &Path) -> Result<u64> {
+    ensure!(ledger_dir.is_dir(), "No ledger was found in \"{}\"", ledger_dir.display());
+    ensure!(!output.exists(), "The snapshot file \"{}\" already exists", output.display());
+
+    // Collect the files of the ledger, in a deterministic order.
+    let mut files = Vec::new();
+    collect_files(ledger_dir, "", &mut files)?;
+    files.sort();
+
+    let result = write_snapshot(ledger_dir, header, &files, output);
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    result.map(|_| files.len() as u64)
+}
+
+/// Writes the snapshot of the given files of the ledger directory into a new snapshot file.
+fn write_snapshot(ledger_dir: &Path, header: &SnapshotHeader, files: &[String], output: &Path) -> Result<()> {
+    let mut writer = Checksummed::new(BufWriter::new(fs::File::create(output)?));
+    writer.write_all(SNAPSHOT_MAGIC)?;
+    writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+    writer.write_all(&header.network.to_le_bytes())?;
+    writer.write_all(&header.height.to_le_bytes())?;
+    writer.write_all(&(header.state_root.len() as u16).to_le_bytes())?;
+    writer.write_all(header.state_root.as_bytes())?;
+    writer.write_all(&(files.len() as u64).to_le_bytes())?;
+    for name in files {
+        let mut file = fs::File::open(ledger_dir.join(name))?;
+        let size = file.metadata()?.len();
+        writer.write_all(&(name.len() as u16).to_le_bytes())?;
+        writer.write_all(name.as_bytes())?;
+        writer.write_all(&size.to_le_bytes())?;
+        let num_bytes = io::copy(&mut (&mut file).take(size), &mut writer)?;
+        ensure!(num_bytes == size, "The file \"{name}\" changed during the export (is the node stopped?)");
+    }
+    let (mut writer, checksum) = writer.finish();
+    writer.write_all(&checksum)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Collects the relative paths of the files in the given directory, recursively.
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(file_name) = entry.file_name().to_str().map(|file_name| format!("{prefix}{file_name}")) else {
+            bail!("The ledger contains a file with an invalid name in \"{}\"", dir.display());
+        };
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &format!("{file_name}/"), files)?;
+        } else if file_type.is_file() {
+            files.push(file_name);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the given snapshot file into a new ledger directory, and returns the header of the snapshot.
+/// The files are staged in a temporary directory, so that a failed import (e.g. a snapshot which does not match
+/// its checksum) does not leave a partial ledger behind.
+fn import_snapshot(input: &Path, network: u16, ledger_dir: &Path) -> Result<SnapshotHeader> {
+    ensure!(!ledger_dir.exists(), "A ledger already exists in \"{}\" (clean it first)", ledger_dir.display());
+
+    let mut reader = Checksummed::new(BufReader::new(fs::File::open(input)?));
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    ensure!(&magic == SNAPSHOT_MAGIC, "The file \"{}\" is not a snapshot", input.display());
+    let version = u16::from_le_bytes(read_array(&mut reader)?);
+    ensure!(version == SNAPSHOT_VERSION, "The snapshot format version {version} is not supported");
+    let snapshot_network = u16::from_le_bytes(read_array(&mut reader)?);
+    ensure!(snapshot_network == network, "The snapshot is for network {snapshot_network}, not network {network}");
+    let height = u32::from_le_bytes(read_array(&mut reader)?);
+    let state_root_len = u16::from_le_bytes(read_array(&mut reader)?) as usize;
+    let mut state_root = vec![0u8; state_root_len];
+    reader.read_exact(&mut state_root)?;
+    let state_root = String::from_utf8(state_root)?;
+    let num_files = u64::from_le_bytes(read_array(&mut reader)?);
+
+    let mut staging_name = ledger_dir.file_name().unwrap_or_default().to_os_string();
+    staging_name.push(".import");
+    let staging_dir = ledger_dir.with_file_name(staging_name);
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    if let Err(error) = read_files(&mut reader, num_files, &staging_dir).and_then(|_| verify_checksum(reader)) {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(error);
+    }
+    fs::rename(&staging_dir, ledger_dir)?;
+
+    Ok(SnapshotHeader { network, height, state_root })
+}
+
+/// Ensures the checksum at the end of the snapshot matches the bytes read so far, and is the end of the snapshot.
+fn verify_checksum<R: Read>(reader: Checksummed<R>) -> Result<()> {
+    let (mut reader, expected_checksum) = reader.finish();
+    let checksum: [u8; SNAPSHOT_CHECKSUM_SIZE] = read_array(&mut reader)?;
+    ensure!(checksum == expected_checksum, "The checksum of the snapshot does not match its contents");
+    // Ensure there is nothing after the checksum.
+    ensure!(reader.read(&mut [0u8; 1])? == 0, "The snapshot contains unexpected trailing bytes");
+    Ok(())
+}
+
+/// Reads the given number of files from the snapshot into the given directory.
+fn read_files<R: Read>(reader: &mut R, num_files: u64, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    for _ in 0..num_files {
+        let name_len = u16::from_le_bytes(read_array(reader)?) as usize;
+        let mut name = vec![0u8; name_len];
+        reader.read_exact(&mut name)?;
+        let name = String::from_utf8(name)?;
+        // Ensure the file stays within the ledger directory.
+        if name.split('/').any(|component| matches!(component, "" | "." | "..") || component.contains('\\')) {
+            bail!("The snapshot contains an invalid file path \"{name}\"");
+        }
+        let size = u64::from_le_bytes(read_array(reader)?);
+
+        let path = dir.join(&name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(&path)?;
+        let num_bytes = io::copy(&mut reader.take(size), &mut file)?;
+        ensure!(num_bytes == size, "The snapshot is truncated (in \"{name}\")");
+    }
+    Ok(())
+}
+
+/// Reads an array of bytes from the given reader.
+fn read_array<R: Read, const SIZE: usize>(reader: &mut R) -> Result<[u8; SIZE]> {
+    let mut bytes = [0u8; SIZE];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let directory = std::env::temp_dir().join(format!("snarkos-snapshot-{}", std::process::id()));
+        let ledger_dir = directory.join("ledger");
+        let snapshot = directory.join("ledger.snapshot");
+        let new_ledger_dir = directory.join("new-ledger");
+        let _ = fs::remove_dir_all(&directory);
+
+        // Prepare a ledger with nested files.
+        fs::create_dir_all(ledger_dir.join("nested")).unwrap();
+        fs::write(ledger_dir.join("CURRENT"), b"MANIFEST-000001").unwrap();
+        fs::write(ledger_dir.join("nested").join("000001.sst"), vec![7u8; 10_000]).unwrap();
+        fs::write(ledger_dir.join("LOCK"), b"").unwrap();
+
+        // Export the ledger, and import it as a new ledger.
+        let header = SnapshotHeader { network: 3, height: 10, state_root: "sr1state".to_string() };
+        assert_eq!(export_snapshot(&ledger_dir, &header, &snapshot).unwrap(), 3);
+        assert!(export_snapshot(&ledger_dir, &header, &snapshot).is_err());
+        assert!(import_snapshot(&snapshot, 4, &new_ledger_dir).is_err());
+        assert_eq!(import_snapshot(&snapshot, 3, &new_ledger_dir).unwrap(), header);
+        assert_eq!(fs::read(new_ledger_dir.join("CURRENT")).unwrap(), b"MANIFEST-000001");
+        assert_eq!(fs::read(new_ledger_dir.join("nested").join("000001.sst")).unwrap(), vec![7u8; 10_000]);
+        assert!(fs::read(new_ledger_dir.join("LOCK")).unwrap().is_empty());
+        // An existing ledger is not overwritten.
+        assert!(import_snapshot(&snapshot, 3, &new_ledger_dir).is_err());
+
+        // A truncated snapshot is rejected, without leaving a partial ledger behind.
+        let truncated = directory.join("truncated.snapshot");
+        let bytes = fs::read(&snapshot).unwrap();
+        fs::write(&truncated, &bytes[..bytes.len() - 1]).unwrap();
+        let truncated_ledger_dir = directory.join("truncated-ledger");
+        assert!(import_snapshot(&truncated, 3, &truncated_ledger_dir).is_err());
+        assert!(!truncated_ledger_dir.exists());
+
+        // A corrupted snapshot is rejected by its checksum, without leaving a partial ledger behind.
+        let corrupted = directory.join("corrupted.snapshot");
+        let mut bytes = fs::read(&snapshot).unwrap();
+        let index = bytes.len() - SNAPSHOT_CHECKSUM_SIZE - 1;
+        bytes[index] ^= 1;
+        fs::write(&corrupted, &bytes).unwrap();
+        let corrupted_ledger_dir = directory.join("corrupted-ledger");
+        let error = import_snapshot(&corrupted, 3, &corrupted_ledger_dir).unwrap_err();
+        assert!(error.to_string().contains("checksum"));
+        assert!(!corrupted_ledger_dir.exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_rejects_invalid_paths() {
+        for name in ["../escape", "/absolute", "nested//file", "nested\\..\\file"] {
+            let mut bytes = Vec::new();
+            bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(&0u64.to_le_bytes());
+
+            let directory = std::env::temp_dir().join(format!("snarkos-snapshot-paths-{}", std::process::id()));
+            assert!(read_files(&mut &bytes[..], 1, &directory).is_err());
+            let _ = fs::remove_dir_all(&directory);
+        }
+    }
+}
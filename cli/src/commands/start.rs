@@ -14,7 +14,21 @@
 
 use snarkos_account::Account;
 use snarkos_display::Display;
-use snarkos_node::{bft::MEMORY_POOL_PORT, router::messages::NodeType, Node};
+use snarkos_node::{
+    bft::MEMORY_POOL_PORT,
+    router::{
+        messages::NodeType,
+        ConnectionPolicy,
+        LivenessPolicy,
+        RelayPolicy,
+        RouterConfig,
+        TlsConfig,
+        ValidatorListener,
+    },
+    tcp::ProxyConfig,
+    Node,
+    NodeConfig,
+};
 use snarkvm::{
     console::{
         account::{Address, PrivateKey},
@@ -67,6 +81,9 @@ pub struct Start {
     /// Specify this node as a client
     #[clap(long = "client")]
     pub client: bool,
+    /// Specify this node as a relay, which validates and relays the gossip of the network without a ledger
+    #[clap(long = "relay")]
+    pub relay: bool,
 
     /// Specify the account private key of the node
     #[clap(long = "private-key")]
@@ -75,18 +92,95 @@ pub struct Start {
     #[clap(long = "private-key-file")]
     pub private_key_file: Option<PathBuf>,
 
-    /// Specify the IP address and port for the node server
+    /// Specify the IP address and port for the node server (use '[::]' to listen on both IPv4 and IPv6)
     #[clap(default_value = "0.0.0.0:4133", long = "node")]
     pub node: SocketAddr,
     /// Specify the IP address and port for the BFT
     #[clap(long = "bft")]
     pub bft: Option<SocketAddr>,
-    /// Specify the IP address and port of the peer(s) to connect to
+    /// Specify the IP address and port of the trusted peer(s) to connect to, which are always kept connected
     #[clap(default_value = "", long = "peers")]
     pub peers: String,
     /// Specify the IP address and port of the validator(s) to connect to
     #[clap(default_value = "", long = "validators")]
     pub validators: String,
+    /// If the flag is set, the node will negotiate Noise-encrypted connections with peers that support it
+    #[clap(long)]
+    pub noise: bool,
+    /// If the flag is set, the node will not compress large messages for peers that support it
+    #[clap(long)]
+    pub nocompression: bool,
+    /// Specify the maximum number of inbound, and of outbound, peer connections per /24 (IPv4) or /48 (IPv6) subnet
+    #[clap(default_value = "8", long = "max-peers-per-subnet")]
+    pub max_peers_per_subnet: u16,
+    /// Specify the number of outbound peer connections to maintain, separately from the inbound peer connections
+    #[clap(long = "target-outbound-peers")]
+    pub target_outbound_peers: Option<u16>,
+    /// Specify the maximum number of inbound peer connections, separately from the outbound peer connections
+    #[clap(long = "max-inbound-peers")]
+    pub max_inbound_peers: Option<u16>,
+    /// Specify the maximum bandwidth in KiB/s received from all peers combined, and likewise sent
+    #[clap(long = "max-bandwidth")]
+    pub max_bandwidth: Option<u32>,
+    /// Specify the maximum bandwidth in KiB/s received from each peer, and likewise sent
+    #[clap(long = "max-peer-bandwidth")]
+    pub max_peer_bandwidth: Option<u32>,
+    /// Specify the minimum fee in microcredits of the unconfirmed transactions accepted from peers
+    #[clap(default_value = "0", long = "relay-min-fee")]
+    pub relay_min_fee: u64,
+    /// Specify the maximum size in bytes of the unconfirmed transactions accepted from peers
+    #[clap(default_value = "131072", long = "relay-max-tx-size")]
+    pub relay_max_tx_size: usize,
+    /// Specify the maximum number of unconfirmed transactions accepted from each peer per minute
+    #[clap(default_value = "1000", long = "relay-max-tx-rate")]
+    pub relay_max_tx_rate: usize,
+    /// Specify the duration in seconds between a pong from a peer and the next ping sent to it
+    #[clap(default_value = "20", long = "ping-interval")]
+    pub ping_interval: u64,
+    /// Specify the duration in seconds after which a peer that did not answer a ping is disconnected
+    #[clap(default_value = "60", long = "pong-timeout")]
+    pub pong_timeout: u64,
+    /// Specify the duration in seconds after which a peer that did not send any message is disconnected
+    #[clap(default_value = "150", long = "idle-timeout")]
+    pub idle_timeout: u64,
+    /// Specify the IP address and port to listen on for the committee members, separately from the public peers
+    #[clap(long = "validator-listener")]
+    pub validator_listener: Option<SocketAddr>,
+    /// Specify the maximum number of validator peers connected through the validator listener
+    #[clap(default_value = "100", long = "max-validator-peers")]
+    pub max_validator_peers: u16,
+    /// If the flag is set, the node will only connect to peers, and will not listen for inbound connections
+    #[clap(long)]
+    pub nolisten: bool,
+    /// If the flag is set, the node will forward its listener port on the gateway using UPnP or NAT-PMP
+    #[clap(long)]
+    pub upnp: bool,
+    /// Specify the public IP address(es) and port(s) to advertise to peers, e.g. the IPv4 and IPv6 addresses of a node
+    #[clap(default_value = "", long = "public-addrs")]
+    pub public_addrs: String,
+    /// Specify the hostname(s) of the DNS seed(s) to discover peers from, with an optional port (default: 4133)
+    #[clap(default_value = "", long = "dns-seeds")]
+    pub dns_seeds: String,
+    /// Specify the path to the PEM-encoded certificate chain, to terminate node connections over TLS
+    #[clap(long = "tls-cert")]
+    pub tls_cert: Option<PathBuf>,
+    /// Specify the path to the PEM-encoded (PKCS #8) private key of the TLS certificate
+    #[clap(long = "tls-key")]
+    pub tls_key: Option<PathBuf>,
+    /// Specify the path to the PEM-encoded certificate authority used to verify the TLS certificates of peers
+    #[clap(long = "tls-ca")]
+    pub tls_ca: Option<PathBuf>,
+
+    /// Specify the IP address and port of the SOCKS5 proxy to establish the outbound node connections through
+    /// (the DNS seeds are not resolved while a proxy is used, as the lookups would bypass the proxy)
+    #[clap(long = "socks5-proxy")]
+    pub socks5_proxy: Option<SocketAddr>,
+    /// Specify the username to authenticate with the SOCKS5 proxy
+    #[clap(long = "socks5-username")]
+    pub socks5_username: Option<String>,
+    /// Specify the password to authenticate with the SOCKS5 proxy
+    #[clap(long = "socks5-password")]
+    pub socks5_password: Option<String>,
 
     /// Specify the IP address and port for the REST server
     #[clap(default_value = "0.0.0.0:3033", long = "rest")]
@@ -197,17 +291,131 @@ impl Start {
         }
     }
 
+    /// Returns the hostname(s) of the DNS seed(s), from the given configurations.
+    fn parse_dns_seeds(&self) -> Vec<String> {
+        self.dns_seeds.split(',').map(str::trim).filter(|seed| !seed.is_empty()).map(str::to_string).collect()
+    }
+
+    /// Returns the TLS configuration of the node connections, from the given configurations.
+    fn parse_tls(&self) -> Result<Option<TlsConfig>> {
+        match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => Ok(Some(TlsConfig::load(cert, key, self.tls_ca.as_deref())?)),
+            (None, None) => match self.tls_ca {
+                Some(_) => bail!("The '--tls-ca' flag requires the '--tls-cert' and '--tls-key' flags"),
+                None => Ok(None),
+            },
+            // Ensure the certificate and the private key are provided together.
+            _ => bail!("The '--tls-cert' and '--tls-key' flags must be used together"),
+        }
+    }
+
+    /// Returns the SOCKS5 proxy configuration of the outbound node connections, from the given configurations.
+    fn parse_proxy(&self) -> Result<Option<ProxyConfig>> {
+        let credentials = match (&self.socks5_username, &self.socks5_password) {
+            (Some(username), Some(password)) => Some((username.clone(), password.clone())),
+            (None, None) => None,
+            // Ensure the username and the password are provided together.
+            _ => bail!("The '--socks5-username' and '--socks5-password' flags must be used together"),
+        };
+        // Ensure the port mapping does not reveal the IP of the node to its gateway while a proxy is used.
+        if self.socks5_proxy.is_some() && self.upnp {
+            bail!("The '--socks5-proxy' flag cannot be used with the '--upnp' flag");
+        }
+        match (self.socks5_proxy, credentials) {
+            (Some(addr), credentials) => Ok(Some(ProxyConfig::new(addr, credentials))),
+            (None, None) => Ok(None),
+            (None, Some(_)) => bail!("The SOCKS5 credentials require the '--socks5-proxy' flag"),
+        }
+    }
+
+    /// Returns the policy for accepting and relaying the unconfirmed transactions of peers, from the given configurations.
+    fn parse_relay_policy(&self) -> RelayPolicy {
+        RelayPolicy {
+            min_fee: self.relay_min_fee,
+            max_transaction_size: self.relay_max_tx_size,
+            max_transactions_per_peer: self.relay_max_tx_rate,
+        }
+    }
+
+    /// Returns the policy for keeping the connections to peers alive, from the given configurations.
+    fn parse_liveness_policy(&self) -> Result<LivenessPolicy> {
+        let liveness_policy = LivenessPolicy {
+            ping_interval_in_secs: self.ping_interval,
+            pong_timeout_in_secs: self.pong_timeout,
+            idle_timeout_in_secs: self.idle_timeout,
+        };
+        if let Err(error) = liveness_policy.ensure_is_valid() {
+            bail!("Invalid liveness policy - {error}")
+        }
+        Ok(liveness_policy)
+    }
+
+    /// Returns the policy splitting the connections between outbound and inbound peers, if either limit is specified.
+    /// Otherwise, the node keeps a single limit for all of its public peers.
+    fn parse_connection_policy(&self) -> Result<Option<ConnectionPolicy>> {
+        if self.target_outbound_peers.is_none() && self.max_inbound_peers.is_none() {
+            return Ok(None);
+        }
+        let default = ConnectionPolicy::default();
+        let connection_policy = ConnectionPolicy {
+            target_outbound_peers: self.target_outbound_peers.unwrap_or(default.target_outbound_peers),
+            max_inbound_peers: self.max_inbound_peers.unwrap_or(default.max_inbound_peers),
+        };
+        if let Err(error) = connection_policy.ensure_is_valid() {
+            bail!("Invalid connection policy - {error}")
+        }
+        Ok(Some(connection_policy))
+    }
+
+    /// Returns the listener dedicated to the validator peers, from the given configurations.
+    fn parse_validator_listener(&self) -> Result<Option<ValidatorListener>> {
+        let Some(addr) = self.validator_listener else {
+            return Ok(None);
+        };
+        if !self.validator {
+            bail!("The validator listener is only available to validators")
+        }
+        let validator_listener =
+            ValidatorListener { max_peers: self.max_validator_peers, ..ValidatorListener::new(addr) };
+        if let Err(error) = validator_listener.ensure_is_valid() {
+            bail!("Invalid validator listener - {error}")
+        }
+        Ok(Some(validator_listener))
+    }
+
+    /// Returns the public addresses of the node to advertise to peers, from the given configurations.
+    fn parse_public_addrs(&self) -> Result<Vec<SocketAddr>> {
+        if self.public_addrs.is_empty() {
+            return Ok(vec![]);
+        }
+        if self.nolisten {
+            bail!("The '--nolisten' flag cannot be used with the '--public-addrs' flag");
+        }
+        let mut public_addrs = Vec::new();
+        for addr in self.public_addrs.split(',') {
+            let public_addr = match addr.parse::<SocketAddr>() {
+                Ok(public_addr) => public_addr,
+                Err(e) => bail!("The IP supplied to --public-addrs ('{addr}') is malformed: {e}"),
+            };
+            if public_addr.ip().is_unspecified() || public_addr.port() == 0 {
+                bail!("The IP supplied to --public-addrs ('{addr}') must have a specified IP address and port");
+            }
+            public_addrs.push(public_addr);
+        }
+        Ok(public_addrs)
+    }
+
     /// Returns the CDN to prefetch initial blocks from, from the given configurations.
     fn parse_cdn(&self) -> Option<String> {
         // Determine if the node type is not declared.
-        let is_no_node_type = !(self.validator || self.prover || self.client);
+        let is_no_node_type = !(self.validator || self.prover || self.client || self.relay);
 
         // Disable CDN if:
         //  1. The node is in development mode.
         //  2. The user has explicitly disabled CDN.
-        //  3. The node is a prover (no need to sync).
+        //  3. The node is a prover or a relay (no need to sync).
         //  4. The node type is not declared (defaults to client) (no need to sync).
-        if self.dev.is_some() || self.cdn.is_empty() || self.nocdn || self.prover || is_no_node_type {
+        if self.dev.is_some() || self.cdn.is_empty() || self.nocdn || self.prover || self.relay || is_no_node_type {
             None
         }
         // Enable the CDN otherwise.
@@ -228,8 +436,8 @@ impl Start {
                     check_permissions(path)?;
                     Account::from_str(std::fs::read_to_string(path)?.trim())
                 }
-                // Ensure the private key is provided to the CLI, except for clients or nodes in development mode.
-                (None, None) => match self.client {
+                // Ensure the private key is provided to the CLI, except for clients, relays, or development nodes.
+                (None, None) => match self.client || self.relay {
                     true => Account::new(&mut rand::thread_rng()),
                     false => bail!("Missing the '--private-key' or '--private-key-file' argument"),
                 },
@@ -392,8 +600,27 @@ impl Start {
         // Parse the development configurations.
         self.parse_development(&mut trusted_peers, &mut trusted_validators)?;
 
+        // Parse the DNS seeds.
+        let dns_seeds = self.parse_dns_seeds();
+        // Parse the bandwidth limits, in bytes per second.
+        let max_bandwidth = self.max_bandwidth.map(|kib| kib.saturating_mul(1024));
+        let max_peer_bandwidth = self.max_peer_bandwidth.map(|kib| kib.saturating_mul(1024));
+        // Parse the relay policy.
+        let relay_policy = self.parse_relay_policy();
+        // Parse the liveness policy.
+        let liveness_policy = self.parse_liveness_policy()?;
+        // Parse the connection policy.
+        let connection_policy = self.parse_connection_policy()?;
+        // Parse the validator listener.
+        let validator_listener = self.parse_validator_listener()?;
+        // Parse the public addresses.
+        let public_addrs = self.parse_public_addrs()?;
         // Parse the CDN.
         let cdn = self.parse_cdn();
+        // Parse the TLS configuration.
+        let tls = self.parse_tls()?;
+        // Parse the proxy configuration.
+        let proxy = self.parse_proxy()?;
 
         // Parse the genesis block.
         let genesis = self.parse_genesis::<N>()?;
@@ -401,6 +628,23 @@ impl Start {
         let account = self.parse_private_key::<N>()?;
         // Parse the node type.
         let node_type = self.parse_node_type();
+        // Ensure the relay mode is not combined with another node type.
+        if self.relay && (self.validator || self.prover || self.client) {
+            bail!("The '--relay' flag cannot be used with the '--validator', '--prover', or '--client' flags");
+        }
+        // Ensure the validator does not connect through a proxy, as its peers must be able to reach it.
+        if proxy.is_some() && node_type.is_validator() {
+            bail!("The '--socks5-proxy' flag cannot be used with the '--validator' flag");
+        }
+        // Ensure the outbound-only mode is not combined with a validator (which must be reachable) or with UPnP.
+        if self.nolisten {
+            if node_type.is_validator() {
+                bail!("The '--nolisten' flag cannot be used with the '--validator' flag");
+            }
+            if self.upnp {
+                bail!("The '--nolisten' flag cannot be used with the '--upnp' flag");
+            }
+        }
 
         // Parse the REST IP.
         let rest_ip = match self.norest {
@@ -452,12 +696,41 @@ impl Start {
             None => StorageMode::from(self.dev),
         };
 
+        // Initialize the node configuration.
+        let config = NodeConfig {
+            router: RouterConfig {
+                node_ip: self.node,
+                trusted_peers,
+                max_peers_per_subnet: self.max_peers_per_subnet,
+                max_bandwidth,
+                max_peer_bandwidth,
+                relay_policy,
+                liveness_policy,
+                connection_policy,
+                dns_seeds,
+                tls,
+                noise: self.noise,
+                compression: !self.nocompression,
+                port_mapping: self.upnp,
+                public_addrs,
+                listen: !self.nolisten,
+                validator_listener,
+                proxy,
+                ..Default::default()
+            },
+            bft_ip: if self.dev.is_some() { self.bft } else { None },
+            rest_ip,
+            rest_rps: self.rest_rps,
+            trusted_validators,
+            cdn,
+        };
+
         // Initialize the node.
-        let bft_ip = if self.dev.is_some() { self.bft } else { None };
         match node_type {
-            NodeType::Validator => Node::new_validator(self.node, bft_ip, rest_ip, self.rest_rps, account, &trusted_peers, &trusted_validators, genesis, cdn, storage_mode).await,
-            NodeType::Prover => Node::new_prover(self.node, account, &trusted_peers, genesis, storage_mode).await,
-            NodeType::Client => Node::new_client(self.node, rest_ip, self.rest_rps, account, &trusted_peers, genesis, cdn, storage_mode).await,
+            NodeType::Validator => Node::new_validator(account, genesis, storage_mode, config).await,
+            NodeType::Prover => Node::new_prover(account, genesis, storage_mode, config).await,
+            NodeType::Client if self.relay => Node::new_relay(account, genesis, storage_mode, config).await,
+            NodeType::Client => Node::new_client(account, genesis, storage_mode, config).await,
         }
     }
 
@@ -629,6 +902,178 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_parse_dns_seeds() {
+        let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
+        assert!(config.parse_dns_seeds().is_empty());
+
+        let seeds = "seed1.example.com, seed2.example.com:4140";
+        let config = Start::try_parse_from(["snarkos", "--dns-seeds", seeds].iter()).unwrap();
+        assert_eq!(config.parse_dns_seeds(), vec!["seed1.example.com", "seed2.example.com:4140"]);
+    }
+
+    #[test]
+    fn test_parse_tls() {
+        let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
+        assert!(config.parse_tls().unwrap().is_none());
+
+        let config = Start::try_parse_from(["snarkos", "--tls-cert", "cert.pem"].iter()).unwrap();
+        assert!(config.parse_tls().is_err());
+        let config = Start::try_parse_from(["snarkos", "--tls-key", "key.pem"].iter()).unwrap();
+        assert!(config.parse_tls().is_err());
+        let config = Start::try_parse_from(["snarkos", "--tls-ca", "ca.pem"].iter()).unwrap();
+        assert!(config.parse_tls().is_err());
+
+        // The certificate and private key files must exist.
+        let config =
+            Start::try_parse_from(["snarkos", "--tls-cert", "missing.pem", "--tls-key", "missing.pem"].iter()).unwrap();
+        assert!(config.parse_tls().is_err());
+    }
+
+    #[test]
+    fn test_parse_proxy() {
+        let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
+        assert!(config.parse_proxy().unwrap().is_none());
+
+        let config = Start::try_parse_from(["snarkos", "--socks5-proxy", "127.0.0.1:9050"].iter()).unwrap();
+        assert_eq!(config.parse_proxy().unwrap(), Some(ProxyConfig::new("127.0.0.1:9050".parse().unwrap(), None)));
+
+        let config = Start::try_parse_from(
+            ["snarkos", "--socks5-proxy", "127.0.0.1:9050", "--socks5-username", "user", "--socks5-password", "pass"]
+                .iter(),
+        )
+        .unwrap();
+        let credentials = Some(("user".to_string(), "pass".to_string()));
+        assert_eq!(
+            config.parse_proxy().unwrap(),
+            Some(ProxyConfig::new("127.0.0.1:9050".parse().unwrap(), credentials))
+        );
+
+        // The username and password must be used together, and require a proxy.
+        let config =
+            Start::try_parse_from(["snarkos", "--socks5-proxy", "127.0.0.1:9050", "--socks5-username", "user"].iter())
+                .unwrap();
+        assert!(config.parse_proxy().is_err());
+        let config =
+            Start::try_parse_from(["snarkos", "--socks5-username", "user", "--socks5-password", "pass"].iter())
+                .unwrap();
+        assert!(config.parse_proxy().is_err());
+
+        // The port mapping cannot be used with a proxy.
+        let config = Start::try_parse_from(["snarkos", "--socks5-proxy", "127.0.0.1:9050", "--upnp"].iter()).unwrap();
+        assert!(config.parse_proxy().is_err());
+    }
+
+    #[test]
+    fn test_parse_relay_policy() {
+        let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
+        assert_eq!(config.parse_relay_policy(), RelayPolicy::default());
+
+        let config = Start::try_parse_from(
+            ["snarkos", "--relay-min-fee", "1000", "--relay-max-tx-size", "4096", "--relay-max-tx-rate", "10"].iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.parse_relay_policy(),
+            RelayPolicy { min_fee: 1000, max_transaction_size: 4096, max_transactions_per_peer: 10 }
+        );
+    }
+
+    #[test]
+    fn test_parse_liveness_policy() {
+        let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
+        assert_eq!(config.parse_liveness_policy().unwrap(), LivenessPolicy::default());
+
+        let config = Start::try_parse_from(
+            ["snarkos", "--ping-interval", "10", "--pong-timeout", "15", "--idle-timeout", "60"].iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.parse_liveness_policy().unwrap(),
+            LivenessPolicy { ping_interval_in_secs: 10, pong_timeout_in_secs: 15, idle_timeout_in_secs: 60 }
+        );
+
+        // The idle timeout must exceed the ping interval.
+        let config =
+            Start::try_parse_from(["snarkos", "--ping-interval", "60", "--idle-timeout", "60"].iter()).unwrap();
+        assert!(config.parse_liveness_policy().is_err());
+    }
+
+    #[test]
+    fn test_parse_connection_policy() {
+        let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
+        assert_eq!(config.parse_connection_policy().unwrap(), None);
+
+        let config =
+            Start::try_parse_from(["snarkos", "--target-outbound-peers", "16", "--max-inbound-peers", "0"].iter())
+                .unwrap();
+        assert_eq!(
+            config.parse_connection_policy().unwrap(),
+            Some(ConnectionPolicy { target_outbound_peers: 16, max_inbound_peers: 0 })
+        );
+
+        // The unspecified limit falls back to its default.
+        let config = Start::try_parse_from(["snarkos", "--max-inbound-peers", "64"].iter()).unwrap();
+        assert_eq!(
+            config.parse_connection_policy().unwrap(),
+            Some(ConnectionPolicy { max_inbound_peers: 64, ..Default::default() })
+        );
+
+        // The node must maintain at least one outbound connection.
+        let config = Start::try_parse_from(["snarkos", "--target-outbound-peers", "0"].iter()).unwrap();
+        assert!(config.parse_connection_policy().is_err());
+    }
+
+    #[test]
+    fn test_parse_validator_listener() {
+        let config = Start::try_parse_from(["snarkos", "--validator"].iter()).unwrap();
+        assert_eq!(config.parse_validator_listener().unwrap(), None);
+
+        let config = Start::try_parse_from(
+            ["snarkos", "--validator", "--validator-listener", "0.0.0.0:4140", "--max-validator-peers", "50"].iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.parse_validator_listener().unwrap(),
+            Some(ValidatorListener { max_peers: 50, ..ValidatorListener::new("0.0.0.0:4140".parse().unwrap()) })
+        );
+
+        // The validator listener is only available to validators.
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--validator-listener", "0.0.0.0:4140"].iter()).unwrap();
+        assert!(config.parse_validator_listener().is_err());
+        // The validator listener must admit at least one validator peer.
+        let config = Start::try_parse_from(
+            ["snarkos", "--validator", "--validator-listener", "0.0.0.0:4140", "--max-validator-peers", "0"].iter(),
+        )
+        .unwrap();
+        assert!(config.parse_validator_listener().is_err());
+    }
+
+    #[test]
+    fn test_parse_public_addrs() {
+        let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
+        assert!(config.parse_public_addrs().unwrap().is_empty());
+
+        let config =
+            Start::try_parse_from(["snarkos", "--public-addrs", "1.2.3.4:4133,[2001:db8::1]:4133"].iter()).unwrap();
+        assert_eq!(config.parse_public_addrs().unwrap(), vec![
+            SocketAddr::from_str("1.2.3.4:4133").unwrap(),
+            SocketAddr::from_str("[2001:db8::1]:4133").unwrap()
+        ]);
+
+        // The public addresses must be well-formed, and have a specified IP address and port.
+        let config = Start::try_parse_from(["snarkos", "--public-addrs", "1.2.3.4"].iter()).unwrap();
+        assert!(config.parse_public_addrs().is_err());
+        let config = Start::try_parse_from(["snarkos", "--public-addrs", "[::]:4133"].iter()).unwrap();
+        assert!(config.parse_public_addrs().is_err());
+        let config = Start::try_parse_from(["snarkos", "--public-addrs", "1.2.3.4:0"].iter()).unwrap();
+        assert!(config.parse_public_addrs().is_err());
+        // An outbound-only node has no public address.
+        let config = Start::try_parse_from(["snarkos", "--nolisten", "--public-addrs", "1.2.3.4:4133"].iter()).unwrap();
+        assert!(config.parse_public_addrs().is_err());
+    }
+
     #[test]
     fn test_parse_cdn() {
         // Validator (Prod)
@@ -707,6 +1152,12 @@ mod tests {
         .unwrap();
         assert!(config.parse_cdn().is_none());
 
+        // Relay (Prod)
+        let config = Start::try_parse_from(["snarkos", "--relay"].iter()).unwrap();
+        assert!(config.parse_cdn().is_none());
+        let config = Start::try_parse_from(["snarkos", "--relay", "--cdn", "url"].iter()).unwrap();
+        assert!(config.parse_cdn().is_none());
+
         // Default (Prod)
         let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
         assert!(config.parse_cdn().is_none());
@@ -0,0 +1,448 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::LedgerArgs;
+use snarkvm::{
+    ledger::{
+        block::{Block, Transaction, Transition},
+        store::{helpers::rocksdb::ConsensusDB, BlockStorage, BlockStore, ConsensusStore},
+    },
+    prelude::{FromBytes, Network, Testnet3, ToBytes},
+};
+
+use aleo_std::StorageMode;
+use anyhow::{bail, ensure, Result};
+use arrow_array::{
+    builder::{BooleanBuilder, Int64Builder, StringBuilder, UInt32Builder, UInt64Builder},
+    ArrayRef,
+    RecordBatch,
+};
+use clap::Parser;
+use colored::Colorize;
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The number of blocks in each partition of the exported tables.
+const EXPORT_PARTITION_SIZE: u32 = 10_000;
+
+/// Commands to manage the snarkOS node storage.
+#[derive(Debug, Parser)]
+pub enum Storage {
+    /// Verifies the integrity of the ledger of a stopped node, and reports the first inconsistency
+    Verify {
+        #[clap(flatten)]
+        ledger: LedgerArgs,
+        /// Specify the height of the first block to verify
+        #[clap(default_value = "0", long = "start")]
+        start: u32,
+    },
+    /// Exports the blocks, transactions, and transitions of the ledger of a stopped node into Parquet files
+    Export {
+        #[clap(flatten)]
+        ledger: LedgerArgs,
+        /// Specify the height of the first block to export
+        #[clap(default_value = "0", long = "start")]
+        start: u32,
+        /// Specify the height after the last block to export (default: the latest block)
+        #[clap(long = "end")]
+        end: Option<u32>,
+        /// Specify the path of the directory to create, with a subdirectory for each table
+        #[clap(long = "output")]
+        output: PathBuf,
+    },
+}
+
+impl Storage {
+    /// Runs the storage command.
+    pub fn parse(self) -> Result<String> {
+        match self {
+            Self::Verify { ledger, start } => {
+                let storage_mode = ledger.storage_mode();
+                let ledger_dir = ledger.ledger_dir();
+                let path_string = format!("(in \"{}\")", ledger_dir.display()).dimmed();
+                let latest_height = match ledger.network {
+                    Testnet3::ID => verify_ledger::<Testnet3>(storage_mode, start)?,
+                    _ => bail!("Unsupported network ID {}", ledger.network),
+                };
+                Ok(format!("✅ Verified the blocks {start} to {latest_height} of the ledger {path_string}"))
+            }
+            Self::Export { ledger, start, end, output } => {
+                ensure!(!output.exists(), "The directory \"{}\" already exists", output.display());
+                let storage_mode = ledger.storage_mode();
+                let ledger_dir = ledger.ledger_dir();
+                let path_string = format!("(from \"{}\")", ledger_dir.display()).dimmed();
+                let num_blocks = match ledger.network {
+                    Testnet3::ID => export_ledger::<Testnet3>(storage_mode, start, end, &output)?,
+                    _ => bail!("Unsupported network ID {}", ledger.network),
+                };
+                Ok(format!("✅ Exported {num_blocks} blocks to \"{}\" {path_string}", output.display()))
+            }
+        }
+    }
+}
+
+/// Retrieves the block at the given height, and ensures it is stored under its hash and height.
+fn get_block<N: Network, B: BlockStorage<N>>(block_store: &BlockStore<N, B>, height: u32) -> Result<Block<N>> {
+    let Some(hash) = block_store.get_block_hash(height)? else {
+        bail!("Block {height} is missing from the ledger");
+    };
+    let Some(block) = block_store.get_block(&hash)? else {
+        bail!("Block {height} ('{hash}') is indexed, but its contents are missing from the ledger");
+    };
+    ensure!(block.hash() == hash, "Block {height} is stored as '{hash}', but contains block '{}'", block.hash());
+    ensure!(block.height() == height, "Block {height} ('{hash}') contains the height {}", block.height());
+    Ok(block)
+}
+
+/// Walks the blocks of the ledger from the given height, and returns the height of the latest block.
+fn verify_ledger<N: Network>(storage_mode: StorageMode, start: u32) -> Result<u32> {
+    let store = ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode)?;
+    verify_blocks(store.block_store(), start)
+}
+
+/// Walks the blocks of the given block store from the given height, and returns the height of the latest block.
+///
+/// For each block, the hash is recomputed from its contents, the transactions root is recomputed from its
+/// transactions, the block is checked to extend the previous block, and its previous state root is checked
+/// to be one of the state roots of the ledger. The first inconsistency is returned as an error.
+fn verify_blocks<N: Network, B: BlockStorage<N>>(block_store: &BlockStore<N, B>, start: u32) -> Result<u32> {
+    let Some(latest_height) = block_store.max_height() else {
+        bail!("No ledger was found");
+    };
+    ensure!(start <= latest_height, "The ledger ends at block {latest_height}, below block {start}");
+
+    let mut previous_hash = match start {
+        0 => N::BlockHash::default(),
+        _ => get_block(block_store, start - 1)?.hash(),
+    };
+    for height in start..=latest_height {
+        let block = get_block(block_store, height)?;
+        let hash = block.hash();
+
+        // Recompute the hash of the block, which is verified as the block is deserialized.
+        if let Err(error) = Block::<N>::read_le(&block.to_bytes_le()?[..]) {
+            bail!("Block {height} ('{hash}') does not match its hash - {error}");
+        }
+        // Recompute the transactions root of the block.
+        let transactions_root = block.transactions().to_transactions_root()?;
+        if transactions_root != block.header().transactions_root() {
+            bail!("Block {height} ('{hash}') does not match its transactions root");
+        }
+        // Ensure the block extends the previous block.
+        if block.previous_hash() != previous_hash {
+            bail!("Block {height} ('{hash}') does not extend block {} ('{previous_hash}')", height.saturating_sub(1));
+        }
+        // Ensure the previous state root of the block is one of the state roots of the ledger.
+        if height > 0 && !block_store.contains_state_root(&block.previous_state_root())? {
+            bail!("Block {height} ('{hash}') refers to an unknown state root '{}'", block.previous_state_root());
+        }
+        previous_hash = hash;
+
+        if height % 10_000 == 0 && height > start {
+            println!("Verified the blocks {start} to {height}...");
+        }
+    }
+
+    Ok(latest_height)
+}
+
+/// Exports the blocks in the given range of the ledger into Parquet files, and returns the number of blocks.
+///
+/// The blocks, transactions, and transitions are written into the `blocks`, `transactions`, and `transitions`
+/// subdirectories of the output directory, partitioned by `EXPORT_PARTITION_SIZE` blocks, e.g. the transactions
+/// of the blocks 10,000 to 19,999 are in `transactions/partition=10000/10000.parquet`.
+fn export_ledger<N: Network>(storage_mode: StorageMode, start: u32, end: Option<u32>, output: &Path) -> Result<u32> {
+    let store = ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode)?;
+    let block_store = store.block_store();
+    let Some(latest_height) = block_store.max_height() else {
+        bail!("No ledger was found");
+    };
+    let end = end.unwrap_or(latest_height + 1);
+    ensure!(start < end, "The block range {start}..{end} is empty");
+    ensure!(end <= latest_height + 1, "The ledger ends at block {latest_height}, below block {}", end - 1);
+
+    let mut columns = ExportColumns::default();
+    let mut first_height = start;
+    for height in start..end {
+        // Write the tables of the previous partition, once it is complete.
+        if height % EXPORT_PARTITION_SIZE == 0 && height > first_height {
+            columns.write(output, first_height)?;
+            first_height = height;
+            println!("Exported the blocks {start} to {}...", height - 1);
+        }
+        columns.push(&get_block(block_store, height)?);
+    }
+    columns.write(output, first_height)?;
+
+    Ok(end - start)
+}
+
+/// The columns of the exported tables, for the blocks of one partition.
+#[derive(Default)]
+struct ExportColumns {
+    blocks: BlockColumns,
+    transactions: TransactionColumns,
+    transitions: TransitionColumns,
+}
+
+impl ExportColumns {
+    /// Adds the rows of the given block to the tables.
+    fn push<N: Network>(&mut self, block: &Block<N>) {
+        self.blocks.push(block);
+        for confirmed in block.transactions().iter() {
+            let transaction = confirmed.transaction();
+            self.transactions.push(block.height(), confirmed.index(), confirmed.is_accepted(), transaction);
+            for transition in transaction.transitions() {
+                self.transitions.push(block.height(), transaction.id(), transition);
+            }
+        }
+    }
+
+    /// Writes the tables of the partition starting at the given height, and resets the columns.
+    fn write(&mut self, output: &Path, first_height: u32) -> Result<()> {
+        let partition = first_height / EXPORT_PARTITION_SIZE * EXPORT_PARTITION_SIZE;
+        let file_name = format!("partition={partition}/{first_height}.parquet");
+        write_parquet(&output.join("blocks").join(&file_name), &self.blocks.finish()?)?;
+        write_parquet(&output.join("transactions").join(&file_name), &self.transactions.finish()?)?;
+        write_parquet(&output.join("transitions").join(&file_name), &self.transitions.finish()?)
+    }
+}
+
+/// The columns of the `blocks` table.
+#[derive(Default)]
+struct BlockColumns {
+    height: UInt32Builder,
+    hash: StringBuilder,
+    previous_hash: StringBuilder,
+    previous_state_root: StringBuilder,
+    timestamp: Int64Builder,
+    round: UInt64Builder,
+    coinbase_target: UInt64Builder,
+    proof_target: UInt64Builder,
+    num_transactions: UInt32Builder,
+}
+
+impl BlockColumns {
+    /// Adds the row of the given block.
+    fn push<N: Network>(&mut self, block: &Block<N>) {
+        self.height.append_value(block.height());
+        self.hash.append_value(block.hash().to_string());
+        self.previous_hash.append_value(block.previous_hash().to_string());
+        self.previous_state_root.append_value(block.previous_state_root().to_string());
+        self.timestamp.append_value(block.timestamp());
+        self.round.append_value(block.round());
+        self.coinbase_target.append_value(block.coinbase_target());
+        self.proof_target.append_value(block.proof_target());
+        self.num_transactions.append_value(block.transactions().len() as u32);
+    }
+
+    /// Returns the rows added so far, and resets the columns.
+    fn finish(&mut self) -> Result<RecordBatch> {
+        Ok(RecordBatch::try_from_iter([
+            ("height", Arc::new(self.height.finish()) as ArrayRef),
+            ("hash", Arc::new(self.hash.finish())),
+            ("previous_hash", Arc::new(self.previous_hash.finish())),
+            ("previous_state_root", Arc::new(self.previous_state_root.finish())),
+            ("timestamp", Arc::new(self.timestamp.finish())),
+            ("round", Arc::new(self.round.finish())),
+            ("coinbase_target", Arc::new(self.coinbase_target.finish())),
+            ("proof_target", Arc::new(self.proof_target.finish())),
+            ("num_transactions", Arc::new(self.num_transactions.finish())),
+        ])?)
+    }
+}
+
+/// The columns of the `transactions` table.
+#[derive(Default)]
+struct TransactionColumns {
+    height: UInt32Builder,
+    index: UInt32Builder,
+    id: StringBuilder,
+    kind: StringBuilder,
+    is_accepted: BooleanBuilder,
+    fee: UInt64Builder,
+    num_transitions: UInt32Builder,
+}
+
+impl TransactionColumns {
+    /// Adds the row of the given transaction.
+    fn push<N: Network>(&mut self, height: u32, index: u32, is_accepted: bool, transaction: &Transaction<N>) {
+        self.height.append_value(height);
+        self.index.append_value(index);
+        self.id.append_value(transaction.id().to_string());
+        self.kind.append_value(match transaction {
+            Transaction::Deploy(..) => "deploy",
+            Transaction::Execute(..) => "execute",
+            Transaction::Fee(..) => "fee",
+        });
+        self.is_accepted.append_value(is_accepted);
+        self.fee.append_option(transaction.fee_amount().ok().map(|fee| *fee));
+        self.num_transitions.append_value(transaction.transitions().count() as u32);
+    }
+
+    /// Returns the rows added so far, and resets the columns.
+    fn finish(&mut self) -> Result<RecordBatch> {
+        Ok(RecordBatch::try_from_iter([
+            ("height", Arc::new(self.height.finish()) as ArrayRef),
+            ("index", Arc::new(self.index.finish())),
+            ("id", Arc::new(self.id.finish())),
+            ("kind", Arc::new(self.kind.finish())),
+            ("is_accepted", Arc::new(self.is_accepted.finish())),
+            ("fee", Arc::new(self.fee.finish())),
+            ("num_transitions", Arc::new(self.num_transitions.finish())),
+        ])?)
+    }
+}
+
+/// The columns of the `transitions` table.
+#[derive(Default)]
+struct TransitionColumns {
+    height: UInt32Builder,
+    transaction_id: StringBuilder,
+    id: StringBuilder,
+    program_id: StringBuilder,
+    function_name: StringBuilder,
+    num_inputs: UInt32Builder,
+    num_outputs: UInt32Builder,
+}
+
+impl TransitionColumns {
+    /// Adds the row of the given transition.
+    fn push<N: Network>(&mut self, height: u32, transaction_id: N::TransactionID, transition: &Transition<N>) {
+        self.height.append_value(height);
+        self.transaction_id.append_value(transaction_id.to_string());
+        self.id.append_value(transition.id().to_string());
+        self.program_id.append_value(transition.program_id().to_string());
+        self.function_name.append_value(transition.function_name().to_string());
+        self.num_inputs.append_value(transition.inputs().len() as u32);
+        self.num_outputs.append_value(transition.outputs().len() as u32);
+    }
+
+    /// Returns the rows added so far, and resets the columns.
+    fn finish(&mut self) -> Result<RecordBatch> {
+        Ok(RecordBatch::try_from_iter([
+            ("height", Arc::new(self.height.finish()) as ArrayRef),
+            ("transaction_id", Arc::new(self.transaction_id.finish())),
+            ("id", Arc::new(self.id.finish())),
+            ("program_id", Arc::new(self.program_id.finish())),
+            ("function_name", Arc::new(self.function_name.finish())),
+            ("num_inputs", Arc::new(self.num_inputs.finish())),
+            ("num_outputs", Arc::new(self.num_outputs.finish())),
+        ])?)
+    }
+}
+
+/// Writes the given rows into a new Parquet file.
+fn write_parquet(path: &Path, batch: &RecordBatch) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(fs::File::create(path)?, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::ledger::store::helpers::{memory::BlockMemory, Map};
+
+    use arrow_array::{cast::AsArray, types::UInt32Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    type CurrentNetwork = Testnet3;
+
+    /// Returns the block storage of a ledger which contains the genesis block.
+    fn sample_block_storage() -> BlockMemory<CurrentNetwork> {
+        let storage = BlockMemory::<CurrentNetwork>::open(None).unwrap();
+        let genesis = Block::<CurrentNetwork>::from_bytes_le(CurrentNetwork::genesis_bytes()).unwrap();
+        storage.insert(&genesis).unwrap();
+        storage
+    }
+
+    #[test]
+    fn test_verify_blocks() {
+        let block_store = BlockStore::from(sample_block_storage()).unwrap();
+        assert_eq!(verify_blocks(&block_store, 0).unwrap(), 0);
+        // The range must not start beyond the latest block.
+        assert!(verify_blocks(&block_store, 1).is_err());
+
+        // An empty ledger is rejected.
+        let block_store = BlockStore::<CurrentNetwork, _>::from(BlockMemory::open(None).unwrap()).unwrap();
+        assert!(verify_blocks(&block_store, 0).is_err());
+    }
+
+    #[test]
+    fn test_verify_blocks_missing_block() {
+        // Index a block at height 1, whose contents are not in the ledger.
+        let storage = sample_block_storage();
+        storage.id_map().insert(1, Default::default()).unwrap();
+        let block_store = BlockStore::from(storage).unwrap();
+
+        let error = verify_blocks(&block_store, 0).unwrap_err();
+        assert!(error.to_string().contains("Block 1"), "{error}");
+        assert!(verify_blocks(&block_store, 1).is_err());
+    }
+
+    #[test]
+    fn test_verify_blocks_corrupted_block() {
+        // Index the genesis block at height 1 as well, so the ledger contains a block under the wrong height.
+        let storage = sample_block_storage();
+        let genesis = Block::<CurrentNetwork>::from_bytes_le(CurrentNetwork::genesis_bytes()).unwrap();
+        storage.id_map().insert(1, genesis.hash()).unwrap();
+        let block_store = BlockStore::from(storage).unwrap();
+
+        let error = verify_blocks(&block_store, 0).unwrap_err();
+        assert!(error.to_string().contains("contains the height 0"), "{error}");
+    }
+
+    #[test]
+    fn test_write_parquet() {
+        let directory = std::env::temp_dir().join(format!("snarkos-parquet-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        // Write the rows of a table into a nested file.
+        let mut columns = TransitionColumns::default();
+        for height in 0..3 {
+            columns.height.append_value(height);
+            columns.transaction_id.append_value(format!("at1{height}"));
+            columns.id.append_value(format!("au1{height}"));
+            columns.program_id.append_value("credits.aleo");
+            columns.function_name.append_value("transfer_public");
+            columns.num_inputs.append_value(2);
+            columns.num_outputs.append_value(1);
+        }
+        let batch = columns.finish().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        let path = directory.join("transitions").join("partition=0").join("0.parquet");
+        write_parquet(&path, &batch).unwrap();
+        // The columns are reset.
+        assert_eq!(columns.finish().unwrap().num_rows(), 0);
+
+        // Read the rows back.
+        let reader = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(&path).unwrap()).unwrap().build().unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches, vec![batch]);
+        let heights = batches[0].column_by_name("height").unwrap().as_primitive::<UInt32Type>();
+        assert_eq!(heights.values().to_vec(), vec![0, 1, 2]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
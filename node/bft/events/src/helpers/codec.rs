@@ -12,17 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::Event;
+use crate::{read_handshake_message, write_handshake_message, Event, NoiseTransport};
 use snarkvm::prelude::{FromBytes, Network, ToBytes};
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use core::marker::PhantomData;
-use rayon::{
-    iter::{IndexedParallelIterator, ParallelIterator},
-    prelude::ParallelSlice,
-};
-use snow::{HandshakeState, StatelessTransportState};
-use std::{io, sync::Arc};
+use snow::HandshakeState;
+use std::io;
 use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
 use tracing::*;
 
@@ -31,9 +27,6 @@ const MAX_HANDSHAKE_SIZE: usize = 1024 * 1024; // 1 MiB
 /// The maximum size of an event that can be transmitted in the network.
 const MAX_EVENT_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
 
-/// The type of noise handshake to use for network encryption.
-pub const NOISE_HANDSHAKE_TYPE: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
-
 /// The codec used to decode and encode network `Event`s.
 pub struct EventCodec<N: Network> {
     codec: LengthDelimitedCodec,
@@ -98,9 +91,6 @@ impl<N: Network> Decoder for EventCodec<N> {
 
 /* NOISE CODEC */
 
-// The maximum message size for noise messages. If the data to be encrypted exceeds it, it is chunked.
-const MAX_MESSAGE_LEN: usize = 65535;
-
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum EventOrBytes<N: Network> {
     Bytes(Bytes),
@@ -122,16 +112,9 @@ impl<N: Network> ToBytes for EventOrBytes<N> {
     }
 }
 
-#[derive(Clone)]
-pub struct PostHandshakeState {
-    state: Arc<StatelessTransportState>,
-    tx_nonce: u64,
-    rx_nonce: u64,
-}
-
 pub enum NoiseState {
     Handshake(Box<HandshakeState>),
-    PostHandshake(PostHandshakeState),
+    PostHandshake(NoiseTransport),
     Failed,
 }
 
@@ -148,14 +131,8 @@ impl Clone for NoiseState {
 impl NoiseState {
     pub fn into_post_handshake_state(self) -> Self {
         if let Self::Handshake(noise_state) = self {
-            match noise_state.into_stateless_transport_mode() {
-                Ok(new_state) => {
-                    return Self::PostHandshake(PostHandshakeState {
-                        state: Arc::new(new_state),
-                        tx_nonce: 0,
-                        rx_nonce: 0,
-                    });
-                }
+            match NoiseTransport::new(*noise_state) {
+                Ok(transport) => return Self::PostHandshake(transport),
                 Err(error) => {
                     warn!("Handshake not finished - {error}");
                 }
@@ -193,15 +170,12 @@ impl<N: Network> Encoder<EventOrBytes<N>> for NoiseCodec<N> {
                     // Don't allow message sending before the noise handshake has completed.
                     EventOrBytes::Event(_) => unimplemented!(),
                     EventOrBytes::Bytes(bytes) => {
-                        let mut buffer = [0u8; MAX_MESSAGE_LEN];
-                        let len = noise
-                            .write_message(&bytes, &mut buffer[..])
-                            .map_err(|e| Self::Error::new(io::ErrorKind::InvalidInput, e))?;
+                        let ciphertext = write_handshake_message(noise, &bytes)?;
 
                         #[cfg(feature = "metrics")]
-                        metrics::histogram(metrics::tcp::NOISE_CODEC_ENCRYPTION_SIZE, len as f64);
+                        metrics::histogram(metrics::tcp::NOISE_CODEC_ENCRYPTION_SIZE, ciphertext.len() as f64);
 
-                        buffer[..len].into()
+                        ciphertext
                     }
                 }
             }
@@ -219,36 +193,7 @@ impl<N: Network> Encoder<EventOrBytes<N>> for NoiseCodec<N> {
                 metrics::histogram(metrics::tcp::NOISE_CODEC_ENCRYPTION_SIZE, bytes.len() as f64);
 
                 // Chunk the payload if necessary and encrypt with Noise.
-                //
-                // A Noise transport message is simply an AEAD ciphertext that is less than or
-                // equal to 65535 bytes in length, and that consists of an encrypted payload plus
-                // 16 bytes of authentication data.
-                //
-                // See: https://noiseprotocol.org/noise.html#the-handshakestate-object
-                const TAG_LEN: usize = 16;
-                let encrypted_chunks = bytes
-                    .par_chunks(MAX_MESSAGE_LEN - TAG_LEN)
-                    .enumerate()
-                    .map(|(nonce_offset, plaintext_chunk)| {
-                        let mut buffer = vec![0u8; MAX_MESSAGE_LEN];
-                        let len = noise
-                            .state
-                            .write_message(noise.tx_nonce + nonce_offset as u64, plaintext_chunk, &mut buffer)
-                            .map_err(|e| Self::Error::new(io::ErrorKind::InvalidInput, e))?;
-
-                        buffer.truncate(len);
-
-                        Ok(buffer)
-                    })
-                    .collect::<io::Result<Vec<Vec<u8>>>>()?;
-
-                let mut buffer = BytesMut::with_capacity(encrypted_chunks.len());
-                for chunk in encrypted_chunks {
-                    buffer.extend_from_slice(&chunk);
-                    noise.tx_nonce += 1;
-                }
-
-                buffer
+                noise.encrypt(&bytes)?.freeze()
             }
 
             NoiseState::Failed => unreachable!("Noise handshake failed to encode"),
@@ -256,7 +201,7 @@ impl<N: Network> Encoder<EventOrBytes<N>> for NoiseCodec<N> {
 
         // Encode the resulting ciphertext using the length-delimited codec.
         #[allow(clippy::let_and_return)]
-        let result = self.codec.encode(ciphertext.freeze(), dst);
+        let result = self.codec.encode(ciphertext, dst);
 
         #[cfg(feature = "metrics")]
         metrics::histogram(metrics::tcp::NOISE_CODEC_ENCRYPTION_TIME, start.elapsed().as_micros() as f64);
@@ -282,37 +227,12 @@ impl<N: Network> Decoder for NoiseCodec<N> {
         let msg = match self.noise_state {
             NoiseState::Handshake(ref mut noise) => {
                 // Decrypt the ciphertext in handshake mode.
-                let mut buffer = [0u8; MAX_MESSAGE_LEN];
-                let len = noise.read_message(&bytes, &mut buffer).map_err(|_| io::ErrorKind::InvalidData)?;
-
-                Some(EventOrBytes::Bytes(Bytes::copy_from_slice(&buffer[..len])))
+                Some(EventOrBytes::Bytes(read_handshake_message(noise, &bytes)?))
             }
 
             NoiseState::PostHandshake(ref mut noise) => {
-                // Noise decryption.
-                let decrypted_chunks = bytes
-                    .par_chunks(MAX_MESSAGE_LEN)
-                    .enumerate()
-                    .map(|(nonce_offset, encrypted_chunk)| {
-                        let mut buffer = vec![0u8; MAX_MESSAGE_LEN];
-
-                        // Decrypt the ciphertext in post-handshake mode.
-                        let len = noise
-                            .state
-                            .read_message(noise.rx_nonce + nonce_offset as u64, encrypted_chunk, &mut buffer)
-                            .map_err(|_| io::ErrorKind::InvalidData)?;
-
-                        buffer.truncate(len);
-                        Ok(buffer)
-                    })
-                    .collect::<io::Result<Vec<Vec<u8>>>>()?;
-
-                // Collect chunks into plaintext to be passed to the message codecs.
-                let mut plaintext = BytesMut::new();
-                for chunk in decrypted_chunks {
-                    plaintext.extend_from_slice(&chunk);
-                    noise.rx_nonce += 1;
-                }
+                // Noise decryption, into plaintext to be passed to the message codecs.
+                let mut plaintext = noise.decrypt(&bytes)?;
 
                 // Decode with message codecs.
                 self.event_codec.decode(&mut plaintext)?.map(|msg| EventOrBytes::Event(msg))
@@ -330,7 +250,7 @@ impl<N: Network> Decoder for NoiseCodec<N> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::prop_tests::any_event;
+    use crate::{prop_tests::any_event, NOISE_HANDSHAKE_TYPE};
 
     use snow::{params::NoiseParams, Builder};
     use test_strategy::proptest;
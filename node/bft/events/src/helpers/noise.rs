@@ -0,0 +1,230 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::{Bytes, BytesMut};
+use rayon::{
+    iter::{IndexedParallelIterator, ParallelIterator},
+    prelude::ParallelSlice,
+};
+use snow::{HandshakeState, StatelessTransportState};
+use std::{io, sync::Arc};
+use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
+
+/// The type of noise handshake to use for network encryption.
+pub const NOISE_HANDSHAKE_TYPE: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// The maximum message size for noise messages. If the data to be encrypted exceeds it, it is chunked.
+const MAX_NOISE_MESSAGE_LEN: usize = 65535;
+/// The size of the authentication data appended to every noise transport message.
+const NOISE_TAG_LEN: usize = 16;
+
+/// Writes the given payload as the next message of the noise handshake, returning the ciphertext.
+pub(crate) fn write_handshake_message(state: &mut HandshakeState, payload: &[u8]) -> io::Result<Bytes> {
+    let mut buffer = [0u8; MAX_NOISE_MESSAGE_LEN];
+    let len =
+        state.write_message(payload, &mut buffer[..]).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    Ok(Bytes::copy_from_slice(&buffer[..len]))
+}
+
+/// Reads the given ciphertext as the next message of the noise handshake, returning the payload.
+pub(crate) fn read_handshake_message(state: &mut HandshakeState, ciphertext: &[u8]) -> io::Result<Bytes> {
+    let mut buffer = [0u8; MAX_NOISE_MESSAGE_LEN];
+    let len = state.read_message(ciphertext, &mut buffer).map_err(|_| io::ErrorKind::InvalidData)?;
+    Ok(Bytes::copy_from_slice(&buffer[..len]))
+}
+
+/// The post-handshake state of an encrypted connection.
+///
+/// The state is cloned into both the reading and the writing codec of a connection;
+/// the former only advances `rx_nonce`, and the latter only advances `tx_nonce`.
+#[derive(Clone)]
+pub struct NoiseTransport {
+    state: Arc<StatelessTransportState>,
+    tx_nonce: u64,
+    rx_nonce: u64,
+}
+
+impl NoiseTransport {
+    /// Initializes the transport state from the given concluded handshake state.
+    pub(crate) fn new(state: HandshakeState) -> io::Result<Self> {
+        let state = state.into_stateless_transport_mode().map_err(io::Error::other)?;
+        Ok(Self { state: Arc::new(state), tx_nonce: 0, rx_nonce: 0 })
+    }
+
+    /// Encrypts the given plaintext, chunking it if it exceeds the maximum noise message size.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> io::Result<BytesMut> {
+        // A Noise transport message is simply an AEAD ciphertext that is less than or
+        // equal to 65535 bytes in length, and that consists of an encrypted payload plus
+        // 16 bytes of authentication data.
+        //
+        // See: https://noiseprotocol.org/noise.html#the-handshakestate-object
+        let encrypted_chunks = plaintext
+            .par_chunks(MAX_NOISE_MESSAGE_LEN - NOISE_TAG_LEN)
+            .enumerate()
+            .map(|(nonce_offset, plaintext_chunk)| {
+                let mut buffer = vec![0u8; MAX_NOISE_MESSAGE_LEN];
+                let len = self
+                    .state
+                    .write_message(self.tx_nonce + nonce_offset as u64, plaintext_chunk, &mut buffer)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                buffer.truncate(len);
+                Ok(buffer)
+            })
+            .collect::<io::Result<Vec<Vec<u8>>>>()?;
+
+        let mut ciphertext = BytesMut::with_capacity(plaintext.len() + encrypted_chunks.len() * NOISE_TAG_LEN);
+        for chunk in encrypted_chunks {
+            ciphertext.extend_from_slice(&chunk);
+            self.tx_nonce += 1;
+        }
+        Ok(ciphertext)
+    }
+
+    /// Decrypts the given ciphertext, which may consist of multiple noise messages.
+    pub fn decrypt(&mut self, ciphertext: &[u8]) -> io::Result<BytesMut> {
+        let decrypted_chunks = ciphertext
+            .par_chunks(MAX_NOISE_MESSAGE_LEN)
+            .enumerate()
+            .map(|(nonce_offset, encrypted_chunk)| {
+                let mut buffer = vec![0u8; MAX_NOISE_MESSAGE_LEN];
+                let len = self
+                    .state
+                    .read_message(self.rx_nonce + nonce_offset as u64, encrypted_chunk, &mut buffer)
+                    .map_err(|_| io::ErrorKind::InvalidData)?;
+                buffer.truncate(len);
+                Ok(buffer)
+            })
+            .collect::<io::Result<Vec<Vec<u8>>>>()?;
+
+        let mut plaintext = BytesMut::with_capacity(ciphertext.len());
+        for chunk in decrypted_chunks {
+            plaintext.extend_from_slice(&chunk);
+            self.rx_nonce += 1;
+        }
+        Ok(plaintext)
+    }
+}
+
+/// The codec used to exchange the noise handshake messages, which precede an encrypted connection.
+pub struct NoiseHandshakeCodec {
+    codec: LengthDelimitedCodec,
+    state: Option<Box<HandshakeState>>,
+}
+
+impl NoiseHandshakeCodec {
+    /// Initializes a new codec from the given (initiator or responder) handshake state.
+    pub fn new(state: HandshakeState) -> Self {
+        Self { codec: LengthDelimitedCodec::builder().little_endian().new_codec(), state: Some(Box::new(state)) }
+    }
+
+    /// Returns the static public key of the peer, if the handshake has revealed it.
+    pub fn remote_static_key(&self) -> Option<Vec<u8>> {
+        self.state.as_ref().and_then(|state| state.get_remote_static().map(|key| key.to_vec()))
+    }
+
+    /// Concludes the handshake, returning the transport state for the encrypted connection.
+    pub fn take_transport(&mut self) -> io::Result<NoiseTransport> {
+        let Some(state) = self.state.take() else {
+            return Err(io::Error::other("The noise handshake was already concluded"));
+        };
+        NoiseTransport::new(*state)
+    }
+
+    /// Returns a mutable reference to the handshake state, or an error if the handshake was concluded.
+    fn state_mut(&mut self) -> io::Result<&mut HandshakeState> {
+        match self.state {
+            Some(ref mut state) => Ok(state),
+            None => Err(io::Error::other("The noise handshake was already concluded")),
+        }
+    }
+}
+
+impl Encoder<Bytes> for NoiseHandshakeCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, payload: Bytes, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let ciphertext = write_handshake_message(self.state_mut()?, &payload)?;
+        self.codec.encode(ciphertext, dst)
+    }
+}
+
+impl Decoder for NoiseHandshakeCodec {
+    type Error = io::Error;
+    type Item = Bytes;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // Decode the ciphertext with the length-delimited codec.
+        let Some(bytes) = self.codec.decode(src)? else {
+            return Ok(None);
+        };
+        // Decrypt the ciphertext in handshake mode.
+        read_handshake_message(self.state_mut()?, &bytes).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use snow::{params::NoiseParams, Builder};
+
+    /// Performs a noise XX handshake between two codecs, returning the initiator and responder transports.
+    fn handshake_xx() -> (NoiseTransport, NoiseTransport) {
+        let params: NoiseParams = NOISE_HANDSHAKE_TYPE.parse().unwrap();
+        let initiator_builder = Builder::new(params.clone());
+        let initiator_kp = initiator_builder.generate_keypair().unwrap();
+        let initiator = initiator_builder.local_private_key(&initiator_kp.private).build_initiator().unwrap();
+
+        let responder_builder = Builder::new(params);
+        let responder_kp = responder_builder.generate_keypair().unwrap();
+        let responder = responder_builder.local_private_key(&responder_kp.private).build_responder().unwrap();
+
+        let mut initiator_codec = NoiseHandshakeCodec::new(initiator);
+        let mut responder_codec = NoiseHandshakeCodec::new(responder);
+
+        let mut ciphertext = BytesMut::new();
+
+        // -> e
+        initiator_codec.encode(Bytes::new(), &mut ciphertext).unwrap();
+        assert!(responder_codec.decode(&mut ciphertext).unwrap().unwrap().is_empty());
+        // <- e, ee, s, es
+        responder_codec.encode(Bytes::new(), &mut ciphertext).unwrap();
+        assert!(initiator_codec.decode(&mut ciphertext).unwrap().unwrap().is_empty());
+        // -> s, se
+        initiator_codec.encode(Bytes::new(), &mut ciphertext).unwrap();
+        assert!(responder_codec.decode(&mut ciphertext).unwrap().unwrap().is_empty());
+
+        // Ensure the static keys were exchanged.
+        assert_eq!(initiator_codec.remote_static_key().unwrap(), responder_kp.public);
+        assert_eq!(responder_codec.remote_static_key().unwrap(), initiator_kp.public);
+
+        (initiator_codec.take_transport().unwrap(), responder_codec.take_transport().unwrap())
+    }
+
+    #[test]
+    fn test_noise_roundtrip() {
+        let (mut initiator, mut responder) = handshake_xx();
+
+        // Ensure small and chunked payloads survive the roundtrip, in both directions.
+        for size in [0, 1, MAX_NOISE_MESSAGE_LEN - NOISE_TAG_LEN, 3 * MAX_NOISE_MESSAGE_LEN + 7] {
+            let plaintext = (0..size).map(|i| i as u8).collect::<Vec<u8>>();
+
+            let ciphertext = initiator.encrypt(&plaintext).unwrap();
+            assert_eq!(responder.decrypt(&ciphertext).unwrap(), plaintext);
+
+            let ciphertext = responder.encrypt(&plaintext).unwrap();
+            assert_eq!(initiator.decrypt(&ciphertext).unwrap(), plaintext);
+        }
+    }
+}
@@ -49,6 +49,7 @@ use snarkos_node_tcp::{
     Config,
     Connection,
     ConnectionSide,
+    Stream,
     Tcp,
     P2P,
 };
@@ -65,7 +66,6 @@ use parking_lot::{Mutex, RwLock};
 use rand::seq::{IteratorRandom, SliceRandom};
 use std::{collections::HashSet, future::Future, io, net::SocketAddr, sync::Arc, time::Duration};
 use tokio::{
-    net::TcpStream,
     sync::{oneshot, OnceCell},
     task::{self, JoinHandle},
 };
@@ -1137,7 +1137,7 @@ macro_rules! expect_event {
 
 /// Send the given message to the peer.
 async fn send_event<N: Network>(
-    framed: &mut Framed<&mut TcpStream, EventCodec<N>>,
+    framed: &mut Framed<&mut Stream, EventCodec<N>>,
     peer_addr: SocketAddr,
     event: Event<N>,
 ) -> io::Result<()> {
@@ -1151,8 +1151,8 @@ impl<N: Network> Gateway<N> {
         &'a self,
         peer_addr: SocketAddr,
         peer_ip: Option<SocketAddr>,
-        stream: &'a mut TcpStream,
-    ) -> io::Result<(SocketAddr, Framed<&mut TcpStream, EventCodec<N>>)> {
+        stream: &'a mut Stream,
+    ) -> io::Result<(SocketAddr, Framed<&mut Stream, EventCodec<N>>)> {
         // This value is immediately guaranteed to be present, so it can be unwrapped.
         let peer_ip = peer_ip.unwrap();
 
@@ -1211,8 +1211,8 @@ impl<N: Network> Gateway<N> {
         &'a self,
         peer_addr: SocketAddr,
         peer_ip: &mut Option<SocketAddr>,
-        stream: &'a mut TcpStream,
-    ) -> io::Result<(SocketAddr, Framed<&mut TcpStream, EventCodec<N>>)> {
+        stream: &'a mut Stream,
+    ) -> io::Result<(SocketAddr, Framed<&mut Stream, EventCodec<N>>)> {
         // Construct the stream.
         let mut framed = Framed::new(stream, EventCodec::<N>::handshake());
 
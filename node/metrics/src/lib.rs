@@ -19,6 +19,16 @@ pub use names::*;
 // Re-export the snarkVM metrics.
 pub use snarkvm::metrics::*;
 
+/// Increments the counter of the given name, with the given labels, by the given value.
+pub fn counter_with_labels(name: &'static str, labels: &[(&'static str, String)], value: u64) {
+    ::metrics::counter!(name, labels).increment(value);
+}
+
+/// Records the given value in the histogram of the given name, with the given labels.
+pub fn histogram_with_labels(name: &'static str, labels: &[(&'static str, String)], value: f64) {
+    ::metrics::histogram!(name, labels).record(value);
+}
+
 /// Initializes the metrics and returns a handle to the task running the metrics exporter.
 pub fn initialize_metrics() {
     // Build the Prometheus exporter.
@@ -12,7 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub(super) const COUNTER_NAMES: [&str; 1] = [bft::LEADERS_ELECTED];
+pub(super) const COUNTER_NAMES: [&str; 5] = [
+    bft::LEADERS_ELECTED,
+    router::MESSAGES_RECEIVED,
+    router::MESSAGES_SENT,
+    router::MESSAGE_BYTES_RECEIVED,
+    router::MESSAGE_BYTES_SENT,
+];
 
 pub(super) const GAUGE_NAMES: [&str; 12] = [
     bft::CONNECTED,
@@ -29,10 +35,11 @@ pub(super) const GAUGE_NAMES: [&str; 12] = [
     tcp::TCP_TASKS,
 ];
 
-pub(super) const HISTOGRAM_NAMES: [&str; 7] = [
+pub(super) const HISTOGRAM_NAMES: [&str; 8] = [
     bft::COMMIT_ROUNDS_LATENCY,
     consensus::CERTIFICATE_COMMIT_LATENCY,
     consensus::BLOCK_LATENCY,
+    router::MESSAGE_PROCESSING_TIME,
     tcp::NOISE_CODEC_ENCRYPTION_TIME,
     tcp::NOISE_CODEC_DECRYPTION_TIME,
     tcp::NOISE_CODEC_ENCRYPTION_SIZE,
@@ -64,6 +71,11 @@ pub mod router {
     pub const CONNECTED: &str = "snarkos_router_connected_total";
     pub const CANDIDATE: &str = "snarkos_router_candidate_total";
     pub const RESTRICTED: &str = "snarkos_router_restricted_total";
+    pub const MESSAGES_RECEIVED: &str = "snarkos_router_messages_received_total";
+    pub const MESSAGES_SENT: &str = "snarkos_router_messages_sent_total";
+    pub const MESSAGE_BYTES_RECEIVED: &str = "snarkos_router_message_bytes_received_total";
+    pub const MESSAGE_BYTES_SENT: &str = "snarkos_router_message_bytes_sent_total";
+    pub const MESSAGE_PROCESSING_TIME: &str = "snarkos_router_message_processing_micros";
 }
 
 pub mod tcp {
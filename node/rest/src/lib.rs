@@ -20,6 +20,17 @@ extern crate tracing;
 mod helpers;
 pub use helpers::*;
 
+/// A helper macro to read from the ledger on the blocking thread pool, so that slow reads from storage
+/// do not stall the runtime threads that also handle the peer-to-peer traffic.
+macro_rules! spawn_blocking {
+    ($expr:expr) => {
+        match tokio::task::spawn_blocking(move || $expr).await {
+            Ok(value) => value,
+            Err(error) => Err(anyhow::anyhow!("[tokio::spawn_blocking] {error}")),
+        }
+    };
+}
+
 mod routes;
 
 use snarkos_node_consensus::Consensus;
@@ -122,6 +133,10 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
 
             // All the endpoints before the call to `route_layer` are protected with JWT auth.
             .route("/testnet3/node/address", get(Self::get_node_address))
+            .route("/testnet3/peers/bans", get(Self::get_peers_bans))
+            .route("/testnet3/peers/ban", post(Self::ban_peer))
+            .route("/testnet3/peers/unban/:ip", post(Self::unban_peer))
+            .route("/testnet3/peers/connect", post(Self::connect_peer))
             .route_layer(middleware::from_fn(auth_middleware))
 
             // ----------------- DEPRECATED ROUTES -----------------
@@ -171,6 +186,10 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             .route("/testnet3/peers/count", get(Self::get_peers_count))
             .route("/testnet3/peers/all", get(Self::get_peers_all))
             .route("/testnet3/peers/all/metrics", get(Self::get_peers_all_metrics))
+            .route("/testnet3/peers/health", get(Self::get_peers_health))
+            .route("/testnet3/peers/traffic", get(Self::get_peers_traffic))
+            .route("/testnet3/peers/reputation", get(Self::get_peers_reputation))
+            .route("/testnet3/peers/offenses", get(Self::get_peers_offenses))
 
             // GET ../program/..
             .route("/testnet3/program/:id", get(Self::get_program))
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use super::*;
-use snarkos_node_router::messages::UnconfirmedSolution;
+use snarkos_node_router::{messages::UnconfirmedSolution, Outbound};
 use snarkvm::{
     ledger::coinbase::ProverSolution,
     prelude::{block::Transaction, Identifier, Plaintext},
@@ -23,6 +23,7 @@ use indexmap::IndexMap;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
 
 /// The `get_blocks` query object.
 #[derive(Deserialize, Serialize)]
@@ -33,6 +34,24 @@ pub(crate) struct BlockRange {
     end: u32,
 }
 
+/// The `ban_peer` request object.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct BanRequest {
+    /// The peer IP to ban.
+    ip: IpAddr,
+    /// The reason for the ban.
+    reason: Option<String>,
+    /// The duration of the ban in seconds. If omitted, the ban is permanent.
+    duration_in_secs: Option<u64>,
+}
+
+/// The `connect_peer` request object.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct ConnectRequest {
+    /// The peer IP to whitelist and connect to.
+    ip: SocketAddr,
+}
+
 /// The `get_mapping_value` query object.
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Metadata {
@@ -111,13 +130,13 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         // Manually parse the height or the height or the hash, axum doesn't support different types
         // for the same path param.
         let block = if let Ok(height) = height_or_hash.parse::<u32>() {
-            rest.ledger.get_block(height)?
+            spawn_blocking!(rest.ledger.get_block(height))?
         } else {
             let hash = height_or_hash
                 .parse::<N::BlockHash>()
                 .map_err(|_| RestError("invalid input, it is neither a block height nor a block hash".to_string()))?;
 
-            rest.ledger.get_block_by_hash(&hash)?
+            spawn_blocking!(rest.ledger.get_block_by_hash(&hash))?
         };
 
         Ok(ErasedJson::pretty(block))
@@ -146,17 +165,22 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             )));
         }
 
-        let mut blocks_and_committees = Vec::new();
+        // Retrieve and serialize the blocks on the blocking thread pool, as they are read from storage.
+        let blocks_and_committees = spawn_blocking!({
+            let mut blocks_and_committees = Vec::new();
 
-        for height in start_height..end_height {
-            if let Ok(committee) = rest.ledger.get_committee(height) {
-                if let Ok(block) = rest.ledger.get_block(height) {
-                    blocks_and_committees.push((committee, block));
+            for height in start_height..end_height {
+                if let Ok(committee) = rest.ledger.get_committee(height) {
+                    if let Ok(block) = rest.ledger.get_block(height) {
+                        blocks_and_committees.push((committee, block));
+                    }
                 }
             }
-        }
 
-        Ok(ErasedJson::pretty(blocks_and_committees))
+            Ok(ErasedJson::pretty(blocks_and_committees))
+        })?;
+
+        Ok(blocks_and_committees)
     }
 
     // GET /testnet3/blocks/committees?start={start_height}&end={end_height}
@@ -182,18 +206,23 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             )));
         }
 
-        let blocks_and_committees = cfg_into_iter!((start_height..end_height))
-            .filter_map(|height| {
-                let block = rest.ledger.get_block(height);
-                let committee = rest.ledger.get_committee(height);
-                match (block, committee) {
-                    (Ok(block), Ok(committee)) => Some((block, committee)),
-                    _ => None,
-                }
-            })
-            .collect::<Vec<_>>();
+        // Retrieve and serialize the blocks on the blocking thread pool, as they are read from storage.
+        let blocks_and_committees = spawn_blocking!({
+            let blocks_and_committees = cfg_into_iter!((start_height..end_height))
+                .filter_map(|height| {
+                    let block = rest.ledger.get_block(height);
+                    let committee = rest.ledger.get_committee(height);
+                    match (block, committee) {
+                        (Ok(block), Ok(committee)) => Some((block, committee)),
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            Ok(ErasedJson::pretty(blocks_and_committees))
+        })?;
 
-        Ok(ErasedJson::pretty(blocks_and_committees))
+        Ok(blocks_and_committees)
     }
 
     // GET /testnet3/blocks?start={start_height}&end={end_height}
@@ -219,11 +248,15 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             )));
         }
 
-        let blocks = cfg_into_iter!((start_height..end_height))
-            .map(|height| rest.ledger.get_block(height))
-            .collect::<Result<Vec<_>, _>>()?;
+        // Retrieve and serialize the blocks on the blocking thread pool, as they are read from storage.
+        let blocks = spawn_blocking!(
+            cfg_into_iter!((start_height..end_height))
+                .map(|height| rest.ledger.get_block(height))
+                .collect::<Result<Vec<_>, _>>()
+                .map(ErasedJson::pretty)
+        )?;
 
-        Ok(ErasedJson::pretty(blocks))
+        Ok(blocks)
     }
 
     // GET /testnet3/height/{blockHash}
@@ -239,7 +272,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(height): Path<u32>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.get_transactions(height)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.get_transactions(height))?))
     }
 
     // GET /testnet3/transaction/{transactionID}
@@ -247,7 +280,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(tx_id): Path<N::TransactionID>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.get_transaction(tx_id)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.get_transaction(tx_id))?))
     }
 
     // GET /testnet3/transaction/confirmed/{transactionID}
@@ -255,7 +288,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(tx_id): Path<N::TransactionID>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.get_confirmed_transaction(tx_id)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.get_confirmed_transaction(tx_id))?))
     }
 
     // GET /testnet3/memoryPool/transmissions
@@ -289,7 +322,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(id): Path<ProgramID<N>>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.get_program(id)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.get_program(id))?))
     }
 
     // GET /testnet3/program/{programID}/mappings
@@ -308,7 +341,8 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         metadata: Option<Query<Metadata>>,
     ) -> Result<ErasedJson, RestError> {
         // Retrieve the mapping values.
-        let mapping_values = rest.ledger.vm().finalize_store().get_mapping_confirmed(id, name)?;
+        let ledger = rest.ledger.clone();
+        let mapping_values = spawn_blocking!(ledger.vm().finalize_store().get_mapping_confirmed(id, name))?;
 
         // Check if metadata is requested and return the values with metadata if so.
         if metadata.map(|q| q.metadata).unwrap_or(false) {
@@ -349,7 +383,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(commitment): Path<Field<N>>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.get_state_path_for_commitment(&commitment)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.get_state_path_for_commitment(&commitment))?))
     }
 
     // GET /testnet3/stateRoot/latest
@@ -377,6 +411,73 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         ErasedJson::pretty(rest.routing.router().connected_metrics())
     }
 
+    // GET /testnet3/peers/health
+    pub(crate) async fn get_peers_health(State(rest): State<Self>) -> ErasedJson {
+        let health = rest.routing.connected_io_health().into_iter().map(|(peer_ip, health)| {
+            json!({
+                "peer": peer_ip,
+                "queued_messages": health.queued_msgs,
+                "pending_bytes": health.pending_bytes,
+                "dropped_messages": health.dropped_msgs,
+                "avg_queue_time_ms": health.avg_queue_time.as_millis() as u64,
+                "max_queue_time_ms": health.max_queue_time.as_millis() as u64,
+                "stalls": health.num_stalls,
+                "is_stalled": health.is_stalled,
+            })
+        });
+        ErasedJson::pretty(health.collect::<Vec<_>>())
+    }
+
+    // GET /testnet3/peers/traffic
+    pub(crate) async fn get_peers_traffic(State(rest): State<Self>) -> ErasedJson {
+        let traffic = rest
+            .routing
+            .router()
+            .connected_traffic()
+            .into_iter()
+            .map(|(peer_ip, traffic)| json!({ "peer": peer_ip, "messages": traffic }));
+        ErasedJson::pretty(traffic.collect::<Vec<_>>())
+    }
+
+    // GET /testnet3/peers/reputation
+    pub(crate) async fn get_peers_reputation(State(rest): State<Self>) -> ErasedJson {
+        ErasedJson::pretty(rest.routing.router().reputation().scores())
+    }
+
+    // GET /testnet3/peers/offenses
+    pub(crate) async fn get_peers_offenses(State(rest): State<Self>) -> ErasedJson {
+        ErasedJson::pretty(rest.routing.router().reputation().all_offenses())
+    }
+
+    // GET /testnet3/peers/bans
+    pub(crate) async fn get_peers_bans(State(rest): State<Self>) -> ErasedJson {
+        ErasedJson::pretty(rest.routing.router().banned_peers())
+    }
+
+    // POST /testnet3/peers/ban
+    pub(crate) async fn ban_peer(
+        State(rest): State<Self>,
+        Json(request): Json<BanRequest>,
+    ) -> Result<ErasedJson, RestError> {
+        let reason = request.reason.unwrap_or_else(|| "Banned manually".to_string());
+        rest.routing.router().ban_peer(request.ip, reason, request.duration_in_secs)?;
+        Ok(ErasedJson::pretty(request.ip))
+    }
+
+    // POST /testnet3/peers/connect
+    pub(crate) async fn connect_peer(
+        State(rest): State<Self>,
+        Json(request): Json<ConnectRequest>,
+    ) -> Result<ErasedJson, RestError> {
+        rest.routing.router().connect_trusted(request.ip).await?;
+        Ok(ErasedJson::pretty(request.ip))
+    }
+
+    // POST /testnet3/peers/unban/{ip}
+    pub(crate) async fn unban_peer(State(rest): State<Self>, Path(ip): Path<IpAddr>) -> Result<ErasedJson, RestError> {
+        Ok(ErasedJson::pretty(rest.routing.router().unban_peer(&ip)?))
+    }
+
     // GET /testnet3/node/address
     pub(crate) async fn get_node_address(State(rest): State<Self>) -> ErasedJson {
         ErasedJson::pretty(rest.routing.router().address())
@@ -387,7 +488,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(tx_id): Path<N::TransactionID>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.find_block_hash(&tx_id)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.find_block_hash(&tx_id))?))
     }
 
     // GET /testnet3/find/transactionID/deployment/{programID}
@@ -395,7 +496,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(program_id): Path<ProgramID<N>>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.find_transaction_id_from_program_id(&program_id)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.find_transaction_id_from_program_id(&program_id))?))
     }
 
     // GET /testnet3/find/transactionID/{transitionID}
@@ -403,7 +504,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(transition_id): Path<N::TransitionID>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.find_transaction_id_from_transition_id(&transition_id)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.find_transaction_id_from_transition_id(&transition_id))?))
     }
 
     // GET /testnet3/find/transitionID/{inputOrOutputID}
@@ -411,7 +512,7 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Path(input_or_output_id): Path<Field<N>>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.find_transition_id(&input_or_output_id)?))
+        Ok(ErasedJson::pretty(spawn_blocking!(rest.ledger.find_transition_id(&input_or_output_id))?))
     }
 
     // POST /testnet3/transaction/broadcast
@@ -0,0 +1,96 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressAnnouncement {
+    /// The listening addresses of the sender, which replace the addresses it was previously reachable at.
+    pub addrs: Vec<SocketAddr>,
+}
+
+impl AddressAnnouncement {
+    /// The maximum number of addresses in an address announcement.
+    pub const MAXIMUM_NUMBER_OF_ADDRESSES: usize = 8;
+}
+
+impl MessageTrait for AddressAnnouncement {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "AddressAnnouncement".into()
+    }
+}
+
+impl ToBytes for AddressAnnouncement {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        // Ensure that the number of addresses is within the allowed range.
+        if self.addrs.len() > Self::MAXIMUM_NUMBER_OF_ADDRESSES {
+            return Err(error("Message exceeds maximum number of addresses"));
+        }
+        (self.addrs.len() as u8).write_le(&mut writer)?;
+        self.addrs.iter().try_for_each(|addr| addr.write_le(&mut writer))
+    }
+}
+
+impl FromBytes for AddressAnnouncement {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let num_addrs = u8::read_le(&mut reader)?;
+        // Ensure that the number of addresses is within the allowed range.
+        if num_addrs as usize > Self::MAXIMUM_NUMBER_OF_ADDRESSES {
+            return Err(error("Message exceeds maximum number of addresses"));
+        }
+        let addrs = (0..num_addrs).map(|_| SocketAddr::read_le(&mut reader)).collect::<io::Result<_>>()?;
+        Ok(Self { addrs })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{peer_response::prop_tests::any_valid_socket_addr, AddressAnnouncement};
+    use snarkvm::utilities::{FromBytes, ToBytes};
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        prelude::{BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    pub fn any_address_announcement() -> BoxedStrategy<AddressAnnouncement> {
+        vec(any_valid_socket_addr(), 0..=AddressAnnouncement::MAXIMUM_NUMBER_OF_ADDRESSES)
+            .prop_map(|addrs| AddressAnnouncement { addrs })
+            .boxed()
+    }
+
+    #[proptest]
+    fn address_announcement_roundtrip(#[strategy(any_address_announcement())] original: AddressAnnouncement) {
+        let mut buf = BytesMut::default().writer();
+        original.write_le(&mut buf).unwrap();
+
+        let deserialized = AddressAnnouncement::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_address_announcement_with_too_many_addresses_is_rejected() {
+        let addrs = vec!["1.2.3.4:4133".parse().unwrap(); AddressAnnouncement::MAXIMUM_NUMBER_OF_ADDRESSES + 1];
+        let mut buf = BytesMut::default().writer();
+        assert!(AddressAnnouncement { addrs }.write_le(&mut buf).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockAnnouncement<N: Network> {
+    /// The header of the new block.
+    pub block_header: BlockHeader<N>,
+}
+
+impl<N: Network> MessageTrait for BlockAnnouncement<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        format!("BlockAnnouncement {}", self.block_header.height()).into()
+    }
+}
+
+impl<N: Network> ToBytes for BlockAnnouncement<N> {
+    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.block_header.write_le(writer)
+    }
+}
+
+impl<N: Network> FromBytes for BlockAnnouncement<N> {
+    fn read_le<R: io::Read>(reader: R) -> io::Result<Self> {
+        Ok(Self { block_header: BlockHeader::read_le(reader)? })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{header_response::prop_tests::any_block_header, BlockAnnouncement};
+    use snarkvm::utilities::{FromBytes, ToBytes};
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{BoxedStrategy, Strategy};
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    pub fn any_block_announcement() -> BoxedStrategy<BlockAnnouncement<CurrentNetwork>> {
+        any_block_header().prop_map(|block_header| BlockAnnouncement { block_header }).boxed()
+    }
+
+    #[proptest]
+    fn block_announcement_roundtrip(#[strategy(any_block_announcement())] original: BlockAnnouncement<CurrentNetwork>) {
+        let mut buf = BytesMut::default().writer();
+        BlockAnnouncement::write_le(&original, &mut buf).unwrap();
+
+        let deserialized = BlockAnnouncement::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
@@ -25,6 +25,8 @@ pub struct ChallengeRequest<N: Network> {
     pub node_type: NodeType,
     pub address: Address<N>,
     pub nonce: u64,
+    pub features: u32,
+    pub node_id: Option<NodeId>,
 }
 
 impl<N: Network> MessageTrait for ChallengeRequest<N> {
@@ -42,6 +44,17 @@ impl<N: Network> ToBytes for ChallengeRequest<N> {
         self.node_type.write_le(&mut writer)?;
         self.address.write_le(&mut writer)?;
         self.nonce.write_le(&mut writer)?;
+        // The feature flags are only serialized from the protocol version which introduced them.
+        if self.version >= Self::FEATURES_VERSION {
+            self.features.write_le(&mut writer)?;
+        }
+        // The node ID is only serialized from the protocol version which introduced it.
+        if self.version >= Self::IDENTITY_VERSION {
+            self.node_id.is_some().write_le(&mut writer)?;
+            if let Some(node_id) = &self.node_id {
+                node_id.write_le(&mut writer)?;
+            }
+        }
         Ok(())
     }
 }
@@ -53,20 +66,93 @@ impl<N: Network> FromBytes for ChallengeRequest<N> {
         let node_type = NodeType::read_le(&mut reader)?;
         let address = Address::<N>::read_le(&mut reader)?;
         let nonce = u64::read_le(&mut reader)?;
-
-        Ok(Self { version, listener_port, node_type, address, nonce })
+        // The challenge requests of the older protocol versions do not have the feature flags.
+        let features = match version >= Self::FEATURES_VERSION {
+            true => u32::read_le(&mut reader)?,
+            false => 0,
+        };
+        // The challenge requests of the older protocol versions do not have the node ID.
+        let node_id = match version >= Self::IDENTITY_VERSION && bool::read_le(&mut reader)? {
+            true => Some(NodeId::read_le(&mut reader)?),
+            false => None,
+        };
+
+        Ok(Self { version, listener_port, node_type, address, nonce, features, node_id })
     }
 }
 
 impl<N: Network> ChallengeRequest<N> {
-    pub fn new(listener_port: u16, node_type: NodeType, address: Address<N>, nonce: u64) -> Self {
-        Self { version: Message::<N>::VERSION, listener_port, node_type, address, nonce }
+    /// The protocol version which introduced the feature flags.
+    pub const FEATURES_VERSION: u32 = 15;
+    /// The protocol version which introduced the node ID.
+    pub const IDENTITY_VERSION: u32 = 16;
+
+    /// The feature flag signalling support for Noise-encrypted connections.
+    /// note: The frames of an encrypted connection are bound to its ephemeral session keys, so this is also what
+    /// prevents the captured messages from being replayed, reordered, or injected into a connection.
+    pub const FEATURE_NOISE: u32 = 1 << 0;
+    /// The feature flag signalling support for the zstd compression of large messages.
+    pub const FEATURE_COMPRESSION: u32 = 1 << 1;
+    /// The feature flag signalling support for compact block propagation.
+    /// note: This flag is reserved for the rollout of compact blocks, and is not advertised yet.
+    pub const FEATURE_COMPACT_BLOCKS: u32 = 1 << 2;
+    /// The feature flag signalling support for block announcements and the headers-first block sync.
+    pub const FEATURE_HEADERS_FIRST: u32 = 1 << 3;
+    /// The feature flag signalling support for the CRC-32C checksum of the message frames.
+    pub const FEATURE_CHECKSUM: u32 = 1 << 4;
+    /// The feature flag signalling support for the announcement of unconfirmed transactions by their IDs.
+    pub const FEATURE_TRANSACTION_ANNOUNCEMENTS: u32 = 1 << 5;
+    /// The feature flag signalling support for the exchange of the peer metadata in the `Ping` messages.
+    pub const FEATURE_PEER_METADATA: u32 = 1 << 6;
+    /// The feature flag signalling support for the reports of the observed addresses in the `Pong` messages,
+    /// and for the announcements of the changed listening addresses.
+    pub const FEATURE_ADDRESS_ANNOUNCEMENTS: u32 = 1 << 7;
+
+    pub fn new(
+        listener_port: u16,
+        node_type: NodeType,
+        address: Address<N>,
+        nonce: u64,
+        features: u32,
+        node_id: Option<NodeId>,
+    ) -> Self {
+        Self { version: Message::<N>::VERSION, listener_port, node_type, address, nonce, features, node_id }
+    }
+
+    /// Returns the challenge request in the format of the given protocol version, if it is older,
+    /// so that it can be parsed by a peer which did not update yet.
+    pub fn with_version(mut self, version: u32) -> Self {
+        if version < self.version {
+            self.version = version;
+            if version < Self::FEATURES_VERSION {
+                self.features = 0;
+            }
+            if version < Self::IDENTITY_VERSION {
+                self.node_id = None;
+            }
+        }
+        self
+    }
+
+    /// Returns `true` if the sender supports all of the given features.
+    pub fn supports(&self, features: u32) -> bool {
+        self.features & features == features
+    }
+
+    /// Returns `true` if the sender supports Noise-encrypted connections.
+    pub fn supports_noise(&self) -> bool {
+        self.supports(Self::FEATURE_NOISE)
+    }
+
+    /// Returns `true` if the sender supports the zstd compression of large messages.
+    pub fn supports_compression(&self) -> bool {
+        self.supports(Self::FEATURE_COMPRESSION)
     }
 }
 
 #[cfg(test)]
 pub mod prop_tests {
-    use crate::{ChallengeRequest, NodeType};
+    use crate::{ChallengeRequest, Message, NodeId, NodeType};
     use snarkvm::{
         console::prelude::{FromBytes, ToBytes},
         prelude::{Address, TestRng, Uniform},
@@ -93,14 +179,22 @@ pub mod prop_tests {
             .boxed()
     }
 
+    pub fn any_node_id() -> BoxedStrategy<Option<NodeId>> {
+        any::<Option<[u8; 32]>>().prop_map(|bytes| bytes.map(NodeId)).boxed()
+    }
+
     pub fn any_challenge_request() -> BoxedStrategy<ChallengeRequest<CurrentNetwork>> {
-        (any_valid_address(), any::<u64>(), any::<u32>(), any::<u16>(), any_node_type())
-            .prop_map(|(address, nonce, version, listener_port, node_type)| ChallengeRequest {
+        (any_valid_address(), any::<u64>(), any::<u32>(), any::<u16>(), any_node_type(), any::<u32>(), any_node_id())
+            .prop_map(|(address, nonce, version, listener_port, node_type, features, node_id)| ChallengeRequest {
                 address,
                 nonce,
                 version,
                 listener_port,
                 node_type,
+                // The feature flags are not serialized for the protocol versions older than the features.
+                features: if version >= ChallengeRequest::<CurrentNetwork>::FEATURES_VERSION { features } else { 0 },
+                // The node ID is not serialized for the protocol versions older than the node ID.
+                node_id: if version >= ChallengeRequest::<CurrentNetwork>::IDENTITY_VERSION { node_id } else { None },
             })
             .boxed()
     }
@@ -114,4 +208,47 @@ pub mod prop_tests {
             ChallengeRequest::read_le(buf.into_inner().reader()).unwrap();
         assert_eq!(original, deserialized);
     }
+
+    #[proptest]
+    fn challenge_request_previous_version(
+        #[strategy(any_challenge_request())] original: ChallengeRequest<CurrentNetwork>,
+    ) {
+        // Serialize the challenge request in the format of the previous protocol version, without the node ID.
+        let mut buf = BytesMut::default().writer();
+        original.version.write_le(&mut buf).unwrap();
+        original.listener_port.write_le(&mut buf).unwrap();
+        original.node_type.write_le(&mut buf).unwrap();
+        original.address.write_le(&mut buf).unwrap();
+        original.nonce.write_le(&mut buf).unwrap();
+        original.features.write_le(&mut buf).unwrap();
+
+        let previous_version = ChallengeRequest::<CurrentNetwork>::IDENTITY_VERSION - 1;
+        let mut buf = buf.into_inner();
+        buf[..4].copy_from_slice(&previous_version.to_le_bytes());
+
+        // Ensure the challenge request of the previous protocol version is parsed, without a node ID.
+        let deserialized: ChallengeRequest<CurrentNetwork> = ChallengeRequest::read_le(buf.reader()).unwrap();
+        assert_eq!(deserialized, ChallengeRequest { version: previous_version, node_id: None, ..original });
+    }
+
+    #[proptest]
+    fn challenge_request_minimum_version(
+        #[strategy(any_challenge_request())] original: ChallengeRequest<CurrentNetwork>,
+    ) {
+        // Serialize the challenge request in the format of the last released protocol version.
+        let version = Message::<CurrentNetwork>::MINIMUM_VERSION;
+        let original = ChallengeRequest { version, features: 0, node_id: None, ..original };
+        let mut buf = BytesMut::default().writer();
+        original.version.write_le(&mut buf).unwrap();
+        original.listener_port.write_le(&mut buf).unwrap();
+        original.node_type.write_le(&mut buf).unwrap();
+        original.address.write_le(&mut buf).unwrap();
+        original.nonce.write_le(&mut buf).unwrap();
+
+        // Ensure the challenge request of the last released protocol version is accepted and parsed.
+        assert!(Message::<CurrentNetwork>::is_compatible_version(version));
+        let deserialized: ChallengeRequest<CurrentNetwork> =
+            ChallengeRequest::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(deserialized, original);
+    }
 }
@@ -25,6 +25,8 @@ use std::borrow::Cow;
 pub struct ChallengeResponse<N: Network> {
     pub genesis_header: Header<N>,
     pub signature: Data<Signature<N>>,
+    /// The (Ed25519) signature of the nonce by the node ID, which is only sent to the peers supporting node IDs.
+    pub node_signature: Option<[u8; 64]>,
 }
 
 impl<N: Network> MessageTrait for ChallengeResponse<N> {
@@ -38,13 +40,29 @@ impl<N: Network> MessageTrait for ChallengeResponse<N> {
 impl<N: Network> ToBytes for ChallengeResponse<N> {
     fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
         self.genesis_header.write_le(&mut writer)?;
-        self.signature.write_le(&mut writer)
+        self.signature.write_le(&mut writer)?;
+        if let Some(node_signature) = &self.node_signature {
+            writer.write_all(node_signature)?;
+        }
+        Ok(())
     }
 }
 
 impl<N: Network> FromBytes for ChallengeResponse<N> {
     fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
-        Ok(Self { genesis_header: Header::read_le(&mut reader)?, signature: Data::read_le(reader)? })
+        let genesis_header = Header::read_le(&mut reader)?;
+        let signature = Data::read_le(&mut reader)?;
+        // The signature by the node ID is trailing, so that the responses to older peers remain unchanged.
+        let mut node_signature = [0u8; 64];
+        let node_signature = match reader.read(&mut node_signature[..1])? {
+            0 => None,
+            _ => {
+                reader.read_exact(&mut node_signature[1..])?;
+                Some(node_signature)
+            }
+        };
+
+        Ok(Self { genesis_header, signature, node_signature })
     }
 }
 
@@ -59,7 +77,11 @@ pub mod prop_tests {
     };
 
     use bytes::{Buf, BufMut, BytesMut};
-    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use proptest::{
+        collection::vec,
+        option,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
     use test_strategy::proptest;
 
     type CurrentNetwork = snarkvm::prelude::Testnet3;
@@ -79,9 +101,17 @@ pub mod prop_tests {
         any::<u64>().prop_map(|seed| *sample_genesis_block(&mut TestRng::fixed(seed)).header()).boxed()
     }
 
+    pub fn any_node_signature() -> BoxedStrategy<Option<[u8; 64]>> {
+        option::of(vec(any::<u8>(), 64)).prop_map(|bytes| bytes.map(|bytes| bytes.try_into().unwrap())).boxed()
+    }
+
     pub fn any_challenge_response() -> BoxedStrategy<ChallengeResponse<CurrentNetwork>> {
-        (any_signature(), any_genesis_header())
-            .prop_map(|(sig, genesis_header)| ChallengeResponse { signature: Data::Object(sig), genesis_header })
+        (any_signature(), any_genesis_header(), any_node_signature())
+            .prop_map(|(sig, genesis_header, node_signature)| ChallengeResponse {
+                signature: Data::Object(sig),
+                genesis_header,
+                node_signature,
+            })
             .boxed()
     }
 
@@ -94,6 +124,7 @@ pub mod prop_tests {
             ChallengeResponse::read_le(buf.into_inner().reader()).unwrap();
 
         assert_eq!(original.genesis_header, deserialized.genesis_header);
+        assert_eq!(original.node_signature, deserialized.node_signature);
         assert_eq!(
             original.signature.deserialize_blocking().unwrap(),
             deserialized.signature.deserialize_blocking().unwrap()
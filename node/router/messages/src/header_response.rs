@@ -0,0 +1,123 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use anyhow::bail;
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderResponse<N: Network> {
+    /// The original header request.
+    pub request: HeaderRequest,
+    /// The block headers.
+    pub headers: Vec<BlockHeader<N>>,
+}
+
+impl<N: Network> HeaderResponse<N> {
+    /// Ensures that the block headers are well-formed in a header response.
+    pub fn ensure_response_is_well_formed(&self, peer_ip: SocketAddr) -> anyhow::Result<()> {
+        let HeaderRequest { start_height, end_height } = self.request;
+
+        // Ensure the block headers are not empty.
+        if self.headers.is_empty() {
+            bail!("Peer '{peer_ip}' sent an empty header response ({start_height}..{end_height})")
+        }
+        // Check that the block headers are sequentially ordered, and linked by their block hashes.
+        if !self.headers.windows(2).all(|w| w[0].height() + 1 == w[1].height() && w[0].hash() == w[1].previous_hash) {
+            bail!("Peer '{peer_ip}' sent an invalid header response (headers are not linked)")
+        }
+        // Check that the block headers are within the range of the header request.
+        // Note: The response may end early, if the peer does not have all of the requested headers.
+        let candidate_start_height = self.headers.first().map(|h| h.height()).unwrap_or(0);
+        let candidate_end_height = 1 + self.headers.last().map(|h| h.height()).unwrap_or(0);
+        if start_height != candidate_start_height || end_height < candidate_end_height {
+            bail!("Peer '{peer_ip}' sent an invalid header response (range does not match header request)")
+        }
+        Ok(())
+    }
+}
+
+impl<N: Network> MessageTrait for HeaderResponse<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        format!("HeaderResponse {}", self.request).into()
+    }
+}
+
+impl<N: Network> ToBytes for HeaderResponse<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        // Ensure that the number of block headers is within the allowed range.
+        if self.headers.len() > HeaderRequest::MAXIMUM_NUMBER_OF_HEADERS as usize {
+            return Err(error("Header response exceeds maximum number of headers"));
+        }
+
+        self.request.write_le(&mut writer)?;
+        (self.headers.len() as u16).write_le(&mut writer)?;
+        self.headers.iter().try_for_each(|header| header.write_le(&mut writer))
+    }
+}
+
+impl<N: Network> FromBytes for HeaderResponse<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let request = HeaderRequest::read_le(&mut reader)?;
+        let num_headers = u16::read_le(&mut reader)?;
+        // Ensure that the number of block headers is within the allowed range.
+        if num_headers as u32 > HeaderRequest::MAXIMUM_NUMBER_OF_HEADERS {
+            return Err(error("Header response exceeds maximum number of headers"));
+        }
+        let headers = (0..num_headers).map(|_| BlockHeader::read_le(&mut reader)).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { request, headers })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{header_request::prop_tests::any_header_request, BlockHeader, HeaderResponse};
+    use snarkvm::{
+        ledger::ledger_test_helpers::sample_genesis_block,
+        utilities::{FromBytes, TestRng, ToBytes},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    pub fn any_block_header() -> BoxedStrategy<BlockHeader<CurrentNetwork>> {
+        any::<u64>().prop_map(|seed| BlockHeader::from_block(&sample_genesis_block(&mut TestRng::fixed(seed)))).boxed()
+    }
+
+    pub fn any_header_response() -> BoxedStrategy<HeaderResponse<CurrentNetwork>> {
+        (any_header_request(), vec(any_block_header(), 0..3))
+            .prop_map(|(request, headers)| HeaderResponse { request, headers })
+            .boxed()
+    }
+
+    #[proptest]
+    fn header_response_roundtrip(#[strategy(any_header_response())] original: HeaderResponse<CurrentNetwork>) {
+        let mut buf = BytesMut::default().writer();
+        HeaderResponse::write_le(&original, &mut buf).unwrap();
+
+        let deserialized = HeaderResponse::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
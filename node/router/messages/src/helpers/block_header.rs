@@ -0,0 +1,103 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{
+    block::{Block, Header},
+    error,
+    FromBytes,
+    Network,
+    ToBits,
+    ToBytes,
+};
+
+use anyhow::Result;
+use std::io;
+
+/// The header of a block, along with the previous block hash, from which the block hash is computed.
+/// The block headers are announced and synced ahead of the blocks, so that the blocks can be fetched on demand.
+///
+/// The block hash is not part of the wire format: it is recomputed when the block header is deserialized,
+/// so that a peer cannot announce a block hash that does not commit to the header it sent along with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockHeader<N: Network> {
+    /// The previous block hash.
+    pub previous_hash: N::BlockHash,
+    /// The block header.
+    pub header: Header<N>,
+    /// The block hash, computed from the previous block hash and the block header.
+    hash: N::BlockHash,
+}
+
+impl<N: Network> BlockHeader<N> {
+    /// Initializes the block header from the given previous block hash and header, computing the block hash.
+    pub fn new(previous_hash: N::BlockHash, header: Header<N>) -> Result<Self> {
+        // Note: This is the block hash as computed by `Block::from`.
+        let preimage = [previous_hash.to_bits_le(), header.to_root()?.to_bits_le()].concat();
+        let hash = N::hash_bhp1024(&preimage)?.into();
+        Ok(Self { previous_hash, header, hash })
+    }
+
+    /// Initializes the block header of the given block.
+    pub fn from_block(block: &Block<N>) -> Self {
+        Self { previous_hash: block.previous_hash(), header: *block.header(), hash: block.hash() }
+    }
+
+    /// Returns the block hash.
+    pub const fn hash(&self) -> N::BlockHash {
+        self.hash
+    }
+
+    /// Returns the block height.
+    pub fn height(&self) -> u32 {
+        self.header.height()
+    }
+}
+
+impl<N: Network> ToBytes for BlockHeader<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        self.previous_hash.write_le(&mut writer)?;
+        self.header.write_le(&mut writer)
+    }
+}
+
+impl<N: Network> FromBytes for BlockHeader<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let previous_hash = N::BlockHash::read_le(&mut reader)?;
+        let header = Header::read_le(&mut reader)?;
+        Self::new(previous_hash, header).map_err(|e| error(format!("Failed to compute the block hash: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{ledger::ledger_test_helpers::sample_genesis_block, prelude::TestRng};
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    #[test]
+    fn test_block_hash_is_recomputed() {
+        let block = sample_genesis_block(&mut TestRng::default());
+
+        // The block hash is recomputed from the previous block hash and the header, as in the block.
+        let bytes = BlockHeader::from_block(&block).to_bytes_le().unwrap();
+        let block_header = BlockHeader::<CurrentNetwork>::from_bytes_le(&bytes).unwrap();
+        assert_eq!(block_header.hash(), block.hash());
+
+        // A block header with another previous block hash does not commit to the block hash.
+        let previous_hash = BlockHeader::from_block(&block).hash();
+        let block_header = BlockHeader::<CurrentNetwork>::new(previous_hash, *block.header()).unwrap();
+        assert_ne!(block_header.hash(), block.hash());
+    }
+}
@@ -12,22 +12,125 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::Message;
+use crate::{
+    crc32c,
+    HeaderRequest,
+    Message,
+    MessageKind,
+    MessageTraffic,
+    NoiseTransport,
+    Ping,
+    TransactionAnnouncement,
+};
 use snarkvm::prelude::{FromBytes, Network, ToBytes};
 
 use ::bytes::{Buf, BufMut, BytesMut};
 use core::marker::PhantomData;
+use std::{io::Read, sync::Arc};
 use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
 
 /// The maximum size of a message that can be transmitted during the handshake.
-const MAXIMUM_HANDSHAKE_MESSAGE_SIZE: usize = 1024 * 1024; // 1 MiB
+/// Larger frames are rejected as soon as their length prefix is read, before the payload is received.
+const MAXIMUM_HANDSHAKE_MESSAGE_SIZE: usize = 64 * 1024; // 64 KiB
 
 /// The maximum size of a message that can be transmitted in the network.
 pub(crate) const MAXIMUM_MESSAGE_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
 
+/// The zstd compression level used for the messages.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// The prefix of an uncompressed message payload, if compression is enabled.
+const PAYLOAD_UNCOMPRESSED: u8 = 0;
+/// The prefix of a zstd-compressed message payload, if compression is enabled.
+const PAYLOAD_COMPRESSED: u8 = 1;
+
+/// The size of the CRC-32C checksum, which follows the length prefix of a frame, if checksums are enabled.
+const CHECKSUM_SIZE: usize = 4;
+
+/// Returns the serialized size from which the given message is compressed, or `None` if it is never compressed.
+fn compression_threshold<N: Network>(message: &Message<N>) -> Option<usize> {
+    match message {
+        Message::BlockResponse(..) | Message::HeaderResponse(..) => Some(1024), // 1 KiB
+        Message::PuzzleResponse(..) | Message::UnconfirmedTransaction(..) => Some(4 * 1024), // 4 KiB
+        _ => None,
+    }
+}
+
+/// Returns the maximum serialized size (including the ID) of a message of the given kind, so that a single frame
+/// under the maximum message size cannot cause large allocations while it is deserialized.
+fn maximum_size<N: Network>(kind: MessageKind) -> usize {
+    const KIB: usize = 1024;
+    const MIB: usize = 1024 * KIB;
+    match kind {
+        MessageKind::BlockRequest
+        | MessageKind::Disconnect
+        | MessageKind::PeerRequest
+        | MessageKind::Pong
+        | MessageKind::PuzzleRequest
+        | MessageKind::HeaderRequest
+        | MessageKind::AddressAnnouncement => KIB,
+        MessageKind::BlockResponse => MAXIMUM_MESSAGE_SIZE,
+        MessageKind::ChallengeRequest
+        | MessageKind::ChallengeResponse
+        | MessageKind::PuzzleResponse
+        | MessageKind::UnconfirmedSolution
+        | MessageKind::BlockAnnouncement => 16 * KIB,
+        // A peer response has at most 255 addresses.
+        MessageKind::PeerResponse => 8 * KIB,
+        // The block locators of a ping are pairs of a 4-byte height and a 32-byte hash.
+        MessageKind::Ping => {
+            let num_locators = Ping::<N>::MAXIMUM_NUMBER_OF_RECENTS + Ping::<N>::MAXIMUM_NUMBER_OF_CHECKPOINTS;
+            KIB + num_locators as usize * 36
+        }
+        MessageKind::UnconfirmedTransaction => 8 * MIB,
+        // The block headers are well below 2 KiB each.
+        MessageKind::HeaderResponse => HeaderRequest::MAXIMUM_NUMBER_OF_HEADERS as usize * 2 * KIB,
+        // The transaction IDs are 32 bytes each.
+        MessageKind::TransactionAnnouncement | MessageKind::TransactionRequest => {
+            KIB + TransactionAnnouncement::<N>::MAXIMUM_NUMBER_OF_TRANSACTIONS * 32
+        }
+    }
+}
+
+/// Returns the kind of the message whose serialized payload starts with the given bytes.
+/// A payload that is too short, or whose message ID is unknown, fails the decoding.
+fn message_kind(bytes: &[u8]) -> std::io::Result<MessageKind> {
+    if bytes.len() < 2 {
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    let id = u16::from_le_bytes([bytes[0], bytes[1]]);
+    MessageKind::from_id(id).ok_or_else(|| {
+        warn!("Failed to deserialize a message: unknown message ID {id}");
+        std::io::ErrorKind::InvalidData.into()
+    })
+}
+
+/// Decompresses the given zstd-compressed payload after the given prefix, failing if the result exceeds `limit` bytes.
+/// The payload is decompressed as a stream, so that the allocation grows with the decompressed bytes,
+/// instead of the limit being reserved for every compressed frame.
+fn decompress(prefix: &[u8], compressed: &[u8], limit: usize) -> std::io::Result<BytesMut> {
+    let decoder = zstd::stream::read::Decoder::new(compressed)?;
+    let mut writer = BytesMut::from(prefix).writer();
+    std::io::copy(&mut decoder.take(limit.saturating_sub(prefix.len()) as u64 + 1), &mut writer)?;
+    let decompressed = writer.into_inner();
+    if decompressed.len() > limit {
+        warn!("Failed to decompress a message: the message exceeds {limit} bytes");
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    Ok(decompressed)
+}
+
 /// The codec used to decode and encode network `Message`s.
 pub struct MessageCodec<N: Network> {
     codec: LengthDelimitedCodec,
+    /// The noise transport state, if the connection is encrypted.
+    noise: Option<NoiseTransport>,
+    /// The boolean flag for the zstd compression of large messages.
+    compression: bool,
+    /// The boolean flag for the CRC-32C checksum of the frames.
+    checksum: bool,
+    /// The traffic of the connection by message kind, if it is tracked.
+    traffic: Option<Arc<MessageTraffic>>,
     _phantom: PhantomData<N>,
 }
 
@@ -37,12 +140,62 @@ impl<N: Network> MessageCodec<N> {
         codec.codec.set_max_frame_length(MAXIMUM_HANDSHAKE_MESSAGE_SIZE);
         codec
     }
+
+    /// Initializes a codec that encrypts and decrypts messages with the given noise transport state.
+    pub fn encrypted(noise: NoiseTransport) -> Self {
+        Self { noise: Some(noise), ..Self::default() }
+    }
+
+    /// Returns `true` if the codec encrypts its messages.
+    pub fn is_encrypted(&self) -> bool {
+        self.noise.is_some()
+    }
+
+    /// Enables the zstd compression of large messages.
+    /// This must only be enabled if the peer has agreed to it during the handshake.
+    pub fn enable_compression(&mut self) {
+        self.compression = true;
+    }
+
+    /// Returns `true` if the codec compresses large messages.
+    pub fn is_compressed(&self) -> bool {
+        self.compression
+    }
+
+    /// Enables the CRC-32C checksum of the frames, so that corrupted frames are rejected before they are decoded.
+    /// This must only be enabled if the peer has agreed to it during the handshake.
+    pub fn enable_checksum(&mut self) {
+        self.checksum = true;
+    }
+
+    /// Returns `true` if the codec checksums its frames.
+    pub fn is_checksummed(&self) -> bool {
+        self.checksum
+    }
+
+    /// Tracks the traffic of the connection by message kind, in the given tally.
+    pub fn track_traffic(&mut self, traffic: Arc<MessageTraffic>) {
+        self.traffic = Some(traffic);
+    }
+
+    /// Records a message of the given kind and size in the given metrics, labelled with the message kind.
+    /// Note: The metrics are not labelled by peer, as the peers churn; the per-peer traffic is tracked separately.
+    #[cfg(feature = "metrics")]
+    fn record_message(&self, messages: &'static str, bytes: &'static str, kind: &'static str, num_bytes: usize) {
+        let labels = [("message", kind.to_string())];
+        metrics::counter_with_labels(messages, &labels, 1);
+        metrics::counter_with_labels(bytes, &labels, num_bytes as u64);
+    }
 }
 
 impl<N: Network> Default for MessageCodec<N> {
     fn default() -> Self {
         Self {
             codec: LengthDelimitedCodec::builder().max_frame_length(MAXIMUM_MESSAGE_SIZE).little_endian().new_codec(),
+            noise: None,
+            compression: false,
+            checksum: false,
+            traffic: None,
             _phantom: Default::default(),
         }
     }
@@ -52,15 +205,65 @@ impl<N: Network> Encoder<Message<N>> for MessageCodec<N> {
     type Error = std::io::Error;
 
     fn encode(&mut self, message: Message<N>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // Retrieve the kind of the message, which labels the message metrics.
+        let kind = message.kind().name();
+
         // Serialize the payload directly into dst.
         message
             .write_le(&mut dst.writer())
             // This error should never happen, the conversion is for greater compatibility.
             .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "serialization error"))?;
 
-        let serialized_message = dst.split_to(dst.len()).freeze();
+        let mut serialized_message = dst.split_to(dst.len());
+
+        // Compress the payload, if compression is enabled and the message is large enough.
+        if self.compression {
+            let is_compressible = compression_threshold(&message).map_or(false, |min| serialized_message.len() >= min);
+            serialized_message = match is_compressible {
+                true => {
+                    // Note: The message ID is not compressed, so that the receiver bounds the decompression
+                    // by the maximum size of the message variant.
+                    let compressed = zstd::bulk::compress(&serialized_message[2..], COMPRESSION_LEVEL)?;
+                    let mut payload = BytesMut::with_capacity(3 + compressed.len());
+                    payload.put_u8(PAYLOAD_COMPRESSED);
+                    payload.extend_from_slice(&serialized_message[..2]);
+                    payload.extend_from_slice(&compressed);
+                    payload
+                }
+                false => {
+                    let mut payload = BytesMut::with_capacity(1 + serialized_message.len());
+                    payload.put_u8(PAYLOAD_UNCOMPRESSED);
+                    payload.extend_from_slice(&serialized_message);
+                    payload
+                }
+            };
+        }
+
+        // Encrypt the payload, if the connection is encrypted.
+        let serialized_message = match self.noise {
+            Some(ref mut noise) => noise.encrypt(&serialized_message)?,
+            None => serialized_message,
+        };
 
-        self.codec.encode(serialized_message, dst)
+        // Prefix the payload with its checksum, if checksums are enabled.
+        let serialized_message = match self.checksum {
+            true => {
+                let mut frame = BytesMut::with_capacity(CHECKSUM_SIZE + serialized_message.len());
+                frame.put_u32_le(crc32c(&serialized_message));
+                frame.extend_from_slice(&serialized_message);
+                frame.freeze()
+            }
+            false => serialized_message.freeze(),
+        };
+
+        self.codec.encode(serialized_message, dst)?;
+
+        #[cfg(feature = "metrics")]
+        self.record_message(metrics::router::MESSAGES_SENT, metrics::router::MESSAGE_BYTES_SENT, kind, dst.len());
+        if let Some(traffic) = &self.traffic {
+            traffic.record_sent(kind, dst.len());
+        }
+        Ok(())
     }
 }
 
@@ -70,15 +273,77 @@ impl<N: Network> Decoder for MessageCodec<N> {
 
     fn decode(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         // Decode a frame containing bytes belonging to a message.
+        let initial_len = source.len();
         let bytes = match self.codec.decode(source)? {
             Some(bytes) => bytes,
             None => return Ok(None),
         };
+        let num_bytes = initial_len - source.len();
+
+        // Verify the checksum of the frame, if checksums are enabled.
+        // A corrupted frame fails the decoding, which resets the connection.
+        let bytes = match self.checksum {
+            true => {
+                if bytes.len() < CHECKSUM_SIZE {
+                    return Err(std::io::ErrorKind::InvalidData.into());
+                }
+                let mut bytes = bytes;
+                let checksum = bytes.get_u32_le();
+                if checksum != crc32c(&bytes) {
+                    warn!("Failed to verify the checksum of a message frame");
+                    return Err(std::io::ErrorKind::InvalidData.into());
+                }
+                bytes
+            }
+            false => bytes,
+        };
+
+        // Decrypt the payload, if the connection is encrypted.
+        let mut bytes = match self.noise {
+            Some(ref mut noise) => noise.decrypt(&bytes)?,
+            None => bytes,
+        };
+
+        // Decompress the payload, if compression is enabled.
+        if self.compression {
+            if bytes.is_empty() {
+                return Err(std::io::ErrorKind::InvalidData.into());
+            }
+            bytes = match bytes.get_u8() {
+                PAYLOAD_UNCOMPRESSED => bytes,
+                // Note: The decompressed size is bounded by the maximum size of the message variant,
+                // to prevent decompression bombs.
+                PAYLOAD_COMPRESSED => {
+                    let kind = message_kind(&bytes)?;
+                    decompress(&bytes[..2], &bytes[2..], maximum_size::<N>(kind))?
+                }
+                _ => return Err(std::io::ErrorKind::InvalidData.into()),
+            };
+        }
+
+        // Ensure the message does not exceed the maximum size of its variant, before it is deserialized.
+        let kind = message_kind(&bytes)?;
+        if bytes.len() > maximum_size::<N>(kind) {
+            warn!("Failed to deserialize a message: the '{kind}' message is too large ({} bytes)", bytes.len());
+            return Err(std::io::ErrorKind::InvalidData.into());
+        }
 
         // Convert the bytes to a message, or fail if it is not valid.
         let reader = bytes.reader();
         match Message::read_le(reader) {
-            Ok(message) => Ok(Some(message)),
+            Ok(message) => {
+                #[cfg(feature = "metrics")]
+                self.record_message(
+                    metrics::router::MESSAGES_RECEIVED,
+                    metrics::router::MESSAGE_BYTES_RECEIVED,
+                    message.kind().name(),
+                    num_bytes,
+                );
+                if let Some(traffic) = &self.traffic {
+                    traffic.record_received(message.kind().name(), num_bytes);
+                }
+                Ok(Some(message))
+            }
             Err(error) => {
                 error!("Failed to deserialize a message: {}", error);
                 Err(std::io::ErrorKind::InvalidData.into())
@@ -86,3 +351,156 @@ impl<N: Network> Decoder for MessageCodec<N> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_response::prop_tests::any_block_response, BlockResponse, PeerRequest, Ping};
+
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    /// Encodes the given message with one compressed codec and decodes it with another.
+    fn compressed_roundtrip(message: Message<CurrentNetwork>) -> (usize, Message<CurrentNetwork>) {
+        let mut encoder = MessageCodec::<CurrentNetwork>::default();
+        encoder.enable_compression();
+        let mut decoder = MessageCodec::<CurrentNetwork>::default();
+        decoder.enable_compression();
+
+        let mut buf = BytesMut::new();
+        encoder.encode(message, &mut buf).unwrap();
+        let num_bytes = buf.len();
+        (num_bytes, decoder.decode(&mut buf).unwrap().unwrap())
+    }
+
+    #[proptest]
+    fn block_response_compressed_roundtrip(
+        #[strategy(any_block_response())] block_response: BlockResponse<CurrentNetwork>,
+    ) {
+        let (_, decoded) = compressed_roundtrip(Message::BlockResponse(block_response.clone()));
+        let Message::BlockResponse(decoded) = decoded else { panic!("Unexpected message") };
+        assert_eq!(block_response.request, decoded.request);
+        assert_eq!(
+            block_response.blocks.deserialize_blocking().unwrap(),
+            decoded.blocks.deserialize_blocking().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_oversized_handshake_message_is_rejected() {
+        // Only the length prefix of an oversized message is received.
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(MAXIMUM_HANDSHAKE_MESSAGE_SIZE as u32 + 1);
+        assert!(MessageCodec::<CurrentNetwork>::handshake().decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_oversized_message_variant_is_rejected() {
+        // A frame under the maximum message size, whose payload exceeds the maximum size of a `PeerRequest`.
+        let mut payload = BytesMut::new();
+        payload.put_u16_le(Message::<CurrentNetwork>::PeerRequest(PeerRequest).id());
+        payload.put_bytes(0, maximum_size::<CurrentNetwork>(MessageKind::PeerRequest));
+
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(payload.len() as u32);
+        buf.extend_from_slice(&payload);
+        assert!(MessageCodec::<CurrentNetwork>::default().decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_unknown_message_id_is_rejected() {
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(2);
+        buf.put_u16_le(u16::MAX);
+        assert!(MessageCodec::<CurrentNetwork>::default().decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_decompression_is_bounded() {
+        let compressed = zstd::bulk::compress(&[0u8; 4096], COMPRESSION_LEVEL).unwrap();
+        assert_eq!(decompress(&[1, 2], &compressed, 4098).unwrap().len(), 4098);
+        assert!(decompress(&[1, 2], &compressed, 4097).is_err());
+    }
+
+    #[test]
+    fn test_oversized_compressed_message_variant_is_rejected() {
+        // A small compressed frame, which decompresses beyond the maximum size of a `PeerRequest`.
+        let mut payload = BytesMut::new();
+        payload.put_u8(PAYLOAD_COMPRESSED);
+        payload.put_u16_le(Message::<CurrentNetwork>::PeerRequest(PeerRequest).id());
+        let decompressed = vec![0u8; maximum_size::<CurrentNetwork>(MessageKind::PeerRequest)];
+        payload.extend_from_slice(&zstd::bulk::compress(&decompressed, COMPRESSION_LEVEL).unwrap());
+
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(payload.len() as u32);
+        buf.extend_from_slice(&payload);
+        let mut decoder = MessageCodec::<CurrentNetwork>::default();
+        decoder.enable_compression();
+        assert!(decoder.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_checksummed_roundtrip() {
+        let ping = Message::Ping(Ping::<CurrentNetwork>::new(crate::NodeType::Client, None));
+        let mut encoder = MessageCodec::<CurrentNetwork>::default();
+        encoder.enable_checksum();
+        let mut decoder = MessageCodec::<CurrentNetwork>::default();
+        decoder.enable_checksum();
+
+        let mut buf = BytesMut::new();
+        encoder.encode(ping.clone(), &mut buf).unwrap();
+        assert_eq!(decoder.decode(&mut buf).unwrap().unwrap(), ping);
+    }
+
+    #[test]
+    fn test_corrupted_frame_is_rejected() {
+        let ping = Message::Ping(Ping::<CurrentNetwork>::new(crate::NodeType::Client, None));
+        let mut encoder = MessageCodec::<CurrentNetwork>::default();
+        encoder.enable_checksum();
+        let mut decoder = MessageCodec::<CurrentNetwork>::default();
+        decoder.enable_checksum();
+
+        // Flip a bit in the payload, after the length prefix and the checksum.
+        let mut buf = BytesMut::new();
+        encoder.encode(ping, &mut buf).unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 1;
+        assert!(decoder.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_traffic_is_tracked_by_message_kind() {
+        let ping = Message::Ping(Ping::<CurrentNetwork>::new(crate::NodeType::Client, None));
+        let traffic = Arc::new(MessageTraffic::default());
+        let mut encoder = MessageCodec::<CurrentNetwork>::default();
+        encoder.track_traffic(traffic.clone());
+        let mut decoder = MessageCodec::<CurrentNetwork>::default();
+        decoder.track_traffic(traffic.clone());
+
+        let mut buf = BytesMut::new();
+        encoder.encode(ping.clone(), &mut buf).unwrap();
+        let num_bytes = buf.len() as u64;
+        decoder.decode(&mut buf).unwrap().unwrap();
+
+        let counts = traffic.counts();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts["Ping"], crate::TrafficCounts {
+            messages_sent: 1,
+            bytes_sent: num_bytes,
+            messages_received: 1,
+            bytes_received: num_bytes
+        });
+    }
+
+    #[test]
+    fn test_small_message_is_not_compressed() {
+        let ping = Message::Ping(Ping::<CurrentNetwork>::new(crate::NodeType::Client, None));
+        // The uncompressed payload is prefixed with a single byte.
+        let mut buf = BytesMut::new();
+        MessageCodec::<CurrentNetwork>::default().encode(ping.clone(), &mut buf).unwrap();
+        let (num_bytes, decoded) = compressed_roundtrip(ping.clone());
+        assert_eq!(num_bytes, buf.len() + 1);
+        assert_eq!(decoded, ping);
+    }
+}
@@ -49,6 +49,12 @@ pub enum DisconnectReason {
     YouNeedToSyncFirst,
     /// The peer's listening port is closed.
     YourPortIsClosed(u16),
+    /// The peer sent a message with a malformed payload.
+    MalformedMessage,
+    /// The peer sent a response that was not requested.
+    UnsolicitedResponse,
+    /// The peer sent invalid block locators.
+    InvalidBlockLocators,
 }
 
 impl ToBytes for DisconnectReason {
@@ -72,6 +78,9 @@ impl ToBytes for DisconnectReason {
                 14u8.write_le(&mut writer)?;
                 port.write_le(writer)
             }
+            Self::MalformedMessage => 15u8.write_le(writer),
+            Self::UnsolicitedResponse => 16u8.write_le(writer),
+            Self::InvalidBlockLocators => 17u8.write_le(writer),
         }
     }
 }
@@ -97,6 +106,9 @@ impl FromBytes for DisconnectReason {
                 let port = u16::read_le(reader)?;
                 Ok(Self::YourPortIsClosed(port))
             }
+            15 => Ok(Self::MalformedMessage),
+            16 => Ok(Self::UnsolicitedResponse),
+            17 => Ok(Self::InvalidBlockLocators),
             _ => Err(error("Invalid disconnect reason")),
         }
     }
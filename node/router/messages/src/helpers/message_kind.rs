@@ -0,0 +1,122 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// The kind of a message, which identifies its variant regardless of its contents.
+/// The discriminant of each kind is the ID of the message on the wire.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum MessageKind {
+    BlockRequest = 0,
+    BlockResponse,
+    ChallengeRequest,
+    ChallengeResponse,
+    Disconnect,
+    PeerRequest,
+    PeerResponse,
+    Ping,
+    Pong,
+    PuzzleRequest,
+    PuzzleResponse,
+    UnconfirmedSolution,
+    UnconfirmedTransaction,
+    BlockAnnouncement,
+    HeaderRequest,
+    HeaderResponse,
+    TransactionAnnouncement,
+    TransactionRequest,
+    AddressAnnouncement,
+}
+
+impl MessageKind {
+    /// Returns the message kind with the given ID, or `None` if the ID is unknown.
+    pub const fn from_id(id: u16) -> Option<Self> {
+        match id {
+            0 => Some(Self::BlockRequest),
+            1 => Some(Self::BlockResponse),
+            2 => Some(Self::ChallengeRequest),
+            3 => Some(Self::ChallengeResponse),
+            4 => Some(Self::Disconnect),
+            5 => Some(Self::PeerRequest),
+            6 => Some(Self::PeerResponse),
+            7 => Some(Self::Ping),
+            8 => Some(Self::Pong),
+            9 => Some(Self::PuzzleRequest),
+            10 => Some(Self::PuzzleResponse),
+            11 => Some(Self::UnconfirmedSolution),
+            12 => Some(Self::UnconfirmedTransaction),
+            13 => Some(Self::BlockAnnouncement),
+            14 => Some(Self::HeaderRequest),
+            15 => Some(Self::HeaderResponse),
+            16 => Some(Self::TransactionAnnouncement),
+            17 => Some(Self::TransactionRequest),
+            18 => Some(Self::AddressAnnouncement),
+            _ => None,
+        }
+    }
+
+    /// Returns the message ID.
+    pub const fn id(self) -> u16 {
+        self as u16
+    }
+
+    /// Returns the name of the message kind.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::BlockRequest => "BlockRequest",
+            Self::BlockResponse => "BlockResponse",
+            Self::ChallengeRequest => "ChallengeRequest",
+            Self::ChallengeResponse => "ChallengeResponse",
+            Self::Disconnect => "Disconnect",
+            Self::PeerRequest => "PeerRequest",
+            Self::PeerResponse => "PeerResponse",
+            Self::Ping => "Ping",
+            Self::Pong => "Pong",
+            Self::PuzzleRequest => "PuzzleRequest",
+            Self::PuzzleResponse => "PuzzleResponse",
+            Self::UnconfirmedSolution => "UnconfirmedSolution",
+            Self::UnconfirmedTransaction => "UnconfirmedTransaction",
+            Self::BlockAnnouncement => "BlockAnnouncement",
+            Self::HeaderRequest => "HeaderRequest",
+            Self::HeaderResponse => "HeaderResponse",
+            Self::TransactionAnnouncement => "TransactionAnnouncement",
+            Self::TransactionRequest => "TransactionRequest",
+            Self::AddressAnnouncement => "AddressAnnouncement",
+        }
+    }
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_kind_ids() {
+        // The IDs are contiguous, and every ID maps back to its kind.
+        let mut id = 0;
+        while let Some(kind) = MessageKind::from_id(id) {
+            assert_eq!(kind.id(), id);
+            id += 1;
+        }
+        assert_eq!(id, MessageKind::AddressAnnouncement.id() + 1);
+        assert_eq!(MessageKind::from_id(u16::MAX), None);
+    }
+}
@@ -12,11 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod block_header;
+pub use block_header::BlockHeader;
+
+mod checksum;
+pub use checksum::crc32c;
+
 mod codec;
 pub use codec::MessageCodec;
 
 mod disconnect;
 pub use disconnect::DisconnectReason;
 
+mod message_kind;
+pub use message_kind::MessageKind;
+
+mod node_id;
+pub use node_id::NodeId;
+
 mod node_type;
 pub use node_type::*;
+
+mod peer_metadata;
+pub use peer_metadata::PeerMetadata;
+
+mod traffic;
+pub use traffic::{MessageTraffic, TrafficCounts};
@@ -0,0 +1,103 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::io;
+
+/// The metadata a node advertises to its peers after the handshake: the services it provides,
+/// and the lowest block height it serves, which is above the genesis block if the node pruned its history.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct PeerMetadata {
+    /// The service flags of the node.
+    pub services: u32,
+    /// The lowest block height served by the node.
+    pub lowest_block_height: u32,
+}
+
+impl PeerMetadata {
+    /// The service flag signalling that the node serves the blocks of its ledger.
+    pub const SERVICE_ARCHIVAL: u32 = 1 << 0;
+    /// The service flag signalling that the node operates a prover pool.
+    pub const SERVICE_POOL: u32 = 1 << 1;
+
+    /// Initializes a new instance of `PeerMetadata`.
+    pub const fn new(services: u32, lowest_block_height: u32) -> Self {
+        Self { services, lowest_block_height }
+    }
+
+    /// Returns `true` if the node provides all of the given services.
+    pub const fn provides(&self, services: u32) -> bool {
+        self.services & services == services
+    }
+
+    /// Returns `true` if the node serves the blocks of its ledger.
+    pub const fn is_archival(&self) -> bool {
+        self.provides(Self::SERVICE_ARCHIVAL)
+    }
+
+    /// Returns `true` if the node operates a prover pool.
+    pub const fn is_pool(&self) -> bool {
+        self.provides(Self::SERVICE_POOL)
+    }
+
+    /// Returns `true` if the node pruned the blocks below its lowest block height.
+    pub const fn is_pruned(&self) -> bool {
+        self.lowest_block_height > 0
+    }
+
+    /// Returns `true` if the node serves the block at the given height.
+    pub const fn serves_block(&self, height: u32) -> bool {
+        self.is_archival() && height >= self.lowest_block_height
+    }
+}
+
+impl ToBytes for PeerMetadata {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        self.services.write_le(&mut writer)?;
+        self.lowest_block_height.write_le(&mut writer)
+    }
+}
+
+impl FromBytes for PeerMetadata {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let services = u32::read_le(&mut reader)?;
+        let lowest_block_height = u32::read_le(&mut reader)?;
+        Ok(Self { services, lowest_block_height })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_metadata() {
+        let metadata = PeerMetadata::new(PeerMetadata::SERVICE_ARCHIVAL, 0);
+        assert!(metadata.is_archival());
+        assert!(!metadata.is_pool());
+        assert!(!metadata.is_pruned());
+        assert!(metadata.serves_block(0));
+
+        // A pruned node only serves the blocks at or above its lowest block height.
+        let metadata = PeerMetadata::new(PeerMetadata::SERVICE_ARCHIVAL | PeerMetadata::SERVICE_POOL, 100);
+        assert!(metadata.is_pool());
+        assert!(metadata.is_pruned());
+        assert!(!metadata.serves_block(99));
+        assert!(metadata.serves_block(100));
+
+        // A node which is not archival does not serve any block.
+        assert!(!PeerMetadata::default().serves_block(0));
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// The number of messages and bytes of one message kind, exchanged with a peer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TrafficCounts {
+    /// The number of messages sent to the peer.
+    pub messages_sent: u64,
+    /// The number of bytes sent to the peer.
+    pub bytes_sent: u64,
+    /// The number of messages received from the peer.
+    pub messages_received: u64,
+    /// The number of bytes received from the peer.
+    pub bytes_received: u64,
+}
+
+/// The traffic of a connection, by message kind. It is shared by the reading and the writing codec of the connection,
+/// so that the per-peer figures are available without labelling the metrics by peer.
+#[derive(Debug, Default)]
+pub struct MessageTraffic(Mutex<IndexMap<&'static str, TrafficCounts>>);
+
+impl MessageTraffic {
+    /// Records a message of the given kind and size, sent to the peer.
+    pub fn record_sent(&self, kind: &'static str, num_bytes: usize) {
+        let mut traffic = self.0.lock();
+        let counts = traffic.entry(kind).or_default();
+        counts.messages_sent += 1;
+        counts.bytes_sent += num_bytes as u64;
+    }
+
+    /// Records a message of the given kind and size, received from the peer.
+    pub fn record_received(&self, kind: &'static str, num_bytes: usize) {
+        let mut traffic = self.0.lock();
+        let counts = traffic.entry(kind).or_default();
+        counts.messages_received += 1;
+        counts.bytes_received += num_bytes as u64;
+    }
+
+    /// Returns the traffic of the connection, by message kind.
+    pub fn counts(&self) -> IndexMap<&'static str, TrafficCounts> {
+        self.0.lock().clone()
+    }
+}
@@ -20,6 +20,12 @@ extern crate tracing;
 pub mod helpers;
 pub use helpers::*;
 
+mod address_announcement;
+pub use address_announcement::AddressAnnouncement;
+
+mod block_announcement;
+pub use block_announcement::BlockAnnouncement;
+
 mod block_request;
 pub use block_request::BlockRequest;
 
@@ -35,6 +41,12 @@ pub use challenge_response::ChallengeResponse;
 mod disconnect;
 pub use disconnect::Disconnect;
 
+mod header_request;
+pub use header_request::HeaderRequest;
+
+mod header_response;
+pub use header_response::HeaderResponse;
+
 mod peer_request;
 pub use peer_request::PeerRequest;
 
@@ -53,24 +65,33 @@ pub use puzzle_request::PuzzleRequest;
 mod puzzle_response;
 pub use puzzle_response::PuzzleResponse;
 
+mod transaction_announcement;
+pub use transaction_announcement::TransactionAnnouncement;
+
+mod transaction_request;
+pub use transaction_request::TransactionRequest;
+
 mod unconfirmed_solution;
 pub use unconfirmed_solution::UnconfirmedSolution;
 
 mod unconfirmed_transaction;
 pub use unconfirmed_transaction::UnconfirmedTransaction;
 
-pub use snarkos_node_bft_events::DataBlocks;
+pub use snarkos_node_bft_events::{DataBlocks, NoiseHandshakeCodec, NoiseTransport, NOISE_HANDSHAKE_TYPE};
 
 use snarkos_node_sync_locators::BlockLocators;
-use snarkvm::prelude::{
-    block::{Header, Transaction},
-    coinbase::{EpochChallenge, ProverSolution, PuzzleCommitment},
-    error,
-    Address,
-    FromBytes,
-    Network,
-    Signature,
-    ToBytes,
+use snarkvm::{
+    ledger::narwhal::Data,
+    prelude::{
+        block::{Header, Transaction},
+        coinbase::{EpochChallenge, ProverSolution, PuzzleCommitment},
+        error,
+        Address,
+        FromBytes,
+        Network,
+        Signature,
+        ToBytes,
+    },
 };
 
 use std::{
@@ -101,6 +122,12 @@ pub enum Message<N: Network> {
     PuzzleResponse(PuzzleResponse<N>),
     UnconfirmedSolution(UnconfirmedSolution<N>),
     UnconfirmedTransaction(UnconfirmedTransaction<N>),
+    BlockAnnouncement(BlockAnnouncement<N>),
+    HeaderRequest(HeaderRequest),
+    HeaderResponse(HeaderResponse<N>),
+    TransactionAnnouncement(TransactionAnnouncement<N>),
+    TransactionRequest(TransactionRequest<N>),
+    AddressAnnouncement(AddressAnnouncement),
 }
 
 impl<N: Network> From<DisconnectReason> for Message<N> {
@@ -111,7 +138,16 @@ impl<N: Network> From<DisconnectReason> for Message<N> {
 
 impl<N: Network> Message<N> {
     /// The version of the network protocol; it can be incremented in order to force users to update.
-    pub const VERSION: u32 = 13;
+    pub const VERSION: u32 = 16;
+    /// The oldest version of the network protocol accepted from peers, which is the last released version,
+    /// so that the peers which did not update yet stay connected while the new version is rolled out.
+    /// note: This must be pinned to a released version, as the unreleased versions in between were never deployed.
+    pub const MINIMUM_VERSION: u32 = 13;
+
+    /// Returns `true` if the messages of the given protocol version can be parsed.
+    pub const fn is_compatible_version(version: u32) -> bool {
+        version >= Self::MINIMUM_VERSION
+    }
 
     /// Returns the message name.
     #[inline]
@@ -130,30 +166,81 @@ impl<N: Network> Message<N> {
             Self::PuzzleResponse(message) => message.name(),
             Self::UnconfirmedSolution(message) => message.name(),
             Self::UnconfirmedTransaction(message) => message.name(),
+            Self::BlockAnnouncement(message) => message.name(),
+            Self::HeaderRequest(message) => message.name(),
+            Self::HeaderResponse(message) => message.name(),
+            Self::TransactionAnnouncement(message) => message.name(),
+            Self::TransactionRequest(message) => message.name(),
+            Self::AddressAnnouncement(message) => message.name(),
+        }
+    }
+
+    /// Returns the kind of the message, which (unlike the message name) does not depend on its contents.
+    #[inline]
+    pub fn kind(&self) -> MessageKind {
+        match self {
+            Self::BlockRequest(..) => MessageKind::BlockRequest,
+            Self::BlockResponse(..) => MessageKind::BlockResponse,
+            Self::ChallengeRequest(..) => MessageKind::ChallengeRequest,
+            Self::ChallengeResponse(..) => MessageKind::ChallengeResponse,
+            Self::Disconnect(..) => MessageKind::Disconnect,
+            Self::PeerRequest(..) => MessageKind::PeerRequest,
+            Self::PeerResponse(..) => MessageKind::PeerResponse,
+            Self::Ping(..) => MessageKind::Ping,
+            Self::Pong(..) => MessageKind::Pong,
+            Self::PuzzleRequest(..) => MessageKind::PuzzleRequest,
+            Self::PuzzleResponse(..) => MessageKind::PuzzleResponse,
+            Self::UnconfirmedSolution(..) => MessageKind::UnconfirmedSolution,
+            Self::UnconfirmedTransaction(..) => MessageKind::UnconfirmedTransaction,
+            Self::BlockAnnouncement(..) => MessageKind::BlockAnnouncement,
+            Self::HeaderRequest(..) => MessageKind::HeaderRequest,
+            Self::HeaderResponse(..) => MessageKind::HeaderResponse,
+            Self::TransactionAnnouncement(..) => MessageKind::TransactionAnnouncement,
+            Self::TransactionRequest(..) => MessageKind::TransactionRequest,
+            Self::AddressAnnouncement(..) => MessageKind::AddressAnnouncement,
         }
     }
 
     /// Returns the message ID.
     #[inline]
     pub fn id(&self) -> u16 {
+        self.kind().id()
+    }
+
+    /// Returns `true` if the message relays unconfirmed solutions or transactions. Unlike the consensus messages,
+    /// these are propagated by many peers, and may therefore be dropped for a peer that fails to keep up.
+    pub fn is_relay(&self) -> bool {
+        matches!(
+            self,
+            Self::UnconfirmedSolution(..) | Self::UnconfirmedTransaction(..) | Self::TransactionAnnouncement(..)
+        )
+    }
+
+    /// Serializes the deferred payload of the message, if it is not serialized yet.
+    /// This ensures a message that is sent to many peers is serialized once only, instead of once per peer,
+    /// and that cloning the message for each peer does not copy its payload.
+    pub fn serialize_payload(&mut self) -> io::Result<()> {
         match self {
-            Self::BlockRequest(..) => 0,
-            Self::BlockResponse(..) => 1,
-            Self::ChallengeRequest(..) => 2,
-            Self::ChallengeResponse(..) => 3,
-            Self::Disconnect(..) => 4,
-            Self::PeerRequest(..) => 5,
-            Self::PeerResponse(..) => 6,
-            Self::Ping(..) => 7,
-            Self::Pong(..) => 8,
-            Self::PuzzleRequest(..) => 9,
-            Self::PuzzleResponse(..) => 10,
-            Self::UnconfirmedSolution(..) => 11,
-            Self::UnconfirmedTransaction(..) => 12,
+            Self::BlockResponse(message) => serialize_data(&mut message.blocks),
+            Self::ChallengeResponse(message) => serialize_data(&mut message.signature),
+            Self::PuzzleResponse(message) => serialize_data(&mut message.block_header),
+            Self::UnconfirmedSolution(message) => serialize_data(&mut message.solution),
+            Self::UnconfirmedTransaction(message) => serialize_data(&mut message.transaction),
+            _ => Ok(()),
         }
     }
 }
 
+/// Replaces the given deferred object with its serialized form, if it is not serialized yet.
+fn serialize_data<T: FromBytes + ToBytes + Send + 'static>(data: &mut Data<T>) -> io::Result<()> {
+    if let Data::Object(object) = data {
+        let mut bytes = Vec::new();
+        object.write_le(&mut bytes)?;
+        *data = Data::Buffer(bytes.into());
+    }
+    Ok(())
+}
+
 impl<N: Network> ToBytes for Message<N> {
     fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
         self.id().write_le(&mut writer)?;
@@ -172,6 +259,12 @@ impl<N: Network> ToBytes for Message<N> {
             Self::PuzzleResponse(message) => message.write_le(writer),
             Self::UnconfirmedSolution(message) => message.write_le(writer),
             Self::UnconfirmedTransaction(message) => message.write_le(writer),
+            Self::BlockAnnouncement(message) => message.write_le(writer),
+            Self::HeaderRequest(message) => message.write_le(writer),
+            Self::HeaderResponse(message) => message.write_le(writer),
+            Self::TransactionAnnouncement(message) => message.write_le(writer),
+            Self::TransactionRequest(message) => message.write_le(writer),
+            Self::AddressAnnouncement(message) => message.write_le(writer),
         }
     }
 }
@@ -184,21 +277,33 @@ impl<N: Network> FromBytes for Message<N> {
         let id = u16::from_le_bytes(id_bytes);
 
         // Deserialize the data field.
-        let message = match id {
-            0 => Self::BlockRequest(BlockRequest::read_le(&mut reader)?),
-            1 => Self::BlockResponse(BlockResponse::read_le(&mut reader)?),
-            2 => Self::ChallengeRequest(ChallengeRequest::read_le(&mut reader)?),
-            3 => Self::ChallengeResponse(ChallengeResponse::read_le(&mut reader)?),
-            4 => Self::Disconnect(Disconnect::read_le(&mut reader)?),
-            5 => Self::PeerRequest(PeerRequest::read_le(&mut reader)?),
-            6 => Self::PeerResponse(PeerResponse::read_le(&mut reader)?),
-            7 => Self::Ping(Ping::read_le(&mut reader)?),
-            8 => Self::Pong(Pong::read_le(&mut reader)?),
-            9 => Self::PuzzleRequest(PuzzleRequest::read_le(&mut reader)?),
-            10 => Self::PuzzleResponse(PuzzleResponse::read_le(&mut reader)?),
-            11 => Self::UnconfirmedSolution(UnconfirmedSolution::read_le(&mut reader)?),
-            12 => Self::UnconfirmedTransaction(UnconfirmedTransaction::read_le(&mut reader)?),
-            13.. => return Err(error("Unknown message ID {id}")),
+        let Some(kind) = MessageKind::from_id(id) else {
+            return Err(error(format!("Unknown message ID {id}")));
+        };
+        let message = match kind {
+            MessageKind::BlockRequest => Self::BlockRequest(BlockRequest::read_le(&mut reader)?),
+            MessageKind::BlockResponse => Self::BlockResponse(BlockResponse::read_le(&mut reader)?),
+            MessageKind::ChallengeRequest => Self::ChallengeRequest(ChallengeRequest::read_le(&mut reader)?),
+            MessageKind::ChallengeResponse => Self::ChallengeResponse(ChallengeResponse::read_le(&mut reader)?),
+            MessageKind::Disconnect => Self::Disconnect(Disconnect::read_le(&mut reader)?),
+            MessageKind::PeerRequest => Self::PeerRequest(PeerRequest::read_le(&mut reader)?),
+            MessageKind::PeerResponse => Self::PeerResponse(PeerResponse::read_le(&mut reader)?),
+            MessageKind::Ping => Self::Ping(Ping::read_le(&mut reader)?),
+            MessageKind::Pong => Self::Pong(Pong::read_le(&mut reader)?),
+            MessageKind::PuzzleRequest => Self::PuzzleRequest(PuzzleRequest::read_le(&mut reader)?),
+            MessageKind::PuzzleResponse => Self::PuzzleResponse(PuzzleResponse::read_le(&mut reader)?),
+            MessageKind::UnconfirmedSolution => Self::UnconfirmedSolution(UnconfirmedSolution::read_le(&mut reader)?),
+            MessageKind::UnconfirmedTransaction => {
+                Self::UnconfirmedTransaction(UnconfirmedTransaction::read_le(&mut reader)?)
+            }
+            MessageKind::BlockAnnouncement => Self::BlockAnnouncement(BlockAnnouncement::read_le(&mut reader)?),
+            MessageKind::HeaderRequest => Self::HeaderRequest(HeaderRequest::read_le(&mut reader)?),
+            MessageKind::HeaderResponse => Self::HeaderResponse(HeaderResponse::read_le(&mut reader)?),
+            MessageKind::TransactionAnnouncement => {
+                Self::TransactionAnnouncement(TransactionAnnouncement::read_le(&mut reader)?)
+            }
+            MessageKind::TransactionRequest => Self::TransactionRequest(TransactionRequest::read_le(&mut reader)?),
+            MessageKind::AddressAnnouncement => Self::AddressAnnouncement(AddressAnnouncement::read_le(&mut reader)?),
         };
 
         // Ensure that there are no "dangling" bytes.
@@ -14,6 +14,7 @@
 
 use super::*;
 
+use snarkos_node_sync_locators::{CHECKPOINT_INTERVAL, NUM_RECENT_BLOCKS};
 use snarkvm::prelude::{FromBytes, ToBytes};
 
 use indexmap::IndexMap;
@@ -24,6 +25,8 @@ pub struct Ping<N: Network> {
     pub version: u32,
     pub node_type: NodeType,
     pub block_locators: Option<BlockLocators<N>>,
+    /// The metadata of the sender, which is only sent to the peers supporting the peer metadata.
+    pub metadata: Option<PeerMetadata>,
 }
 
 impl<N: Network> MessageTrait for Ping<N> {
@@ -55,6 +58,9 @@ impl<N: Network> ToBytes for Ping<N> {
         } else {
             0u8.write_le(&mut writer)?;
         }
+        if let Some(metadata) = &self.metadata {
+            metadata.write_le(&mut writer)?;
+        }
 
         Ok(())
     }
@@ -67,11 +73,14 @@ impl<N: Network> FromBytes for Ping<N> {
 
         let selector = u8::read_le(&mut reader)?;
 
-        if selector == 0 {
-            Ok(Self { version, node_type, block_locators: None })
+        let block_locators = if selector == 0 {
+            None
         } else if selector == 1 {
             let mut recents = IndexMap::new();
             let num_recents = u32::read_le(&mut reader)?;
+            if num_recents > Self::MAXIMUM_NUMBER_OF_RECENTS {
+                return Err(error(format!("Too many recent block locators in ping message ({num_recents})")));
+            }
             for _ in 0..num_recents {
                 let height = u32::read_le(&mut reader)?;
                 let hash = N::BlockHash::read_le(&mut reader)?;
@@ -80,46 +89,90 @@ impl<N: Network> FromBytes for Ping<N> {
 
             let mut checkpoints = IndexMap::new();
             let num_checkpoints = u32::read_le(&mut reader)?;
+            if num_checkpoints > Self::MAXIMUM_NUMBER_OF_CHECKPOINTS {
+                return Err(error(format!("Too many checkpoint block locators in ping message ({num_checkpoints})")));
+            }
             for _ in 0..num_checkpoints {
                 let height = u32::read_le(&mut reader)?;
                 let hash = N::BlockHash::read_le(&mut reader)?;
                 checkpoints.insert(height, hash);
             }
 
-            let block_locators = Some(BlockLocators { recents, checkpoints });
-
-            Ok(Self { version, node_type, block_locators })
+            Some(BlockLocators { recents, checkpoints })
         } else {
-            Err(error("Invalid selector of optional block locators in ping message"))
-        }
+            return Err(error("Invalid selector of optional block locators in ping message"));
+        };
+
+        // The metadata is trailing, so that the pings to older peers remain unchanged.
+        let mut metadata = [0u8; 8];
+        let metadata = match reader.read(&mut metadata[..1])? {
+            0 => None,
+            _ => {
+                reader.read_exact(&mut metadata[1..])?;
+                Some(PeerMetadata::read_le(&metadata[..])?)
+            }
+        };
+
+        Ok(Self { version, node_type, block_locators, metadata })
     }
 }
 
 impl<N: Network> Ping<N> {
+    /// The maximum number of recent block locators.
+    pub const MAXIMUM_NUMBER_OF_RECENTS: u32 = NUM_RECENT_BLOCKS as u32;
+    /// The maximum block height covered by the checkpoint block locators, which the chain does not reach
+    /// for decades (e.g. over 25 years at 3-second blocks). It bounds the size of a ping,
+    /// and can be raised with a new message version.
+    pub const MAXIMUM_CHECKPOINT_HEIGHT: u32 = 1 << 28;
+    /// The maximum number of checkpoint block locators, which is one per checkpoint interval of the block heights.
+    pub const MAXIMUM_NUMBER_OF_CHECKPOINTS: u32 = Self::MAXIMUM_CHECKPOINT_HEIGHT / CHECKPOINT_INTERVAL + 1;
+
     pub fn new(node_type: NodeType, block_locators: Option<BlockLocators<N>>) -> Self {
-        Self { version: <Message<N>>::VERSION, node_type, block_locators }
+        Self { version: <Message<N>>::VERSION, node_type, block_locators, metadata: None }
+    }
+
+    /// Returns the ping with the given metadata of the sender.
+    pub fn with_metadata(mut self, metadata: PeerMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
     }
 }
 
 #[cfg(test)]
 pub mod prop_tests {
-    use crate::{challenge_request::prop_tests::any_node_type, Ping};
+    use crate::{challenge_request::prop_tests::any_node_type, Message, NodeType, PeerMetadata, Ping};
     use snarkos_node_sync_locators::{test_helpers::sample_block_locators, BlockLocators};
-    use snarkvm::utilities::{FromBytes, ToBytes};
+    use snarkvm::{
+        prelude::{Field, Network},
+        utilities::{FromBytes, ToBytes},
+    };
 
     use bytes::{Buf, BufMut, BytesMut};
-    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use proptest::{
+        option,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
     use test_strategy::proptest;
 
     type CurrentNetwork = snarkvm::prelude::Testnet3;
 
     pub fn any_block_locators() -> BoxedStrategy<BlockLocators<CurrentNetwork>> {
-        any::<u32>().prop_map(sample_block_locators).boxed()
+        (0..=Ping::<CurrentNetwork>::MAXIMUM_CHECKPOINT_HEIGHT).prop_map(sample_block_locators).boxed()
+    }
+
+    pub fn any_metadata() -> BoxedStrategy<Option<PeerMetadata>> {
+        option::of((any::<u32>(), any::<u32>()).prop_map(|(services, height)| PeerMetadata::new(services, height)))
+            .boxed()
     }
 
     pub fn any_ping() -> BoxedStrategy<Ping<CurrentNetwork>> {
-        (any::<u32>(), any_block_locators(), any_node_type())
-            .prop_map(|(version, bls, node_type)| Ping { version, block_locators: Some(bls), node_type })
+        (any::<u32>(), any_block_locators(), any_node_type(), any_metadata())
+            .prop_map(|(version, bls, node_type, metadata)| Ping {
+                version,
+                block_locators: Some(bls),
+                node_type,
+                metadata,
+            })
             .boxed()
     }
 
@@ -130,4 +183,21 @@ pub mod prop_tests {
         let decoded = Ping::<CurrentNetwork>::read_le(&mut bytes.into_inner().reader()).unwrap();
         assert_eq!(ping, decoded);
     }
+
+    #[test]
+    fn test_ping_with_too_many_locators_is_rejected() {
+        // Serialize a ping with one recent block locator more than the maximum.
+        let num_recents = Ping::<CurrentNetwork>::MAXIMUM_NUMBER_OF_RECENTS + 1;
+        let mut bytes = BytesMut::default().writer();
+        Message::<CurrentNetwork>::VERSION.write_le(&mut bytes).unwrap();
+        NodeType::Client.write_le(&mut bytes).unwrap();
+        1u8.write_le(&mut bytes).unwrap();
+        num_recents.write_le(&mut bytes).unwrap();
+        for height in 0..num_recents {
+            height.write_le(&mut bytes).unwrap();
+            <CurrentNetwork as Network>::BlockHash::from(Field::from_u32(height)).write_le(&mut bytes).unwrap();
+        }
+        0u32.write_le(&mut bytes).unwrap();
+        assert!(Ping::<CurrentNetwork>::read_le(&mut bytes.into_inner().reader()).is_err());
+    }
 }
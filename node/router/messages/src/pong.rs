@@ -21,6 +21,22 @@ use std::borrow::Cow;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Pong {
     pub is_fork: Option<bool>,
+    /// The listening address of the recipient, as observed by the sender, which is only sent to the peers
+    /// supporting the address announcements.
+    pub observed_addr: Option<SocketAddr>,
+}
+
+impl Pong {
+    /// Initializes a new `Pong` message.
+    pub fn new(is_fork: Option<bool>) -> Self {
+        Self { is_fork, observed_addr: None }
+    }
+
+    /// Returns the pong with the given listening address of the recipient, as observed by the sender.
+    pub fn with_observed_addr(mut self, observed_addr: SocketAddr) -> Self {
+        self.observed_addr = Some(observed_addr);
+        self
+    }
 }
 
 impl MessageTrait for Pong {
@@ -32,14 +48,19 @@ impl MessageTrait for Pong {
 }
 
 impl ToBytes for Pong {
-    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
         let serialized_is_fork: u8 = match self.is_fork {
             Some(true) => 0,
             Some(false) => 1,
             None => 2,
         };
 
-        serialized_is_fork.write_le(writer)
+        serialized_is_fork.write_le(&mut writer)?;
+        if let Some(observed_addr) = &self.observed_addr {
+            1u8.write_le(&mut writer)?;
+            observed_addr.write_le(&mut writer)?;
+        }
+        Ok(())
     }
 }
 
@@ -51,14 +72,21 @@ impl FromBytes for Pong {
             2 => None,
             _ => return Err(error("Invalid 'Pong' message")),
         };
+        // The observed address is trailing, so that the pongs to older peers remain unchanged.
+        let mut selector = [0u8; 1];
+        let observed_addr = match reader.read(&mut selector)? {
+            0 => None,
+            _ if selector[0] == 1 => Some(SocketAddr::read_le(&mut reader)?),
+            _ => return Err(error("Invalid selector of the observed address in 'Pong' message")),
+        };
 
-        Ok(Self { is_fork })
+        Ok(Self { is_fork, observed_addr })
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use crate::Pong;
+    use crate::{peer_response::prop_tests::any_valid_socket_addr, Pong};
     use snarkvm::utilities::{FromBytes, ToBytes};
 
     use bytes::{Buf, BufMut, BytesMut};
@@ -69,7 +97,9 @@ pub mod tests {
     use test_strategy::proptest;
 
     pub fn any_pong() -> BoxedStrategy<Pong> {
-        of(any::<bool>()).prop_map(|is_fork| Pong { is_fork }).boxed()
+        (of(any::<bool>()), of(any_valid_socket_addr()))
+            .prop_map(|(is_fork, observed_addr)| Pong { is_fork, observed_addr })
+            .boxed()
     }
 
     #[proptest]
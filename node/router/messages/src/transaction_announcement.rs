@@ -0,0 +1,118 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionAnnouncement<N: Network> {
+    /// The IDs of the unconfirmed transactions.
+    pub transaction_ids: Vec<N::TransactionID>,
+}
+
+impl<N: Network> TransactionAnnouncement<N> {
+    /// The maximum number of transaction IDs in a transaction announcement or request.
+    pub const MAXIMUM_NUMBER_OF_TRANSACTIONS: usize = 256;
+}
+
+impl<N: Network> MessageTrait for TransactionAnnouncement<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "TransactionAnnouncement".into()
+    }
+}
+
+impl<N: Network> ToBytes for TransactionAnnouncement<N> {
+    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        write_transaction_ids::<N, W>(&self.transaction_ids, writer)
+    }
+}
+
+impl<N: Network> FromBytes for TransactionAnnouncement<N> {
+    fn read_le<R: io::Read>(reader: R) -> io::Result<Self> {
+        Ok(Self { transaction_ids: read_transaction_ids::<N, R>(reader)? })
+    }
+}
+
+/// Writes the given transaction IDs, prefixed with their number.
+pub(crate) fn write_transaction_ids<N: Network, W: io::Write>(
+    transaction_ids: &[N::TransactionID],
+    mut writer: W,
+) -> io::Result<()> {
+    // Ensure that the number of transaction IDs is within the allowed range.
+    if transaction_ids.len() > TransactionAnnouncement::<N>::MAXIMUM_NUMBER_OF_TRANSACTIONS {
+        return Err(error("Message exceeds maximum number of transactions"));
+    }
+    (transaction_ids.len() as u16).write_le(&mut writer)?;
+    transaction_ids.iter().try_for_each(|transaction_id| transaction_id.write_le(&mut writer))
+}
+
+/// Reads the transaction IDs, prefixed with their number.
+pub(crate) fn read_transaction_ids<N: Network, R: io::Read>(mut reader: R) -> io::Result<Vec<N::TransactionID>> {
+    let num_transactions = u16::read_le(&mut reader)?;
+    // Ensure that the number of transaction IDs is within the allowed range.
+    if num_transactions as usize > TransactionAnnouncement::<N>::MAXIMUM_NUMBER_OF_TRANSACTIONS {
+        return Err(error("Message exceeds maximum number of transactions"));
+    }
+    (0..num_transactions).map(|_| N::TransactionID::read_le(&mut reader)).collect()
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::TransactionAnnouncement;
+    use snarkvm::{
+        prelude::{Field, Network, TestRng, Uniform},
+        utilities::{FromBytes, ToBytes},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    pub fn any_transaction_id() -> BoxedStrategy<<CurrentNetwork as Network>::TransactionID> {
+        any::<u64>().prop_map(|seed| Field::<CurrentNetwork>::rand(&mut TestRng::fixed(seed)).into()).boxed()
+    }
+
+    pub fn any_transaction_announcement() -> BoxedStrategy<TransactionAnnouncement<CurrentNetwork>> {
+        vec(any_transaction_id(), 0..10).prop_map(|transaction_ids| TransactionAnnouncement { transaction_ids }).boxed()
+    }
+
+    #[proptest]
+    fn transaction_announcement_roundtrip(
+        #[strategy(any_transaction_announcement())] original: TransactionAnnouncement<CurrentNetwork>,
+    ) {
+        let mut buf = BytesMut::default().writer();
+        TransactionAnnouncement::write_le(&original, &mut buf).unwrap();
+
+        let deserialized = TransactionAnnouncement::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_oversized_transaction_announcement_is_rejected() {
+        let num_transactions = TransactionAnnouncement::<CurrentNetwork>::MAXIMUM_NUMBER_OF_TRANSACTIONS + 1;
+        let transaction_ids = vec![Default::default(); num_transactions];
+        let mut buf = BytesMut::default().writer();
+        assert!(TransactionAnnouncement::<CurrentNetwork> { transaction_ids }.write_le(&mut buf).is_err());
+    }
+}
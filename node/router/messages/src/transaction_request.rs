@@ -0,0 +1,74 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::transaction_announcement::{read_transaction_ids, write_transaction_ids};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionRequest<N: Network> {
+    /// The IDs of the announced transactions, which are requested in full.
+    pub transaction_ids: Vec<N::TransactionID>,
+}
+
+impl<N: Network> MessageTrait for TransactionRequest<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "TransactionRequest".into()
+    }
+}
+
+impl<N: Network> ToBytes for TransactionRequest<N> {
+    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        write_transaction_ids::<N, W>(&self.transaction_ids, writer)
+    }
+}
+
+impl<N: Network> FromBytes for TransactionRequest<N> {
+    fn read_le<R: io::Read>(reader: R) -> io::Result<Self> {
+        Ok(Self { transaction_ids: read_transaction_ids::<N, R>(reader)? })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{transaction_announcement::prop_tests::any_transaction_id, TransactionRequest};
+    use snarkvm::utilities::{FromBytes, ToBytes};
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        prelude::{BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    pub fn any_transaction_request() -> BoxedStrategy<TransactionRequest<CurrentNetwork>> {
+        vec(any_transaction_id(), 0..10).prop_map(|transaction_ids| TransactionRequest { transaction_ids }).boxed()
+    }
+
+    #[proptest]
+    fn transaction_request_roundtrip(
+        #[strategy(any_transaction_request())] original: TransactionRequest<CurrentNetwork>,
+    ) {
+        let mut buf = BytesMut::default().writer();
+        TransactionRequest::write_le(&original, &mut buf).unwrap();
+
+        let deserialized = TransactionRequest::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
@@ -58,7 +58,7 @@ impl<N: Network> FromBytes for UnconfirmedTransaction<N> {
 
 #[cfg(test)]
 pub mod prop_tests {
-    use crate::{Transaction, UnconfirmedTransaction};
+    use crate::{Message, Transaction, UnconfirmedTransaction};
     use snarkvm::{
         ledger::{ledger_test_helpers::sample_fee_public_transaction, narwhal::Data},
         prelude::{FromBytes, TestRng, ToBytes},
@@ -100,4 +100,17 @@ pub mod prop_tests {
             deserialized.transaction.deserialize_blocking().unwrap(),
         );
     }
+
+    #[proptest]
+    fn unconfirmed_transaction_serialize_payload(
+        #[strategy(any_unconfirmed_transaction())] original: UnconfirmedTransaction<CurrentNetwork>,
+    ) {
+        let mut message = Message::UnconfirmedTransaction(original.clone());
+        message.serialize_payload().unwrap();
+
+        // Ensure the payload is serialized, and the message is serialized to the same bytes.
+        let Message::UnconfirmedTransaction(serialized) = &message else { unreachable!() };
+        assert!(matches!(serialized.transaction, Data::Buffer(_)));
+        assert_eq!(message.to_bytes_le().unwrap(), Message::UnconfirmedTransaction(original).to_bytes_le().unwrap());
+    }
 }
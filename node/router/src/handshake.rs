@@ -13,21 +13,37 @@
 // limitations under the License.
 
 use crate::{
-    messages::{ChallengeRequest, ChallengeResponse, DisconnectReason, Message, MessageCodec, MessageTrait},
+    messages::{
+        ChallengeRequest,
+        ChallengeResponse,
+        DisconnectReason,
+        Message,
+        MessageCodec,
+        MessageTrait,
+        NodeId,
+        NoiseHandshakeCodec,
+        NOISE_HANDSHAKE_TYPE,
+    },
+    NodeIdentity,
+    Offense,
     Peer,
     Router,
 };
 use snarkos_node_tcp::{ConnectionSide, Tcp, P2P};
 use snarkvm::{
     ledger::narwhal::Data,
-    prelude::{block::Header, error, Address, Network},
+    prelude::{block::Header, error, FromBytes, Network, Signature, ToBytes},
 };
 
 use anyhow::{bail, Result};
+use bytes::Bytes;
 use futures::SinkExt;
-use rand::{rngs::OsRng, Rng};
-use std::{io, net::SocketAddr};
-use tokio::net::TcpStream;
+use rand::Rng;
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_stream::StreamExt;
 use tokio_util::codec::Framed;
 
@@ -70,8 +86,8 @@ macro_rules! expect_message {
 }
 
 /// Send the given message to the peer.
-async fn send<N: Network>(
-    framed: &mut Framed<&mut TcpStream, MessageCodec<N>>,
+async fn send<N: Network, S: AsyncRead + AsyncWrite + Unpin>(
+    framed: &mut Framed<&mut S, MessageCodec<N>>,
     peer_addr: SocketAddr,
     message: Message<N>,
 ) -> io::Result<()> {
@@ -79,15 +95,35 @@ async fn send<N: Network>(
     framed.send(message).await
 }
 
+/// The domain separator of the signature which binds the static Noise key of a node to its account.
+const NOISE_STATIC_KEY_DOMAIN: &[u8] = b"snarkos-noise-static-key:";
+
+/// Receive a noise handshake message from the peer, and return its payload.
+async fn receive_noise<S: AsyncRead + AsyncWrite + Unpin>(
+    framed: &mut Framed<&mut S, NoiseHandshakeCodec>,
+    peer_addr: SocketAddr,
+) -> io::Result<Bytes> {
+    match framed.try_next().await? {
+        Some(payload) => {
+            trace!("Received a noise handshake message from '{peer_addr}'");
+            Ok(payload)
+        }
+        None => Err(error(format!("'{peer_addr}' disconnected during the noise handshake"))),
+    }
+}
+
 impl<N: Network> Router<N> {
-    /// Executes the handshake protocol.
-    pub async fn handshake<'a>(
+    /// Executes the handshake protocol over the given (plain TCP or TLS) stream.
+    /// The `local_addr` parameter is the local address of the connection, which tells apart the listener of
+    /// an inbound connection.
+    pub async fn handshake<'a, S: AsyncRead + AsyncWrite + Unpin + Send>(
         &'a self,
         peer_addr: SocketAddr,
-        stream: &'a mut TcpStream,
+        stream: &'a mut S,
         peer_side: ConnectionSide,
+        local_addr: Option<SocketAddr>,
         genesis_header: Header<N>,
-    ) -> io::Result<(SocketAddr, Framed<&mut TcpStream, MessageCodec<N>>)> {
+    ) -> io::Result<(SocketAddr, Framed<&'a mut S, MessageCodec<N>>)> {
         // If this is an inbound connection, we log it, but don't know the listening address yet.
         // Otherwise, we can immediately register the listening address.
         let mut peer_ip = if peer_side == ConnectionSide::Initiator {
@@ -98,17 +134,34 @@ impl<N: Network> Router<N> {
             Some(peer_addr)
         };
 
+        // The node ID of the peer, once it is verified and reserved.
+        let mut node_id = None;
+
         // Perform the handshake; we pass on a mutable reference to peer_ip in case the process is broken at any point in time.
         let handshake_result = if peer_side == ConnectionSide::Responder {
-            self.handshake_inner_initiator(peer_addr, &mut peer_ip, stream, genesis_header).await
+            self.handshake_inner_initiator(peer_addr, &mut peer_ip, &mut node_id, stream, genesis_header).await
         } else {
-            self.handshake_inner_responder(peer_addr, &mut peer_ip, stream, genesis_header).await
+            // Determine whether the peer connected through the validator listener.
+            let is_validator_listener = local_addr.map_or(false, |addr| self.is_validator_listener_addr(addr));
+            self.handshake_inner_responder(
+                peer_addr,
+                &mut peer_ip,
+                &mut node_id,
+                stream,
+                is_validator_listener,
+                genesis_header,
+            )
+            .await
         };
 
         // Remove the address from the collection of connecting peers (if the handshake got to the point where it's known).
         if let Some(ip) = peer_ip {
             self.connecting_peers.lock().remove(&ip);
         }
+        // Release the node ID of the peer, if it was reserved; on success, the peer is already connected.
+        if let Some(node_id) = node_id {
+            self.connecting_node_ids.lock().remove(&node_id);
+        }
 
         // If the handshake succeeded, announce it.
         if let Ok((ref peer_ip, _)) = handshake_result {
@@ -119,27 +172,35 @@ impl<N: Network> Router<N> {
     }
 
     /// The connection initiator side of the handshake.
-    async fn handshake_inner_initiator<'a>(
+    async fn handshake_inner_initiator<'a, S: AsyncRead + AsyncWrite + Unpin + Send>(
         &'a self,
         peer_addr: SocketAddr,
         peer_ip: &mut Option<SocketAddr>,
-        stream: &'a mut TcpStream,
+        node_id: &mut Option<NodeId>,
+        stream: &'a mut S,
         genesis_header: Header<N>,
-    ) -> io::Result<(SocketAddr, Framed<&mut TcpStream, MessageCodec<N>>)> {
+    ) -> io::Result<(SocketAddr, Framed<&'a mut S, MessageCodec<N>>)> {
         // This value is immediately guaranteed to be present, so it can be unwrapped.
         let peer_ip = peer_ip.unwrap();
         // Construct the stream.
         let mut framed = Framed::new(stream, MessageCodec::<N>::handshake());
 
         // Initialize an RNG.
-        let rng = &mut OsRng;
+        let rng = &mut self.rng();
 
         /* Step 1: Send the challenge request. */
 
         // Sample a random nonce.
         let our_nonce = rng.gen();
         // Send a challenge request to the peer.
-        let our_request = ChallengeRequest::new(self.local_ip().port(), self.node_type, self.address(), our_nonce);
+        let our_request = ChallengeRequest::new(
+            self.listener_port(),
+            self.node_type,
+            self.address(),
+            our_nonce,
+            self.features(),
+            Some(self.node_id()),
+        );
         send(&mut framed, peer_addr, Message::ChallengeRequest(our_request)).await?;
 
         /* Step 2: Receive the peer's challenge response followed by the challenge request. */
@@ -150,42 +211,71 @@ impl<N: Network> Router<N> {
         let peer_request = expect_message!(Message::ChallengeRequest, framed, peer_addr);
 
         // Verify the challenge response. If a disconnect reason was returned, send the disconnect message and abort.
-        if let Some(reason) = self
-            .verify_challenge_response(peer_addr, peer_request.address, peer_response, genesis_header, our_nonce)
-            .await
+        if let Some(reason) =
+            self.verify_challenge_response(peer_addr, &peer_request, peer_response, genesis_header, our_nonce).await
         {
+            self.record_offense(peer_ip, Offense::HandshakeViolation);
             send(&mut framed, peer_addr, reason.into()).await?;
             return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
         }
         // Verify the challenge request. If a disconnect reason was returned, send the disconnect message and abort.
+        // note: An outdated peer is disconnected without lowering its reputation, as it may not have updated yet.
         if let Some(reason) = self.verify_challenge_request(peer_addr, &peer_request) {
             send(&mut framed, peer_addr, reason.into()).await?;
             return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
         }
+        // Ensure the node ID of the peer is neither this node, nor the node ID of a connected or connecting peer.
+        if let Err(forbidden_message) = self.ensure_node_id_is_allowed(&peer_request, node_id) {
+            return Err(error(format!("{forbidden_message}")));
+        }
+
         /* Step 3: Send the challenge response. */
 
         // Sign the counterparty nonce.
         let Ok(our_signature) = self.account.sign_bytes(&peer_request.nonce.to_le_bytes(), rng) else {
             return Err(error(format!("Failed to sign the challenge request nonce from '{peer_addr}'")));
         };
+        // Sign the counterparty nonce with the node ID, if the peer is able to parse the signature.
+        let node_signature = (peer_request.version >= ChallengeRequest::<N>::IDENTITY_VERSION)
+            .then(|| self.identity.sign_nonce(peer_request.nonce));
         // Send the challenge response.
-        let our_response = ChallengeResponse { genesis_header, signature: Data::Object(our_signature) };
+        let our_response = ChallengeResponse { genesis_header, signature: Data::Object(our_signature), node_signature };
         send(&mut framed, peer_addr, Message::ChallengeResponse(our_response)).await?;
 
+        /* Step 4: Establish the encrypted connection, if both sides support it. */
+
+        let framed = self.noise_handshake(peer_addr, framed, &peer_request, ConnectionSide::Initiator).await?;
+        // Negotiate the features supported by both sides, and enable compression and checksums if they are among them.
+        let features = self.negotiate_features(&peer_request);
+        if features & ChallengeRequest::<N>::FEATURE_COMPRESSION != 0 {
+            self.compressed_peers.write().insert(peer_addr);
+        }
+        if features & ChallengeRequest::<N>::FEATURE_CHECKSUM != 0 {
+            self.checksummed_peers.write().insert(peer_addr);
+        }
+
         // Add the peer to the router.
-        self.insert_connected_peer(Peer::new(peer_ip, &peer_request), peer_addr);
+        let peer = Peer::new(peer_ip, ConnectionSide::Responder, &peer_request, features, false);
+        self.insert_connected_peer(peer, peer_addr);
 
         Ok((peer_ip, framed))
     }
 
     /// The connection responder side of the handshake.
-    async fn handshake_inner_responder<'a>(
+    async fn handshake_inner_responder<'a, S: AsyncRead + AsyncWrite + Unpin + Send>(
         &'a self,
         peer_addr: SocketAddr,
         peer_ip: &mut Option<SocketAddr>,
-        stream: &'a mut TcpStream,
+        node_id: &mut Option<NodeId>,
+        stream: &'a mut S,
+        is_validator_listener: bool,
         genesis_header: Header<N>,
-    ) -> io::Result<(SocketAddr, Framed<&mut TcpStream, MessageCodec<N>>)> {
+    ) -> io::Result<(SocketAddr, Framed<&'a mut S, MessageCodec<N>>)> {
+        // Ensure the connecting IP is allowed, before reading anything from the peer.
+        if let Err(forbidden_message) = self.ensure_ip_is_allowed(peer_addr.ip()) {
+            return Err(error(format!("{forbidden_message}")));
+        }
+
         // Construct the stream.
         let mut framed = Framed::new(stream, MessageCodec::<N>::handshake());
 
@@ -203,6 +293,7 @@ impl<N: Network> Router<N> {
             return Err(error(format!("{forbidden_message}")));
         }
         // Verify the challenge request. If a disconnect reason was returned, send the disconnect message and abort.
+        // note: An outdated peer is disconnected without lowering its reputation, as it may not have updated yet.
         if let Some(reason) = self.verify_challenge_request(peer_addr, &peer_request) {
             send(&mut framed, peer_addr, reason.into()).await?;
             return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
@@ -210,20 +301,31 @@ impl<N: Network> Router<N> {
         /* Step 2: Send the challenge response followed by own challenge request. */
 
         // Initialize an RNG.
-        let rng = &mut OsRng;
+        let rng = &mut self.rng();
 
         // Sign the counterparty nonce.
         let Ok(our_signature) = self.account.sign_bytes(&peer_request.nonce.to_le_bytes(), rng) else {
             return Err(error(format!("Failed to sign the challenge request nonce from '{peer_addr}'")));
         };
+        // Sign the counterparty nonce with the node ID, if the peer is able to parse the signature.
+        let node_signature = (peer_request.version >= ChallengeRequest::<N>::IDENTITY_VERSION)
+            .then(|| self.identity.sign_nonce(peer_request.nonce));
         // Send the challenge response.
-        let our_response = ChallengeResponse { genesis_header, signature: Data::Object(our_signature) };
+        let our_response = ChallengeResponse { genesis_header, signature: Data::Object(our_signature), node_signature };
         send(&mut framed, peer_addr, Message::ChallengeResponse(our_response)).await?;
 
         // Sample a random nonce.
         let our_nonce = rng.gen();
-        // Send the challenge request.
-        let our_request = ChallengeRequest::new(self.local_ip().port(), self.node_type, self.address(), our_nonce);
+        // Send the challenge request, in the format of the peer's protocol version if it is older.
+        let our_request = ChallengeRequest::new(
+            self.listener_port(),
+            self.node_type,
+            self.address(),
+            our_nonce,
+            self.features(),
+            Some(self.node_id()),
+        )
+        .with_version(peer_request.version);
         send(&mut framed, peer_addr, Message::ChallengeRequest(our_request)).await?;
 
         /* Step 3: Receive the challenge response. */
@@ -231,19 +333,143 @@ impl<N: Network> Router<N> {
         // Listen for the challenge response message.
         let peer_response = expect_message!(Message::ChallengeResponse, framed, peer_addr);
         // Verify the challenge response. If a disconnect reason was returned, send the disconnect message and abort.
-        if let Some(reason) = self
-            .verify_challenge_response(peer_addr, peer_request.address, peer_response, genesis_header, our_nonce)
-            .await
+        if let Some(reason) =
+            self.verify_challenge_response(peer_addr, &peer_request, peer_response, genesis_header, our_nonce).await
         {
+            self.record_offense(peer_ip, Offense::HandshakeViolation);
             send(&mut framed, peer_addr, reason.into()).await?;
             return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
         }
+        // Ensure the node ID of the peer is neither this node, nor the node ID of a connected or connecting peer.
+        if let Err(forbidden_message) = self.ensure_node_id_is_allowed(&peer_request, node_id) {
+            return Err(error(format!("{forbidden_message}")));
+        }
+        // Ensure the listener the peer connected through admits the peer.
+        if let Some(reason) = self.verify_listener(&peer_request, is_validator_listener) {
+            send(&mut framed, peer_addr, reason.into()).await?;
+            return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
+        }
+
+        /* Step 4: Establish the encrypted connection, if both sides support it. */
+
+        let mut framed = self.noise_handshake(peer_addr, framed, &peer_request, ConnectionSide::Responder).await?;
+
+        // Now that the handshake succeeded, make room for the peer, if the public peers are at capacity.
+        if !is_validator_listener {
+            if let Some(reason) = self.make_room_for_inbound_peer() {
+                send(&mut framed, peer_addr, reason.into()).await?;
+                return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
+            }
+        }
+        // Negotiate the features supported by both sides, and enable compression and checksums if they are among them.
+        let features = self.negotiate_features(&peer_request);
+        if features & ChallengeRequest::<N>::FEATURE_COMPRESSION != 0 {
+            self.compressed_peers.write().insert(peer_addr);
+        }
+        if features & ChallengeRequest::<N>::FEATURE_CHECKSUM != 0 {
+            self.checksummed_peers.write().insert(peer_addr);
+        }
+
         // Add the peer to the router.
-        self.insert_connected_peer(Peer::new(peer_ip, &peer_request), peer_addr);
+        let peer = Peer::new(peer_ip, ConnectionSide::Initiator, &peer_request, features, is_validator_listener);
+        self.insert_connected_peer(peer, peer_addr);
 
         Ok((peer_ip, framed))
     }
 
+    /// Performs the Noise handshake over the given stream, if both the node and the peer support it.
+    /// The `own_side` parameter indicates the connection side **from the node's perspective**.
+    async fn noise_handshake<'a, S: AsyncRead + AsyncWrite + Unpin + Send>(
+        &self,
+        peer_addr: SocketAddr,
+        framed: Framed<&'a mut S, MessageCodec<N>>,
+        peer_request: &ChallengeRequest<N>,
+        own_side: ConnectionSide,
+    ) -> io::Result<Framed<&'a mut S, MessageCodec<N>>> {
+        // If either side does not support encrypted connections, proceed in plaintext.
+        let Some(keypair) = &self.noise_keypair else {
+            return Ok(framed);
+        };
+        if !peer_request.supports_noise() {
+            return Ok(framed);
+        }
+
+        // Initialize the handshake state.
+        let params = NOISE_HANDSHAKE_TYPE.parse().map_err(|e| error(format!("Invalid noise parameters: {e}")))?;
+        let builder = snow::Builder::new(params).local_private_key(&keypair.private);
+        let state = match own_side {
+            ConnectionSide::Initiator => builder.build_initiator(),
+            ConnectionSide::Responder => builder.build_responder(),
+        }
+        .map_err(|e| error(format!("Failed to initialize the noise handshake with '{peer_addr}': {e}")))?;
+
+        // Sign the static key of the node with its account. The signatures are exchanged in the (encrypted) payloads
+        // of the handshake, so that each side ties the static key of the peer to the account which signed the
+        // challenge nonce; otherwise, a man-in-the-middle could relay the challenges, and run its own Noise handshake
+        // with each side.
+        let our_binding = self.sign_noise_static_key(peer_addr, &keypair.public)?;
+
+        // Exchange the noise handshake messages.
+        let mut framed = framed.map_codec(|_| NoiseHandshakeCodec::new(state));
+        match own_side {
+            ConnectionSide::Initiator => {
+                // -> e
+                framed.send(Bytes::new()).await?;
+                // <- e, ee, s, es
+                let peer_binding = receive_noise(&mut framed, peer_addr).await?;
+                self.verify_noise_static_key(peer_addr, peer_request, framed.codec(), &peer_binding)?;
+                // -> s, se
+                framed.send(our_binding).await?;
+            }
+            ConnectionSide::Responder => {
+                // -> e
+                receive_noise(&mut framed, peer_addr).await?;
+                // <- e, ee, s, es
+                framed.send(our_binding).await?;
+                // -> s, se
+                let peer_binding = receive_noise(&mut framed, peer_addr).await?;
+                self.verify_noise_static_key(peer_addr, peer_request, framed.codec(), &peer_binding)?;
+            }
+        }
+
+        // Conclude the handshake, and register the transport state of the encrypted connection.
+        let transport = framed.codec_mut().take_transport()?;
+        self.noise_states.write().insert(peer_addr, transport.clone());
+        debug!("Established an encrypted connection with '{peer_addr}'");
+
+        Ok(framed.map_codec(|_| MessageCodec::encrypted(transport)))
+    }
+
+    /// Signs the given static Noise key of the node with its account, and returns the serialized signature.
+    fn sign_noise_static_key(&self, peer_addr: SocketAddr, static_key: &[u8]) -> io::Result<Bytes> {
+        let message = [NOISE_STATIC_KEY_DOMAIN, static_key].concat();
+        match self.account.sign_bytes(&message, &mut self.rng()).and_then(|signature| signature.to_bytes_le()) {
+            Ok(signature) => Ok(Bytes::from(signature)),
+            Err(_) => Err(error(format!("Failed to sign the static noise key for '{peer_addr}'"))),
+        }
+    }
+
+    /// Verifies that the static Noise key revealed by the peer is signed by the account of its challenge request.
+    fn verify_noise_static_key(
+        &self,
+        peer_addr: SocketAddr,
+        peer_request: &ChallengeRequest<N>,
+        codec: &NoiseHandshakeCodec,
+        binding: &[u8],
+    ) -> io::Result<()> {
+        let Some(static_key) = codec.remote_static_key() else {
+            return Err(error(format!("'{peer_addr}' did not reveal its static noise key")));
+        };
+        let Ok(signature) = Signature::<N>::from_bytes_le(binding) else {
+            return Err(error(format!("'{peer_addr}' sent an invalid static noise key signature")));
+        };
+        let message = [NOISE_STATIC_KEY_DOMAIN, &static_key].concat();
+        if !signature.verify_bytes(&peer_request.address, &message) {
+            return Err(error(format!("'{peer_addr}' did not sign its static noise key with its account")));
+        }
+        Ok(())
+    }
+
     /// Ensure the peer is allowed to connect.
     fn ensure_peer_is_allowed(&self, peer_ip: SocketAddr) -> Result<()> {
         // Ensure the peer IP is not this node.
@@ -262,15 +488,114 @@ impl<N: Network> Router<N> {
         if self.is_restricted(&peer_ip) {
             bail!("Dropping connection request from '{peer_ip}' (restricted)")
         }
-        // Ensure the peer is not spamming connection attempts.
-        if !peer_ip.ip().is_loopback() {
+        // Ensure the peer is not banned.
+        if self.is_banned(&peer_ip) {
+            bail!("Dropping connection request from '{peer_ip}' (banned)")
+        }
+        // Ensure the node does not surpass the maximum number of inbound peer connections in the subnet.
+        if self.is_subnet_full(&peer_ip, ConnectionSide::Initiator) {
+            bail!("Dropping connection request from '{peer_ip}' (maximum inbound peers in subnet reached)")
+        }
+        Ok(())
+    }
+
+    /// Ensures the node ID of the peer, if it has one, is neither this node, nor the node ID of a connected or
+    /// connecting peer, so that a node connecting over several IPs does not occupy several slots. On success, the node
+    /// ID is reserved in `reserved`, until the handshake concludes. The node ID must be verified first.
+    fn ensure_node_id_is_allowed(
+        &self,
+        peer_request: &ChallengeRequest<N>,
+        reserved: &mut Option<NodeId>,
+    ) -> Result<()> {
+        let Some(node_id) = peer_request.node_id else {
+            return Ok(());
+        };
+        // Ensure the node ID is not this node.
+        if node_id == self.node_id() {
+            bail!("Dropping connection request from node '{node_id}' (attempted to self-connect)")
+        }
+        // Note: The lock is held across both checks, so that the node ID is either reserved or connected at any time.
+        let mut connecting_node_ids = self.connecting_node_ids.lock();
+        // Ensure the node is not already connected to this node ID.
+        if self.is_connected_node_id(&node_id) {
+            bail!("Dropping connection request from node '{node_id}' (already connected)")
+        }
+        // Ensure the node is not already shaking hands with this node ID.
+        if !connecting_node_ids.insert(node_id) {
+            bail!("Dropping connection request from node '{node_id}' (already shaking hands)")
+        }
+        *reserved = Some(node_id);
+        Ok(())
+    }
+
+    /// Verifies the listener the peer connected through admits it. Returns a disconnect reason if it does not.
+    /// Only the members of the current committee may connect through the validator listener, as the node type of
+    /// a peer is self-declared, and each listener has its own connection limit.
+    /// If the public peers are at capacity, the peer is only admitted if there is an inbound peer to evict for it,
+    /// which is evicted once the handshake succeeds.
+    fn verify_listener(
+        &self,
+        peer_request: &ChallengeRequest<N>,
+        is_validator_listener: bool,
+    ) -> Option<DisconnectReason> {
+        if is_validator_listener {
+            if !peer_request.node_type.is_validator() || !self.is_committee_member(&peer_request.address) {
+                warn!("Dropping '{}' from the validator listener (not a committee member)", peer_request.address);
+                return Some(DisconnectReason::ProtocolViolation);
+            }
+            if self.number_of_validator_listener_peers() >= self.max_validator_listener_peers() {
+                return Some(DisconnectReason::TooManyPeers);
+            }
+        } else {
+            if self.is_inbound_at_capacity() && self.select_peer_to_evict().is_none() {
+                return Some(DisconnectReason::TooManyPeers);
+            }
+        }
+        None
+    }
+
+    /// Makes room for a new inbound peer whose handshake succeeded, by evicting the lowest-quality inbound peer,
+    /// if the public peers are at capacity. Returns a disconnect reason if there is no peer to evict.
+    fn make_room_for_inbound_peer(&self) -> Option<DisconnectReason> {
+        if self.is_inbound_at_capacity() {
+            let Some(evicted_peer) = self.select_peer_to_evict() else {
+                return Some(DisconnectReason::TooManyPeers);
+            };
+            info!("Disconnecting from '{evicted_peer}' (making room for a new inbound peer)");
+            self.disconnect(evicted_peer);
+        }
+        None
+    }
+
+    /// Returns `true` if the public peers are at capacity, so that a new inbound peer requires an eviction.
+    fn is_inbound_at_capacity(&self) -> bool {
+        // The inbound peers may not take the slots reserved for the outbound peers.
+        let is_inbound_full = self
+            .connection_policy()
+            .is_some_and(|policy| self.number_of_inbound_peers() >= policy.max_inbound_peers as usize);
+        is_inbound_full || self.number_of_public_peers() >= self.max_connected_peers()
+    }
+
+    /// Ensures the given connecting IP is not banned nor restricted, and is not spamming connection attempts.
+    /// This is checked before any message is read from the peer, so that connection floods are rejected cheaply.
+    fn ensure_ip_is_allowed(&self, ip: IpAddr) -> Result<()> {
+        // Ensure the IP is not banned.
+        if self.ban_list.is_banned(&ip) {
+            bail!("Dropping connection request from '{ip}' (banned)")
+        }
+        // Ensure the IP is not restricted.
+        if self.is_restricted_ip(&ip) {
+            bail!("Dropping connection request from '{ip}' (restricted)")
+        }
+        // Ensure the IP is not spamming connection attempts. Trusted peers are exempt, as they reconnect eagerly.
+        if !ip.is_loopback() && !self.trusted_peers().iter().any(|peer_ip| peer_ip.ip() == ip) {
             // Add this connection attempt and retrieve the number of attempts.
-            let num_attempts = self.cache.insert_inbound_connection(peer_ip.ip(), Self::RADIO_SILENCE_IN_SECS as i64);
-            // Ensure the connecting peer has not surpassed the connection attempt limit.
+            let num_attempts = self.cache.insert_inbound_connection(ip, Self::RADIO_SILENCE_IN_SECS as i64);
+            // Ensure the connecting IP has not surpassed the connection attempt limit.
             if num_attempts > Self::MAXIMUM_CONNECTION_FAILURES {
-                // Restrict the peer.
-                self.insert_restricted_peer(peer_ip);
-                bail!("Dropping connection request from '{peer_ip}' (tried {num_attempts} times)")
+                // Restrict the IP.
+                self.insert_restricted_ip(ip);
+                bail!("Dropping connection request from '{ip}' (tried {num_attempts} times)")
             }
         }
         Ok(())
@@ -283,10 +608,18 @@ impl<N: Network> Router<N> {
         message: &ChallengeRequest<N>,
     ) -> Option<DisconnectReason> {
         // Retrieve the components of the challenge request.
-        let &ChallengeRequest { version, listener_port: _, node_type: _, address: _, nonce: _ } = message;
-
-        // Ensure the message protocol version is not outdated.
-        if version < Message::<N>::VERSION {
+        let &ChallengeRequest {
+            version,
+            listener_port: _,
+            node_type: _,
+            address: _,
+            nonce: _,
+            features: _,
+            node_id: _,
+        } = message;
+
+        // Ensure the message protocol version is not outdated, while accepting the peers on the last released version.
+        if !Message::<N>::is_compatible_version(version) {
             warn!("Dropping '{peer_addr}' on version {version} (outdated)");
             return Some(DisconnectReason::OutdatedClientVersion);
         }
@@ -297,13 +630,13 @@ impl<N: Network> Router<N> {
     async fn verify_challenge_response(
         &self,
         peer_addr: SocketAddr,
-        peer_address: Address<N>,
+        peer_request: &ChallengeRequest<N>,
         response: ChallengeResponse<N>,
         expected_genesis_header: Header<N>,
         expected_nonce: u64,
     ) -> Option<DisconnectReason> {
         // Retrieve the components of the challenge response.
-        let ChallengeResponse { genesis_header, signature } = response;
+        let ChallengeResponse { genesis_header, signature, node_signature } = response;
 
         // Verify the challenge response, by checking that the block header matches.
         if genesis_header != expected_genesis_header {
@@ -316,10 +649,20 @@ impl<N: Network> Router<N> {
             return Some(DisconnectReason::InvalidChallengeResponse);
         };
         // Verify the signature.
-        if !signature.verify_bytes(&peer_address, &expected_nonce.to_le_bytes()) {
+        if !signature.verify_bytes(&peer_request.address, &expected_nonce.to_le_bytes()) {
             warn!("Handshake with '{peer_addr}' failed (invalid signature)");
             return Some(DisconnectReason::InvalidChallengeResponse);
         }
+        // Verify the signature of the node ID, if the peer claims one.
+        if let Some(node_id) = &peer_request.node_id {
+            match node_signature {
+                Some(node_signature) if NodeIdentity::verify_nonce(node_id, expected_nonce, &node_signature) => (),
+                _ => {
+                    warn!("Handshake with '{peer_addr}' failed (invalid node ID signature)");
+                    return Some(DisconnectReason::InvalidChallengeResponse);
+                }
+            }
+        }
         None
     }
 }
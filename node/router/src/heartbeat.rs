@@ -13,14 +13,15 @@
 // limitations under the License.
 
 use crate::{
-    messages::{DisconnectReason, Message, PeerRequest},
+    messages::{AddressAnnouncement, DisconnectReason, Message, PeerRequest},
+    ConnectionPolicy,
+    Offense,
     Outbound,
-    Router,
 };
 use snarkvm::prelude::Network;
 
 use colored::Colorize;
-use rand::{prelude::IteratorRandom, rngs::OsRng};
+use rand::prelude::IteratorRandom;
 
 /// A helper function to compute the maximum of two numbers.
 /// See Rust issue 92391: https://github.com/rust-lang/rust/issues/92391.
@@ -46,6 +47,8 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
         self.safety_check_minimum_number_of_peers();
         self.log_connected_peers();
 
+        // Regenerate the reputation of the peers.
+        self.router().reputation().regenerate();
         // Remove any stale connected peers.
         self.remove_stale_connected_peers();
         // Remove the oldest connected peer.
@@ -54,8 +57,12 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
         self.handle_connected_peers();
         // Keep the bootstrap peers within the allowed range.
         self.handle_bootstrap_peers();
+        // Resolve the DNS seeds, if there are not enough connected peers.
+        self.handle_dns_seeds();
         // Keep the trusted peers connected.
         self.handle_trusted_peers();
+        // Announce the listening address of the node, if it changed.
+        self.handle_observed_addrs();
         // Keep the puzzle request up to date.
         self.handle_puzzle_request();
     }
@@ -82,25 +89,36 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
         }
     }
 
-    /// This function removes any connected peers that have not communicated within the predefined time.
+    /// This function removes any connected peers that have not communicated, or have not answered
+    /// a `Ping`, within the times of the liveness policy.
     fn remove_stale_connected_peers(&self) {
+        let liveness_policy = *self.router().liveness_policy();
         // Check if any connected peer is stale.
         for peer in self.router().get_connected_peers() {
-            // Disconnect if the peer has not communicated back within the predefined time.
             let elapsed = peer.last_seen().elapsed().as_secs();
-            if elapsed > Router::<N>::RADIO_SILENCE_IN_SECS {
+            // Disconnect if the peer has not communicated back within the idle timeout.
+            if elapsed > liveness_policy.idle_timeout_in_secs {
                 warn!("Peer {} has not communicated in {elapsed} seconds", peer.ip());
-                // Disconnect from this peer.
-                self.router().disconnect(peer.ip());
             }
+            // Disconnect if the peer has not answered the last `Ping` within the pong timeout.
+            else if peer.is_pong_overdue(liveness_policy.pong_timeout()) {
+                warn!("Peer {} has not answered a ping in {} seconds", peer.ip(), liveness_policy.pong_timeout_in_secs);
+            } else {
+                continue;
+            }
+            // Lower the reputation of this peer.
+            self.router().record_offense(peer.ip(), Offense::StalePing);
+            // Disconnect from this peer.
+            self.router().disconnect(peer.ip());
         }
     }
 
     /// This function removes the oldest connected peer, to keep the connections fresh.
     /// This function only triggers if the router is above the minimum number of connected peers.
+    /// The peers connected through the validator listener are not refreshed.
     fn remove_oldest_connected_peer(&self) {
         // Skip if the router is at or below the minimum number of connected peers.
-        if self.router().number_of_connected_peers() <= Self::MINIMUM_NUMBER_OF_PEERS {
+        if self.router().number_of_public_peers() <= Self::MINIMUM_NUMBER_OF_PEERS {
             return;
         }
 
@@ -114,6 +132,7 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
             .router()
             .get_connected_peers()
             .iter()
+            .filter(|peer| !peer.is_validator_listener())
             .filter(|peer| !trusted.contains(&peer.ip()) && !bootstrap.contains(&peer.ip()))
             .min_by_key(|peer| peer.last_seen())
             .map(|peer| peer.ip());
@@ -129,9 +148,15 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
 
     /// TODO (howardwu): If the node is a validator, keep the validator.
     /// This function keeps the number of connected peers within the allowed range.
+    /// The peers connected through the validator listener are subject to the limit of the listener instead.
     fn handle_connected_peers(&self) {
+        // If a connection policy is configured, the outbound and inbound peers are maintained separately.
+        if let Some(policy) = self.router().connection_policy().copied() {
+            self.handle_connection_policy(policy);
+            return;
+        }
         // Obtain the number of connected peers.
-        let num_connected = self.router().number_of_connected_peers();
+        let num_connected = self.router().number_of_public_peers();
         // Compute the number of surplus peers.
         let num_surplus = num_connected.saturating_sub(Self::MAXIMUM_NUMBER_OF_PEERS);
         // Compute the number of deficit peers.
@@ -146,14 +171,14 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
             let bootstrap = self.router().bootstrap_peers();
 
             // Initialize an RNG.
-            let rng = &mut OsRng;
+            let rng = &mut self.router().rng();
 
             // TODO (howardwu): As a validator, prioritize disconnecting from clients and provers.
             //  Remove RNG, pick the `n` oldest nodes.
             // Determine the peers to disconnect from.
             let peer_ips_to_disconnect = self
                 .router()
-                .connected_peers()
+                .public_peers()
                 .into_iter()
                 .filter(|peer_ip| !trusted.contains(peer_ip) && !bootstrap.contains(peer_ip))
                 .choose_multiple(rng, num_surplus);
@@ -178,10 +203,63 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
 
         if num_deficient > 0 {
             // Initialize an RNG.
-            let rng = &mut OsRng;
+            let rng = &mut self.router().rng();
 
             // Attempt to connect to more peers.
-            for peer_ip in self.router().candidate_peers().into_iter().choose_multiple(rng, num_deficient) {
+            for peer_ip in self.router().select_candidate_peers(num_deficient) {
+                self.router().connect(peer_ip);
+            }
+            // Request more peers from the connected peers.
+            for peer_ip in self.router().connected_peers().into_iter().choose_multiple(rng, 3) {
+                self.send(peer_ip, Message::PeerRequest(PeerRequest));
+            }
+        }
+    }
+
+    /// This function maintains the target number of outbound peers, and keeps the number of inbound peers
+    /// within the limit of the connection policy. Trusted and bootstrap peers are never disconnected.
+    fn handle_connection_policy(&self, policy: ConnectionPolicy) {
+        // Retrieve the trusted peers.
+        let trusted = self.router().trusted_peers();
+        // Retrieve the bootstrap peers.
+        let bootstrap = self.router().bootstrap_peers();
+        // Split the untrusted public peers into the outbound and inbound peers.
+        let (inbound, outbound): (Vec<_>, Vec<_>) = self
+            .router()
+            .get_connected_peers()
+            .into_iter()
+            .filter(|peer| !peer.is_validator_listener())
+            .filter(|peer| !trusted.contains(&peer.ip()) && !bootstrap.contains(&peer.ip()))
+            .partition(|peer| peer.is_inbound());
+
+        // Initialize an RNG.
+        let rng = &mut self.router().rng();
+
+        // Compute the number of surplus peers on each side.
+        let num_outbound = self.router().number_of_outbound_peers();
+        let num_surplus_outbound = num_outbound.saturating_sub(policy.target_outbound_peers as usize);
+        let num_surplus_inbound =
+            self.router().number_of_inbound_peers().saturating_sub(policy.max_inbound_peers as usize);
+        // Determine the peers to disconnect from.
+        let peer_ips_to_disconnect = outbound
+            .into_iter()
+            .map(|peer| peer.ip())
+            .choose_multiple(rng, num_surplus_outbound)
+            .into_iter()
+            .chain(inbound.into_iter().map(|peer| peer.ip()).choose_multiple(rng, num_surplus_inbound));
+        // Proceed to send disconnect requests to these peers.
+        for peer_ip in peer_ips_to_disconnect {
+            info!("Disconnecting from '{peer_ip}' (exceeded maximum connections)");
+            self.send(peer_ip, Message::Disconnect(DisconnectReason::TooManyPeers.into()));
+            // Disconnect from this peer.
+            self.router().disconnect(peer_ip);
+        }
+
+        // Compute the number of missing outbound peers.
+        let num_deficient = (policy.target_outbound_peers as usize).saturating_sub(num_outbound);
+        if num_deficient > 0 {
+            // Attempt to connect to more peers.
+            for peer_ip in self.router().select_candidate_peers(num_deficient) {
                 self.router().connect(peer_ip);
             }
             // Request more peers from the connected peers.
@@ -202,10 +280,11 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
                 false => candidate_bootstrap.push(bootstrap_ip),
             }
         }
-        // If there are not enough connected bootstrap peers, connect to more.
-        if connected_bootstrap.is_empty() {
+        // If there are not enough connected peers, connect to a bootstrap peer. Once the node is connected to enough
+        // (e.g. known) peers, it does not rely on the bootstrap peers, which are not to be overloaded.
+        if connected_bootstrap.is_empty() && self.router().number_of_public_peers() < Self::MINIMUM_NUMBER_OF_PEERS {
             // Initialize an RNG.
-            let rng = &mut OsRng;
+            let rng = &mut self.router().rng();
             // Attempt to connect to a bootstrap peer.
             if let Some(peer_ip) = candidate_bootstrap.into_iter().choose(rng) {
                 self.router().connect(peer_ip);
@@ -215,7 +294,7 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
         let num_surplus = connected_bootstrap.len().saturating_sub(1);
         if num_surplus > 0 {
             // Initialize an RNG.
-            let rng = &mut OsRng;
+            let rng = &mut self.router().rng();
             // Proceed to send disconnect requests to these bootstrap peers.
             for peer_ip in connected_bootstrap.into_iter().choose_multiple(rng, num_surplus) {
                 info!("Disconnecting from '{peer_ip}' (exceeded maximum bootstrap)");
@@ -226,18 +305,73 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
         }
     }
 
+    /// This function resolves the DNS seeds, if the router is below the minimum number of connected peers.
+    fn handle_dns_seeds(&self) {
+        if self.router().number_of_connected_peers() < Self::MINIMUM_NUMBER_OF_PEERS {
+            self.router().resolve_dns_seeds();
+        }
+    }
+
     /// This function attempts to connect to any disconnected trusted peers.
+    /// If the router is at the maximum number of connected peers, an untrusted peer is evicted to make room.
     fn handle_trusted_peers(&self) {
+        // Retrieve the trusted peers.
+        let trusted = self.router().trusted_peers();
         // Ensure that the trusted nodes are connected.
-        for peer_ip in self.router().trusted_peers() {
+        for peer_ip in &trusted {
             // If the peer is not connected, attempt to connect to it.
             if !self.router().is_connected(peer_ip) {
+                // If there is no room for the trusted peer, evict an untrusted peer that is not a bootstrap peer.
+                if self.router().number_of_public_peers() >= self.router().max_connected_peers() {
+                    let bootstrap = self.router().bootstrap_peers();
+                    let evicted_peer = self
+                        .router()
+                        .public_peers()
+                        .into_iter()
+                        .find(|peer_ip| !trusted.contains(peer_ip) && !bootstrap.contains(peer_ip));
+                    if let Some(evicted_peer) = evicted_peer {
+                        info!("Disconnecting from '{evicted_peer}' (making room for trusted peer '{peer_ip}')");
+                        self.send(evicted_peer, Message::Disconnect(DisconnectReason::TooManyPeers.into()));
+                        self.router().disconnect(evicted_peer);
+                    }
+                    // The trusted peer is connected to in the next heartbeat, once the eviction completed.
+                    continue;
+                }
                 // Attempt to connect to the trusted peer.
                 self.router().connect(*peer_ip);
             }
         }
     }
 
+    /// This function updates the advertised addresses if the peers observe the node at a new address,
+    /// and announces the new addresses to the connected peers.
+    fn handle_observed_addrs(&self) {
+        let mut is_updated = false;
+        for observed_addr in self.router().observed_addrs() {
+            let advertised_addrs = self.router().advertised_addrs();
+            // Skip the address if it is already advertised.
+            if advertised_addrs.contains(&observed_addr) {
+                continue;
+            }
+            // Only replace a stale address of the same address family.
+            let is_ipv4 = observed_addr.is_ipv4();
+            let Some(stale_addr) = advertised_addrs.iter().find(|addr| addr.is_ipv4() == is_ipv4) else {
+                continue;
+            };
+            info!("The external address changed from '{stale_addr}' to '{observed_addr}'");
+            self.router().update_advertised_addr(observed_addr);
+            is_updated = true;
+        }
+        if is_updated {
+            // Announce the new addresses to the connected peers.
+            let mut addrs = self.router().advertised_addrs();
+            addrs.truncate(AddressAnnouncement::MAXIMUM_NUMBER_OF_ADDRESSES);
+            for peer_ip in self.router().connected_peers() {
+                self.send(peer_ip, Message::AddressAnnouncement(AddressAnnouncement { addrs: addrs.clone() }));
+            }
+        }
+    }
+
     /// This function updates the coinbase puzzle if network has updated.
     fn handle_puzzle_request(&self) {
         // No-op
@@ -0,0 +1,297 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_tcp::subnet;
+
+use parking_lot::RwLock;
+use rand::{seq::IteratorRandom, Rng};
+use std::{
+    collections::{hash_map::RandomState, HashMap, HashSet},
+    hash::BuildHasher,
+    net::{IpAddr, SocketAddr},
+};
+
+/// The table an address is stored in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Table {
+    /// The addresses learned from other peers, which were never connected to.
+    New,
+    /// The addresses which were successfully connected to.
+    Tried,
+}
+
+/// The bucketed tables of addresses.
+#[derive(Debug)]
+struct Tables {
+    /// The slots of the new table, in buckets of `BUCKET_SIZE` consecutive slots.
+    new: Vec<Option<SocketAddr>>,
+    /// The slots of the tried table, in buckets of `BUCKET_SIZE` consecutive slots.
+    tried: Vec<Option<SocketAddr>>,
+    /// The map of the stored addresses to their table and slot.
+    positions: HashMap<SocketAddr, (Table, usize)>,
+}
+
+impl Tables {
+    /// Returns the slots of the given table.
+    fn slots(&mut self, table: Table) -> &mut Vec<Option<SocketAddr>> {
+        match table {
+            Table::New => &mut self.new,
+            Table::Tried => &mut self.tried,
+        }
+    }
+
+    /// Removes the given address from its table, returning `true` if it was stored.
+    fn remove(&mut self, addr: &SocketAddr) -> bool {
+        match self.positions.remove(addr) {
+            Some((table, slot)) => {
+                self.slots(table)[slot] = None;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The address manager of the candidate peers, which separates the addresses into a table of new addresses
+/// (learned from other peers) and a table of tried addresses (which were successfully connected to).
+///
+/// Every address is placed into a single slot of a bucket, which is derived from a secret, per-node salt.
+/// New addresses are bucketed by their subnet and the subnet of the source that advertised them, so that
+/// a single source can only ever occupy a small fraction of the new table, and can not evict the tried addresses.
+#[derive(Debug)]
+pub struct AddressManager {
+    /// The secret salt of the bucket and slot positions.
+    salt: RandomState,
+    /// The bucketed tables of addresses.
+    tables: RwLock<Tables>,
+}
+
+impl Default for AddressManager {
+    fn default() -> Self {
+        Self {
+            salt: RandomState::new(),
+            tables: RwLock::new(Tables {
+                new: vec![None; Self::NEW_BUCKET_COUNT * Self::BUCKET_SIZE],
+                tried: vec![None; Self::TRIED_BUCKET_COUNT * Self::BUCKET_SIZE],
+                positions: Default::default(),
+            }),
+        }
+    }
+}
+
+impl AddressManager {
+    /// The number of buckets in the new table.
+    pub const NEW_BUCKET_COUNT: usize = 256;
+    /// The number of buckets in the tried table.
+    pub const TRIED_BUCKET_COUNT: usize = 64;
+    /// The number of slots in every bucket.
+    pub const BUCKET_SIZE: usize = 32;
+    /// The number of new buckets the addresses advertised by a single source subnet are spread across.
+    pub const NEW_BUCKETS_PER_SOURCE: u64 = 16;
+    /// The number of tried buckets the addresses of a single subnet are spread across.
+    pub const TRIED_BUCKETS_PER_SUBNET: u64 = 4;
+
+    /// Returns the number of stored addresses.
+    pub fn len(&self) -> usize {
+        self.tables.read().positions.len()
+    }
+
+    /// Returns `true` if there are no stored addresses.
+    pub fn is_empty(&self) -> bool {
+        self.tables.read().positions.is_empty()
+    }
+
+    /// Returns `true` if the given address is stored.
+    pub fn contains(&self, addr: &SocketAddr) -> bool {
+        self.tables.read().positions.contains_key(addr)
+    }
+
+    /// Returns `true` if the given address is stored in the tried table.
+    pub fn is_tried(&self, addr: &SocketAddr) -> bool {
+        self.tables.read().positions.get(addr).is_some_and(|(table, _)| *table == Table::Tried)
+    }
+
+    /// Returns the set of stored addresses.
+    pub fn addresses(&self) -> HashSet<SocketAddr> {
+        self.tables.read().positions.keys().copied().collect()
+    }
+
+    /// Inserts the given address, advertised by the given source, into the new table.
+    /// Returns `false` if the address is already stored, or if its slot is taken.
+    pub fn insert_new(&self, addr: SocketAddr, source: IpAddr) -> bool {
+        let slot = self.new_slot(&addr, source);
+        let mut tables = self.tables.write();
+        if tables.positions.contains_key(&addr) || tables.new[slot].is_some() {
+            return false;
+        }
+        tables.new[slot] = Some(addr);
+        tables.positions.insert(addr, (Table::New, slot));
+        true
+    }
+
+    /// Moves the given address into the tried table, as it was successfully connected to.
+    /// If its slot is taken, the previous address is moved back into the new table.
+    pub fn insert_tried(&self, addr: SocketAddr) {
+        let slot = self.tried_slot(&addr);
+        let evicted = {
+            let mut tables = self.tables.write();
+            tables.remove(&addr);
+            let evicted = tables.tried[slot].replace(addr);
+            if let Some(evicted) = evicted {
+                tables.positions.remove(&evicted);
+            }
+            tables.positions.insert(addr, (Table::Tried, slot));
+            evicted
+        };
+        // The evicted address was connected to before, so it is attributed to itself.
+        if let Some(evicted) = evicted {
+            self.insert_new(evicted, evicted.ip());
+        }
+    }
+
+    /// Removes the given address, returning `true` if it was stored.
+    pub fn remove(&self, addr: &SocketAddr) -> bool {
+        self.tables.write().remove(addr)
+    }
+
+    /// Retains only the addresses for which the given predicate returns `true`.
+    pub fn retain(&self, mut predicate: impl FnMut(&SocketAddr) -> bool) {
+        let mut tables = self.tables.write();
+        let removed = tables.positions.keys().filter(|addr| !predicate(addr)).copied().collect::<Vec<_>>();
+        for addr in removed {
+            tables.remove(&addr);
+        }
+    }
+
+    /// Removes all of the stored addresses.
+    pub fn clear(&self) {
+        let mut tables = self.tables.write();
+        tables.new.iter_mut().for_each(|slot| *slot = None);
+        tables.tried.iter_mut().for_each(|slot| *slot = None);
+        tables.positions.clear();
+    }
+
+    /// Selects up to `num` random addresses, drawing from the tried and new tables with equal probability.
+    pub fn select<R: Rng>(&self, rng: &mut R, num: usize) -> Vec<SocketAddr> {
+        let (mut tried, mut new) = {
+            let tables = self.tables.read();
+            (
+                tables.tried.iter().flatten().copied().choose_multiple(rng, num),
+                tables.new.iter().flatten().copied().choose_multiple(rng, num),
+            )
+        };
+        let mut selected = Vec::with_capacity(num);
+        while selected.len() < num {
+            let addr = match rng.gen_bool(0.5) {
+                true => tried.pop().or_else(|| new.pop()),
+                false => new.pop().or_else(|| tried.pop()),
+            };
+            match addr {
+                Some(addr) => selected.push(addr),
+                None => break,
+            }
+        }
+        selected
+    }
+
+    /// Returns the slot of the given address, advertised by the given source, in the new table.
+    fn new_slot(&self, addr: &SocketAddr, source: IpAddr) -> usize {
+        let (addr_subnet, source_subnet) = (subnet(addr.ip()), subnet(source));
+        let offset = self.salt.hash_one((addr_subnet, source_subnet)) % Self::NEW_BUCKETS_PER_SOURCE;
+        let bucket = self.salt.hash_one((source_subnet, offset)) % Self::NEW_BUCKET_COUNT as u64;
+        self.slot(Table::New, bucket, addr)
+    }
+
+    /// Returns the slot of the given address in the tried table.
+    fn tried_slot(&self, addr: &SocketAddr) -> usize {
+        let offset = self.salt.hash_one(addr) % Self::TRIED_BUCKETS_PER_SUBNET;
+        let bucket = self.salt.hash_one((subnet(addr.ip()), offset)) % Self::TRIED_BUCKET_COUNT as u64;
+        self.slot(Table::Tried, bucket, addr)
+    }
+
+    /// Returns the slot of the given address in the given bucket of the given table.
+    fn slot(&self, table: Table, bucket: u64, addr: &SocketAddr) -> usize {
+        let position = self.salt.hash_one((table, bucket, addr)) % Self::BUCKET_SIZE as u64;
+        (bucket * Self::BUCKET_SIZE as u64 + position) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_flooding_from_a_single_source() {
+        let addrman = AddressManager::default();
+
+        // A single source advertises a flood of addresses, across many subnets.
+        let source = IpAddr::from([1, 2, 3, 4]);
+        for i in 0..10_000u32 {
+            addrman.insert_new(SocketAddr::from((i.to_be_bytes(), 4133)), source);
+        }
+        // Ensure the source only occupies its share of the new table.
+        let flooded = addrman.len();
+        let limit = AddressManager::NEW_BUCKETS_PER_SOURCE as usize * AddressManager::BUCKET_SIZE;
+        assert!(flooded <= limit, "{flooded} > {limit}");
+
+        // Ensure the addresses advertised by other sources are still accepted.
+        let addr = SocketAddr::from(([5, 6, 7, 8], 4133));
+        assert!(addrman.insert_new(addr, IpAddr::from([9, 9, 9, 9])));
+        assert!(addrman.contains(&addr));
+        assert_eq!(addrman.len(), flooded + 1);
+    }
+
+    #[test]
+    fn test_tried_and_new_tables() {
+        let addrman = AddressManager::default();
+        let addr = SocketAddr::from(([1, 2, 3, 4], 4133));
+        let source = IpAddr::from([5, 6, 7, 8]);
+
+        // Insert the address into the new table.
+        assert!(addrman.insert_new(addr, source));
+        assert!(!addrman.insert_new(addr, source));
+        assert!(!addrman.is_tried(&addr));
+
+        // Move the address into the tried table.
+        addrman.insert_tried(addr);
+        assert!(addrman.is_tried(&addr));
+        assert!(!addrman.insert_new(addr, source));
+        assert_eq!(addrman.len(), 1);
+
+        // Ensure the address is selected, and removed.
+        assert_eq!(addrman.select(&mut OsRng, 5), vec![addr]);
+        assert!(addrman.remove(&addr));
+        assert!(addrman.is_empty());
+        assert!(addrman.select(&mut OsRng, 5).is_empty());
+    }
+
+    #[test]
+    fn test_retain_and_clear() {
+        let addrman = AddressManager::default();
+        let source = IpAddr::from([5, 6, 7, 8]);
+        for i in 1..=10u8 {
+            addrman.insert_new(SocketAddr::from(([i, i, i, i], 4133)), source);
+        }
+        let num_addresses = addrman.len();
+
+        addrman.retain(|addr| addr.ip() != IpAddr::from([1, 1, 1, 1]));
+        assert!(!addrman.contains(&SocketAddr::from(([1, 1, 1, 1], 4133))));
+        assert_eq!(addrman.len(), num_addresses - 1);
+
+        addrman.clear();
+        assert!(addrman.is_empty());
+        assert!(addrman.addresses().is_empty());
+    }
+}
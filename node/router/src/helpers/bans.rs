@@ -0,0 +1,155 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+use time::OffsetDateTime;
+
+/// A ban on a peer IP.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ban {
+    /// The reason for the ban.
+    pub reason: String,
+    /// The UNIX timestamp (in seconds) at which the ban expires, if it is not permanent.
+    pub expires_at: Option<i64>,
+}
+
+impl Ban {
+    /// Returns `true` if the ban has expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map(|expires_at| expires_at <= OffsetDateTime::now_utc().unix_timestamp()).unwrap_or(false)
+    }
+}
+
+/// The list of banned peer IPs, which is persisted to disk (if a path is given), so that bans survive restarts.
+#[derive(Debug, Default)]
+pub struct BanList {
+    /// The path to the file the bans are persisted to.
+    path: Option<PathBuf>,
+    /// The map of banned peer IPs to their bans.
+    bans: RwLock<BTreeMap<IpAddr, Ban>>,
+}
+
+impl BanList {
+    /// Loads the ban list from the given path, if one is given. Expired bans are discarded.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let mut bans = match &path {
+            Some(path) if path.exists() => {
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Failed to read the ban list from '{}'", path.display()))?;
+                serde_json::from_slice::<BTreeMap<IpAddr, Ban>>(&bytes)
+                    .with_context(|| format!("Failed to parse the ban list in '{}'", path.display()))?
+            }
+            _ => Default::default(),
+        };
+        bans.retain(|_, ban| !ban.is_expired());
+        Ok(Self { path, bans: RwLock::new(bans) })
+    }
+
+    /// Returns the path to the file the bans are persisted to, if one is set.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns `true` if the given IP is banned.
+    pub fn is_banned(&self, ip: &IpAddr) -> bool {
+        self.bans.read().get(ip).map(|ban| !ban.is_expired()).unwrap_or(false)
+    }
+
+    /// Returns the list of unexpired bans.
+    pub fn bans(&self) -> Vec<(IpAddr, Ban)> {
+        self.bans.read().iter().filter(|(_, ban)| !ban.is_expired()).map(|(ip, ban)| (*ip, ban.clone())).collect()
+    }
+
+    /// Bans the given IP for the given number of seconds (or permanently), and persists the ban list.
+    pub fn insert(&self, ip: IpAddr, reason: String, duration_in_secs: Option<u64>) -> Result<()> {
+        let expires_at =
+            duration_in_secs.map(|secs| OffsetDateTime::now_utc().unix_timestamp().saturating_add(secs as i64));
+        self.bans.write().insert(ip, Ban { reason, expires_at });
+        self.save()
+    }
+
+    /// Lifts the ban on the given IP, and persists the ban list. Returns `true` if the IP was banned.
+    pub fn remove(&self, ip: &IpAddr) -> Result<bool> {
+        let is_removed = self.bans.write().remove(ip).is_some();
+        if is_removed {
+            self.save()?;
+        }
+        Ok(is_removed)
+    }
+
+    /// Persists the unexpired bans to disk, if a path is set.
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        // Discard the expired bans.
+        let bytes = {
+            let mut bans = self.bans.write();
+            bans.retain(|_, ban| !ban.is_expired());
+            serde_json::to_vec_pretty(&*bans)?
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, bytes).with_context(|| format!("Failed to write the ban list to '{}'", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ban_list_persistence() {
+        let directory = std::env::temp_dir().join(format!("snarkos-bans-{}", std::process::id()));
+        let path = directory.join("bans.json");
+        let ip = IpAddr::from([1, 2, 3, 4]);
+
+        // Ban the IP, and ensure the ban survives a reload.
+        let bans = BanList::load(Some(path.clone())).unwrap();
+        bans.insert(ip, "spam".to_string(), None).unwrap();
+        assert!(bans.is_banned(&ip));
+        let bans = BanList::load(Some(path.clone())).unwrap();
+        assert!(bans.is_banned(&ip));
+        assert_eq!(bans.bans(), vec![(ip, Ban { reason: "spam".to_string(), expires_at: None })]);
+
+        // Lift the ban, and ensure the removal survives a reload.
+        assert!(bans.remove(&ip).unwrap());
+        assert!(!bans.remove(&ip).unwrap());
+        let bans = BanList::load(Some(path)).unwrap();
+        assert!(!bans.is_banned(&ip));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_ban_expiry() {
+        let bans = BanList::default();
+        let ip = IpAddr::from([1, 2, 3, 4]);
+
+        bans.insert(ip, "spam".to_string(), Some(0)).unwrap();
+        assert!(!bans.is_banned(&ip));
+        assert!(bans.bans().is_empty());
+
+        bans.insert(ip, "spam".to_string(), Some(60)).unwrap();
+        assert!(bans.is_banned(&ip));
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::messages::BlockRequest;
+use crate::messages::{BlockRequest, HeaderRequest};
 use snarkvm::prelude::{coinbase::PuzzleCommitment, Network};
 
 use core::hash::Hash;
@@ -40,18 +40,30 @@ pub struct Cache<N: Network> {
     seen_inbound_messages: RwLock<HashMap<SocketAddr, VecDeque<OffsetDateTime>>>,
     /// The map of peer IPs to their recent timestamps.
     seen_inbound_puzzle_requests: RwLock<HashMap<SocketAddr, VecDeque<OffsetDateTime>>>,
+    /// The map of peer IPs to the timestamps of their recent unconfirmed transactions.
+    seen_inbound_transaction_timestamps: RwLock<HashMap<SocketAddr, VecDeque<OffsetDateTime>>>,
     /// The map of solution commitments to their last seen timestamp.
     seen_inbound_solutions: RwLock<LinkedHashMap<SolutionKey<N>, OffsetDateTime>>,
     /// The map of transaction IDs to their last seen timestamp.
     seen_inbound_transactions: RwLock<LinkedHashMap<TransactionKey<N>, OffsetDateTime>>,
+    /// The map of solution commitments to the timestamp they were last seen from any peer.
+    seen_solutions: RwLock<LinkedHashMap<PuzzleCommitment<N>, OffsetDateTime>>,
+    /// The map of transaction IDs to the timestamp they were last seen from any peer.
+    seen_transactions: RwLock<LinkedHashMap<N::TransactionID, OffsetDateTime>>,
     /// The map of peer IPs to their block requests.
     seen_outbound_block_requests: RwLock<HashMap<SocketAddr, HashSet<BlockRequest>>>,
+    /// The map of peer IPs to their header requests.
+    seen_outbound_header_requests: RwLock<HashMap<SocketAddr, HashSet<HeaderRequest>>>,
     /// The map of peer IPs to the number of puzzle requests.
     seen_outbound_puzzle_requests: RwLock<HashMap<SocketAddr, u32>>,
     /// The map of solution commitments to their last seen timestamp.
     seen_outbound_solutions: RwLock<LinkedHashMap<SolutionKey<N>, OffsetDateTime>>,
     /// The map of transaction IDs to their last seen timestamp.
     seen_outbound_transactions: RwLock<LinkedHashMap<TransactionKey<N>, OffsetDateTime>>,
+    /// The map of transaction IDs announced to (or by) a peer to their last seen timestamp.
+    seen_outbound_transaction_announcements: RwLock<LinkedHashMap<TransactionKey<N>, OffsetDateTime>>,
+    /// The map of requested transaction IDs to the timestamp of their last request.
+    seen_outbound_transaction_requests: RwLock<LinkedHashMap<N::TransactionID, OffsetDateTime>>,
     /// The map of peer IPs to the number of sent peer requests.
     seen_outbound_peer_requests: RwLock<HashMap<SocketAddr, u32>>,
 }
@@ -70,12 +82,18 @@ impl<N: Network> Cache<N> {
             seen_inbound_connections: Default::default(),
             seen_inbound_messages: Default::default(),
             seen_inbound_puzzle_requests: Default::default(),
+            seen_inbound_transaction_timestamps: Default::default(),
             seen_inbound_solutions: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
             seen_inbound_transactions: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
+            seen_solutions: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
+            seen_transactions: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
             seen_outbound_block_requests: Default::default(),
+            seen_outbound_header_requests: Default::default(),
             seen_outbound_puzzle_requests: Default::default(),
             seen_outbound_solutions: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
             seen_outbound_transactions: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
+            seen_outbound_transaction_announcements: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
+            seen_outbound_transaction_requests: RwLock::new(LinkedHashMap::with_capacity(MAX_CACHE_SIZE)),
             seen_outbound_peer_requests: Default::default(),
         }
     }
@@ -97,6 +115,11 @@ impl<N: Network> Cache<N> {
         Self::retain_and_insert(&self.seen_inbound_puzzle_requests, peer_ip, 60)
     }
 
+    /// Inserts a new timestamp for the given peer unconfirmed transaction, returning the number of recent transactions.
+    pub fn insert_inbound_transaction_timestamp(&self, peer_ip: SocketAddr) -> usize {
+        Self::retain_and_insert(&self.seen_inbound_transaction_timestamps, peer_ip, 60)
+    }
+
     /// Inserts a solution commitment into the cache, returning the previously seen timestamp if it existed.
     pub fn insert_inbound_solution(
         &self,
@@ -114,6 +137,26 @@ impl<N: Network> Cache<N> {
     ) -> Option<OffsetDateTime> {
         Self::refresh_and_insert(&self.seen_inbound_transactions, (peer_ip, transaction))
     }
+
+    /// Returns `true` if the solution commitment was seen from any peer.
+    pub fn contains_seen_solution(&self, solution: &PuzzleCommitment<N>) -> bool {
+        self.seen_solutions.read().contains_key(solution)
+    }
+
+    /// Returns `true` if the transaction ID was seen from any peer.
+    pub fn contains_seen_transaction(&self, transaction: &N::TransactionID) -> bool {
+        self.seen_transactions.read().contains_key(transaction)
+    }
+
+    /// Inserts a solution commitment seen from any peer, returning the previously seen timestamp if it existed.
+    pub fn insert_seen_solution(&self, solution: PuzzleCommitment<N>) -> Option<OffsetDateTime> {
+        Self::refresh_and_insert(&self.seen_solutions, solution)
+    }
+
+    /// Inserts a transaction ID seen from any peer, returning the previously seen timestamp if it existed.
+    pub fn insert_seen_transaction(&self, transaction: N::TransactionID) -> Option<OffsetDateTime> {
+        Self::refresh_and_insert(&self.seen_transactions, transaction)
+    }
 }
 
 impl<N: Network> Cache<N> {
@@ -136,6 +179,20 @@ impl<N: Network> Cache<N> {
         if let Some(requests) = map_write.get_mut(&peer_ip) { requests.remove(request) } else { false }
     }
 
+    /// Inserts the header request for the given peer IP, returning the number of recent requests.
+    pub fn insert_outbound_header_request(&self, peer_ip: SocketAddr, request: HeaderRequest) -> usize {
+        let mut map_write = self.seen_outbound_header_requests.write();
+        let requests = map_write.entry(peer_ip).or_default();
+        requests.insert(request);
+        requests.len()
+    }
+
+    /// Removes the header request for the given peer IP, returning `true` if the request was present.
+    pub fn remove_outbound_header_request(&self, peer_ip: SocketAddr, request: &HeaderRequest) -> bool {
+        let mut map_write = self.seen_outbound_header_requests.write();
+        if let Some(requests) = map_write.get_mut(&peer_ip) { requests.remove(request) } else { false }
+    }
+
     /// Returns `true` if the cache contains a puzzle request from the given peer.
     pub fn contains_outbound_puzzle_request(&self, peer_ip: &SocketAddr) -> bool {
         self.seen_outbound_puzzle_requests.read().get(peer_ip).map(|r| *r > 0).unwrap_or(false)
@@ -169,6 +226,30 @@ impl<N: Network> Cache<N> {
         Self::refresh_and_insert(&self.seen_outbound_transactions, (peer_ip, transaction))
     }
 
+    /// Inserts a transaction ID announced to (or by) the peer, returning the previously seen timestamp if it existed.
+    pub fn insert_outbound_transaction_announcement(
+        &self,
+        peer_ip: SocketAddr,
+        transaction: N::TransactionID,
+    ) -> Option<OffsetDateTime> {
+        Self::refresh_and_insert(&self.seen_outbound_transaction_announcements, (peer_ip, transaction))
+    }
+
+    /// Inserts a request for the given transaction ID, unless it was requested within the given interval.
+    /// Returns `true` if the transaction should be requested.
+    pub fn insert_outbound_transaction_request(&self, transaction: N::TransactionID, interval_in_secs: i64) -> bool {
+        // Fetch the current timestamp.
+        let now = OffsetDateTime::now_utc();
+        // Skip the transaction if it was requested within the interval.
+        if let Some(timestamp) = self.seen_outbound_transaction_requests.read().get(&transaction) {
+            if now - *timestamp <= Duration::seconds(interval_in_secs) {
+                return false;
+            }
+        }
+        Self::refresh_and_insert(&self.seen_outbound_transaction_requests, transaction);
+        true
+    }
+
     /// Returns `true` if the cache contains a peer request from the given peer.
     pub fn contains_outbound_peer_request(&self, peer_ip: SocketAddr) -> bool {
         self.seen_outbound_peer_requests.read().get(&peer_ip).map(|r| *r > 0).unwrap_or(false)
@@ -310,6 +391,40 @@ mod tests {
         assert_eq!(cache.seen_inbound_transactions.read().len(), 1);
     }
 
+    #[test]
+    fn test_seen_transaction() {
+        let cache = Cache::<CurrentNetwork>::default();
+        let peer_a = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 1234);
+        let peer_b = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 5678);
+        let transaction = Default::default();
+
+        // Receive the transaction from the first peer.
+        assert!(cache.insert_inbound_transaction(peer_a, transaction).is_none());
+        assert!(!cache.contains_seen_transaction(&transaction));
+        assert!(cache.insert_seen_transaction(transaction).is_none());
+
+        // Receive the same transaction from the second peer, which is new for the peer, but not for the node.
+        assert!(cache.insert_inbound_transaction(peer_b, transaction).is_none());
+        assert!(cache.contains_seen_transaction(&transaction));
+        assert!(cache.insert_seen_transaction(transaction).is_some());
+
+        // Check that the cache contains the transaction once.
+        assert_eq!(cache.seen_transactions.read().len(), 1);
+    }
+
+    #[test]
+    fn test_outbound_transaction_request() {
+        let cache = Cache::<CurrentNetwork>::default();
+        let transaction = Default::default();
+
+        // Request the transaction.
+        assert!(cache.insert_outbound_transaction_request(transaction, 10));
+        // Ensure the transaction is not requested again within the interval.
+        assert!(!cache.insert_outbound_transaction_request(transaction, 10));
+        // Ensure the transaction is requested again after the interval.
+        assert!(cache.insert_outbound_transaction_request(transaction, -1));
+    }
+
     #[test]
     fn test_outbound_solution() {
         let cache = Cache::<CurrentNetwork>::default();
@@ -0,0 +1,105 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{ConnectionPolicy, LivenessPolicy, RelayPolicy, TlsConfig, ValidatorListener};
+use snarkos_node_tcp::ProxyConfig;
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
+
+/// The Router's configuration. See the source of [`RouterConfig::default`] for the defaults.
+#[derive(Clone)]
+pub struct RouterConfig {
+    /// The listening address of the node.
+    pub node_ip: SocketAddr,
+    /// The peers the node always stays connected to.
+    pub trusted_peers: Vec<SocketAddr>,
+    /// The maximum number of connected peers, excluding the peers of the validator listener.
+    ///
+    /// note: If a connection policy is configured, the peers are limited by the policy instead.
+    pub max_peers: u16,
+    /// The maximum number of inbound peers from a single subnet.
+    pub max_peers_per_subnet: u16,
+    /// The maximum number of bytes per second read from all the connections combined, and likewise written.
+    /// If set to `None`, the bandwidth is not limited.
+    pub max_bandwidth: Option<u32>,
+    /// The maximum number of bytes per second read from each connection, and likewise written.
+    /// If set to `None`, the bandwidth is not limited.
+    pub max_peer_bandwidth: Option<u32>,
+    /// The policy for accepting and relaying unconfirmed transactions.
+    pub relay_policy: RelayPolicy,
+    /// The policy for keeping the connections to peers alive.
+    pub liveness_policy: LivenessPolicy,
+    /// The policy for splitting the connections between outbound and inbound peers, if one is configured.
+    pub connection_policy: Option<ConnectionPolicy>,
+    /// The hostnames that are resolved to the candidate peers of the node.
+    pub dns_seeds: Vec<String>,
+    /// The TLS configuration of the node, if node-to-node connections are terminated over TLS.
+    pub tls: Option<TlsConfig>,
+    /// If `true`, the connections are encrypted with Noise, if the peer supports it.
+    pub noise: bool,
+    /// If `true`, the large messages are compressed with zstd, if the peer supports it.
+    pub compression: bool,
+    /// The path of the persistent list of banned peers. If set to `None`, the bans are not persisted.
+    pub ban_list_path: Option<PathBuf>,
+    /// The path of the persistent identity of the node. If set to `None`, a new identity is generated on every start.
+    pub identity_path: Option<PathBuf>,
+    /// The path of the persistent book of known good peers. If set to `None`, the peers are not persisted.
+    pub peer_book_path: Option<PathBuf>,
+    /// If `true`, the listener port of the node is forwarded on the gateway using UPnP or NAT-PMP.
+    pub port_mapping: bool,
+    /// The public addresses of the node (e.g. an IPv4 and an IPv6 address), which are advertised to peers.
+    pub public_addrs: Vec<SocketAddr>,
+    /// If `false`, the node does not listen for inbound connections.
+    pub listen: bool,
+    /// The listener dedicated to the validator peers, if one is configured.
+    pub validator_listener: Option<ValidatorListener>,
+    /// The SOCKS5 proxy through which the outbound connections are established.
+    /// If set to `None`, the outbound connections are established directly.
+    pub proxy: Option<ProxyConfig>,
+    /// If `true`, the node runs in development mode.
+    pub is_dev: bool,
+}
+
+impl Default for RouterConfig {
+    /// Initializes a new Router configuration with the default values.
+    fn default() -> Self {
+        Self {
+            node_ip: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 4133),
+            trusted_peers: vec![],
+            max_peers: 21,
+            max_peers_per_subnet: 8,
+            max_bandwidth: None,
+            max_peer_bandwidth: None,
+            relay_policy: RelayPolicy::default(),
+            liveness_policy: LivenessPolicy::default(),
+            connection_policy: None,
+            dns_seeds: vec![],
+            tls: None,
+            noise: false,
+            compression: true,
+            ban_list_path: None,
+            identity_path: None,
+            peer_book_path: None,
+            port_mapping: false,
+            public_addrs: vec![],
+            listen: true,
+            validator_listener: None,
+            proxy: None,
+            is_dev: false,
+        }
+    }
+}
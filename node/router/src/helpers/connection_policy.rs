@@ -0,0 +1,74 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Result};
+
+/// The policy for splitting the connections of the node between outbound and inbound peers.
+/// The outbound peers are chosen by the node, which makes it harder to eclipse the node with inbound connections.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConnectionPolicy {
+    /// The number of outbound connections the node maintains.
+    pub target_outbound_peers: u16,
+    /// The maximum number of inbound connections the node accepts.
+    pub max_inbound_peers: u16,
+}
+
+impl Default for ConnectionPolicy {
+    /// Initializes the default connection policy.
+    fn default() -> Self {
+        Self {
+            target_outbound_peers: Self::DEFAULT_TARGET_OUTBOUND_PEERS,
+            max_inbound_peers: Self::DEFAULT_MAX_INBOUND_PEERS,
+        }
+    }
+}
+
+impl ConnectionPolicy {
+    /// The default number of outbound connections the node maintains.
+    pub const DEFAULT_TARGET_OUTBOUND_PEERS: u16 = 8;
+    /// The default maximum number of inbound connections the node accepts.
+    pub const DEFAULT_MAX_INBOUND_PEERS: u16 = 32;
+
+    /// Returns the maximum number of connections, i.e. the outbound target plus the inbound limit.
+    pub const fn max_peers(&self) -> u16 {
+        self.target_outbound_peers.saturating_add(self.max_inbound_peers)
+    }
+
+    /// Ensures the policy lets the node maintain outbound connections.
+    pub fn ensure_is_valid(&self) -> Result<()> {
+        if self.target_outbound_peers == 0 {
+            bail!("the target number of outbound peers must be at least 1")
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_policy() {
+        assert!(ConnectionPolicy::default().ensure_is_valid().is_ok());
+
+        let policy = ConnectionPolicy { target_outbound_peers: 10, max_inbound_peers: 0 };
+        assert!(policy.ensure_is_valid().is_ok());
+        assert_eq!(policy.max_peers(), 10);
+
+        // The node must maintain at least one outbound connection.
+        assert!(ConnectionPolicy { target_outbound_peers: 0, ..policy }.ensure_is_valid().is_err());
+        // The maximum number of connections saturates.
+        assert_eq!(ConnectionPolicy { target_outbound_peers: u16::MAX, max_inbound_peers: 1 }.max_peers(), u16::MAX);
+    }
+}
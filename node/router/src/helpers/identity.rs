@@ -0,0 +1,140 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::NodeId;
+
+use anyhow::{anyhow, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand::rngs::OsRng;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// The persistent (Ed25519) identity of the node, whose public key is the node ID.
+/// The identity is persisted to disk (if a path is given), so that the node ID survives restarts.
+pub struct NodeIdentity {
+    /// The signing key of the identity.
+    signing_key: SigningKey,
+}
+
+impl NodeIdentity {
+    /// The domain separator of the signed handshake nonces.
+    const NONCE_DOMAIN: &'static [u8] = b"snarkos-node-identity";
+
+    /// Loads the identity from the given path, if one is given.
+    /// If the file does not exist yet, a new identity is generated and persisted to it.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self { signing_key: SigningKey::generate(&mut OsRng) });
+        };
+        // Read the identity, if it was persisted already.
+        if path.exists() {
+            let bytes = std::fs::read(&path)
+                .with_context(|| format!("Failed to read the node identity from '{}'", path.display()))?;
+            let secret_key = bytes
+                .try_into()
+                .map_err(|_| anyhow!("Invalid node identity in '{}' (expected 32 bytes)", path.display()))?;
+            return Ok(Self { signing_key: SigningKey::from_bytes(&secret_key) });
+        }
+        // Otherwise, generate a new identity and persist it.
+        let signing_key = SigningKey::generate(&mut OsRng);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::write_secret_key(&path, signing_key.as_bytes())
+            .with_context(|| format!("Failed to write the node identity to '{}'", path.display()))?;
+        Ok(Self { signing_key })
+    }
+
+    /// Writes the given secret key to a new file at the given path. The file is created with access restricted
+    /// to the owner, so that the secret key is never readable by others, and an existing file is never overwritten.
+    fn write_secret_key(path: &Path, secret_key: &[u8]) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(path)?.write_all(secret_key)
+    }
+
+    /// Returns the node ID.
+    pub fn node_id(&self) -> NodeId {
+        NodeId(self.signing_key.verifying_key().to_bytes())
+    }
+
+    /// Signs the given handshake nonce of a peer.
+    pub fn sign_nonce(&self, nonce: u64) -> [u8; 64] {
+        self.signing_key.sign(&Self::nonce_message(nonce)).to_bytes()
+    }
+
+    /// Returns `true` if the given signature of the handshake nonce was produced by the given node ID.
+    pub fn verify_nonce(node_id: &NodeId, nonce: u64, signature: &[u8; 64]) -> bool {
+        match VerifyingKey::from_bytes(&node_id.0) {
+            Ok(verifying_key) => {
+                verifying_key.verify_strict(&Self::nonce_message(nonce), &Signature::from_bytes(signature)).is_ok()
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the signed message of the given handshake nonce.
+    fn nonce_message(nonce: u64) -> Vec<u8> {
+        [Self::NONCE_DOMAIN, &nonce.to_le_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_identity_is_persisted() {
+        let path = std::env::temp_dir().join(format!("snarkos-identity-{}", std::process::id()));
+
+        // Generate the identity, and ensure it is loaded back from the same path.
+        let identity = NodeIdentity::load(Some(path.clone())).unwrap();
+        let reloaded = NodeIdentity::load(Some(path.clone())).unwrap();
+        assert_eq!(identity.node_id(), reloaded.node_id());
+        // Ensure an identity without a path is ephemeral.
+        assert_ne!(identity.node_id(), NodeIdentity::load(None).unwrap().node_id());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_node_identity_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("snarkos-identity-mode-{}", std::process::id()));
+        NodeIdentity::load(Some(path.clone())).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_node_identity_signature() {
+        let identity = NodeIdentity::load(None).unwrap();
+        let signature = identity.sign_nonce(7);
+
+        assert!(NodeIdentity::verify_nonce(&identity.node_id(), 7, &signature));
+        // Ensure the signature does not verify for another nonce, nor for another node ID.
+        assert!(!NodeIdentity::verify_nonce(&identity.node_id(), 8, &signature));
+        assert!(!NodeIdentity::verify_nonce(&NodeIdentity::load(None).unwrap().node_id(), 7, &signature));
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::UnconfirmedTransaction;
+use snarkvm::prelude::Network;
+
+use linked_hash_map::LinkedHashMap;
+use parking_lot::RwLock;
+
+/// The inventory of the unconfirmed transactions recently relayed by the node.
+/// The transactions are announced to the peers by their IDs, and served from the inventory to the peers that request them.
+#[derive(Debug)]
+pub struct TransactionInventory<N: Network> {
+    /// The map of transaction IDs to the serialized transactions, in order of insertion.
+    transactions: RwLock<LinkedHashMap<N::TransactionID, UnconfirmedTransaction<N>>>,
+}
+
+impl<N: Network> Default for TransactionInventory<N> {
+    fn default() -> Self {
+        Self { transactions: RwLock::new(LinkedHashMap::with_capacity(Self::MAXIMUM_NUMBER_OF_TRANSACTIONS)) }
+    }
+}
+
+impl<N: Network> TransactionInventory<N> {
+    /// The maximum number of transactions in the inventory; the oldest transactions are evicted first.
+    pub const MAXIMUM_NUMBER_OF_TRANSACTIONS: usize = 512;
+
+    /// Returns the number of transactions in the inventory.
+    pub fn len(&self) -> usize {
+        self.transactions.read().len()
+    }
+
+    /// Returns `true` if the inventory is empty.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.read().is_empty()
+    }
+
+    /// Returns the transaction with the given ID, if it is in the inventory.
+    pub fn get(&self, transaction_id: &N::TransactionID) -> Option<UnconfirmedTransaction<N>> {
+        self.transactions.read().get(transaction_id).cloned()
+    }
+
+    /// Inserts the given transaction into the inventory, evicting the oldest transaction if the inventory is full.
+    pub fn insert(&self, transaction: UnconfirmedTransaction<N>) {
+        let mut transactions = self.transactions.write();
+        transactions.insert(transaction.transaction_id, transaction);
+        while transactions.len() > Self::MAXIMUM_NUMBER_OF_TRANSACTIONS {
+            transactions.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{ledger::narwhal::Data, prelude::Field};
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    /// Returns a sample (unserialized) transaction message with the given ID.
+    fn sample_transaction(id: u32) -> UnconfirmedTransaction<CurrentNetwork> {
+        UnconfirmedTransaction {
+            transaction_id: Field::<CurrentNetwork>::from_u32(id).into(),
+            transaction: Data::Buffer(Default::default()),
+        }
+    }
+
+    #[test]
+    fn test_transaction_inventory() {
+        let inventory = TransactionInventory::<CurrentNetwork>::default();
+        assert!(inventory.is_empty());
+
+        // Fill the inventory beyond its capacity.
+        let max = TransactionInventory::<CurrentNetwork>::MAXIMUM_NUMBER_OF_TRANSACTIONS as u32;
+        for id in 0..=max {
+            inventory.insert(sample_transaction(id));
+        }
+        assert_eq!(inventory.len(), max as usize);
+
+        // Ensure the oldest transaction was evicted.
+        assert!(inventory.get(&sample_transaction(0).transaction_id).is_none());
+        assert_eq!(inventory.get(&sample_transaction(max).transaction_id), Some(sample_transaction(max)));
+    }
+}
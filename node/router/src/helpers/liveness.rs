@@ -0,0 +1,102 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Result};
+use std::time::Duration;
+
+/// The policy for keeping the connections to peers alive, and for evicting the peers that stop responding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LivenessPolicy {
+    /// The duration in seconds between a `Pong` from a peer and the next `Ping` sent to it.
+    pub ping_interval_in_secs: u64,
+    /// The duration in seconds after which a peer that did not answer a `Ping` is disconnected.
+    pub pong_timeout_in_secs: u64,
+    /// The duration in seconds after which a peer that did not send any message is disconnected.
+    pub idle_timeout_in_secs: u64,
+}
+
+impl Default for LivenessPolicy {
+    /// Initializes the default liveness policy.
+    fn default() -> Self {
+        Self {
+            ping_interval_in_secs: Self::DEFAULT_PING_INTERVAL_IN_SECS,
+            pong_timeout_in_secs: Self::DEFAULT_PONG_TIMEOUT_IN_SECS,
+            idle_timeout_in_secs: Self::DEFAULT_IDLE_TIMEOUT_IN_SECS,
+        }
+    }
+}
+
+impl LivenessPolicy {
+    /// The default duration in seconds between a `Pong` from a peer and the next `Ping` sent to it.
+    pub const DEFAULT_PING_INTERVAL_IN_SECS: u64 = 20; // 20 seconds
+    /// The default duration in seconds after which a peer that did not answer a `Ping` is disconnected.
+    pub const DEFAULT_PONG_TIMEOUT_IN_SECS: u64 = 60; // 1 minute
+    /// The default duration in seconds after which a peer that did not send any message is disconnected.
+    pub const DEFAULT_IDLE_TIMEOUT_IN_SECS: u64 = 150; // 2.5 minutes
+
+    /// Returns the duration between a `Pong` from a peer and the next `Ping` sent to it.
+    pub const fn ping_interval(&self) -> Duration {
+        Duration::from_secs(self.ping_interval_in_secs)
+    }
+
+    /// Returns the duration after which a peer that did not answer a `Ping` is disconnected.
+    pub const fn pong_timeout(&self) -> Duration {
+        Duration::from_secs(self.pong_timeout_in_secs)
+    }
+
+    /// Returns the duration after which a peer that did not send any message is disconnected.
+    pub const fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_in_secs)
+    }
+
+    /// Ensures the policy does not evict the peers that keep responding to the `Ping` messages.
+    pub fn ensure_is_valid(&self) -> Result<()> {
+        if self.ping_interval_in_secs == 0 {
+            bail!("the ping interval must be at least 1 second")
+        }
+        if self.pong_timeout_in_secs == 0 {
+            bail!("the pong timeout must be at least 1 second")
+        }
+        if self.idle_timeout_in_secs <= self.ping_interval_in_secs {
+            bail!(
+                "the idle timeout ({} seconds) must exceed the ping interval ({} seconds)",
+                self.idle_timeout_in_secs,
+                self.ping_interval_in_secs
+            )
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_liveness_policy() {
+        assert!(LivenessPolicy::default().ensure_is_valid().is_ok());
+
+        let policy = LivenessPolicy { ping_interval_in_secs: 10, pong_timeout_in_secs: 5, idle_timeout_in_secs: 30 };
+        assert!(policy.ensure_is_valid().is_ok());
+        assert_eq!(policy.ping_interval(), Duration::from_secs(10));
+        assert_eq!(policy.pong_timeout(), Duration::from_secs(5));
+        assert_eq!(policy.idle_timeout(), Duration::from_secs(30));
+
+        // The timers must be non-zero.
+        assert!(LivenessPolicy { ping_interval_in_secs: 0, ..policy }.ensure_is_valid().is_err());
+        assert!(LivenessPolicy { pong_timeout_in_secs: 0, ..policy }.ensure_is_valid().is_err());
+        // The idle timeout must exceed the ping interval.
+        assert!(LivenessPolicy { idle_timeout_in_secs: 10, ..policy }.ensure_is_valid().is_err());
+    }
+}
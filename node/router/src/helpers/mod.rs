@@ -12,11 +12,53 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod addrman;
+pub use addrman::*;
+
+mod bans;
+pub use bans::*;
+
 mod cache;
 pub use cache::Cache;
 
+mod config;
+pub use config::*;
+
+mod connection_policy;
+pub use connection_policy::*;
+
+mod identity;
+pub use identity::*;
+
+mod inventory;
+pub use inventory::*;
+
+mod liveness;
+pub use liveness::*;
+
+mod nat;
+pub use nat::*;
+
 mod peer;
 pub use peer::*;
 
+mod peer_book;
+pub use peer_book::*;
+
+mod relay;
+pub use relay::*;
+
+mod reputation;
+pub use reputation::*;
+
 mod resolver;
 pub use resolver::*;
+
+mod rng;
+pub use rng::*;
+
+mod tls;
+pub use tls::*;
+
+mod validator_listener;
+pub use validator_listener::*;
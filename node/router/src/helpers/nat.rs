@@ -0,0 +1,162 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, bail, ensure, Result};
+use igd_next::{aio::tokio::search_gateway, PortMappingProtocol, SearchOptions};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+use tokio::net::UdpSocket;
+
+/// The description of the port mapping, as shown by the gateway.
+const PORT_MAPPING_DESCRIPTION: &str = "snarkOS";
+/// The lifetime in seconds of a port mapping, after which it must be renewed.
+pub const PORT_MAPPING_LEASE_IN_SECS: u32 = 3600; // 1 hour
+/// The duration in seconds to wait for a response from the gateway.
+const PORT_MAPPING_TIMEOUT_IN_SECS: u64 = 10;
+/// The port on which the gateway listens for NAT-PMP requests.
+const NATPMP_PORT: u16 = 5351;
+/// The delay in milliseconds before the first retransmission of a NAT-PMP request, which doubles on every retry.
+const NATPMP_INITIAL_DELAY_IN_MS: u64 = 250;
+
+/// Forwards the given listener port of the node on the gateway, using UPnP, or NAT-PMP as a fallback.
+/// Returns the external address of the node. The mapping expires after `PORT_MAPPING_LEASE_IN_SECS`.
+pub async fn map_port(port: u16) -> Result<SocketAddr> {
+    match map_port_upnp(port).await {
+        Ok(external_addr) => Ok(external_addr),
+        Err(upnp_error) => match map_port_natpmp(port).await {
+            Ok(external_addr) => Ok(external_addr),
+            Err(natpmp_error) => bail!("UPnP failed ({upnp_error}), and NAT-PMP failed ({natpmp_error})"),
+        },
+    }
+}
+
+/// Forwards the given port on the gateway using UPnP, returning the external address of the node.
+async fn map_port_upnp(port: u16) -> Result<SocketAddr> {
+    let timeout = Duration::from_secs(PORT_MAPPING_TIMEOUT_IN_SECS);
+    let gateway = search_gateway(SearchOptions { timeout: Some(timeout), ..Default::default() }).await?;
+    // Determine the local IP of the node on the network of the gateway.
+    let local_ip = local_ip_towards(gateway.addr).await?;
+    let external_ip = gateway.get_external_ip().await?;
+    gateway
+        .add_port(
+            PortMappingProtocol::TCP,
+            port,
+            SocketAddr::new(local_ip, port),
+            PORT_MAPPING_LEASE_IN_SECS,
+            PORT_MAPPING_DESCRIPTION,
+        )
+        .await?;
+    Ok(SocketAddr::new(external_ip, port))
+}
+
+/// Forwards the given port on the gateway using NAT-PMP (RFC 6886), returning the external address of the node.
+async fn map_port_natpmp(port: u16) -> Result<SocketAddr> {
+    let gateway = SocketAddr::new(IpAddr::V4(default_gateway()?), NATPMP_PORT);
+    let socket = UdpSocket::bind(SocketAddr::new([0, 0, 0, 0].into(), 0)).await?;
+    socket.connect(gateway).await?;
+
+    // Request the external IP of the gateway.
+    let response = natpmp_request(&socket, &[0, 0], 12).await?;
+    let external_ip = Ipv4Addr::new(response[8], response[9], response[10], response[11]);
+
+    // Request the port mapping, suggesting the same external port.
+    let mut request = vec![0, 2, 0, 0];
+    request.extend_from_slice(&port.to_be_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    request.extend_from_slice(&PORT_MAPPING_LEASE_IN_SECS.to_be_bytes());
+    let response = natpmp_request(&socket, &request, 16).await?;
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    Ok(SocketAddr::new(IpAddr::V4(external_ip), external_port))
+}
+
+/// Sends the given NAT-PMP request to the gateway, retransmitting it until the gateway responds.
+/// Returns the response, which is at least of the given size.
+async fn natpmp_request(socket: &UdpSocket, request: &[u8], response_size: usize) -> Result<Vec<u8>> {
+    let deadline = Instant::now() + Duration::from_secs(PORT_MAPPING_TIMEOUT_IN_SECS);
+    let mut delay = Duration::from_millis(NATPMP_INITIAL_DELAY_IN_MS);
+    let mut buffer = [0u8; 16];
+    while Instant::now() < deadline {
+        socket.send(request).await?;
+        if let Ok(num_bytes) = tokio::time::timeout(delay, socket.recv(&mut buffer)).await {
+            // Ignore the responses to other requests, whose opcode is not the opcode of the request plus 128.
+            let num_bytes = num_bytes?;
+            if num_bytes < response_size || buffer[0] != 0 || buffer[1] != request[1] + 128 {
+                continue;
+            }
+            let result_code = u16::from_be_bytes([buffer[2], buffer[3]]);
+            ensure!(result_code == 0, "The gateway refused the NAT-PMP request (result code {result_code})");
+            return Ok(buffer[..num_bytes].to_vec());
+        }
+        delay *= 2;
+    }
+    bail!("The gateway did not respond to the NAT-PMP request")
+}
+
+/// Returns the IPv4 address of the default gateway, from the routing table.
+fn default_gateway() -> Result<Ipv4Addr> {
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(gateway) = parse_default_gateway(&std::fs::read_to_string("/proc/net/route")?)? {
+            return Ok(gateway);
+        }
+    }
+    bail!("Unable to determine the default gateway")
+}
+
+/// Returns the IPv4 address of the default gateway in the given routing table, in the format of `/proc/net/route`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_default_gateway(routes: &str) -> Result<Option<Ipv4Addr>> {
+    // Every route is a line of fields, with the destination, the gateway and the flags in hexadecimal.
+    for route in routes.lines().skip(1) {
+        if let [_, destination, gateway, flags, ..] = route.split_whitespace().collect::<Vec<_>>()[..] {
+            // The default route has no destination, and goes through a gateway (`RTF_GATEWAY`).
+            if destination == "00000000" && u16::from_str_radix(flags, 16)? & 0x2 != 0 {
+                // The address is printed as a native integer, whose native bytes are in the network byte order.
+                return Ok(Some(Ipv4Addr::from(u32::from_str_radix(gateway, 16)?.to_ne_bytes())));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the local IP used to reach the given address.
+async fn local_ip_towards(addr: SocketAddr) -> Result<IpAddr> {
+    let socket = UdpSocket::bind(SocketAddr::new([0, 0, 0, 0].into(), 0)).await?;
+    socket.connect(addr).await?;
+    let local_ip = socket.local_addr()?.ip();
+    match local_ip.is_unspecified() {
+        true => Err(anyhow!("Unable to determine the local IP towards '{addr}'")),
+        false => Ok(local_ip),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn test_parse_default_gateway() {
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                      eth0\t0002A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+                      eth0\t00000000\t0102A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+        assert_eq!(parse_default_gateway(routes).unwrap(), Some(Ipv4Addr::new(192, 168, 2, 1)));
+
+        // A routing table without a default route has no default gateway.
+        let routes = routes.lines().take(2).collect::<Vec<_>>().join("\n");
+        assert_eq!(parse_default_gateway(&routes).unwrap(), None);
+    }
+}
@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::messages::{ChallengeRequest, NodeType};
+use crate::messages::{ChallengeRequest, NodeId, NodeType, PeerMetadata};
+use snarkos_node_tcp::ConnectionSide;
 use snarkvm::prelude::{Address, Network};
 
-use std::{net::SocketAddr, time::Instant};
+use std::{net::SocketAddr, time::Duration};
+use tokio::time::Instant;
 
 /// The state for each connected peer.
 #[derive(Clone, Debug)]
@@ -26,24 +28,52 @@ pub struct Peer<N: Network> {
     address: Address<N>,
     /// The node type of the peer.
     node_type: NodeType,
+    /// The connection side **from the peer's perspective**.
+    side: ConnectionSide,
     /// The message version of the peer.
     version: u32,
+    /// The features negotiated with the peer during the handshake.
+    features: u32,
+    /// The node ID of the peer, if it has one.
+    node_id: Option<NodeId>,
+    /// If `true`, the peer connected through the validator listener.
+    is_validator_listener: bool,
+    /// The metadata of the peer, if it was received.
+    metadata: Option<PeerMetadata>,
     /// The timestamp of the first message received from the peer.
     first_seen: Instant,
     /// The timestamp of the last message received from this peer.
     last_seen: Instant,
+    /// The timestamp of the last `Ping` sent to the peer, if it was not answered yet.
+    ping_sent: Option<Instant>,
+    /// The smoothed round-trip time to the peer, measured from the `Ping` and `Pong` messages.
+    rtt: Option<Duration>,
 }
 
 impl<N: Network> Peer<N> {
     /// Initializes a new instance of `Peer`.
-    pub fn new(listening_ip: SocketAddr, challenge_request: &ChallengeRequest<N>) -> Self {
+    /// The `side` parameter indicates the connection side **from the peer's perspective**.
+    pub fn new(
+        listening_ip: SocketAddr,
+        side: ConnectionSide,
+        challenge_request: &ChallengeRequest<N>,
+        features: u32,
+        is_validator_listener: bool,
+    ) -> Self {
         Self {
             peer_ip: listening_ip,
             address: challenge_request.address,
             node_type: challenge_request.node_type,
+            side,
             version: challenge_request.version,
+            features,
+            node_id: challenge_request.node_id,
+            is_validator_listener,
+            metadata: None,
             first_seen: Instant::now(),
             last_seen: Instant::now(),
+            ping_sent: None,
+            rtt: None,
         }
     }
 
@@ -77,11 +107,46 @@ impl<N: Network> Peer<N> {
         self.node_type.is_client()
     }
 
+    /// Returns `true` if the peer connected through the validator listener.
+    pub const fn is_validator_listener(&self) -> bool {
+        self.is_validator_listener
+    }
+
+    /// Returns the connection side **from the peer's perspective**.
+    pub const fn side(&self) -> ConnectionSide {
+        self.side
+    }
+
+    /// Returns `true` if the peer initiated the connection.
+    pub const fn is_inbound(&self) -> bool {
+        matches!(self.side, ConnectionSide::Initiator)
+    }
+
     /// Returns the message version of the peer.
     pub const fn version(&self) -> u32 {
         self.version
     }
 
+    /// Returns the features negotiated with the peer during the handshake.
+    pub const fn features(&self) -> u32 {
+        self.features
+    }
+
+    /// Returns `true` if all of the given features were negotiated with the peer.
+    pub const fn supports(&self, features: u32) -> bool {
+        self.features & features == features
+    }
+
+    /// Returns the node ID of the peer, if it has one.
+    pub const fn node_id(&self) -> Option<NodeId> {
+        self.node_id
+    }
+
+    /// Returns the metadata of the peer, if it was received.
+    pub const fn metadata(&self) -> Option<PeerMetadata> {
+        self.metadata
+    }
+
     /// Returns the first seen timestamp of the peer.
     pub fn first_seen(&self) -> Instant {
         self.first_seen
@@ -91,6 +156,16 @@ impl<N: Network> Peer<N> {
     pub fn last_seen(&self) -> Instant {
         self.last_seen
     }
+
+    /// Returns `true` if the last `Ping` sent to the peer was not answered within the given timeout.
+    pub fn is_pong_overdue(&self, timeout: Duration) -> bool {
+        self.ping_sent.map_or(false, |ping_sent| ping_sent.elapsed() > timeout)
+    }
+
+    /// Returns the smoothed round-trip time to the peer, if it was measured.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
 }
 
 impl<N: Network> Peer<N> {
@@ -104,8 +179,61 @@ impl<N: Network> Peer<N> {
         self.version = version;
     }
 
+    /// Updates the metadata of the peer.
+    pub fn set_metadata(&mut self, metadata: PeerMetadata) {
+        self.metadata = Some(metadata);
+    }
+
     /// Updates the last seen timestamp of the peer.
     pub fn set_last_seen(&mut self, last_seen: Instant) {
         self.last_seen = last_seen;
     }
+
+    /// Updates the timestamp of the last `Ping` sent to the peer.
+    pub fn set_ping_sent(&mut self, ping_sent: Instant) {
+        self.ping_sent = Some(ping_sent);
+    }
+
+    /// Updates the smoothed round-trip time with the `Pong` received at the given timestamp,
+    /// if it answers a `Ping`. As in TCP, each new sample is weighted by 1/8.
+    pub fn set_pong_received(&mut self, pong_received: Instant) {
+        if let Some(ping_sent) = self.ping_sent.take() {
+            let sample = pong_received.saturating_duration_since(ping_sent);
+            self.rtt = Some(match self.rtt {
+                Some(rtt) => (rtt * 7 + sample) / 8,
+                None => sample,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{TestRng, Uniform};
+
+    type CurrentNetwork = snarkvm::prelude::Testnet3;
+
+    #[test]
+    fn test_rtt() {
+        let address = Address::<CurrentNetwork>::rand(&mut TestRng::default());
+        let request = ChallengeRequest::new(4133, NodeType::Client, address, 0, 0, None);
+        let mut peer = Peer::new(SocketAddr::from(([1, 2, 3, 4], 4133)), ConnectionSide::Initiator, &request, 0, false);
+        assert_eq!(peer.rtt(), None);
+
+        // The first sample is the round-trip time.
+        let now = Instant::now();
+        peer.set_ping_sent(now);
+        peer.set_pong_received(now + Duration::from_millis(80));
+        assert_eq!(peer.rtt(), Some(Duration::from_millis(80)));
+
+        // An unsolicited `Pong` is ignored.
+        peer.set_pong_received(now + Duration::from_secs(10));
+        assert_eq!(peer.rtt(), Some(Duration::from_millis(80)));
+
+        // The next samples are smoothed.
+        peer.set_ping_sent(now);
+        peer.set_pong_received(now + Duration::from_millis(160));
+        assert_eq!(peer.rtt(), Some(Duration::from_millis(90)));
+    }
 }
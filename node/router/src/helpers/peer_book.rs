@@ -0,0 +1,170 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use time::OffsetDateTime;
+
+/// A known good peer, as recorded in the peer book.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    /// The quality score of the peer, when it was last connected.
+    pub score: u32,
+    /// The UNIX timestamp (in seconds) at which the peer was last connected.
+    pub last_seen: i64,
+}
+
+impl PeerRecord {
+    /// Returns `true` if the peer was last connected too long ago to be worth reconnecting to.
+    pub fn is_expired(&self) -> bool {
+        self.last_seen.saturating_add(PeerBook::EXPIRY_IN_SECS) <= OffsetDateTime::now_utc().unix_timestamp()
+    }
+}
+
+/// The book of known good peer addresses, which is persisted to disk (if a path is given),
+/// so that a restarted node reconnects to the peers it knew, instead of rediscovering the network.
+#[derive(Debug, Default)]
+pub struct PeerBook {
+    /// The path to the file the peer book is persisted to.
+    path: Option<PathBuf>,
+    /// The map of peer addresses to their records.
+    records: RwLock<BTreeMap<SocketAddr, PeerRecord>>,
+}
+
+impl PeerBook {
+    /// The maximum number of records kept in the peer book.
+    pub const MAXIMUM_NUMBER_OF_RECORDS: usize = 1024;
+    /// The duration in seconds after which a peer that was not connected is discarded.
+    pub const EXPIRY_IN_SECS: i64 = 7 * 24 * 60 * 60; // 7 days
+
+    /// Loads the peer book from the given path, if one is given. Expired records are discarded.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let mut records = match &path {
+            Some(path) if path.exists() => {
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Failed to read the peer book from '{}'", path.display()))?;
+                serde_json::from_slice::<BTreeMap<SocketAddr, PeerRecord>>(&bytes)
+                    .with_context(|| format!("Failed to parse the peer book in '{}'", path.display()))?
+            }
+            _ => Default::default(),
+        };
+        records.retain(|_, record| !record.is_expired());
+        Ok(Self { path, records: RwLock::new(records) })
+    }
+
+    /// Returns the path to the file the peer book is persisted to, if one is set.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the number of records.
+    pub fn len(&self) -> usize {
+        self.records.read().len()
+    }
+
+    /// Returns `true` if there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.read().is_empty()
+    }
+
+    /// Returns up to `num` unexpired peer addresses, with the highest scores and the most recently connected first.
+    pub fn best_peers(&self, num: usize) -> Vec<SocketAddr> {
+        best_of(&self.records.read(), num)
+    }
+
+    /// Records the given peer address, which is connected now, with the given quality score.
+    pub fn insert(&self, addr: SocketAddr, score: u32) {
+        let last_seen = OffsetDateTime::now_utc().unix_timestamp();
+        self.records.write().insert(addr, PeerRecord { score, last_seen });
+    }
+
+    /// Retains only the records for which the given predicate returns `true`.
+    pub fn retain(&self, mut predicate: impl FnMut(&SocketAddr) -> bool) {
+        self.records.write().retain(|addr, _| predicate(addr));
+    }
+
+    /// Persists the best `MAXIMUM_NUMBER_OF_RECORDS` unexpired records to disk, if a path is set.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        // Discard the expired records, and the lowest-scored records in excess.
+        let bytes = {
+            let mut records = self.records.write();
+            records.retain(|_, record| !record.is_expired());
+            if records.len() > Self::MAXIMUM_NUMBER_OF_RECORDS {
+                let best = best_of(&records, Self::MAXIMUM_NUMBER_OF_RECORDS).into_iter().collect::<HashSet<_>>();
+                records.retain(|addr, _| best.contains(addr));
+            }
+            serde_json::to_vec_pretty(&*records)?
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, bytes).with_context(|| format!("Failed to write the peer book to '{}'", path.display()))
+    }
+}
+
+/// Returns up to `num` unexpired peer addresses of the given records, with the highest scores and the most recently
+/// connected first.
+fn best_of(records: &BTreeMap<SocketAddr, PeerRecord>, num: usize) -> Vec<SocketAddr> {
+    let mut records = records.iter().filter(|(_, record)| !record.is_expired()).collect::<Vec<_>>();
+    records.sort_unstable_by(|(_, a), (_, b)| b.score.cmp(&a.score).then(b.last_seen.cmp(&a.last_seen)));
+    records.into_iter().take(num).map(|(addr, _)| *addr).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_book_persistence() {
+        let directory = std::env::temp_dir().join(format!("snarkos-peers-{}", std::process::id()));
+        let path = directory.join("peers.json");
+        let good_peer = SocketAddr::from(([1, 2, 3, 4], 4133));
+        let bad_peer = SocketAddr::from(([5, 6, 7, 8], 4133));
+
+        // Record the peers, and ensure they survive a reload.
+        let peer_book = PeerBook::load(Some(path.clone())).unwrap();
+        peer_book.insert(bad_peer, 60);
+        peer_book.insert(good_peer, 100);
+        peer_book.save().unwrap();
+        let peer_book = PeerBook::load(Some(path.clone())).unwrap();
+        assert_eq!(peer_book.len(), 2);
+        // The peers with the highest scores come first.
+        assert_eq!(peer_book.best_peers(2), vec![good_peer, bad_peer]);
+        assert_eq!(peer_book.best_peers(1), vec![good_peer]);
+
+        // Remove a peer, and ensure the removal survives a reload.
+        peer_book.retain(|addr| *addr != bad_peer);
+        peer_book.save().unwrap();
+        let peer_book = PeerBook::load(Some(path)).unwrap();
+        assert_eq!(peer_book.best_peers(2), vec![good_peer]);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_peer_record_expiry() {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        assert!(!PeerRecord { score: 100, last_seen: now }.is_expired());
+        assert!(PeerRecord { score: 100, last_seen: now - PeerBook::EXPIRY_IN_SECS }.is_expired());
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Result};
+
+/// The policy for accepting and relaying the unconfirmed transactions received from peers.
+/// Transactions which do not satisfy the policy are dropped, without penalizing the peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RelayPolicy {
+    /// The minimum fee (in microcredits) of an unconfirmed transaction.
+    pub min_fee: u64,
+    /// The maximum size (in bytes) of an unconfirmed transaction.
+    pub max_transaction_size: usize,
+    /// The maximum number of unconfirmed transactions accepted from a single peer per minute.
+    pub max_transactions_per_peer: usize,
+}
+
+impl Default for RelayPolicy {
+    /// Initializes a permissive relay policy.
+    fn default() -> Self {
+        Self {
+            min_fee: 0,
+            max_transaction_size: Self::DEFAULT_MAX_TRANSACTION_SIZE,
+            max_transactions_per_peer: Self::DEFAULT_MAX_TRANSACTIONS_PER_PEER,
+        }
+    }
+}
+
+impl RelayPolicy {
+    /// The default maximum size (in bytes) of an unconfirmed transaction.
+    pub const DEFAULT_MAX_TRANSACTION_SIZE: usize = 128 * 1024; // 128 KiB
+    /// The default maximum number of unconfirmed transactions accepted from a single peer per minute.
+    pub const DEFAULT_MAX_TRANSACTIONS_PER_PEER: usize = 1_000;
+
+    /// Ensures the given transaction size (in bytes) is within the policy.
+    pub fn ensure_size_is_allowed(&self, size: usize) -> Result<()> {
+        if size > self.max_transaction_size {
+            bail!("the transaction is too large ({size} > {} bytes)", self.max_transaction_size)
+        }
+        Ok(())
+    }
+
+    /// Ensures the given transaction fee (in microcredits) is within the policy.
+    pub fn ensure_fee_is_allowed(&self, fee: u64) -> Result<()> {
+        if fee < self.min_fee {
+            bail!("the transaction fee is too low ({fee} < {} microcredits)", self.min_fee)
+        }
+        Ok(())
+    }
+
+    /// Ensures the given number of recent transactions from a peer is within the policy.
+    pub fn ensure_rate_is_allowed(&self, num_transactions: usize) -> Result<()> {
+        if num_transactions > self.max_transactions_per_peer {
+            bail!("the peer sent too many transactions ({num_transactions} in the last minute)")
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_policy() {
+        let policy = RelayPolicy { min_fee: 1_000, max_transaction_size: 1024, max_transactions_per_peer: 10 };
+
+        assert!(policy.ensure_size_is_allowed(1024).is_ok());
+        assert!(policy.ensure_size_is_allowed(1025).is_err());
+        assert!(policy.ensure_fee_is_allowed(1_000).is_ok());
+        assert!(policy.ensure_fee_is_allowed(999).is_err());
+        assert!(policy.ensure_rate_is_allowed(10).is_ok());
+        assert!(policy.ensure_rate_is_allowed(11).is_err());
+    }
+
+    #[test]
+    fn test_default_relay_policy_accepts_zero_fees() {
+        assert!(RelayPolicy::default().ensure_fee_is_allowed(0).is_ok());
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::DisconnectReason;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, net::SocketAddr};
+
+/// The kinds of misbehavior that lower the reputation of a peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Offense {
+    /// The peer violated the handshake protocol.
+    HandshakeViolation,
+    /// The peer sent an invalid or unexpected message.
+    InvalidMessage,
+    /// The peer sent a block response that failed to process.
+    FailedBlockResponse,
+    /// The peer did not communicate within the allowed time.
+    StalePing,
+    /// The peer uses an outdated message version.
+    /// note: This is not misbehavior, as the peer may simply not have updated yet, so it carries no penalty.
+    OutdatedVersion,
+    /// The peer sent a message with a malformed payload.
+    MalformedMessage,
+    /// The peer sent a response that was not requested.
+    UnsolicitedResponse,
+    /// The peer sent invalid block locators.
+    InvalidBlockLocators,
+}
+
+impl Offense {
+    /// Returns the number of points deducted from the reputation score of the peer.
+    pub const fn penalty(&self) -> u32 {
+        match self {
+            Self::HandshakeViolation => 50,
+            Self::InvalidMessage => 25,
+            Self::FailedBlockResponse => 20,
+            Self::StalePing => 10,
+            Self::OutdatedVersion => 0,
+            Self::MalformedMessage => 25,
+            Self::UnsolicitedResponse => 25,
+            Self::InvalidBlockLocators => 20,
+        }
+    }
+
+    /// Returns the reason sent to the peer, when disconnecting from it for this offense.
+    pub const fn disconnect_reason(&self) -> DisconnectReason {
+        match self {
+            Self::HandshakeViolation => DisconnectReason::InvalidChallengeResponse,
+            Self::InvalidMessage | Self::FailedBlockResponse => DisconnectReason::ProtocolViolation,
+            Self::StalePing => DisconnectReason::PeerHasDisconnected,
+            Self::OutdatedVersion => DisconnectReason::OutdatedClientVersion,
+            Self::MalformedMessage => DisconnectReason::MalformedMessage,
+            Self::UnsolicitedResponse => DisconnectReason::UnsolicitedResponse,
+            Self::InvalidBlockLocators => DisconnectReason::InvalidBlockLocators,
+        }
+    }
+
+    /// Returns an error with the given message, which is attributed to this offense.
+    /// The offense can be recovered from the error with `Offense::from_error`.
+    pub fn error<M: fmt::Display + Send + Sync + 'static>(self, message: M) -> anyhow::Error {
+        anyhow::Error::new(self).context(message)
+    }
+
+    /// Returns the offense the given error is attributed to, or `InvalidMessage` if it is not attributed.
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        error.downcast_ref::<Self>().copied().unwrap_or(Self::InvalidMessage)
+    }
+}
+
+impl fmt::Display for Offense {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Offense {}
+
+/// The action to take against a peer, following an offense.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The peer remains connected.
+    None,
+    /// The peer is to be disconnected.
+    Disconnect,
+    /// The peer is to be disconnected and restricted.
+    Ban,
+}
+
+/// The reputation scores of the peers, keyed by their listener address.
+///
+/// Every peer starts with the maximum score, which is lowered on every offense and regenerated
+/// over time. Peers are only tracked while their score is below the maximum, along with the
+/// tally of their offenses.
+#[derive(Debug, Default)]
+pub struct Reputation {
+    scores: RwLock<HashMap<SocketAddr, u32>>,
+    offenses: RwLock<HashMap<SocketAddr, HashMap<Offense, u32>>>,
+}
+
+impl Reputation {
+    /// The maximum (and initial) reputation score of a peer.
+    pub const MAXIMUM_SCORE: u32 = 100;
+    /// The score at or below which a peer is disconnected.
+    pub const DISCONNECT_THRESHOLD: u32 = 50;
+    /// The number of points regenerated on every call to `regenerate`.
+    pub const REGENERATION: u32 = 5;
+
+    /// Returns the reputation score of the given peer.
+    pub fn score(&self, peer_ip: &SocketAddr) -> u32 {
+        self.scores.read().get(peer_ip).copied().unwrap_or(Self::MAXIMUM_SCORE)
+    }
+
+    /// Returns the peers with a lowered reputation score, along with their scores.
+    pub fn scores(&self) -> Vec<(SocketAddr, u32)> {
+        self.scores.read().iter().map(|(peer_ip, score)| (*peer_ip, *score)).collect()
+    }
+
+    /// Returns the tally of offenses of the given peer.
+    pub fn offenses(&self, peer_ip: &SocketAddr) -> HashMap<Offense, u32> {
+        self.offenses.read().get(peer_ip).cloned().unwrap_or_default()
+    }
+
+    /// Returns the peers with a lowered reputation score, along with the tally of their offenses.
+    pub fn all_offenses(&self) -> Vec<(SocketAddr, HashMap<Offense, u32>)> {
+        self.offenses.read().iter().map(|(peer_ip, offenses)| (*peer_ip, offenses.clone())).collect()
+    }
+
+    /// Lowers the reputation score of the given peer, returning the action to take against it.
+    /// If the score of the peer is exhausted, the peer is banned, and its score is reset, so that it starts afresh once the ban expires.
+    /// The offenses without a penalty are only reported, and leave the reputation of the peer untouched.
+    pub fn record(&self, peer_ip: SocketAddr, offense: Offense) -> Verdict {
+        if offense.penalty() == 0 {
+            return Verdict::None;
+        }
+        *self.offenses.write().entry(peer_ip).or_default().entry(offense).or_default() += 1;
+
+        let mut scores = self.scores.write();
+        let score = scores.entry(peer_ip).or_insert(Self::MAXIMUM_SCORE);
+        *score = score.saturating_sub(offense.penalty());
+
+        if *score == 0 {
+            scores.remove(&peer_ip);
+            Verdict::Ban
+        } else if *score <= Self::DISCONNECT_THRESHOLD {
+            Verdict::Disconnect
+        } else {
+            Verdict::None
+        }
+    }
+
+    /// Regenerates the reputation score of every peer, and stops tracking the peers at the maximum score.
+    pub fn regenerate(&self) {
+        let mut scores = self.scores.write();
+        scores.retain(|_, score| {
+            *score = score.saturating_add(Self::REGENERATION).min(Self::MAXIMUM_SCORE);
+            *score < Self::MAXIMUM_SCORE
+        });
+        self.offenses.write().retain(|peer_ip, _| scores.contains_key(peer_ip));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reputation() {
+        let reputation = Reputation::default();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 4130));
+        assert_eq!(reputation.score(&peer_ip), Reputation::MAXIMUM_SCORE);
+
+        // A single offense lowers the score, without further action.
+        assert_eq!(reputation.record(peer_ip, Offense::StalePing), Verdict::None);
+        assert_eq!(reputation.score(&peer_ip), 90);
+        // Crossing the disconnect threshold disconnects the peer.
+        assert_eq!(reputation.record(peer_ip, Offense::HandshakeViolation), Verdict::Disconnect);
+        assert_eq!(reputation.score(&peer_ip), 40);
+
+        // The score regenerates over time.
+        reputation.regenerate();
+        assert_eq!(reputation.score(&peer_ip), 45);
+        assert_eq!(reputation.scores(), vec![(peer_ip, 45)]);
+
+        // Crossing the ban threshold bans the peer, and resets its score.
+        assert_eq!(reputation.record(peer_ip, Offense::HandshakeViolation), Verdict::Ban);
+        assert_eq!(reputation.score(&peer_ip), Reputation::MAXIMUM_SCORE);
+        assert!(reputation.scores().is_empty());
+    }
+
+    #[test]
+    fn test_regenerate_stops_tracking() {
+        let reputation = Reputation::default();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 4130));
+
+        reputation.record(peer_ip, Offense::StalePing);
+        reputation.regenerate();
+        assert_eq!(reputation.score(&peer_ip), 95);
+        reputation.regenerate();
+        assert!(reputation.scores().is_empty());
+        assert!(reputation.all_offenses().is_empty());
+    }
+
+    #[test]
+    fn test_offense_tally() {
+        let reputation = Reputation::default();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 4130));
+
+        reputation.record(peer_ip, Offense::UnsolicitedResponse);
+        reputation.record(peer_ip, Offense::UnsolicitedResponse);
+        reputation.record(peer_ip, Offense::InvalidBlockLocators);
+        let offenses = reputation.offenses(&peer_ip);
+        assert_eq!(offenses.len(), 2);
+        assert_eq!(offenses[&Offense::UnsolicitedResponse], 2);
+        assert_eq!(offenses[&Offense::InvalidBlockLocators], 1);
+        assert_eq!(reputation.all_offenses(), vec![(peer_ip, offenses)]);
+    }
+
+    #[test]
+    fn test_offense_without_penalty() {
+        let reputation = Reputation::default();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 4130));
+
+        // An outdated version is only reported, however often it occurs.
+        for _ in 0..10 {
+            assert_eq!(reputation.record(peer_ip, Offense::OutdatedVersion), Verdict::None);
+        }
+        assert_eq!(reputation.score(&peer_ip), Reputation::MAXIMUM_SCORE);
+        assert!(reputation.offenses(&peer_ip).is_empty());
+        assert_eq!(Offense::OutdatedVersion.disconnect_reason(), DisconnectReason::OutdatedClientVersion);
+    }
+
+    #[test]
+    fn test_offense_from_error() {
+        let error = Offense::MalformedMessage.error("Peer sent a malformed message");
+        assert_eq!(error.to_string(), "Peer sent a malformed message");
+        assert_eq!(Offense::from_error(&error), Offense::MalformedMessage);
+        assert_eq!(Offense::from_error(&anyhow::anyhow!("Peer misbehaved")), Offense::InvalidMessage);
+        assert_eq!(Offense::MalformedMessage.disconnect_reason(), DisconnectReason::MalformedMessage);
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(feature = "test")]
+use rand::rngs::StdRng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+
+/// The RNG of the router, which is the OS RNG, unless the router runs in a simulation,
+/// in which case it is derived from the seed of the simulation, so that the simulation is reproducible.
+///
+/// The seeded RNG only exists with the `test` feature, so that the nonces and keys of a production build
+/// can only ever be drawn from the OS RNG.
+pub enum RouterRng {
+    /// The OS RNG.
+    Os(OsRng),
+    /// An RNG derived from the seed of a simulation.
+    #[cfg(feature = "test")]
+    Seeded(StdRng),
+}
+
+impl RngCore for RouterRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::Os(rng) => rng.next_u32(),
+            #[cfg(feature = "test")]
+            Self::Seeded(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::Os(rng) => rng.next_u64(),
+            #[cfg(feature = "test")]
+            Self::Seeded(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::Os(rng) => rng.fill_bytes(dest),
+            #[cfg(feature = "test")]
+            Self::Seeded(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            Self::Os(rng) => rng.try_fill_bytes(dest),
+            #[cfg(feature = "test")]
+            Self::Seeded(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+impl CryptoRng for RouterRng {}
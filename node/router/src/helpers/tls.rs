@@ -0,0 +1,90 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_tcp::ConnectionSide;
+
+use anyhow::{Context, Result};
+use std::{fmt, io, net::SocketAddr, path::Path};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_native_tls::{
+    native_tls::{self, Certificate, Identity},
+    TlsAcceptor,
+    TlsConnector,
+    TlsStream,
+};
+
+/// The TLS configuration used to terminate the node-to-node connections of the router.
+///
+/// Peers are dialed by their IP address, so the hostnames in their certificates are not verified;
+/// if no certificate authority is provided, any peer certificate is accepted. In either case, the
+/// identity of a peer is established by the challenge exchange that follows the TLS handshake.
+#[derive(Clone)]
+pub struct TlsConfig {
+    /// The acceptor for the connections initiated by peers.
+    acceptor: TlsAcceptor,
+    /// The connector for the connections initiated by the node.
+    connector: TlsConnector,
+}
+
+impl TlsConfig {
+    /// Loads the TLS configuration from the given PEM-encoded certificate chain and PKCS #8 private key,
+    /// and the optional PEM-encoded certificate authority used to verify the certificates of peers.
+    pub fn load(cert_path: &Path, key_path: &Path, ca_path: Option<&Path>) -> Result<Self> {
+        // Load the identity of the node.
+        let cert = std::fs::read(cert_path)
+            .with_context(|| format!("Failed to read the TLS certificate from '{}'", cert_path.display()))?;
+        let key = std::fs::read(key_path)
+            .with_context(|| format!("Failed to read the TLS private key from '{}'", key_path.display()))?;
+        let identity = Identity::from_pkcs8(&cert, &key).context("Failed to load the TLS identity")?;
+
+        // Initialize the connector, verifying the peer certificates against the certificate authority, if one is given.
+        let mut connector = native_tls::TlsConnector::builder();
+        connector.identity(identity.clone()).danger_accept_invalid_hostnames(true);
+        match ca_path {
+            Some(ca_path) => {
+                let ca = std::fs::read(ca_path).with_context(|| {
+                    format!("Failed to read the TLS certificate authority from '{}'", ca_path.display())
+                })?;
+                let ca = Certificate::from_pem(&ca).context("Failed to load the TLS certificate authority")?;
+                connector.disable_built_in_roots(true).add_root_certificate(ca);
+            }
+            None => {
+                connector.danger_accept_invalid_certs(true);
+            }
+        }
+
+        Ok(Self { acceptor: native_tls::TlsAcceptor::new(identity)?.into(), connector: connector.build()?.into() })
+    }
+
+    /// Performs the TLS handshake over the given stream.
+    /// The `peer_side` parameter indicates the connection side **from the peer's perspective**.
+    pub async fn wrap<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        peer_addr: SocketAddr,
+        stream: S,
+        peer_side: ConnectionSide,
+    ) -> io::Result<TlsStream<S>> {
+        let result = match peer_side {
+            ConnectionSide::Initiator => self.acceptor.accept(stream).await,
+            ConnectionSide::Responder => self.connector.connect(&peer_addr.ip().to_string(), stream).await,
+        };
+        result.map_err(|e| io::Error::new(io::ErrorKind::ConnectionRefused, format!("TLS handshake failed - {e}")))
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Result};
+use std::net::SocketAddr;
+
+/// The listener dedicated to the validator peers. It is separate from the public listener, and has its own
+/// connection limit and message limit, so that the connectivity of the validators cannot be crowded out by
+/// the public peers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorListener {
+    /// The address to listen on for the validator peers.
+    pub addr: SocketAddr,
+    /// The maximum number of validator peers connected through the listener.
+    pub max_peers: u16,
+    /// The maximum number of messages accepted from a validator peer within the message limit time frame.
+    pub message_limit: usize,
+}
+
+impl ValidatorListener {
+    /// The default maximum number of validator peers connected through the listener.
+    pub const DEFAULT_MAX_PEERS: u16 = 100;
+    /// The default maximum number of messages accepted from a validator peer within the message limit time frame.
+    pub const DEFAULT_MESSAGE_LIMIT: usize = 5000;
+
+    /// Initializes a new validator listener on the given address, with the default limits.
+    pub const fn new(addr: SocketAddr) -> Self {
+        Self { addr, max_peers: Self::DEFAULT_MAX_PEERS, message_limit: Self::DEFAULT_MESSAGE_LIMIT }
+    }
+
+    /// Ensures the listener accepts at least one validator peer, and at least one message from it.
+    pub fn ensure_is_valid(&self) -> Result<()> {
+        if self.max_peers == 0 {
+            bail!("the maximum number of validator peers must be at least 1")
+        }
+        if self.message_limit == 0 {
+            bail!("the message limit of the validator peers must be at least 1")
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator_listener() {
+        let listener = ValidatorListener::new("127.0.0.1:4140".parse().unwrap());
+        assert_eq!(listener.max_peers, ValidatorListener::DEFAULT_MAX_PEERS);
+        assert_eq!(listener.message_limit, ValidatorListener::DEFAULT_MESSAGE_LIMIT);
+        assert!(listener.ensure_is_valid().is_ok());
+
+        // The limits must be non-zero.
+        assert!(ValidatorListener { max_peers: 0, ..listener }.ensure_is_valid().is_err());
+        assert!(ValidatorListener { message_limit: 0, ..listener }.ensure_is_valid().is_err());
+    }
+}
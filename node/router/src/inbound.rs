@@ -14,16 +14,20 @@
 
 use crate::{
     messages::{
+        BlockHeader,
         BlockRequest,
         BlockResponse,
         DataBlocks,
+        HeaderRequest,
         Message,
         PeerResponse,
         Ping,
         Pong,
+        TransactionRequest,
         UnconfirmedSolution,
         UnconfirmedTransaction,
     },
+    Offense,
     Outbound,
     Peer,
 };
@@ -31,24 +35,25 @@ use snarkos_node_tcp::protocols::Reading;
 use snarkvm::prelude::{
     block::{Block, Header, Transaction},
     coinbase::{EpochChallenge, ProverSolution},
+    ledger::narwhal::Data,
     Network,
 };
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{bail, Result};
 use snarkos_node_tcp::is_bogon_ip;
-use std::{net::SocketAddr, time::Instant};
-use tokio::task::spawn_blocking;
+use std::net::SocketAddr;
+use tokio::{task::spawn_blocking, time::Instant};
 
 #[async_trait]
 pub trait Inbound<N: Network>: Reading + Outbound<N> {
     /// The maximum number of puzzle requests per interval.
     const MAXIMUM_PUZZLE_REQUESTS_PER_INTERVAL: usize = 5;
-    /// The duration in seconds to sleep in between ping requests with a connected peer.
-    const PING_SLEEP_IN_SECS: u64 = 20; // 20 seconds
     /// The time frame to enforce the `MESSAGE_LIMIT`.
     const MESSAGE_LIMIT_TIME_FRAME_IN_SECS: i64 = 5;
     /// The maximum number of messages accepted within `MESSAGE_LIMIT_TIME_FRAME_IN_SECS`.
     const MESSAGE_LIMIT: usize = 500;
+    /// The duration in seconds after which an announced transaction is requested again, if it was not received.
+    const TRANSACTION_REQUEST_TIMEOUT_IN_SECS: i64 = 10;
 
     /// Handles the inbound message from the peer.
     async fn inbound(&self, peer_addr: SocketAddr, message: Message<N>) -> Result<()> {
@@ -60,8 +65,10 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
 
         // Drop the peer, if they have sent more than `MESSAGE_LIMIT` messages
         // in the last `MESSAGE_LIMIT_TIME_FRAME_IN_SECS` seconds.
+        // The peers connected through the validator listener are subject to the message limit of the listener.
+        let message_limit = self.router().validator_listener_message_limit(&peer_ip).unwrap_or(Self::MESSAGE_LIMIT);
         let num_messages = self.router().cache.insert_inbound_message(peer_ip, Self::MESSAGE_LIMIT_TIME_FRAME_IN_SECS);
-        if num_messages > Self::MESSAGE_LIMIT {
+        if num_messages > message_limit {
             bail!("Dropping '{peer_ip}' for spamming messages (num_messages = {num_messages})")
         }
 
@@ -70,6 +77,17 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
         // This match statement handles the inbound message by deserializing the message,
         // checking that the message is valid, and then calling the appropriate (trait) handler.
         match message {
+            Message::BlockAnnouncement(message) => {
+                // Ensure the block announcements were negotiated with the peer.
+                if !self.router().supports_headers_first(&peer_ip) {
+                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected block announcement)")
+                }
+                // Process the block announcement.
+                match self.block_announcement(peer_ip, message.block_header) {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid block announcement"),
+                }
+            }
             Message::BlockRequest(message) => {
                 let BlockRequest { start_height, end_height } = &message;
 
@@ -93,18 +111,27 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
 
                 // Remove the block request, checking if this node previously sent a block request to this peer.
                 if !self.router().cache.remove_outbound_block_request(peer_ip, &request) {
-                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected block response)")
+                    let message = format!("Peer '{peer_ip}' is not following the protocol (unexpected block response)");
+                    return Err(Offense::UnsolicitedResponse.error(message));
                 }
                 // Perform the deferred non-blocking deserialization of the blocks.
-                let blocks = blocks.deserialize().await.map_err(|error| anyhow!("[BlockResponse] {error}"))?;
+                let blocks = blocks
+                    .deserialize()
+                    .await
+                    .map_err(|error| Offense::MalformedMessage.error(format!("[BlockResponse] {error}")))?;
                 // Ensure the block response is well-formed.
-                blocks.ensure_response_is_well_formed(peer_ip, request.start_height, request.end_height)?;
+                blocks
+                    .ensure_response_is_well_formed(peer_ip, request.start_height, request.end_height)
+                    .map_err(|error| Offense::MalformedMessage.error(error))?;
 
                 // Process the block response.
                 let node = self.clone();
                 match spawn_blocking(move || node.block_response(peer_ip, blocks.0)).await? {
                     true => Ok(()),
-                    false => bail!("Peer '{peer_ip}' sent an invalid block response"),
+                    false => {
+                        let message = format!("Peer '{peer_ip}' sent an invalid block response");
+                        Err(Offense::FailedBlockResponse.error(message))
+                    }
                 }
             }
             Message::ChallengeRequest(..) | Message::ChallengeResponse(..) => {
@@ -114,13 +141,51 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
             Message::Disconnect(message) => {
                 bail!("{:?}", message.reason)
             }
+            Message::HeaderRequest(message) => {
+                let HeaderRequest { start_height, end_height } = &message;
+
+                // Ensure the header request is well-formed.
+                if start_height >= end_height {
+                    bail!("Header request from '{peer_ip}' has an invalid range ({start_height}..{end_height})")
+                }
+                // Ensure that the header request is within the allowed bounds.
+                if end_height - start_height > HeaderRequest::MAXIMUM_NUMBER_OF_HEADERS {
+                    bail!("Header request from '{peer_ip}' has an excessive range ({start_height}..{end_height})")
+                }
+
+                let node = self.clone();
+                match spawn_blocking(move || node.header_request(peer_ip, message)).await? {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid header request"),
+                }
+            }
+            Message::HeaderResponse(message) => {
+                // Remove the header request, checking if this node previously sent a header request to this peer.
+                if !self.router().cache.remove_outbound_header_request(peer_ip, &message.request) {
+                    let message =
+                        format!("Peer '{peer_ip}' is not following the protocol (unexpected header response)");
+                    return Err(Offense::UnsolicitedResponse.error(message));
+                }
+                // Ensure the header response is well-formed.
+                message
+                    .ensure_response_is_well_formed(peer_ip)
+                    .map_err(|error| Offense::MalformedMessage.error(error))?;
+
+                // Process the header response.
+                let node = self.clone();
+                match spawn_blocking(move || node.header_response(peer_ip, message.headers)).await? {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid header response"),
+                }
+            }
             Message::PeerRequest(..) => match self.peer_request(peer_ip) {
                 true => Ok(()),
                 false => bail!("Peer '{peer_ip}' sent an invalid peer request"),
             },
             Message::PeerResponse(message) => {
                 if !self.router().cache.contains_outbound_peer_request(peer_ip) {
-                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected peer response)")
+                    let message = format!("Peer '{peer_ip}' is not following the protocol (unexpected peer response)");
+                    return Err(Offense::UnsolicitedResponse.error(message));
                 }
 
                 match self.peer_response(peer_ip, &message.peers) {
@@ -129,9 +194,11 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                 }
             }
             Message::Ping(message) => {
-                // Ensure the message protocol version is not outdated.
-                if message.version < Message::<N>::VERSION {
-                    bail!("Dropping '{peer_ip}' on message version {} (outdated)", message.version);
+                // Ensure the message protocol version is not outdated, while accepting the last released version.
+                if !Message::<N>::is_compatible_version(message.version) {
+                    let message = format!("Dropping '{peer_ip}' on message version {} (outdated)", message.version);
+                    // note: The peer is disconnected with the reason, but its reputation is not lowered.
+                    return Err(Offense::OutdatedVersion.error(message));
                 }
 
                 // If the peer is a client or validator, ensure there are block locators.
@@ -143,6 +210,11 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                 else if message.node_type.is_prover() && message.block_locators.is_some() {
                     bail!("Peer '{peer_ip}' is a prover or client, but block locators were provided");
                 }
+                // Ensure the block locators are well-formed.
+                if let Some(Err(error)) = message.block_locators.as_ref().map(|locators| locators.ensure_is_valid()) {
+                    let message = format!("Peer '{peer_ip}' sent invalid block locators - {error}");
+                    return Err(Offense::InvalidBlockLocators.error(message));
+                }
 
                 // Update the connected peer.
                 if let Err(error) =
@@ -151,6 +223,10 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                         peer.set_version(message.version);
                         // Update the node type of the peer.
                         peer.set_node_type(message.node_type);
+                        // Update the metadata of the peer, if it was provided.
+                        if let Some(metadata) = message.metadata {
+                            peer.set_metadata(metadata);
+                        }
                         // Update the last seen timestamp of the peer.
                         peer.set_last_seen(Instant::now());
                     })
@@ -164,10 +240,22 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                     false => bail!("Peer '{peer_ip}' sent an invalid ping"),
                 }
             }
-            Message::Pong(message) => match self.pong(peer_ip, message) {
-                true => Ok(()),
-                false => bail!("Peer '{peer_ip}' sent an invalid pong"),
-            },
+            Message::Pong(message) => {
+                // Update the round-trip time of the peer.
+                self.router().insert_pong_received(peer_ip);
+                // Record the listening address of this node, as observed by the peer.
+                if let Some(observed_addr) = message.observed_addr {
+                    if !self.router().supports_address_announcements(&peer_ip) {
+                        bail!("Peer '{peer_ip}' is not following the protocol (unexpected observed address)")
+                    }
+                    self.router().insert_observed_addr(peer_ip, observed_addr);
+                }
+
+                match self.pong(peer_ip, message) {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid pong"),
+                }
+            }
             Message::PuzzleRequest(..) => {
                 // Insert the puzzle request for the peer, and fetch the recent frequency.
                 let frequency = self.router().cache.insert_inbound_puzzle_request(peer_ip);
@@ -184,7 +272,9 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
             Message::PuzzleResponse(message) => {
                 // Check that this node previously sent a puzzle request to this peer.
                 if !self.router().cache.contains_outbound_puzzle_request(&peer_ip) {
-                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected puzzle response)")
+                    let message =
+                        format!("Peer '{peer_ip}' is not following the protocol (unexpected puzzle response)");
+                    return Err(Offense::UnsolicitedResponse.error(message));
                 }
                 // Decrement the number of puzzle requests.
                 self.router().cache.decrement_outbound_puzzle_requests(peer_ip);
@@ -192,7 +282,7 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                 // Perform the deferred non-blocking deserialization of the block header.
                 let header = match message.block_header.deserialize().await {
                     Ok(header) => header,
-                    Err(error) => bail!("[PuzzleResponse] {error}"),
+                    Err(error) => return Err(Offense::MalformedMessage.error(format!("[PuzzleResponse] {error}"))),
                 };
                 // Process the puzzle response.
                 match self.puzzle_response(peer_ip, message.epoch_challenge, header) {
@@ -209,21 +299,79 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                 if seen_before {
                     bail!("Skipping 'UnconfirmedSolution' from '{peer_ip}'")
                 }
+                // Skip the solution if it was already received from another peer.
+                if self.router().cache.contains_seen_solution(&message.solution_id) {
+                    trace!("Skipping duplicate 'UnconfirmedSolution' from '{peer_ip}'");
+                    return Ok(());
+                }
                 // Perform the deferred non-blocking deserialization of the solution.
                 let solution = match message.solution.deserialize().await {
                     Ok(solution) => solution,
-                    Err(error) => bail!("[UnconfirmedSolution] {error}"),
+                    Err(error) => return Err(Offense::MalformedMessage.error(format!("[UnconfirmedSolution] {error}"))),
                 };
                 // Check that the solution parameters match.
                 if message.solution_id != solution.commitment() {
                     bail!("Peer '{peer_ip}' is not following the 'UnconfirmedSolution' protocol")
                 }
+                // Mark the solution as seen, now that its commitment is verified.
+                if self.router().cache.insert_seen_solution(message.solution_id).is_some() {
+                    return Ok(());
+                }
                 // Handle the unconfirmed solution.
                 match self.unconfirmed_solution(peer_ip, serialized, solution).await {
                     true => Ok(()),
                     false => bail!("Peer '{peer_ip}' sent an invalid unconfirmed solution"),
                 }
             }
+            Message::TransactionAnnouncement(message) => {
+                // Ensure the transaction announcements were negotiated with the peer.
+                if !self.router().supports_transaction_announcements(&peer_ip) {
+                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected transaction announcement)")
+                }
+                // Request the announced transactions which were not seen yet, nor recently requested from another peer.
+                let cache = &self.router().cache;
+                let transaction_ids = message
+                    .transaction_ids
+                    .into_iter()
+                    .filter(|transaction_id| {
+                        // The peer has the transaction, so it is not announced back to the peer.
+                        cache.insert_outbound_transaction_announcement(peer_ip, *transaction_id);
+                        !cache.contains_seen_transaction(transaction_id)
+                            && cache.insert_outbound_transaction_request(
+                                *transaction_id,
+                                Self::TRANSACTION_REQUEST_TIMEOUT_IN_SECS,
+                            )
+                    })
+                    .collect::<Vec<_>>();
+                if !transaction_ids.is_empty() {
+                    self.send(peer_ip, Message::TransactionRequest(TransactionRequest { transaction_ids }));
+                }
+                Ok(())
+            }
+            Message::TransactionRequest(message) => {
+                // Ensure the transaction announcements were negotiated with the peer.
+                if !self.router().supports_transaction_announcements(&peer_ip) {
+                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected transaction request)")
+                }
+                // Send the requested transactions which are in the inventory.
+                for transaction_id in message.transaction_ids {
+                    if let Some(transaction) = self.router().transaction_inventory().get(&transaction_id) {
+                        self.send(peer_ip, Message::UnconfirmedTransaction(transaction));
+                    }
+                }
+                Ok(())
+            }
+            Message::AddressAnnouncement(message) => {
+                // Ensure the address announcements were negotiated with the peer.
+                if !self.router().supports_address_announcements(&peer_ip) {
+                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected address announcement)")
+                }
+                // The announced addresses are handled like the addresses of a peer response.
+                match self.peer_response(peer_ip, &message.addrs) {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid address announcement"),
+                }
+            }
             Message::UnconfirmedTransaction(message) => {
                 // Clone the serialized message.
                 let serialized = message.clone();
@@ -234,15 +382,46 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                 if seen_before {
                     bail!("Skipping 'UnconfirmedTransaction' from '{peer_ip}'")
                 }
+                // Skip the transaction if it was already received from another peer.
+                if self.router().cache.contains_seen_transaction(&message.transaction_id) {
+                    trace!("Skipping duplicate 'UnconfirmedTransaction' from '{peer_ip}'");
+                    return Ok(());
+                }
+                // Ensure the peer and the transaction size are within the relay policy.
+                let relay_policy = *self.router().relay_policy();
+                let num_transactions = self.router().cache.insert_inbound_transaction_timestamp(peer_ip);
+                let size = match &message.transaction {
+                    Data::Buffer(bytes) => bytes.len(),
+                    Data::Object(_) => 0,
+                };
+                if let Err(error) = relay_policy
+                    .ensure_rate_is_allowed(num_transactions)
+                    .and_then(|_| relay_policy.ensure_size_is_allowed(size))
+                {
+                    debug!("Dropping 'UnconfirmedTransaction' from '{peer_ip}' - {error}");
+                    return Ok(());
+                }
                 // Perform the deferred non-blocking deserialization of the transaction.
                 let transaction = match message.transaction.deserialize().await {
                     Ok(transaction) => transaction,
-                    Err(error) => bail!("[UnconfirmedTransaction] {error}"),
+                    Err(error) => {
+                        return Err(Offense::MalformedMessage.error(format!("[UnconfirmedTransaction] {error}")));
+                    }
                 };
                 // Check that the transaction parameters match.
                 if message.transaction_id != transaction.id() {
                     bail!("Peer '{peer_ip}' is not following the 'UnconfirmedTransaction' protocol")
                 }
+                // Mark the transaction as seen, now that its ID is verified.
+                if self.router().cache.insert_seen_transaction(message.transaction_id).is_some() {
+                    return Ok(());
+                }
+                // Ensure the transaction fee is within the relay policy.
+                let fee = transaction.fee_amount().map(|fee| *fee).unwrap_or_default();
+                if let Err(error) = relay_policy.ensure_fee_is_allowed(fee) {
+                    debug!("Dropping 'UnconfirmedTransaction' from '{peer_ip}' - {error}");
+                    return Ok(());
+                }
                 // Handle the unconfirmed transaction.
                 match self.unconfirmed_transaction(peer_ip, serialized, transaction).await {
                     true => Ok(()),
@@ -252,36 +431,41 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
         }
     }
 
+    /// Handles a `BlockAnnouncement` message.
+    fn block_announcement(&self, peer_ip: SocketAddr, _block_header: BlockHeader<N>) -> bool;
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, peer_ip: SocketAddr, _message: BlockRequest) -> bool;
 
     /// Handles a `BlockResponse` message.
     fn block_response(&self, peer_ip: SocketAddr, _blocks: Vec<Block<N>>) -> bool;
 
+    /// Handles a `HeaderRequest` message.
+    fn header_request(&self, peer_ip: SocketAddr, _message: HeaderRequest) -> bool;
+
+    /// Handles a `HeaderResponse` message.
+    fn header_response(&self, peer_ip: SocketAddr, _headers: Vec<BlockHeader<N>>) -> bool;
+
     /// Handles a `PeerRequest` message.
     fn peer_request(&self, peer_ip: SocketAddr) -> bool {
         // Retrieve the connected peers.
         let peers = self.router().connected_peers();
         // Filter out invalid addresses.
-        let peers = match self.router().is_dev() {
+        let peers: Vec<_> = match self.router().is_dev() {
             // In development mode, relax the validity requirements to make operating devnets more flexible.
-            true => {
-                peers.into_iter().filter(|ip| *ip != peer_ip && !is_bogon_ip(ip.ip())).take(u8::MAX as usize).collect()
-            }
+            true => peers.into_iter().filter(|ip| *ip != peer_ip && !is_bogon_ip(ip.ip())).collect(),
             // In production mode, ensure the peer IPs are valid.
-            false => peers
-                .into_iter()
-                .filter(|ip| *ip != peer_ip && self.router().is_valid_peer_ip(ip))
-                .take(u8::MAX as usize)
-                .collect(),
+            false => peers.into_iter().filter(|ip| *ip != peer_ip && self.router().is_valid_peer_ip(ip)).collect(),
         };
+        // Advertise the addresses of this node first, so that the peers of either address family can discover it.
+        let peers = self.router().advertised_addrs().into_iter().chain(peers).take(u8::MAX as usize).collect();
         // Send a `PeerResponse` message to the peer.
         self.send(peer_ip, Message::PeerResponse(PeerResponse { peers }));
         true
     }
 
     /// Handles a `PeerResponse` message.
-    fn peer_response(&self, _peer_ip: SocketAddr, peers: &[SocketAddr]) -> bool {
+    fn peer_response(&self, peer_ip: SocketAddr, peers: &[SocketAddr]) -> bool {
         // Filter out invalid addresses.
         let peers = match self.router().is_dev() {
             // In development mode, relax the validity requirements to make operating devnets more flexible.
@@ -290,7 +474,7 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
             false => peers.iter().copied().filter(|ip| self.router().is_valid_peer_ip(ip)).collect(),
         };
         // Adds the given peer IPs to the list of candidate peers.
-        self.router().insert_candidate_peers(&peers);
+        self.router().insert_candidate_peers(peer_ip.ip(), &peers);
         true
     }
 
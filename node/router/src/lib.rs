@@ -38,23 +38,44 @@ pub use outbound::*;
 mod routing;
 pub use routing::*;
 
-use crate::messages::NodeType;
+use crate::messages::{
+    ChallengeRequest,
+    MessageCodec,
+    MessageTraffic,
+    NodeId,
+    NodeType,
+    NoiseTransport,
+    PeerMetadata,
+    TrafficCounts,
+    NOISE_HANDSHAKE_TYPE,
+};
 use snarkos_account::Account;
-use snarkos_node_tcp::{is_bogon_ip, is_unspecified_or_broadcast_ip, Config, Tcp};
+use snarkos_node_tcp::{
+    canonical_addr,
+    is_bogon_ip,
+    is_unspecified_or_broadcast_ip,
+    subnet,
+    Config,
+    ConnectionSide,
+    Tcp,
+};
 use snarkvm::prelude::{Address, Network, PrivateKey, ViewKey};
 
 use anyhow::{bail, Result};
+use indexmap::IndexMap;
 use parking_lot::{Mutex, RwLock};
+use rand::rngs::OsRng;
+#[cfg(feature = "test")]
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use std::{
     collections::{HashMap, HashSet},
     future::Future,
-    net::SocketAddr,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
     ops::Deref,
     str::FromStr,
     sync::Arc,
-    time::Instant,
 };
-use tokio::task::JoinHandle;
+use tokio::{task::JoinHandle, time::Instant};
 
 #[derive(Clone)]
 pub struct Router<N: Network>(Arc<InnerRouter<N>>);
@@ -74,12 +95,24 @@ pub struct InnerRouter<N: Network> {
     node_type: NodeType,
     /// The account of the node.
     account: Account<N>,
+    /// The persistent identity of the node.
+    identity: NodeIdentity,
     /// The cache.
     cache: Cache<N>,
+    /// The inventory of the recently relayed unconfirmed transactions.
+    transaction_inventory: TransactionInventory<N>,
     /// The resolver.
     resolver: Resolver,
-    /// The set of trusted peers.
-    trusted_peers: HashSet<SocketAddr>,
+    /// The reputation scores of the peers.
+    reputation: Reputation,
+    /// The maximum number of inbound, and of outbound, connected peers per subnet.
+    max_peers_per_subnet: u16,
+    /// The hostnames of the DNS seeds, which resolve to the addresses of candidate peers.
+    dns_seeds: Vec<String>,
+    /// The timestamp of the last resolution of the DNS seeds.
+    last_dns_seed_lookup: Mutex<Option<Instant>>,
+    /// The set of trusted peers, which can be extended at runtime by whitelisting a peer.
+    trusted_peers: RwLock<HashSet<SocketAddr>>,
     /// The map of connected peer IPs to their peer handlers.
     connected_peers: RwLock<HashMap<SocketAddr, Peer<N>>>,
     /// The set of handshaking peers. While `Tcp` already recognizes the connecting IP addresses
@@ -87,52 +120,212 @@ pub struct InnerRouter<N: Network> {
     /// prevent simultaneous "two-way" connections between two peers (i.e. both nodes simultaneously
     /// attempt to connect to each other). This set is used to prevent this from happening.
     connecting_peers: Mutex<HashSet<SocketAddr>>,
-    /// The set of candidate peer IPs.
-    candidate_peers: RwLock<HashSet<SocketAddr>>,
+    /// The set of node IDs of the handshaking peers, which are reserved once verified, so that simultaneous
+    /// connections from the same node (e.g. over several IPs) cannot all pass the check for a connected node ID.
+    connecting_node_ids: Mutex<HashSet<NodeId>>,
+    /// The address manager of the candidate peer IPs.
+    candidate_peers: AddressManager,
     /// The set of restricted peer IPs.
     restricted_peers: RwLock<HashMap<SocketAddr, Instant>>,
+    /// The set of IPs restricted for spamming connection attempts, as their listener addresses are not known yet.
+    restricted_ips: RwLock<HashMap<IpAddr, Instant>>,
+    /// The persistent list of banned peer IPs.
+    ban_list: BanList,
+    /// The persistent book of known good peers.
+    peer_book: PeerBook,
     /// The spawned handles.
     handles: Mutex<Vec<JoinHandle<()>>>,
+    /// The TLS configuration of the node, if node-to-node connections are terminated over TLS.
+    tls: Option<TlsConfig>,
+    /// The static Noise keypair of the node, if encrypted connections are enabled.
+    noise_keypair: Option<snow::Keypair>,
+    /// The map of (ambiguous) peer addresses to the transport states of their encrypted connections.
+    noise_states: RwLock<HashMap<SocketAddr, NoiseTransport>>,
+    /// The policy for accepting and relaying unconfirmed transactions.
+    relay_policy: RelayPolicy,
+    /// The policy for keeping the connections to peers alive.
+    liveness_policy: LivenessPolicy,
+    /// The policy for splitting the connections between outbound and inbound peers, if one is configured.
+    connection_policy: Option<ConnectionPolicy>,
+    /// The listener dedicated to the validator peers, if one is configured.
+    validator_listener: Option<ValidatorListener>,
+    /// The address of the validator listener, once it is listening.
+    validator_listener_addr: RwLock<Option<SocketAddr>>,
+    /// The addresses of the members of the current committee, which are the only peers admitted through the
+    /// validator listener.
+    validator_committee: RwLock<HashSet<Address<N>>>,
+    /// The boolean flag for the zstd compression of large messages.
+    compression: bool,
+    /// The set of (ambiguous) peer addresses of the connections with compression enabled.
+    compressed_peers: RwLock<HashSet<SocketAddr>>,
+    /// The set of (ambiguous) peer addresses of the connections with frame checksums enabled.
+    checksummed_peers: RwLock<HashSet<SocketAddr>>,
+    /// The map of (ambiguous) peer addresses to the traffic of their connections, by message kind.
+    traffic: RwLock<HashMap<SocketAddr, Arc<MessageTraffic>>>,
+    /// If `true`, the listener port of the node is forwarded on the gateway using UPnP or NAT-PMP.
+    port_mapping: bool,
+    /// The external address of the node, if its listener port is forwarded on the gateway.
+    external_addr: RwLock<Option<SocketAddr>>,
+    /// The public addresses of the node (e.g. an IPv4 and an IPv6 address), which are advertised to peers.
+    public_addrs: RwLock<Vec<SocketAddr>>,
+    /// The map of each connected peer to the listening address of the node, as observed by the peer.
+    observed_addrs: RwLock<HashMap<SocketAddr, SocketAddr>>,
     /// The boolean flag for the development mode.
     is_dev: bool,
+    /// The RNG seeded by the simulation the node runs in, if any.
+    #[cfg(feature = "test")]
+    seeded_rng: Mutex<Option<StdRng>>,
 }
 
 impl<N: Network> Router<N> {
-    /// The maximum number of candidate peers permitted to be stored in the node.
-    const MAXIMUM_CANDIDATE_PEERS: usize = 10_000;
     /// The maximum number of connection failures permitted by an inbound connecting peer.
     const MAXIMUM_CONNECTION_FAILURES: usize = 5;
     /// The duration in seconds after which a connected peer is considered inactive or
     /// disconnected if no message has been received in the meantime.
     const RADIO_SILENCE_IN_SECS: u64 = 150; // 2.5 minutes
+    /// The duration in seconds for which a peer that exhausted its reputation is banned.
+    const BAN_DURATION_IN_SECS: u64 = 86_400; // 1 day
+    /// The minimum duration in seconds in between resolutions of the DNS seeds.
+    const DNS_SEED_INTERVAL_IN_SECS: u64 = 300; // 5 minutes
+    /// The port of the peers resolved from a DNS seed that does not specify one.
+    const DNS_SEED_DEFAULT_PORT: u16 = 4133;
+    /// The maximum duration in milliseconds to establish a connection through a proxy.
+    const PROXY_CONNECTION_TIMEOUT_IN_MS: u16 = 10_000; // 10 seconds
+    /// The number of lowest-latency peers a broadcast is sent to immediately.
+    const NUM_IMMEDIATE_BROADCAST_PEERS: usize = 8;
+    /// The maximum random delay of a broadcast to the remaining peers.
+    const MAXIMUM_BROADCAST_DELAY_IN_MS: u64 = 250;
+    /// The maximum number of relay messages dropped for a peer since its outbound queue was last drained,
+    /// after which the peer is considered to be persistently slow, and is disconnected. The messages dropped
+    /// while the writes to the peer are throttled by the bandwidth limits of the node don't count.
+    const MAXIMUM_DROPPED_MESSAGES: usize = 512;
+    /// The minimum number of distinct subnets of the peers that must report the same listening address of the node,
+    /// before it is considered to be the new external address of the node.
+    const MINIMUM_ADDRESS_REPORTS: usize = 3;
+    /// The number of connections the TCP stack admits beyond the limits of the router, so that an inbound peer
+    /// arriving while the router is at capacity can complete its handshake, and replace a lower-quality peer.
+    const EVICTION_HEADROOM: u16 = 2;
+    /// The maximum number of quality points of a peer for its latency, which are lowered by 1 point per
+    /// `LATENCY_PENALTY_IN_MS` milliseconds of round-trip time.
+    const MAXIMUM_LATENCY_POINTS: u32 = 50;
+    /// The round-trip time in milliseconds that costs a peer 1 quality point.
+    const LATENCY_PENALTY_IN_MS: u128 = 20;
+    /// The number of quality points of a peer for each service it is useful for (serving blocks, or validating).
+    const USEFULNESS_POINTS: u32 = 25;
 }
 
 impl<N: Network> Router<N> {
-    /// Initializes a new `Router` instance.
-    pub async fn new(
-        node_ip: SocketAddr,
-        node_type: NodeType,
-        account: Account<N>,
-        trusted_peers: &[SocketAddr],
-        max_peers: u16,
-        is_dev: bool,
-    ) -> Result<Self> {
-        // Initialize the TCP stack.
-        let tcp = Tcp::new(Config::new(node_ip, max_peers));
+    /// Initializes a new `Router` instance with the given configuration.
+    pub async fn new(node_type: NodeType, account: Account<N>, config: RouterConfig) -> Result<Self> {
+        let RouterConfig {
+            node_ip,
+            trusted_peers,
+            max_peers,
+            max_peers_per_subnet,
+            max_bandwidth,
+            max_peer_bandwidth,
+            relay_policy,
+            liveness_policy,
+            connection_policy,
+            dns_seeds,
+            tls,
+            noise,
+            compression,
+            ban_list_path,
+            identity_path,
+            peer_book_path,
+            port_mapping,
+            public_addrs,
+            listen,
+            validator_listener,
+            proxy,
+            is_dev,
+        } = config;
+        // Ensure the port mapping does not reveal the IP of the node to its gateway while a proxy is used.
+        if port_mapping && proxy.is_some() {
+            bail!("The port mapping cannot be used with a proxy");
+        }
+        if !dns_seeds.is_empty() && proxy.is_some() {
+            warn!("The DNS seeds are not resolved while a proxy is used");
+        }
+        // Initialize the TCP stack. If the node does not listen for inbound connections, the listener is disabled.
+        // If the outbound connections are established through a proxy, more time is allowed to establish them.
+        // The connection limit of the TCP stack covers both the public peers and the validator listener peers.
+        // If a connection policy is configured, the public peers are limited by the policy instead.
+        let max_peers = connection_policy.map_or(max_peers, |policy| policy.max_peers());
+        let config = Config::new(node_ip, max_peers);
+        let listener_ip = config.listener_ip.filter(|_| listen);
+        let connection_timeout_ms = match proxy {
+            Some(_) => Self::PROXY_CONNECTION_TIMEOUT_IN_MS,
+            None => config.connection_timeout_ms,
+        };
+        let max_connections = max_peers
+            .saturating_add(validator_listener.map_or(0, |listener| listener.max_peers))
+            .saturating_add(Self::EVICTION_HEADROOM);
+        let tcp = Tcp::new(Config {
+            listener_ip,
+            max_connections,
+            connection_timeout_ms,
+            max_bandwidth,
+            max_peer_bandwidth,
+            proxy,
+            ..config
+        });
+        // Generate the static Noise keypair, if encrypted connections are enabled.
+        let noise_keypair = match noise {
+            true => Some(snow::Builder::new(NOISE_HANDSHAKE_TYPE.parse()?).generate_keypair()?),
+            false => None,
+        };
+        // Load the ban list.
+        let ban_list = BanList::load(ban_list_path)?;
+        // Load the persistent identity of the node.
+        let identity = NodeIdentity::load(identity_path)?;
+        // Load the peer book, discarding the peers which were banned since.
+        let peer_book = PeerBook::load(peer_book_path)?;
+        peer_book.retain(|peer_ip| !ban_list.is_banned(&peer_ip.ip()));
         // Initialize the router.
         Ok(Self(Arc::new(InnerRouter {
             tcp,
             node_type,
             account,
+            identity,
             cache: Default::default(),
+            transaction_inventory: Default::default(),
             resolver: Default::default(),
-            trusted_peers: trusted_peers.iter().copied().collect(),
+            reputation: Default::default(),
+            max_peers_per_subnet,
+            dns_seeds,
+            last_dns_seed_lookup: Default::default(),
+            trusted_peers: RwLock::new(trusted_peers.into_iter().map(canonical_addr).collect()),
             connected_peers: Default::default(),
             connecting_peers: Default::default(),
+            connecting_node_ids: Default::default(),
             candidate_peers: Default::default(),
             restricted_peers: Default::default(),
+            restricted_ips: Default::default(),
+            ban_list,
+            peer_book,
             handles: Default::default(),
+            tls,
+            noise_keypair,
+            noise_states: Default::default(),
+            relay_policy,
+            liveness_policy,
+            connection_policy,
+            validator_listener,
+            validator_listener_addr: Default::default(),
+            validator_committee: Default::default(),
+            compression,
+            compressed_peers: Default::default(),
+            checksummed_peers: Default::default(),
+            traffic: Default::default(),
+            port_mapping,
+            external_addr: Default::default(),
+            public_addrs: RwLock::new(public_addrs.into_iter().map(canonical_addr).collect()),
+            observed_addrs: Default::default(),
             is_dev,
+            #[cfg(feature = "test")]
+            seeded_rng: Default::default(),
         })))
     }
 }
@@ -140,6 +333,8 @@ impl<N: Network> Router<N> {
 impl<N: Network> Router<N> {
     /// Attempts to connect to the given peer IP.
     pub fn connect(&self, peer_ip: SocketAddr) -> Option<JoinHandle<bool>> {
+        // Treat an IPv4-mapped IPv6 address as the IPv4 address it maps to.
+        let peer_ip = canonical_addr(peer_ip);
         // Return early if the attempt is against the protocol rules.
         if let Err(forbidden_message) = self.check_connection_attempt(peer_ip) {
             warn!("{forbidden_message}");
@@ -165,6 +360,44 @@ impl<N: Network> Router<N> {
         }))
     }
 
+    /// Whitelists the given peer IP as a trusted peer, and connects to it immediately.
+    /// Unlike `connect`, this waits for the handshake to conclude, and returns its result.
+    /// If the router is at the maximum number of connected peers, an untrusted peer is evicted to make room.
+    pub async fn connect_trusted(&self, peer_ip: SocketAddr) -> Result<()> {
+        // Treat an IPv4-mapped IPv6 address as the IPv4 address it maps to.
+        let peer_ip = canonical_addr(peer_ip);
+        // Trust the peer, so that it is exempt from the connection limits, and is reconnected by the heartbeat.
+        self.trusted_peers.write().insert(peer_ip);
+        // Return early if the peer is already connected.
+        if self.is_connected(&peer_ip) {
+            return Ok(());
+        }
+        // If there is no room for the peer, evict an untrusted public peer that is not a bootstrap peer.
+        if self.number_of_public_peers() >= self.max_connected_peers() {
+            let trusted = self.trusted_peers();
+            let bootstrap = self.bootstrap_peers();
+            let evicted_peer =
+                self.public_peers().into_iter().find(|ip| !trusted.contains(ip) && !bootstrap.contains(ip));
+            if let Some(evicted_peer) = evicted_peer {
+                info!("Disconnecting from '{evicted_peer}' (making room for trusted peer '{peer_ip}')");
+                self.disconnect(evicted_peer).await?;
+            }
+        }
+
+        // Attempt to connect to the peer, and wait for the result of the handshake.
+        self.check_connection_attempt(peer_ip)?;
+        match self.tcp.connect(peer_ip).await {
+            Ok(()) => {
+                self.remove_candidate_peer(peer_ip);
+                Ok(())
+            }
+            Err(error) => {
+                self.connecting_peers.lock().remove(&peer_ip);
+                bail!("Unable to connect to '{peer_ip}' - {error}")
+            }
+        }
+    }
+
     /// Ensure we are allowed to connect to the given peer.
     fn check_connection_attempt(&self, peer_ip: SocketAddr) -> Result<()> {
         // Ensure the peer IP is not this node.
@@ -172,9 +405,18 @@ impl<N: Network> Router<N> {
             bail!("Dropping connection attempt to '{peer_ip}' (attempted to self-connect)")
         }
         // Ensure the node does not surpass the maximum number of peer connections.
-        if self.number_of_connected_peers() >= self.max_connected_peers() {
+        if self.number_of_public_peers() >= self.max_connected_peers() {
             bail!("Dropping connection attempt to '{peer_ip}' (maximum peers reached)")
         }
+        // Ensure the node does not surpass the target number of outbound peers. Trusted and bootstrap peers are exempt.
+        if let Some(policy) = self.connection_policy() {
+            if self.number_of_outbound_peers() >= policy.target_outbound_peers as usize
+                && !self.is_trusted(&peer_ip)
+                && !self.bootstrap_peers().contains(&peer_ip)
+            {
+                bail!("Dropping connection attempt to '{peer_ip}' (target outbound peers reached)")
+            }
+        }
         // Ensure the node is not already connected to this peer.
         if self.is_connected(&peer_ip) {
             bail!("Dropping connection attempt to '{peer_ip}' (already connected)")
@@ -183,6 +425,14 @@ impl<N: Network> Router<N> {
         if self.is_restricted(&peer_ip) {
             bail!("Dropping connection attempt to '{peer_ip}' (restricted)")
         }
+        // Ensure the peer is not banned.
+        if self.is_banned(&peer_ip) {
+            bail!("Dropping connection attempt to '{peer_ip}' (banned)")
+        }
+        // Ensure the node does not surpass the maximum number of outbound peer connections in the subnet.
+        if self.is_subnet_full(&peer_ip, ConnectionSide::Responder) {
+            bail!("Dropping connection attempt to '{peer_ip}' (maximum outbound peers in subnet reached)")
+        }
         // Ensure the node is not already connecting to this peer.
         if !self.connecting_peers.lock().insert(peer_ip) {
             bail!("Dropping connection attempt to '{peer_ip}' (already shaking hands as the initiator)")
@@ -210,20 +460,108 @@ impl<N: Network> Router<N> {
         })
     }
 
-    /// Returns the IP address of this node.
+    /// Returns the IP address of this node. If the node does not listen for inbound connections, the port is `0`.
     pub fn local_ip(&self) -> SocketAddr {
-        self.tcp.listening_addr().expect("The TCP listener is not enabled")
+        match self.is_listening() {
+            true => self.tcp.listening_addr().expect("The TCP listener is not enabled"),
+            false => SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0),
+        }
+    }
+
+    /// Returns `true` if the node listens for inbound connections.
+    pub fn is_listening(&self) -> bool {
+        self.tcp.config().listener_ip.is_some()
+    }
+
+    /// Returns the external address of the node, if its listener port is forwarded on the gateway.
+    pub fn external_addr(&self) -> Option<SocketAddr> {
+        *self.external_addr.read()
+    }
+
+    /// Sets the external address of the node, once its listener port is forwarded on the gateway.
+    pub fn set_external_addr(&self, external_addr: SocketAddr) {
+        *self.external_addr.write() = Some(external_addr);
+    }
+
+    /// Returns the addresses of the node advertised to peers, i.e. its public addresses and its external address.
+    /// A node listening on both IPv4 and IPv6 advertises both of its addresses, so that it can be discovered
+    /// by the peers of either address family.
+    pub fn advertised_addrs(&self) -> Vec<SocketAddr> {
+        let mut addrs = self.public_addrs.read().clone();
+        if let Some(external_addr) = self.external_addr() {
+            if !addrs.contains(&external_addr) {
+                addrs.push(external_addr);
+            }
+        }
+        addrs
+    }
+
+    /// Replaces the advertised address of the same address family as the given address, once the external address
+    /// of the node changed (e.g. after the IP of its gateway rotated).
+    pub fn update_advertised_addr(&self, addr: SocketAddr) {
+        // Remove the stale public address.
+        self.public_addrs.write().retain(|public_addr| public_addr.is_ipv4() != addr.is_ipv4());
+        // Replace the external address, or else the public address, with the given address.
+        let mut external_addr = self.external_addr.write();
+        match *external_addr {
+            Some(stale_addr) if stale_addr.is_ipv4() == addr.is_ipv4() => *external_addr = Some(addr),
+            _ => self.public_addrs.write().push(addr),
+        }
+    }
+
+    /// Records the listening address of the node, as observed by the given peer.
+    /// The addresses which are not publicly reachable, or whose port is not the advertised listener port, are ignored.
+    pub fn insert_observed_addr(&self, peer_ip: SocketAddr, observed_addr: SocketAddr) {
+        let observed_addr = canonical_addr(observed_addr);
+        if is_bogon_ip(observed_addr.ip())
+            || is_unspecified_or_broadcast_ip(observed_addr.ip())
+            || observed_addr.port() != self.listener_port()
+        {
+            return;
+        }
+        self.observed_addrs.write().insert(peer_ip, observed_addr);
+    }
+
+    /// Returns the listening addresses of the node, as observed by the connected peers, i.e. for each address family,
+    /// the address reported by the most distinct subnets, if there are at least `MINIMUM_ADDRESS_REPORTS` of them.
+    pub fn observed_addrs(&self) -> Vec<SocketAddr> {
+        // Collect the distinct subnets of the peers reporting each address.
+        let mut reports: HashMap<SocketAddr, HashSet<IpAddr>> = HashMap::new();
+        for (peer_ip, observed_addr) in self.observed_addrs.read().iter() {
+            reports.entry(*observed_addr).or_default().insert(subnet(peer_ip.ip()));
+        }
+        // Select the most reported address of each address family.
+        let mut observed_addrs: Vec<(SocketAddr, usize)> = Vec::new();
+        for (addr, subnets) in reports {
+            if subnets.len() < Self::MINIMUM_ADDRESS_REPORTS {
+                continue;
+            }
+            match observed_addrs.iter_mut().find(|(other, _)| other.is_ipv4() == addr.is_ipv4()) {
+                Some(entry) if entry.1 < subnets.len() => *entry = (addr, subnets.len()),
+                Some(_) => (),
+                None => observed_addrs.push((addr, subnets.len())),
+            }
+        }
+        observed_addrs.into_iter().map(|(addr, _)| addr).collect()
+    }
+
+    /// Returns the listener port advertised to peers, i.e. the external port if it is forwarded on the gateway.
+    pub fn listener_port(&self) -> u16 {
+        self.external_addr().map(|addr| addr.port()).unwrap_or_else(|| self.local_ip().port())
     }
 
     /// Returns `true` if the given IP is this node.
     pub fn is_local_ip(&self, ip: &SocketAddr) -> bool {
         *ip == self.local_ip()
+            || Some(*ip) == self.external_addr()
+            || self.public_addrs.read().contains(ip)
             || (ip.ip().is_unspecified() || ip.ip().is_loopback()) && ip.port() == self.local_ip().port()
     }
 
-    /// Returns `true` if the given IP is not this node, is not a bogon address, and is not unspecified.
+    /// Returns `true` if the given IP is not this node, is not a bogon address, is not unspecified,
+    /// and has a listener port (i.e. the peer is not outbound-only).
     pub fn is_valid_peer_ip(&self, ip: &SocketAddr) -> bool {
-        !self.is_local_ip(ip) && !is_bogon_ip(ip.ip()) && !is_unspecified_or_broadcast_ip(ip.ip())
+        !self.is_local_ip(ip) && !is_bogon_ip(ip.ip()) && !is_unspecified_or_broadcast_ip(ip.ip()) && ip.port() != 0
     }
 
     /// Returns the node type.
@@ -246,11 +584,173 @@ impl<N: Network> Router<N> {
         self.account.address()
     }
 
+    /// Returns the node ID of the node.
+    pub fn node_id(&self) -> NodeId {
+        self.identity.node_id()
+    }
+
+    /// Returns `true` if the listener port of the node is to be forwarded on the gateway.
+    pub fn is_port_mapping_enabled(&self) -> bool {
+        self.port_mapping
+    }
+
     /// Returns `true` if the node is in development mode.
     pub fn is_dev(&self) -> bool {
         self.is_dev
     }
 
+    /// Returns an RNG, which is derived from the seed of the simulation, if the node runs in one.
+    pub fn rng(&self) -> RouterRng {
+        #[cfg(feature = "test")]
+        if let Some(rng) = self.seeded_rng.lock().as_mut() {
+            return RouterRng::Seeded(StdRng::seed_from_u64(rng.next_u64()));
+        }
+        RouterRng::Os(OsRng)
+    }
+
+    /// Runs the node in a simulation: the node connects to its peers over the given in-process network, instead of
+    /// the operating system's, and derives its random choices (e.g. of the peers, and of the handshake nonces) from
+    /// the given seed. It must be called before the listener is enabled.
+    ///
+    /// On a single-threaded runtime with a paused clock (i.e. `#[tokio::test(start_paused = true)]`), the timers
+    /// of the node (e.g. the heartbeat and the timeouts) advance in virtual time, so that a multi-node scenario is
+    /// reproducible. Note that the wall-clock timestamps (e.g. of the bans) and the keys generated at startup are
+    /// not derived from the seed.
+    #[cfg(feature = "test")]
+    pub fn enable_simulation(&self, network: &snarkos_node_tcp::MemoryNetwork, seed: u64) {
+        self.tcp.enable_memory_network(network.clone());
+        *self.seeded_rng.lock() = Some(StdRng::seed_from_u64(seed));
+    }
+
+    /// Returns the TLS configuration of the node, if node-to-node connections are terminated over TLS.
+    pub fn tls(&self) -> Option<&TlsConfig> {
+        self.tls.as_ref()
+    }
+
+    /// Returns `true` if the node negotiates Noise-encrypted connections with its peers.
+    pub fn is_noise_enabled(&self) -> bool {
+        self.noise_keypair.is_some()
+    }
+
+    /// Returns `true` if the node compresses large messages for the peers that support it.
+    pub fn is_compression_enabled(&self) -> bool {
+        self.compression
+    }
+
+    /// Returns the feature flags advertised by the node during the handshake.
+    pub fn features(&self) -> u32 {
+        let mut features = ChallengeRequest::<N>::FEATURE_CHECKSUM
+            | ChallengeRequest::<N>::FEATURE_PEER_METADATA
+            | ChallengeRequest::<N>::FEATURE_ADDRESS_ANNOUNCEMENTS;
+        if self.is_noise_enabled() {
+            features |= ChallengeRequest::<N>::FEATURE_NOISE;
+        }
+        if self.is_compression_enabled() {
+            features |= ChallengeRequest::<N>::FEATURE_COMPRESSION;
+        }
+        // Provers do not maintain a ledger, so they can neither serve nor sync the block headers,
+        // and do not relay the unconfirmed transactions.
+        if !self.node_type.is_prover() {
+            features |= ChallengeRequest::<N>::FEATURE_HEADERS_FIRST;
+            features |= ChallengeRequest::<N>::FEATURE_TRANSACTION_ANNOUNCEMENTS;
+        }
+        features
+    }
+
+    /// Returns the features negotiated with a peer, i.e. the features advertised by both the node and the peer.
+    /// Unknown feature flags of the peer are ignored, so that new features can be rolled out gradually.
+    pub fn negotiate_features(&self, peer_request: &ChallengeRequest<N>) -> u32 {
+        self.features() & peer_request.features
+    }
+
+    /// Returns `true` if the block announcements and the headers-first block sync were negotiated with the given peer.
+    pub fn supports_headers_first(&self, peer_ip: &SocketAddr) -> bool {
+        self.connected_peers
+            .read()
+            .get(peer_ip)
+            .map_or(false, |peer| peer.supports(ChallengeRequest::<N>::FEATURE_HEADERS_FIRST))
+    }
+
+    /// Returns `true` if the announcement of unconfirmed transactions by their IDs was negotiated with the given peer.
+    pub fn supports_transaction_announcements(&self, peer_ip: &SocketAddr) -> bool {
+        self.connected_peers
+            .read()
+            .get(peer_ip)
+            .map_or(false, |peer| peer.supports(ChallengeRequest::<N>::FEATURE_TRANSACTION_ANNOUNCEMENTS))
+    }
+
+    /// Returns `true` if the exchange of the peer metadata was negotiated with the given peer.
+    pub fn supports_peer_metadata(&self, peer_ip: &SocketAddr) -> bool {
+        self.connected_peers
+            .read()
+            .get(peer_ip)
+            .map_or(false, |peer| peer.supports(ChallengeRequest::<N>::FEATURE_PEER_METADATA))
+    }
+
+    /// Returns `true` if the address announcements were negotiated with the given peer.
+    pub fn supports_address_announcements(&self, peer_ip: &SocketAddr) -> bool {
+        self.connected_peers
+            .read()
+            .get(peer_ip)
+            .map_or(false, |peer| peer.supports(ChallengeRequest::<N>::FEATURE_ADDRESS_ANNOUNCEMENTS))
+    }
+
+    /// Returns the metadata advertised by the node to its peers.
+    /// Provers do not maintain a ledger, so they do not serve any block.
+    pub fn metadata(&self) -> PeerMetadata {
+        match self.node_type.is_prover() {
+            true => PeerMetadata::default(),
+            false => PeerMetadata::new(PeerMetadata::SERVICE_ARCHIVAL, 0),
+        }
+    }
+
+    /// Returns the message codec for the given (ambiguous) peer address.
+    /// If the connection is encrypted or compressed, the codec processes the messages accordingly.
+    pub fn message_codec(&self, peer_addr: SocketAddr) -> MessageCodec<N> {
+        let mut codec = match self.noise_states.read().get(&peer_addr) {
+            Some(transport) => MessageCodec::encrypted(transport.clone()),
+            None => MessageCodec::default(),
+        };
+        if self.compressed_peers.read().contains(&peer_addr) {
+            codec.enable_compression();
+        }
+        if self.checksummed_peers.read().contains(&peer_addr) {
+            codec.enable_checksum();
+        }
+        codec.track_traffic(self.traffic.write().entry(peer_addr).or_default().clone());
+        codec
+    }
+
+    /// Returns `true` if the connection with the given peer IP is encrypted.
+    pub fn is_encrypted(&self, peer_ip: &SocketAddr) -> bool {
+        self.resolve_to_ambiguous(peer_ip).map_or(false, |peer_addr| self.noise_states.read().contains_key(&peer_addr))
+    }
+
+    /// Returns the policy for accepting and relaying unconfirmed transactions.
+    pub fn relay_policy(&self) -> &RelayPolicy {
+        &self.relay_policy
+    }
+
+    /// Returns the policy for keeping the connections to peers alive.
+    pub fn liveness_policy(&self) -> &LivenessPolicy {
+        &self.liveness_policy
+    }
+
+    /// Returns the policy for splitting the connections between outbound and inbound peers, if one is configured.
+    pub fn connection_policy(&self) -> Option<&ConnectionPolicy> {
+        self.connection_policy.as_ref()
+    }
+
+    /// Returns the inventory of the recently relayed unconfirmed transactions.
+    pub fn transaction_inventory(&self) -> &TransactionInventory<N> {
+        &self.transaction_inventory
+    }
+
+    /// Returns the reputation scores of the peers.
+    pub fn reputation(&self) -> &Reputation {
+        &self.reputation
+    }
+
     /// Returns the listener IP address from the (ambiguous) peer address.
     pub fn resolve_to_listener(&self, peer_addr: &SocketAddr) -> Option<SocketAddr> {
         self.resolver.get_listener(peer_addr)
@@ -266,6 +766,11 @@ impl<N: Network> Router<N> {
         self.connected_peers.read().contains_key(ip)
     }
 
+    /// Returns `true` if the node is connected to a peer with the given node ID.
+    pub fn is_connected_node_id(&self, node_id: &NodeId) -> bool {
+        self.connected_peers.read().values().any(|peer| peer.node_id() == Some(*node_id))
+    }
+
     /// Returns `true` if the given peer IP is a connected validator.
     pub fn is_connected_validator(&self, peer_ip: &SocketAddr) -> bool {
         self.connected_peers.read().get(peer_ip).map_or(false, |peer| peer.is_validator())
@@ -286,6 +791,11 @@ impl<N: Network> Router<N> {
         self.connecting_peers.lock().contains(ip)
     }
 
+    /// Returns `true` if the given IP is a trusted peer.
+    pub fn is_trusted(&self, ip: &SocketAddr) -> bool {
+        self.trusted_peers.read().contains(ip)
+    }
+
     /// Returns `true` if the given IP is restricted.
     pub fn is_restricted(&self, ip: &SocketAddr) -> bool {
         self.restricted_peers
@@ -293,11 +803,44 @@ impl<N: Network> Router<N> {
             .get(ip)
             .map(|time| time.elapsed().as_secs() < Self::RADIO_SILENCE_IN_SECS)
             .unwrap_or(false)
+            || self.is_restricted_ip(&ip.ip())
     }
 
-    /// Returns the maximum number of connected peers.
+    /// Returns `true` if the given IP is restricted for spamming connection attempts.
+    pub fn is_restricted_ip(&self, ip: &IpAddr) -> bool {
+        self.restricted_ips
+            .read()
+            .get(ip)
+            .map(|time| time.elapsed().as_secs() < Self::RADIO_SILENCE_IN_SECS)
+            .unwrap_or(false)
+    }
+
+    /// Returns `true` if the IP of the given peer is banned.
+    pub fn is_banned(&self, ip: &SocketAddr) -> bool {
+        self.ban_list.is_banned(&ip.ip())
+    }
+
+    /// Returns `true` if the node has reached the maximum number of connected peers in the subnet of the given IP,
+    /// on the given connection side **from the peer's perspective**. Trusted peers and bogon IPs are exempt.
+    pub fn is_subnet_full(&self, ip: &SocketAddr, peer_side: ConnectionSide) -> bool {
+        if self.is_trusted(ip) || is_bogon_ip(ip.ip()) {
+            return false;
+        }
+        let ip_subnet = subnet(ip.ip());
+        let num_peers = self
+            .connected_peers
+            .read()
+            .values()
+            .filter(|peer| peer.side() == peer_side && subnet(peer.ip().ip()) == ip_subnet)
+            .count();
+        num_peers >= self.max_peers_per_subnet as usize
+    }
+
+    /// Returns the maximum number of connected peers, excluding the peers connected through the validator listener.
     pub fn max_connected_peers(&self) -> usize {
-        self.tcp.config().max_connections as usize
+        (self.tcp.config().max_connections as usize)
+            .saturating_sub(self.max_validator_listener_peers())
+            .saturating_sub(Self::EVICTION_HEADROOM as usize)
     }
 
     /// Returns the number of connected peers.
@@ -305,6 +848,84 @@ impl<N: Network> Router<N> {
         self.connected_peers.read().len()
     }
 
+    /// Returns the number of connected peers, excluding the peers connected through the validator listener.
+    pub fn number_of_public_peers(&self) -> usize {
+        self.connected_peers.read().values().filter(|peer| !peer.is_validator_listener()).count()
+    }
+
+    /// Returns the number of public peers which initiated the connection.
+    pub fn number_of_inbound_peers(&self) -> usize {
+        self.connected_peers.read().values().filter(|peer| !peer.is_validator_listener() && peer.is_inbound()).count()
+    }
+
+    /// Returns the number of public peers this node initiated the connection with.
+    pub fn number_of_outbound_peers(&self) -> usize {
+        self.number_of_public_peers().saturating_sub(self.number_of_inbound_peers())
+    }
+
+    /// Returns the listener dedicated to the validator peers, if one is configured.
+    pub fn validator_listener(&self) -> Option<ValidatorListener> {
+        self.validator_listener
+    }
+
+    /// Returns the address of the validator listener, once it is listening.
+    pub fn validator_listener_addr(&self) -> Option<SocketAddr> {
+        *self.validator_listener_addr.read()
+    }
+
+    /// Sets the address of the validator listener, once it is listening.
+    pub fn set_validator_listener_addr(&self, addr: SocketAddr) {
+        *self.validator_listener_addr.write() = Some(addr);
+    }
+
+    /// Returns `true` if the given local address of a connection is the address of the validator listener.
+    pub fn is_validator_listener_addr(&self, local_addr: SocketAddr) -> bool {
+        self.validator_listener_addr().map_or(false, |addr| addr.port() == local_addr.port())
+    }
+
+    /// Returns `true` if the given address is a member of the current committee, which admits it through
+    /// the validator listener.
+    pub fn is_committee_member(&self, address: &Address<N>) -> bool {
+        self.validator_committee.read().contains(address)
+    }
+
+    /// Sets the addresses of the members of the current committee, which are the only peers admitted through
+    /// the validator listener. The peers connected through the validator listener which left the committee
+    /// are disconnected.
+    pub fn set_validator_committee(&self, members: impl IntoIterator<Item = Address<N>>) {
+        let members: HashSet<_> = members.into_iter().collect();
+        let former_members = self
+            .get_connected_peers()
+            .into_iter()
+            .filter(|peer| peer.is_validator_listener() && !members.contains(&peer.address()))
+            .map(|peer| peer.ip())
+            .collect::<Vec<_>>();
+        *self.validator_committee.write() = members;
+        for peer_ip in former_members {
+            info!("Disconnecting from '{peer_ip}' (left the committee)");
+            self.disconnect(peer_ip);
+        }
+    }
+
+    /// Returns the maximum number of peers connected through the validator listener.
+    pub fn max_validator_listener_peers(&self) -> usize {
+        self.validator_listener.map_or(0, |listener| listener.max_peers as usize)
+    }
+
+    /// Returns the number of peers connected through the validator listener.
+    pub fn number_of_validator_listener_peers(&self) -> usize {
+        self.connected_peers.read().values().filter(|peer| peer.is_validator_listener()).count()
+    }
+
+    /// Returns the message limit of the given peer, if it is connected through the validator listener.
+    pub fn validator_listener_message_limit(&self, peer_ip: &SocketAddr) -> Option<usize> {
+        let validator_listener = self.validator_listener?;
+        match self.connected_peers.read().get(peer_ip) {
+            Some(peer) if peer.is_validator_listener() => Some(validator_listener.message_limit),
+            _ => None,
+        }
+    }
+
     /// Returns the number of connected validators.
     pub fn number_of_connected_validators(&self) -> usize {
         self.connected_peers.read().values().filter(|peer| peer.is_validator()).count()
@@ -322,7 +943,7 @@ impl<N: Network> Router<N> {
 
     /// Returns the number of candidate peers.
     pub fn number_of_candidate_peers(&self) -> usize {
-        self.candidate_peers.read().len()
+        self.candidate_peers.len()
     }
 
     /// Returns the number of restricted peers.
@@ -345,6 +966,26 @@ impl<N: Network> Router<N> {
         self.connected_peers.read().keys().copied().collect()
     }
 
+    /// Returns the list of connected peers, excluding the peers connected through the validator listener.
+    pub fn public_peers(&self) -> Vec<SocketAddr> {
+        self.connected_peers
+            .read()
+            .values()
+            .filter(|peer| !peer.is_validator_listener())
+            .map(|peer| peer.ip())
+            .collect()
+    }
+
+    /// Sorts the given peer IPs by their round-trip time, with the lowest-latency peers first.
+    /// The peers without a measured round-trip time are sorted last.
+    pub fn sort_by_latency(&self, peer_ips: &mut [SocketAddr]) {
+        let connected_peers = self.connected_peers.read();
+        peer_ips.sort_by_key(|peer_ip| {
+            let rtt = connected_peers.get(peer_ip).and_then(|peer| peer.rtt());
+            (rtt.is_none(), rtt)
+        });
+    }
+
     /// Returns the list of connected validators.
     pub fn connected_validators(&self) -> Vec<SocketAddr> {
         self.connected_peers.read().iter().filter(|(_, peer)| peer.is_validator()).map(|(ip, _)| *ip).collect()
@@ -362,7 +1003,12 @@ impl<N: Network> Router<N> {
 
     /// Returns the list of candidate peers.
     pub fn candidate_peers(&self) -> HashSet<SocketAddr> {
-        self.candidate_peers.read().clone()
+        self.candidate_peers.addresses()
+    }
+
+    /// Returns up to `num` random candidate peers, drawn from the tried and new addresses with equal probability.
+    pub fn select_candidate_peers(&self, num: usize) -> Vec<SocketAddr> {
+        self.candidate_peers.select(&mut self.rng(), num)
     }
 
     /// Returns the list of restricted peers.
@@ -370,9 +1016,59 @@ impl<N: Network> Router<N> {
         self.restricted_peers.read().keys().copied().collect()
     }
 
+    /// Returns the list of banned peer IPs, along with their bans.
+    pub fn banned_peers(&self) -> Vec<(IpAddr, Ban)> {
+        self.ban_list.bans()
+    }
+
+    /// Returns the book of known good peers, which is persisted across restarts.
+    pub fn peer_book(&self) -> &PeerBook {
+        &self.peer_book
+    }
+
+    /// Records the given peer in the peer book with its quality score, unless it does not listen for inbound
+    /// connections, or its reputation is too low for it to be worth reconnecting to.
+    fn insert_known_peer(&self, peer: &Peer<N>) {
+        if peer.ip().port() != 0 && self.reputation.score(&peer.ip()) > Reputation::DISCONNECT_THRESHOLD {
+            self.peer_book.insert(peer.ip(), self.peer_quality(peer));
+        }
+    }
+
+    /// Returns the quality score of the given peer, from its reputation, its latency, and its usefulness to this node.
+    /// The peers without a measured round-trip time are scored as the slowest peers.
+    pub fn peer_quality(&self, peer: &Peer<N>) -> u32 {
+        let reputation = self.reputation.score(&peer.ip());
+        let latency = peer.rtt().map_or(0, |rtt| {
+            let penalty = (rtt.as_millis() / Self::LATENCY_PENALTY_IN_MS).min(Self::MAXIMUM_LATENCY_POINTS as u128);
+            Self::MAXIMUM_LATENCY_POINTS - penalty as u32
+        });
+        let mut usefulness = 0;
+        if peer.metadata().is_some_and(|metadata| metadata.is_archival()) {
+            usefulness += Self::USEFULNESS_POINTS;
+        }
+        if peer.node_type().is_validator() {
+            usefulness += Self::USEFULNESS_POINTS;
+        }
+        reputation + latency + usefulness
+    }
+
+    /// Returns the lowest-quality peer to evict, to make room for a new inbound peer, if one exists.
+    /// Only the inbound public peers are evicted, as the outbound peers were chosen by the node itself,
+    /// which keeps the node from being eclipsed by inbound connections. Trusted and bootstrap peers are never evicted.
+    pub fn select_peer_to_evict(&self) -> Option<SocketAddr> {
+        let trusted = self.trusted_peers();
+        let bootstrap = self.bootstrap_peers();
+        self.get_connected_peers()
+            .into_iter()
+            .filter(|peer| peer.is_inbound() && !peer.is_validator_listener())
+            .filter(|peer| !trusted.contains(&peer.ip()) && !bootstrap.contains(&peer.ip()))
+            .min_by_key(|peer| self.peer_quality(peer))
+            .map(|peer| peer.ip())
+    }
+
     /// Returns the list of trusted peers.
-    pub fn trusted_peers(&self) -> &HashSet<SocketAddr> {
-        &self.trusted_peers
+    pub fn trusted_peers(&self) -> HashSet<SocketAddr> {
+        self.trusted_peers.read().clone()
     }
 
     /// Returns the list of bootstrap peers.
@@ -389,15 +1085,67 @@ impl<N: Network> Router<N> {
         }
     }
 
+    /// Returns the hostnames of the DNS seeds.
+    pub fn dns_seeds(&self) -> &[String] {
+        &self.dns_seeds
+    }
+
+    /// Resolves the DNS seeds in the background, and inserts the resolved addresses into the candidate peers.
+    /// The resolved addresses are attributed to the node itself, as they are not advertised by a peer.
+    /// This method is a no-op if the DNS seeds were resolved within the last `DNS_SEED_INTERVAL_IN_SECS` seconds.
+    pub fn resolve_dns_seeds(&self) {
+        // note: The DNS seeds are not resolved while a proxy is used, as the lookups would bypass the proxy,
+        // and reveal the IP of the node to its resolver.
+        if self.dns_seeds.is_empty() || self.tcp.config().proxy.is_some() {
+            return;
+        }
+        // Ensure the DNS seeds were not resolved recently.
+        {
+            let mut last_lookup = self.last_dns_seed_lookup.lock();
+            if last_lookup.is_some_and(|time| time.elapsed().as_secs() < Self::DNS_SEED_INTERVAL_IN_SECS) {
+                return;
+            }
+            *last_lookup = Some(Instant::now());
+        }
+
+        let router = self.clone();
+        self.spawn(async move {
+            let mut peer_ips = HashSet::new();
+            for seed in &router.dns_seeds {
+                // Append the default port, if the seed does not specify one.
+                let host = match seed.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+                    true => seed.clone(),
+                    false => format!("{seed}:{}", Self::DNS_SEED_DEFAULT_PORT),
+                };
+                match tokio::net::lookup_host(&host).await {
+                    Ok(addrs) => peer_ips.extend(addrs.filter(|addr| router.is_valid_peer_ip(addr))),
+                    Err(error) => warn!("Unable to resolve the DNS seed '{seed}' - {error}"),
+                }
+            }
+            debug!("Resolved {} candidate peers from the DNS seeds", peer_ips.len());
+            let source = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+            router.insert_candidate_peers(source, &peer_ips.into_iter().collect::<Vec<_>>());
+        });
+    }
+
     /// Returns the list of metrics for the connected peers.
     pub fn connected_metrics(&self) -> Vec<(SocketAddr, NodeType)> {
         self.connected_peers.read().iter().map(|(ip, peer)| (*ip, peer.node_type())).collect()
     }
 
+    /// Returns the traffic of the connected peers by message kind, by their listener IP.
+    pub fn connected_traffic(&self) -> Vec<(SocketAddr, IndexMap<&'static str, TrafficCounts>)> {
+        self.traffic
+            .read()
+            .iter()
+            .filter_map(|(peer_addr, traffic)| Some((self.resolve_to_listener(peer_addr)?, traffic.counts())))
+            .collect()
+    }
+
     #[cfg(feature = "metrics")]
     fn update_metrics(&self) {
         metrics::gauge(metrics::router::CONNECTED, self.connected_peers.read().len() as f64);
-        metrics::gauge(metrics::router::CANDIDATE, self.candidate_peers.read().len() as f64);
+        metrics::gauge(metrics::router::CANDIDATE, self.candidate_peers.len() as f64);
         metrics::gauge(metrics::router::RESTRICTED, self.restricted_peers.read().len() as f64);
     }
 
@@ -409,31 +1157,32 @@ impl<N: Network> Router<N> {
         // Add an entry for this `Peer` in the connected peers.
         self.connected_peers.write().insert(peer_ip, peer);
         // Remove this peer from the candidate peers, if it exists.
-        self.candidate_peers.write().remove(&peer_ip);
+        self.candidate_peers.remove(&peer_ip);
         // Remove this peer from the restricted peers, if it exists.
         self.restricted_peers.write().remove(&peer_ip);
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
 
-    /// Inserts the given peer IPs to the set of candidate peers.
+    /// Inserts the given peer IPs, advertised by the given source IP, into the new candidate peers.
     ///
-    /// This method skips adding any given peers if the combined size exceeds the threshold,
-    /// as the peer providing this list could be subverting the protocol.
-    pub fn insert_candidate_peers(&self, peers: &[SocketAddr]) {
-        // Compute the maximum number of candidate peers.
-        let max_candidate_peers = Self::MAXIMUM_CANDIDATE_PEERS.saturating_sub(self.number_of_candidate_peers());
-        // Ensure the combined number of peers does not surpass the threshold.
-        let eligible_peers = peers
-            .iter()
-            .filter(|peer_ip| {
-                // Ensure the peer is not itself, is not already connected, and is not restricted.
-                !self.is_local_ip(peer_ip) && !self.is_connected(peer_ip) && !self.is_restricted(peer_ip)
-            })
-            .take(max_candidate_peers);
-
-        // Proceed to insert the eligible candidate peer IPs.
-        self.candidate_peers.write().extend(eligible_peers);
+    /// The addresses are bucketed by the subnet of the source, so that a source which floods
+    /// the node with addresses only ever occupies a small fraction of the candidate peers.
+    pub fn insert_candidate_peers(&self, source: IpAddr, peers: &[SocketAddr]) {
+        for peer_ip in peers {
+            // Treat an IPv4-mapped IPv6 address as the IPv4 address it maps to.
+            let peer_ip = &canonical_addr(*peer_ip);
+            // Ensure the peer is not itself, has a listener port, is not already connected,
+            // and is not restricted or banned.
+            if !self.is_local_ip(peer_ip)
+                && peer_ip.port() != 0
+                && !self.is_connected(peer_ip)
+                && !self.is_restricted(peer_ip)
+                && !self.is_banned(peer_ip)
+            {
+                self.candidate_peers.insert_new(*peer_ip, source);
+            }
+        }
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
@@ -441,13 +1190,96 @@ impl<N: Network> Router<N> {
     /// Inserts the given peer into the restricted peers.
     pub fn insert_restricted_peer(&self, peer_ip: SocketAddr) {
         // Remove this peer from the candidate peers, if it exists.
-        self.candidate_peers.write().remove(&peer_ip);
+        self.candidate_peers.remove(&peer_ip);
         // Add the peer to the restricted peers.
         self.restricted_peers.write().insert(peer_ip, Instant::now());
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
 
+    /// Inserts the given IP into the restricted IPs, for spamming connection attempts.
+    pub fn insert_restricted_ip(&self, ip: IpAddr) {
+        // Remove the candidate peers with this IP, if any exist.
+        self.candidate_peers.retain(|peer_ip| peer_ip.ip() != ip);
+        // Add the IP to the restricted IPs.
+        self.restricted_ips.write().insert(ip, Instant::now());
+        #[cfg(feature = "metrics")]
+        self.update_metrics();
+    }
+
+    /// Bans the given peer IP for the given number of seconds (or permanently), and persists the ban.
+    /// Any candidate or connected peers with the given IP are removed or disconnected, respectively.
+    pub fn ban_peer(&self, ip: IpAddr, reason: String, duration_in_secs: Option<u64>) -> Result<()> {
+        // Ensure trusted peers are never banned.
+        if self.trusted_peers.read().iter().any(|peer_ip| peer_ip.ip() == ip) {
+            bail!("Unable to ban '{ip}' (trusted peer)")
+        }
+        // Remove the candidate peers and the known peers with this IP.
+        self.candidate_peers.retain(|peer_ip| peer_ip.ip() != ip);
+        self.peer_book.retain(|peer_ip| peer_ip.ip() != ip);
+        // Disconnect from the connected peers with this IP.
+        for peer_ip in self.connected_peers().into_iter().filter(|peer_ip| peer_ip.ip() == ip) {
+            self.disconnect(peer_ip);
+        }
+        #[cfg(feature = "metrics")]
+        self.update_metrics();
+        // Ban the IP.
+        self.ban_list.insert(ip, reason, duration_in_secs)
+    }
+
+    /// Lifts the ban on the given peer IP, and persists the change. Returns `true` if the IP was banned.
+    pub fn unban_peer(&self, ip: &IpAddr) -> Result<bool> {
+        self.ban_list.remove(ip)
+    }
+
+    /// Lowers the reputation of the given peer for the given offense.
+    /// If the score of the peer crosses a threshold, the peer is disconnected, and possibly banned.
+    pub fn record_offense(&self, peer_ip: SocketAddr, offense: Offense) {
+        match self.reputation.record(peer_ip, offense) {
+            Verdict::None if offense.penalty() == 0 => {
+                debug!("Reported '{peer_ip}' ({offense:?}), without lowering its reputation");
+            }
+            Verdict::None => {
+                debug!("Lowered the reputation of '{peer_ip}' ({offense:?})");
+            }
+            // Ensure trusted peers are never disconnected nor restricted.
+            Verdict::Disconnect | Verdict::Ban if self.is_trusted(&peer_ip) => {
+                warn!("Trusted peer '{peer_ip}' has a low reputation ({offense:?})");
+            }
+            Verdict::Disconnect => {
+                warn!("Disconnecting from '{peer_ip}' (low reputation after {offense:?})");
+                if self.is_connected(&peer_ip) {
+                    self.disconnect(peer_ip);
+                }
+            }
+            Verdict::Ban => {
+                warn!("Banning '{peer_ip}' (exhausted reputation after {offense:?})");
+                self.insert_restricted_peer(peer_ip);
+                let reason = format!("Exhausted reputation after {offense:?}");
+                if let Err(error) = self.ban_peer(peer_ip.ip(), reason, Some(Self::BAN_DURATION_IN_SECS)) {
+                    warn!("Unable to persist the ban on '{peer_ip}' - {error}");
+                }
+                if self.is_connected(&peer_ip) {
+                    self.disconnect(peer_ip);
+                }
+            }
+        }
+    }
+
+    /// Records that a `Ping` was sent to the given peer IP, to measure its round-trip time.
+    pub fn insert_ping_sent(&self, peer_ip: SocketAddr) {
+        if let Some(peer) = self.connected_peers.write().get_mut(&peer_ip) {
+            peer.set_ping_sent(Instant::now());
+        }
+    }
+
+    /// Records that a `Pong` was received from the given peer IP, which updates its round-trip time.
+    pub fn insert_pong_received(&self, peer_ip: SocketAddr) {
+        if let Some(peer) = self.connected_peers.write().get_mut(&peer_ip) {
+            peer.set_pong_received(Instant::now());
+        }
+    }
+
     /// Updates the connected peer with the given function.
     pub fn update_connected_peer<Fn: FnMut(&mut Peer<N>)>(
         &self,
@@ -467,28 +1299,42 @@ impl<N: Network> Router<N> {
         Ok(())
     }
 
-    /// Removes the connected peer and adds them to the candidate peers.
+    /// Removes the connected peer and adds them to the tried candidate peers.
     pub fn remove_connected_peer(&self, peer_ip: SocketAddr) {
+        // Remove the encryption and compression state of the connection, if it exists.
+        if let Some(peer_addr) = self.resolve_to_ambiguous(&peer_ip) {
+            self.noise_states.write().remove(&peer_addr);
+            self.compressed_peers.write().remove(&peer_addr);
+            self.checksummed_peers.write().remove(&peer_addr);
+            self.traffic.write().remove(&peer_addr);
+        }
         // Removes the bidirectional map between the listener address and (ambiguous) peer address.
         self.resolver.remove_peer(&peer_ip);
-        // Remove this peer from the connected peers, if it exists.
-        self.connected_peers.write().remove(&peer_ip);
-        // Add the peer to the candidate peers.
-        self.candidate_peers.write().insert(peer_ip);
+        // Remove the listening address of the node, as observed by this peer.
+        self.observed_addrs.write().remove(&peer_ip);
+        // Remove this peer from the connected peers, and record it in the peer book, if it was connected.
+        let peer = self.connected_peers.write().remove(&peer_ip);
+        if let Some(peer) = peer {
+            self.insert_known_peer(&peer);
+        }
+        // Add the peer to the tried candidate peers, unless it does not listen for inbound connections.
+        if peer_ip.port() != 0 {
+            self.candidate_peers.insert_tried(peer_ip);
+        }
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
 
     #[cfg(feature = "test")]
     pub fn clear_candidate_peers(&self) {
-        self.candidate_peers.write().clear();
+        self.candidate_peers.clear();
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
 
     /// Removes the given address from the candidate peers, if it exists.
     pub fn remove_candidate_peer(&self, peer_ip: SocketAddr) {
-        self.candidate_peers.write().remove(&peer_ip);
+        self.candidate_peers.remove(&peer_ip);
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
@@ -503,6 +1349,13 @@ impl<N: Network> Router<N> {
         info!("Shutting down the router...");
         // Abort the tasks.
         self.handles.lock().iter().for_each(|handle| handle.abort());
+        // Record the connected peers, and persist the peer book.
+        for peer in self.get_connected_peers() {
+            self.insert_known_peer(&peer);
+        }
+        if let Err(error) = self.peer_book.save() {
+            warn!("Unable to persist the peer book - {error}");
+        }
         // Close the listener.
         self.tcp.shut_down().await;
     }
@@ -13,24 +13,50 @@
 // limitations under the License.
 
 use crate::{
-    messages::{Message, Ping},
+    messages::{Message, Ping, Pong, TransactionAnnouncement},
     Router,
 };
 use snarkos_node_sync_locators::BlockLocators;
-use snarkos_node_tcp::protocols::Writing;
+use snarkos_node_tcp::{protocols::Writing, IoHealth};
 use snarkvm::prelude::Network;
 use std::io;
 
-use std::net::SocketAddr;
+use rand::Rng;
+use std::{net::SocketAddr, time::Duration};
 use tokio::sync::oneshot;
 
 pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
     /// Returns a reference to the router.
     fn router(&self) -> &Router<N>;
 
+    /// Returns the health of the outbound messages of the connected peers, by their listener IP.
+    fn connected_io_health(&self) -> Vec<(SocketAddr, IoHealth)> {
+        self.io_health_all()
+            .into_iter()
+            .filter_map(|(peer_addr, health)| Some((self.router().resolve_to_listener(&peer_addr)?, health)))
+            .collect()
+    }
+
     /// Sends a "Ping" message to the given peer.
     fn send_ping(&self, peer_ip: SocketAddr, block_locators: Option<BlockLocators<N>>) {
-        self.send(peer_ip, Message::Ping(Ping::new(self.router().node_type(), block_locators)));
+        // Record the timestamp of the ping, to measure the round-trip time of the peer.
+        self.router().insert_ping_sent(peer_ip);
+        let mut ping = Ping::new(self.router().node_type(), block_locators);
+        // Advertise the metadata of the node, if the peer supports it.
+        if self.router().supports_peer_metadata(&peer_ip) {
+            ping = ping.with_metadata(self.router().metadata());
+        }
+        self.send(peer_ip, Message::Ping(ping));
+    }
+
+    /// Sends a "Pong" message to the given peer.
+    fn send_pong(&self, peer_ip: SocketAddr) {
+        let mut pong = Pong::new(Some(false));
+        // Report the listening address of the peer, as observed by this node, if the peer supports it.
+        if self.router().supports_address_announcements(&peer_ip) && peer_ip.port() != 0 {
+            pong = pong.with_observed_addr(peer_ip);
+        }
+        self.send(peer_ip, Message::Pong(pong));
     }
 
     /// Sends the given message to specified peer.
@@ -55,6 +81,10 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
         if let Message::BlockRequest(request) = message {
             self.router().cache.insert_outbound_block_request(peer_ip, request);
         }
+        // If the message type is a header request, add it to the cache.
+        if let Message::HeaderRequest(request) = message {
+            self.router().cache.insert_outbound_header_request(peer_ip, request);
+        }
         // If the message type is a puzzle request, increment the cache.
         if matches!(message, Message::PuzzleRequest(_)) {
             self.router().cache.increment_outbound_puzzle_requests(peer_ip);
@@ -74,63 +104,98 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
             debug!("Disconnecting from '{peer_ip}' (unable to send)");
             self.router().disconnect(peer_ip);
         }
+        // If the peer persistently fails to keep up with the messages sent to it, disconnect.
+        if result.is_ok() && self.num_dropped_messages(peer_addr) > Router::<N>::MAXIMUM_DROPPED_MESSAGES {
+            warn!("Disconnecting from '{peer_ip}' (persistently slow, dropped too many messages)");
+            self.router().disconnect(peer_ip);
+        }
         result.ok()
     }
 
     /// Sends the given message to every connected peer, excluding the sender and any specified peer IPs.
-    fn propagate(&self, message: Message<N>, excluded_peers: &[SocketAddr]) {
-        // TODO (howardwu): Serialize large messages once only.
-        // // Perform ahead-of-time, non-blocking serialization just once for applicable objects.
-        // if let Message::UnconfirmedSolution(ref mut message) = message {
-        //     if let Ok(serialized_solution) = Data::serialize(message.solution.clone()).await {
-        //         let _ = std::mem::replace(&mut message.solution, Data::Buffer(serialized_solution));
-        //     } else {
-        //         error!("Solution serialization is bugged");
-        //     }
-        // } else if let Message::UnconfirmedTransaction(ref mut message) = message {
-        //     if let Ok(serialized_transaction) = Data::serialize(message.transaction.clone()).await {
-        //         let _ = std::mem::replace(&mut message.transaction, Data::Buffer(serialized_transaction));
-        //     } else {
-        //         error!("Transaction serialization is bugged");
-        //     }
-        // }
+    fn propagate(&self, mut message: Message<N>, excluded_peers: &[SocketAddr]) {
+        // Serialize the payload once only, rather than once for every peer.
+        // The payloads received from peers are already serialized, and are forwarded without being copied.
+        if let Err(error) = message.serialize_payload() {
+            error!("Failed to serialize the payload of '{}' - {error}", message.name());
+        }
 
         // Prepare the peers to send to.
         let connected_peers = self.router().connected_peers();
-        let peers = connected_peers.iter().filter(|peer_ip| !excluded_peers.contains(peer_ip));
+        let peers = connected_peers.into_iter().filter(|peer_ip| !excluded_peers.contains(peer_ip)).collect();
 
-        // Iterate through all peers that are not the sender and excluded peers.
-        for peer_ip in peers {
-            self.send(*peer_ip, message.clone());
-        }
+        // Send to all peers that are not the sender and excluded peers.
+        self.fan_out(message, peers);
     }
 
     /// Sends the given message to every connected validator, excluding the sender and any specified IPs.
-    fn propagate_to_validators(&self, message: Message<N>, excluded_peers: &[SocketAddr]) {
-        // TODO (howardwu): Serialize large messages once only.
-        // // Perform ahead-of-time, non-blocking serialization just once for applicable objects.
-        // if let Message::UnconfirmedSolution(ref mut message) = message {
-        //     if let Ok(serialized_solution) = Data::serialize(message.solution.clone()).await {
-        //         let _ = std::mem::replace(&mut message.solution, Data::Buffer(serialized_solution));
-        //     } else {
-        //         error!("Solution serialization is bugged");
-        //     }
-        // } else if let Message::UnconfirmedTransaction(ref mut message) = message {
-        //     if let Ok(serialized_transaction) = Data::serialize(message.transaction.clone()).await {
-        //         let _ = std::mem::replace(&mut message.transaction, Data::Buffer(serialized_transaction));
-        //     } else {
-        //         error!("Transaction serialization is bugged");
-        //     }
-        // }
+    fn propagate_to_validators(&self, mut message: Message<N>, excluded_peers: &[SocketAddr]) {
+        // Serialize the payload once only, rather than once for every peer.
+        // The payloads received from peers are already serialized, and are forwarded without being copied.
+        if let Err(error) = message.serialize_payload() {
+            error!("Failed to serialize the payload of '{}' - {error}", message.name());
+        }
 
         // Prepare the peers to send to.
         let connected_validators = self.router().connected_validators();
-        let peers = connected_validators.iter().filter(|peer_ip| !excluded_peers.contains(peer_ip));
+        let peers = connected_validators.into_iter().filter(|peer_ip| !excluded_peers.contains(peer_ip)).collect();
 
-        // Iterate through all validators that are not the sender and excluded validators.
+        // Send to all validators that are not the sender and excluded validators.
+        self.fan_out(message, peers);
+    }
+
+    /// Sends the given message to the given peers, in order of their round-trip time.
+    /// The lowest-latency peers are sent to immediately, to propagate the message quickly,
+    /// while the remaining peers are sent to after a small random delay, so that the arrival
+    /// times of the message reveal less about the topology of the network.
+    ///
+    /// The unconfirmed transactions are announced by their IDs to the peers that support it,
+    /// and are served from the transaction inventory to the peers that request them.
+    fn fan_out(&self, message: Message<N>, mut peers: Vec<SocketAddr>) {
+        let announcement = match &message {
+            Message::UnconfirmedTransaction(transaction) => {
+                self.router().transaction_inventory().insert(transaction.clone());
+                let transaction_ids = vec![transaction.transaction_id];
+                Some(Message::TransactionAnnouncement(TransactionAnnouncement { transaction_ids }))
+            }
+            _ => None,
+        };
+        // Returns the announcement of the message, if the peer supports it, or the message itself.
+        let select = move |router: &Router<N>, peer_ip: &SocketAddr| match &announcement {
+            Some(announcement) if router.supports_transaction_announcements(peer_ip) => announcement.clone(),
+            _ => message.clone(),
+        };
+
+        // Order the peers by their round-trip time.
+        self.router().sort_by_latency(&mut peers);
+        let delayed_peers = peers.split_off(peers.len().min(Router::<N>::NUM_IMMEDIATE_BROADCAST_PEERS));
+
+        // Send to the lowest-latency peers.
         for peer_ip in peers {
-            self.send(*peer_ip, message.clone());
+            self.send(peer_ip, select(self.router(), &peer_ip));
+        }
+
+        // Send to the remaining peers, each after a random delay.
+        if delayed_peers.is_empty() {
+            return;
         }
+        let rng = &mut self.router().rng();
+        let mut delayed_peers = delayed_peers
+            .into_iter()
+            .map(|peer_ip| (rng.gen_range(0..=Router::<N>::MAXIMUM_BROADCAST_DELAY_IN_MS), peer_ip))
+            .collect::<Vec<_>>();
+        delayed_peers.sort_unstable();
+        let self_ = self.clone();
+        tokio::spawn(async move {
+            let start = tokio::time::Instant::now();
+            for (delay_in_ms, peer_ip) in delayed_peers {
+                tokio::time::sleep_until(start + Duration::from_millis(delay_in_ms)).await;
+                // Skip the peers which disconnected in the meantime.
+                if self_.router().is_connected(&peer_ip) {
+                    self_.send(peer_ip, select(self_.router(), &peer_ip));
+                }
+            }
+        });
     }
 
     /// Returns `true` if the message can be sent.
@@ -155,6 +220,27 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
                 // Determine whether to send the transaction.
                 !seen_before
             }
+            // Send the headers-first messages only to the peers that negotiated them.
+            Message::BlockAnnouncement(..) | Message::HeaderRequest(..) => {
+                self.router().supports_headers_first(&peer_ip)
+            }
+            Message::TransactionAnnouncement(message) => {
+                // Send the transaction announcements only to the peers that negotiated them.
+                if !self.router().supports_transaction_announcements(&peer_ip) {
+                    return false;
+                }
+                // Update the timestamps for the announced transactions.
+                let num_unseen = message
+                    .transaction_ids
+                    .iter()
+                    .filter(|id| self.router().cache.insert_outbound_transaction_announcement(peer_ip, **id).is_none())
+                    .count();
+                // Determine whether to send the announcement.
+                num_unseen > 0
+            }
+            Message::TransactionRequest(..) => self.router().supports_transaction_announcements(&peer_ip),
+            // Send the address announcements only to the peers that negotiated them.
+            Message::AddressAnnouncement(..) => self.router().supports_address_announcements(&peer_ip),
             // For all other message types, return `true`.
             _ => true,
         }
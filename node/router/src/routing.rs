@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{messages::Message, Heartbeat, Inbound, Outbound};
+use crate::{map_port, messages::Message, Heartbeat, Inbound, Outbound, PORT_MAPPING_LEASE_IN_SECS};
 use snarkos_node_tcp::{
     protocols::{Disconnect, Handshake, OnConnect},
     P2P,
@@ -25,6 +25,9 @@ use core::time::Duration;
 pub trait Routing<N: Network>:
     P2P + Disconnect + OnConnect + Handshake + Inbound<N> + Outbound<N> + Heartbeat<N>
 {
+    /// The duration in seconds to wait for the handshakes with the known peers, when starting up.
+    const KNOWN_PEERS_TIMEOUT_IN_SECS: u64 = 5; // 5 seconds
+
     /// Initialize the routing.
     async fn initialize_routing(&self) {
         // Enable the TCP protocols.
@@ -33,8 +36,17 @@ pub trait Routing<N: Network>:
         self.enable_writing().await;
         self.enable_disconnect().await;
         self.enable_on_connect().await;
-        // Enable the TCP listener. Note: This must be called after the above protocols.
-        self.enable_listener().await;
+        // Enable the TCP listener, unless the node is outbound-only.
+        // Note: This must be called after the above protocols.
+        if self.router().is_listening() {
+            self.enable_listener().await;
+            // Initialize the port mapping.
+            self.initialize_port_mapping();
+        }
+        // Resolve the DNS seeds.
+        self.router().resolve_dns_seeds();
+        // Reconnect to the known peers, before the heartbeat falls back to the bootstrap peers.
+        self.connect_to_known_peers().await;
         // Initialize the heartbeat.
         self.initialize_heartbeat();
         // Initialize the report.
@@ -45,6 +57,58 @@ pub trait Routing<N: Network>:
     // Start listening for inbound connections.
     async fn enable_listener(&self) {
         self.tcp().enable_listener().await.expect("Failed to enable the TCP listener");
+        // Start listening for the validator peers, if a validator listener is configured.
+        if let Some(validator_listener) = self.router().validator_listener() {
+            let addr = self
+                .tcp()
+                .enable_secondary_listener(validator_listener.addr)
+                .await
+                .expect("Failed to enable the validator listener");
+            info!("Listening for validator peers on '{addr}'");
+            self.router().set_validator_listener_addr(addr);
+        }
+    }
+
+    /// Connects to the best peers of the peer book, and waits for the handshakes to complete (up to a timeout),
+    /// so that a restarted node rejoins the network without rediscovering it through the bootstrap peers.
+    async fn connect_to_known_peers(&self) {
+        let num_peers = self
+            .router()
+            .connection_policy()
+            .map_or(Self::MEDIAN_NUMBER_OF_PEERS, |policy| policy.target_outbound_peers as usize);
+        let known_peers = self.router().peer_book().best_peers(num_peers);
+        if known_peers.is_empty() {
+            return;
+        }
+        info!("Reconnecting to {} known peers", known_peers.len());
+        let handles = known_peers.into_iter().filter_map(|peer_ip| self.router().connect(peer_ip));
+        let timeout = Duration::from_secs(Self::KNOWN_PEERS_TIMEOUT_IN_SECS);
+        let _ = tokio::time::timeout(timeout, futures::future::join_all(handles)).await;
+    }
+
+    /// Initialize the port mapping, if it is enabled.
+    /// The mapping is renewed at half of its lifetime, so that it never expires while the node is running.
+    fn initialize_port_mapping(&self) {
+        if !self.router().is_port_mapping_enabled() {
+            return;
+        }
+        let router = self.router().clone();
+        self.router().spawn(async move {
+            loop {
+                // Forward the listener port on the gateway.
+                match map_port(router.local_ip().port()).await {
+                    Ok(external_addr) => {
+                        if router.external_addr() != Some(external_addr) {
+                            info!("Forwarded the listener port on the gateway (external address '{external_addr}')");
+                        }
+                        router.set_external_addr(external_addr);
+                    }
+                    Err(error) => warn!("Unable to forward the listener port on the gateway - {error}"),
+                }
+                // Sleep for half of the lifetime of the port mapping.
+                tokio::time::sleep(Duration::from_secs(PORT_MAPPING_LEASE_IN_SECS as u64 / 2)).await;
+            }
+        });
     }
 
     /// Initialize a new instance of the heartbeat.
@@ -23,7 +23,7 @@ use std::{
 };
 
 use snarkos_account::Account;
-use snarkos_node_router::{messages::NodeType, Router};
+use snarkos_node_router::{messages::NodeType, Router, RouterConfig, ValidatorListener};
 use snarkvm::prelude::{block::Block, FromBytes, Network, Testnet3 as CurrentNetwork};
 
 /// A helper macro to print the TCP listening address, along with the connected and connecting peers.
@@ -68,50 +68,64 @@ pub fn initialize_logger(level: u8) {
     let _ = tracing_subscriber::fmt().with_env_filter(filter).with_target(level == 3).try_init();
 }
 
+/// Returns the configuration of a router in development mode, which listens on the given port,
+/// and admits up to `max_peers` peers. Setting the `listening_port = 0` will result in a random port being assigned.
+pub fn sample_router_config(listening_port: u16, max_peers: u16) -> RouterConfig {
+    RouterConfig {
+        node_ip: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port),
+        max_peers,
+        max_peers_per_subnet: max_peers,
+        is_dev: true,
+        ..Default::default()
+    }
+}
+
+/// Initializes a router of the given node type with the given configuration.
+pub async fn test_router(node_type: NodeType, config: RouterConfig) -> TestRouter<CurrentNetwork> {
+    Router::new(node_type, sample_account(), config).await.expect("couldn't create router").into()
+}
+
 /// Initializes a client router. Setting the `listening_port = 0` will result in a random port being assigned.
 #[allow(dead_code)]
 pub async fn client(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
-    Router::new(
-        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port),
-        NodeType::Client,
-        sample_account(),
-        &[],
-        max_peers,
-        true,
-    )
-    .await
-    .expect("couldn't create client router")
-    .into()
+    test_router(NodeType::Client, sample_router_config(listening_port, max_peers)).await
+}
+
+/// Initializes a client router that negotiates encrypted connections.
+/// Setting the `listening_port = 0` will result in a random port being assigned.
+#[allow(dead_code)]
+pub async fn encrypted_client(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
+    let config = RouterConfig { noise: true, ..sample_router_config(listening_port, max_peers) };
+    test_router(NodeType::Client, config).await
+}
+
+/// Initializes a client router that does not listen for inbound connections.
+#[allow(dead_code)]
+pub async fn outbound_only_client(max_peers: u16) -> TestRouter<CurrentNetwork> {
+    let config = RouterConfig { listen: false, ..sample_router_config(0, max_peers) };
+    test_router(NodeType::Client, config).await
 }
 
 /// Initializes a prover router. Setting the `listening_port = 0` will result in a random port being assigned.
 #[allow(dead_code)]
 pub async fn prover(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
-    Router::new(
-        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port),
-        NodeType::Prover,
-        sample_account(),
-        &[],
-        max_peers,
-        true,
-    )
-    .await
-    .expect("couldn't create prover router")
-    .into()
+    test_router(NodeType::Prover, sample_router_config(listening_port, max_peers)).await
 }
 
 /// Initializes a validator router. Setting the `listening_port = 0` will result in a random port being assigned.
 #[allow(dead_code)]
 pub async fn validator(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
-    Router::new(
-        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port),
-        NodeType::Validator,
-        sample_account(),
-        &[],
-        max_peers,
-        true,
-    )
-    .await
-    .expect("couldn't create validator router")
-    .into()
+    test_router(NodeType::Validator, sample_router_config(listening_port, max_peers)).await
+}
+
+/// Initializes a validator router with a validator listener on a random port.
+/// Setting the `listening_port = 0` will result in a random port being assigned.
+#[allow(dead_code)]
+pub async fn validator_with_listener(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
+    let validator_listener = ValidatorListener::new(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0));
+    let config = RouterConfig {
+        validator_listener: Some(validator_listener),
+        ..sample_router_config(listening_port, max_peers)
+    };
+    test_router(NodeType::Validator, config).await
 }
@@ -15,8 +15,9 @@
 use crate::common::sample_genesis_block;
 use snarkos_node_router::{
     messages::{
+        BlockHeader,
         BlockRequest,
-        DisconnectReason,
+        HeaderRequest,
         Message,
         MessageCodec,
         Ping,
@@ -26,10 +27,12 @@ use snarkos_node_router::{
     },
     Heartbeat,
     Inbound,
+    Offense,
     Outbound,
     Router,
     Routing,
 };
+use snarkos_node_sync::BlockSync;
 use snarkos_node_tcp::{
     protocols::{Disconnect, Handshake, OnConnect, Reading, Writing},
     Connection,
@@ -44,15 +47,33 @@ use snarkvm::prelude::{
 };
 
 use async_trait::async_trait;
-use std::{io, net::SocketAddr};
+use std::{io, net::SocketAddr, sync::Arc};
 use tracing::*;
 
 #[derive(Clone)]
-pub struct TestRouter<N: Network>(Router<N>);
+pub struct TestRouter<N: Network> {
+    /// The router.
+    router: Router<N>,
+    /// The block sync module, which tracks the block locators of the peers, if enabled.
+    sync: Option<Arc<BlockSync<N>>>,
+}
 
 impl<N: Network> From<Router<N>> for TestRouter<N> {
     fn from(router: Router<N>) -> Self {
-        Self(router)
+        Self { router, sync: None }
+    }
+}
+
+impl<N: Network> TestRouter<N> {
+    /// Enables the given block sync module, which is updated with the block locators in the pings of the peers.
+    pub fn with_block_sync(mut self, sync: BlockSync<N>) -> Self {
+        self.sync = Some(Arc::new(sync));
+        self
+    }
+
+    /// Returns the block sync module, if enabled.
+    pub fn block_sync(&self) -> Option<&BlockSync<N>> {
+        self.sync.as_deref()
     }
 }
 
@@ -60,7 +81,7 @@ impl<N: Network> core::ops::Deref for TestRouter<N> {
     type Target = Router<N>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.router
     }
 }
 
@@ -78,9 +99,21 @@ impl<N: Network> Handshake for TestRouter<N> {
         // Perform the handshake.
         let peer_addr = connection.addr();
         let conn_side = connection.side();
-        let stream = self.borrow_stream(&mut connection);
+        let local_addr = connection.local_addr();
         let genesis_header = *sample_genesis_block().header();
-        self.router().handshake(peer_addr, stream, conn_side, genesis_header).await?;
+        match self.router().tls() {
+            // If TLS is enabled, perform the handshake over the TLS stream.
+            Some(tls) => {
+                let stream = self.take_stream(&mut connection);
+                let mut stream = tls.wrap(peer_addr, stream, conn_side).await?;
+                self.router().handshake(peer_addr, &mut stream, conn_side, local_addr, genesis_header).await?;
+                self.return_stream(&mut connection, stream);
+            }
+            None => {
+                let stream = self.borrow_stream(&mut connection);
+                self.router().handshake(peer_addr, stream, conn_side, local_addr, genesis_header).await?;
+            }
+        }
 
         Ok(connection)
     }
@@ -98,6 +131,9 @@ impl<N: Network> Disconnect for TestRouter<N> {
     /// Any extra operations to be performed during a disconnect.
     async fn handle_disconnect(&self, peer_addr: SocketAddr) {
         if let Some(peer_ip) = self.router().resolve_to_listener(&peer_addr) {
+            if let Some(sync) = self.block_sync() {
+                sync.remove_peer(&peer_ip);
+            }
             self.router().remove_connected_peer(peer_ip);
         }
     }
@@ -110,8 +146,13 @@ impl<N: Network> Writing for TestRouter<N> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(addr)
+    }
+
+    /// Returns `true` if the given message may be dropped for a peer that fails to keep up.
+    fn is_droppable(&self, message: &Self::Message) -> bool {
+        message.is_relay()
     }
 }
 
@@ -122,8 +163,8 @@ impl<N: Network> Reading for TestRouter<N> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
@@ -131,7 +172,10 @@ impl<N: Network> Reading for TestRouter<N> {
         // Process the message. Disconnect if the peer violated the protocol.
         if let Err(error) = self.inbound(peer_ip, message).await {
             warn!("Disconnecting from '{peer_ip}' - {error}");
-            self.send(peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
+            // Lower the reputation of this peer, and inform it of the offense.
+            let offense = Offense::from_error(&error);
+            self.router().record_offense(peer_ip, offense);
+            self.send(peer_ip, Message::Disconnect(offense.disconnect_reason().into()));
             // Disconnect from this peer.
             self.router().disconnect(peer_ip);
         }
@@ -147,12 +191,17 @@ impl<N: Network> Heartbeat<N> for TestRouter<N> {}
 impl<N: Network> Outbound<N> for TestRouter<N> {
     /// Returns a reference to the router.
     fn router(&self) -> &Router<N> {
-        &self.0
+        &self.router
     }
 }
 
 #[async_trait]
 impl<N: Network> Inbound<N> for TestRouter<N> {
+    /// Handles a `BlockAnnouncement` message.
+    fn block_announcement(&self, _peer_ip: SocketAddr, _block_header: BlockHeader<N>) -> bool {
+        true
+    }
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, _peer_ip: SocketAddr, _message: BlockRequest) -> bool {
         true
@@ -163,11 +212,25 @@ impl<N: Network> Inbound<N> for TestRouter<N> {
         true
     }
 
-    /// Handles an `Ping` message.
-    fn ping(&self, _peer_ip: SocketAddr, _message: Ping<N>) -> bool {
+    /// Handles a `HeaderRequest` message.
+    fn header_request(&self, _peer_ip: SocketAddr, _message: HeaderRequest) -> bool {
+        true
+    }
+
+    /// Handles a `HeaderResponse` message.
+    fn header_response(&self, _peer_ip: SocketAddr, _headers: Vec<BlockHeader<N>>) -> bool {
         true
     }
 
+    /// Handles an `Ping` message.
+    fn ping(&self, peer_ip: SocketAddr, message: Ping<N>) -> bool {
+        // Update the block locators of the peer, if block sync is enabled.
+        match (self.block_sync(), message.block_locators) {
+            (Some(sync), Some(block_locators)) => sync.update_peer_locators(peer_ip, block_locators).is_ok(),
+            _ => true,
+        }
+    }
+
     /// Handles an `Pong` message.
     fn pong(&self, _peer_ip: SocketAddr, _message: Pong) -> bool {
         true
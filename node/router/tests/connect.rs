@@ -15,9 +15,22 @@
 mod common;
 use common::*;
 
-use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkos_account::Account;
+use snarkos_node_router::{
+    messages::{ChallengeRequest, ChallengeResponse, Message, MessageCodec, NodeType},
+    Router,
+    RouterConfig,
+};
+use snarkos_node_tcp::{protocols::Handshake, ProxyConfig, P2P};
+use snarkvm::ledger::narwhal::Data;
 
 use core::time::Duration;
+use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use tokio::net::TcpStream;
+use tokio_util::codec::Framed;
+
+type CurrentNetwork = snarkvm::prelude::Testnet3;
 
 #[tokio::test]
 async fn test_connect_without_handshake() {
@@ -150,6 +163,79 @@ async fn test_connect_with_handshake() {
     }
 }
 
+#[tokio::test]
+async fn test_connect_with_noise() {
+    // Create 3 routers, 2 of which support encrypted connections.
+    let node0 = encrypted_client(0, 2).await;
+    let node1 = encrypted_client(0, 2).await;
+    let node2 = validator(0, 2).await;
+
+    // Enable handshake protocol.
+    node0.enable_handshake().await;
+    node1.enable_handshake().await;
+    node2.enable_handshake().await;
+
+    // Start listening.
+    node0.tcp().enable_listener().await.unwrap();
+    node1.tcp().enable_listener().await.unwrap();
+    node2.tcp().enable_listener().await.unwrap();
+
+    {
+        // Connect node0 to node1.
+        node0.connect(node1.local_ip());
+        // Sleep briefly.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        print_tcp!(node0);
+        print_tcp!(node1);
+
+        // Check the router level.
+        assert_eq!(node0.number_of_connected_peers(), 1);
+        assert_eq!(node1.number_of_connected_peers(), 1);
+
+        // Check that the connection is encrypted on both sides.
+        assert!(node0.is_encrypted(&node1.local_ip()));
+        assert!(node1.is_encrypted(&node0.local_ip()));
+
+        // Check that compression was negotiated on both sides.
+        let codec0 = node0.message_codec(node0.resolve_to_ambiguous(&node1.local_ip()).unwrap());
+        let codec1 = node1.message_codec(node1.resolve_to_ambiguous(&node0.local_ip()).unwrap());
+        assert!(codec0.is_encrypted() && codec0.is_compressed() && codec0.is_checksummed());
+        assert!(codec1.is_encrypted() && codec1.is_compressed() && codec1.is_checksummed());
+
+        // Check that the negotiated features are recorded on both sides.
+        let features =
+            ChallengeRequest::<CurrentNetwork>::FEATURE_NOISE | ChallengeRequest::<CurrentNetwork>::FEATURE_COMPRESSION;
+        assert!(node0.get_connected_peer(&node1.local_ip()).unwrap().supports(features));
+        assert!(node1.get_connected_peer(&node0.local_ip()).unwrap().supports(features));
+        // Check that the headers-first block sync was negotiated on both sides.
+        assert!(node0.supports_headers_first(&node1.local_ip()));
+        assert!(node1.supports_headers_first(&node0.local_ip()));
+    }
+    {
+        // Connect node0 to node2, which does not support encrypted connections.
+        node0.connect(node2.local_ip());
+        // Sleep briefly.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        print_tcp!(node0);
+        print_tcp!(node2);
+
+        // Check the router level.
+        assert_eq!(node0.number_of_connected_peers(), 2);
+        assert_eq!(node2.number_of_connected_peers(), 1);
+
+        // Check that the connection falls back to plaintext.
+        assert!(!node0.is_encrypted(&node2.local_ip()));
+        assert!(!node2.is_encrypted(&node0.local_ip()));
+
+        // Check that only the features supported by both sides were negotiated.
+        let peer = node0.get_connected_peer(&node2.local_ip()).unwrap();
+        assert!(!peer.supports(ChallengeRequest::<CurrentNetwork>::FEATURE_NOISE));
+        assert!(peer.supports(ChallengeRequest::<CurrentNetwork>::FEATURE_COMPRESSION));
+    }
+}
+
 #[ignore]
 #[tokio::test]
 async fn test_connect_simultaneously_with_handshake() {
@@ -185,3 +271,276 @@ async fn test_connect_simultaneously_with_handshake() {
         assert_eq!(node1.number_of_connected_peers(), 1);
     }
 }
+
+#[tokio::test]
+async fn test_connect_outbound_only() {
+    // Create 2 routers, 1 of which does not listen for inbound connections.
+    let node0 = outbound_only_client(2).await;
+    let node1 = client(0, 2).await;
+    assert!(!node0.is_listening());
+    assert_eq!(node0.local_ip().port(), 0);
+
+    // Enable handshake protocol.
+    node0.enable_handshake().await;
+    node1.enable_handshake().await;
+
+    // Start listening on node1 only.
+    node1.tcp().enable_listener().await.unwrap();
+
+    {
+        // Connect node0 to node1.
+        node0.connect(node1.local_ip());
+        // Sleep briefly.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        print_tcp!(node0);
+        print_tcp!(node1);
+
+        // Check the router level.
+        assert_eq!(node0.number_of_connected_peers(), 1);
+        assert_eq!(node1.number_of_connected_peers(), 1);
+        // Ensure node1 knows node0 does not listen for inbound connections.
+        assert_eq!(node1.connected_peers()[0].port(), 0);
+    }
+    {
+        // Disconnect node1 from node0.
+        let node0_ip = node1.connected_peers()[0];
+        node1.disconnect(node0_ip).await.unwrap();
+        // Sleep briefly.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Ensure node0 is not a candidate peer, as it cannot be dialed.
+        assert_eq!(node1.number_of_connected_peers(), 0);
+        assert!(node1.candidate_peers().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_connect_trusted() {
+    // Create 2 routers.
+    let node0 = client(0, 2).await;
+    let node1 = client(0, 2).await;
+
+    // Enable handshake protocol.
+    node0.enable_handshake().await;
+    node1.enable_handshake().await;
+
+    // Start listening.
+    node0.tcp().enable_listener().await.unwrap();
+    node1.tcp().enable_listener().await.unwrap();
+
+    // Whitelist node1, and connect to it.
+    node0.connect_trusted(node1.local_ip()).await.unwrap();
+
+    // Ensure the handshake concluded before returning, and the peer is trusted.
+    assert!(node0.is_connected(&node1.local_ip()));
+    assert!(node0.is_trusted(&node1.local_ip()));
+
+    // Ensure connecting to the trusted peer again is a no-op.
+    node0.connect_trusted(node1.local_ip()).await.unwrap();
+    assert_eq!(node0.number_of_connected_peers(), 1);
+}
+
+#[tokio::test]
+async fn test_validator_listener() {
+    // Create a validator with a validator listener, along with a client, a validator in the committee,
+    // and a validator outside of the committee.
+    let node0 = validator_with_listener(0, 2).await;
+    let node1 = client(0, 2).await;
+    let node2 = validator(0, 2).await;
+    let account = Account::new(&mut rand::thread_rng()).unwrap();
+    let node3: TestRouter<CurrentNetwork> =
+        Router::new(NodeType::Validator, account, sample_router_config(0, 2)).await.unwrap().into();
+    node0.set_validator_committee([node2.address()]);
+
+    // Enable handshake protocol, and start listening.
+    for node in [&node0, &node1, &node2, &node3] {
+        node.enable_handshake().await;
+        node.tcp().enable_listener().await.unwrap();
+    }
+    let validator_listener = node0.validator_listener().unwrap();
+    let validator_listener_addr = node0.tcp().enable_secondary_listener(validator_listener.addr).await.unwrap();
+    node0.set_validator_listener_addr(validator_listener_addr);
+
+    // Ensure the client is not admitted through the validator listener.
+    node1.connect(validator_listener_addr);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(node0.number_of_connected_peers(), 0);
+
+    // Ensure a validator outside of the committee is not admitted through the validator listener.
+    node3.connect(validator_listener_addr);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(node0.number_of_connected_peers(), 0);
+
+    // Ensure the validator is admitted through the validator listener, separately from the public peers.
+    node2.connect(validator_listener_addr);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(node0.number_of_validator_listener_peers(), 1);
+    assert_eq!(node0.number_of_public_peers(), 0);
+    assert_eq!(node0.max_connected_peers(), 2);
+
+    // Ensure the validator is disconnected once it leaves the committee.
+    node0.set_validator_committee([]);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(node0.number_of_validator_listener_peers(), 0);
+}
+
+#[tokio::test]
+async fn test_inbound_peer_evicts_lowest_quality_peer() {
+    // Create a client with room for a single peer, along with two other clients.
+    let node0 = client(0, 1).await;
+    let node1 = client(0, 1).await;
+    let node2 = client(0, 1).await;
+
+    // Enable handshake protocol, and start listening.
+    for node in [&node0, &node1, &node2] {
+        node.enable_handshake().await;
+        node.tcp().enable_listener().await.unwrap();
+    }
+
+    // Connect node1 to node0, which fills the only slot of node0.
+    node1.connect(node0.local_ip());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(node0.is_connected(&node1.local_ip()));
+    assert_eq!(node0.select_peer_to_evict(), Some(node1.local_ip()));
+
+    // Ensure node2 is admitted, by evicting node1, instead of being rejected.
+    node2.connect(node0.local_ip());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(node0.is_connected(&node2.local_ip()));
+    assert!(!node0.is_connected(&node1.local_ip()));
+    assert_eq!(node0.number_of_connected_peers(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_handshake_does_not_evict_peer() {
+    // Create an encrypted client with room for a single peer, along with another client.
+    let node0 = encrypted_client(0, 1).await;
+    let node1 = client(0, 1).await;
+
+    // Enable handshake protocol, and start listening.
+    for node in [&node0, &node1] {
+        node.enable_handshake().await;
+        node.tcp().enable_listener().await.unwrap();
+    }
+
+    // Connect node1 to node0, which fills the only slot of node0.
+    node1.connect(node0.local_ip());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(node0.is_connected(&node1.local_ip()));
+
+    // A newcomer completes the challenge exchange, and abandons the handshake before the connection is encrypted.
+    let mut stream = TcpStream::connect(node0.local_ip()).await.unwrap();
+    let mut framed = Framed::new(&mut stream, MessageCodec::<CurrentNetwork>::handshake());
+    let account = sample_account();
+    let features = ChallengeRequest::<CurrentNetwork>::FEATURE_NOISE;
+    let request = ChallengeRequest::new(4133, NodeType::Client, account.address(), rand::random(), features, None);
+    framed.send(Message::ChallengeRequest(request)).await.unwrap();
+    let Some(Ok(Message::ChallengeResponse(_))) = framed.next().await else { panic!("Expected a challenge response") };
+    let Some(Ok(Message::ChallengeRequest(request))) = framed.next().await else {
+        panic!("Expected a challenge request")
+    };
+    let signature = account.sign_bytes(&request.nonce.to_le_bytes(), &mut rand::thread_rng()).unwrap();
+    let genesis_header = *sample_genesis_block::<CurrentNetwork>().header();
+    let response = ChallengeResponse { genesis_header, signature: Data::Object(signature), node_signature: None };
+    framed.send(Message::ChallengeResponse(response)).await.unwrap();
+    drop(framed);
+    drop(stream);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // Ensure node1 was not evicted for the newcomer, whose handshake did not succeed.
+    assert!(node0.is_connected(&node1.local_ip()));
+    assert_eq!(node0.number_of_connected_peers(), 1);
+}
+
+#[tokio::test]
+async fn test_candidate_peers_are_canonical() {
+    let node = client(0, 2).await;
+
+    // Insert a candidate peer with an IPv4-mapped IPv6 address.
+    node.insert_candidate_peers("1.2.3.4".parse().unwrap(), &["[::ffff:5.6.7.8]:4133".parse().unwrap()]);
+
+    // Ensure the candidate peer is stored with the IPv4 address it maps to.
+    assert_eq!(node.candidate_peers(), ["5.6.7.8:4133".parse().unwrap()].into_iter().collect());
+}
+
+#[tokio::test]
+async fn test_restricted_ip() {
+    let node = client(0, 2).await;
+
+    // Insert candidate peers on two IPs, and restrict one of the IPs for spamming connection attempts.
+    let spammer_ip = "5.6.7.8".parse().unwrap();
+    node.insert_candidate_peers("1.2.3.4".parse().unwrap(), &[
+        "5.6.7.8:4133".parse().unwrap(),
+        "9.9.9.9:4133".parse().unwrap(),
+    ]);
+    node.insert_restricted_ip(spammer_ip);
+
+    // Ensure every listener address on the restricted IP is restricted, and removed from the candidate peers.
+    assert!(node.is_restricted_ip(&spammer_ip));
+    assert!(node.is_restricted(&"5.6.7.8:4130".parse().unwrap()));
+    assert!(!node.is_restricted(&"9.9.9.9:4133".parse().unwrap()));
+    assert_eq!(node.candidate_peers(), ["9.9.9.9:4133".parse().unwrap()].into_iter().collect());
+
+    // Ensure the addresses on the restricted IP are not inserted into the candidate peers again.
+    node.insert_candidate_peers("1.2.3.4".parse().unwrap(), &["5.6.7.8:4134".parse().unwrap()]);
+    assert_eq!(node.candidate_peers(), ["9.9.9.9:4133".parse().unwrap()].into_iter().collect());
+}
+
+#[tokio::test]
+async fn test_observed_addrs() {
+    let node = client(0, 2).await;
+    node.tcp().enable_listener().await.unwrap();
+    let port = node.listener_port();
+
+    // Two reports are not enough to change the advertised address.
+    let observed_addr = SocketAddr::new("5.6.7.8".parse().unwrap(), port);
+    node.insert_observed_addr("1.1.1.1:4133".parse().unwrap(), observed_addr);
+    node.insert_observed_addr("2.2.2.2:4133".parse().unwrap(), observed_addr);
+    assert!(node.observed_addrs().is_empty());
+
+    // The reports of peers within the same subnet count once.
+    node.insert_observed_addr("2.2.2.3:4133".parse().unwrap(), observed_addr);
+    assert!(node.observed_addrs().is_empty());
+
+    // Addresses which are not publicly reachable, or have another port, are ignored.
+    let private_addr = SocketAddr::new("10.0.0.1".parse().unwrap(), port);
+    let other_port_addr = SocketAddr::new("5.6.7.8".parse().unwrap(), port.wrapping_add(1));
+    node.insert_observed_addr("3.3.3.3:4133".parse().unwrap(), private_addr);
+    node.insert_observed_addr("4.4.4.4:4133".parse().unwrap(), other_port_addr);
+    assert!(node.observed_addrs().is_empty());
+
+    // The third report from a distinct subnet is enough.
+    node.insert_observed_addr("9.9.9.9:4133".parse().unwrap(), observed_addr);
+    assert_eq!(node.observed_addrs(), vec![observed_addr]);
+
+    // The observed address replaces the advertised address of the same address family.
+    node.update_advertised_addr(SocketAddr::new("1.2.3.4".parse().unwrap(), port));
+    node.update_advertised_addr(observed_addr);
+    assert_eq!(node.advertised_addrs(), vec![observed_addr]);
+}
+
+#[tokio::test]
+async fn test_dns_seeds_are_not_resolved_through_a_proxy() {
+    let seed_ip: SocketAddr = "1.2.3.4:4133".parse().unwrap();
+    let dns_seeds = vec![seed_ip.to_string()];
+    let proxy = Some(ProxyConfig::new("127.0.0.1:9050".parse().unwrap(), None));
+
+    // Without a proxy, the DNS seeds are resolved into candidate peers.
+    let config = RouterConfig { dns_seeds: dns_seeds.clone(), ..sample_router_config(0, 2) };
+    let node = test_router(NodeType::Client, config).await;
+    node.resolve_dns_seeds();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(node.candidate_peers().contains(&seed_ip));
+
+    // With a proxy, the DNS seeds are not resolved, as the lookups would bypass the proxy.
+    let config = RouterConfig { dns_seeds, proxy: proxy.clone(), ..sample_router_config(0, 2) };
+    let node = test_router(NodeType::Client, config).await;
+    node.resolve_dns_seeds();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(node.candidate_peers().is_empty());
+
+    // The port mapping cannot be used with a proxy, as it reveals the IP of the node to its gateway.
+    let config = RouterConfig { port_mapping: true, proxy, ..sample_router_config(0, 2) };
+    assert!(Router::<CurrentNetwork>::new(NodeType::Client, sample_account(), config).await.is_err());
+}
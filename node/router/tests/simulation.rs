@@ -0,0 +1,167 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_bft_ledger_service::MockLedgerService;
+use snarkos_node_router::Outbound;
+use snarkos_node_sync::{
+    locators::test_helpers::{sample_block_locators, sample_block_locators_with_fork},
+    BlockSync,
+    BlockSyncMode,
+};
+use snarkos_node_tcp::{
+    protocols::{Handshake, Reading, Writing},
+    MemoryNetwork,
+    P2P,
+};
+use snarkvm::{ledger::committee::test_helpers::sample_committee, prelude::TestRng};
+
+use core::time::Duration;
+use rand::{Rng, RngCore};
+use std::{net::SocketAddr, sync::Arc};
+
+/// The outcome of a simulation, i.e. the listening address and the connected peers of each node,
+/// along with the next random value of each node.
+type Outcome = Vec<(SocketAddr, Vec<SocketAddr>, u64)>;
+
+/// Simulates a network of 3 clients over an in-process network, in which both node0 and node2 connect to node1.
+async fn simulate(seed: u64) -> Outcome {
+    let network = MemoryNetwork::default();
+
+    // Create 3 routers.
+    let nodes = vec![client(0, 3).await, client(0, 3).await, client(0, 3).await];
+    for (i, node) in nodes.iter().enumerate() {
+        node.enable_simulation(&network, seed + i as u64);
+        // Enable handshake protocol.
+        node.enable_handshake().await;
+        // Start listening.
+        node.tcp().enable_listener().await.unwrap();
+    }
+
+    // Connect node0 and node2 to node1.
+    nodes[0].connect(nodes[1].local_ip());
+    nodes[2].connect(nodes[1].local_ip());
+    // Sleep briefly (in virtual time).
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    nodes
+        .iter()
+        .map(|node| {
+            let mut connected_peers = node.connected_peers();
+            connected_peers.sort_unstable();
+            (node.local_ip(), connected_peers, node.rng().next_u64())
+        })
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_simulation_is_reproducible() {
+    let outcome = simulate(1).await;
+
+    // The nodes connected over the in-process network.
+    let [(node0, peers0, _), (node1, peers1, _), (node2, peers2, _)] = &outcome[..] else {
+        panic!("Expected 3 nodes");
+    };
+    assert_eq!(peers0, &vec![*node1]);
+    assert_eq!(peers1, &{
+        let mut peers = vec![*node0, *node2];
+        peers.sort_unstable();
+        peers
+    });
+    assert_eq!(peers2, &vec![*node1]);
+
+    // The same seed leads to the same outcome, and a different seed to different random choices.
+    assert_eq!(simulate(1).await, outcome);
+    assert_ne!(simulate(2).await, outcome);
+}
+
+/// The outcome of a sync simulation, i.e. the sync peers (in order) with their latest heights,
+/// along with their minimum common ancestor, as found by the syncing node.
+type SyncOutcome = Option<(Vec<(SocketAddr, u32)>, u32)>;
+
+/// Simulates a network of 5 clients over an in-process network, in which node0 is at genesis and syncs from the
+/// others: node1 and node2 are at height 30, node3 is at height 25 on the same chain, and node4 is at height 35
+/// on a fork from height 20. The peers race to send their block locators to node0, after a random delay.
+async fn simulate_sync(seed: u64) -> SyncOutcome {
+    let network = MemoryNetwork::default();
+
+    // Create 5 routers, where node0 syncs from a ledger at genesis.
+    let ledger = MockLedgerService::new_at_height(sample_committee(&mut TestRng::default()), 0);
+    let mut nodes = vec![client(0, 5).await.with_block_sync(BlockSync::new(BlockSyncMode::Router, Arc::new(ledger)))];
+    for _ in 1..5 {
+        nodes.push(client(0, 5).await);
+    }
+    for (i, node) in nodes.iter().enumerate() {
+        node.enable_simulation(&network, seed + i as u64);
+        // Enable handshake protocol.
+        node.enable_handshake().await;
+        // Enable the reading and writing protocols.
+        node.enable_reading().await;
+        node.enable_writing().await;
+        // Start listening.
+        node.tcp().enable_listener().await.unwrap();
+    }
+
+    // Connect the peers to node0.
+    for node in &nodes[1..] {
+        node.connect(nodes[0].local_ip());
+    }
+    // Sleep briefly (in virtual time).
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // Send the block locators of each peer to node0, after a random delay.
+    let block_locators = [
+        sample_block_locators(30),
+        sample_block_locators(30),
+        sample_block_locators(25),
+        sample_block_locators_with_fork(35, 20),
+    ];
+    for (node, block_locators) in nodes[1..].iter().zip(block_locators) {
+        let (node, sync_ip) = (node.clone(), nodes[0].local_ip());
+        let delay = Duration::from_millis(node.rng().gen_range(0..100));
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            node.send_ping(sync_ip, Some(block_locators));
+        });
+    }
+    // Sleep briefly (in virtual time).
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let (sync_peers, min_common_ancestor) = nodes[0].block_sync().unwrap().find_sync_peers()?;
+    Some((sync_peers.into_iter().collect(), min_common_ancestor))
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sync_simulation_is_reproducible() {
+    let outcome = simulate_sync(1).await;
+
+    // Node0 syncs from the peers on the same chain, up to their common ancestor, and ignores the fork,
+    // regardless of the order in which the block locators arrived.
+    let (sync_peers, min_common_ancestor) = outcome.clone().expect("Expected node0 to find sync peers");
+    let mut heights = sync_peers.iter().map(|(_, height)| *height).collect::<Vec<_>>();
+    heights.sort_unstable();
+    assert_eq!(heights, vec![25, 30, 30]);
+    assert_eq!(min_common_ancestor, 25);
+
+    // The same seed leads to the same outcome, including the order of the sync peers at the same height.
+    assert_eq!(simulate_sync(1).await, outcome);
+    for seed in 2..5 {
+        let (other_peers, other_ancestor) = simulate_sync(seed).await.expect("Expected node0 to find sync peers");
+        let mut other_heights = other_peers.iter().map(|(_, height)| *height).collect::<Vec<_>>();
+        other_heights.sort_unstable();
+        assert_eq!((other_heights, other_ancestor), (vec![25, 30, 30], 25));
+    }
+}
@@ -14,16 +14,17 @@
 
 mod router;
 
-use crate::traits::NodeInterface;
+use crate::{traits::NodeInterface, NodeConfig};
 use snarkos_account::Account;
 use snarkos_node_bft::ledger_service::CoreLedgerService;
 use snarkos_node_rest::Rest;
 use snarkos_node_router::{
-    messages::{Message, NodeType, UnconfirmedSolution},
+    messages::{BlockAnnouncement, Message, NodeType, UnconfirmedSolution},
     Heartbeat,
     Inbound,
     Outbound,
     Router,
+    RouterConfig,
     Routing,
 };
 use snarkos_node_sync::{BlockSync, BlockSyncMode};
@@ -45,10 +46,7 @@ use aleo_std::StorageMode;
 use anyhow::Result;
 use core::future::Future;
 use parking_lot::Mutex;
-use std::{
-    net::SocketAddr,
-    sync::{atomic::AtomicBool, Arc},
-};
+use std::sync::{atomic::AtomicBool, Arc};
 use tokio::task::JoinHandle;
 
 /// A client node is a full node, capable of querying with the network.
@@ -75,14 +73,10 @@ pub struct Client<N: Network, C: ConsensusStorage<N>> {
 impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
     /// Initializes a new client node.
     pub async fn new(
-        node_ip: SocketAddr,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
         account: Account<N>,
-        trusted_peers: &[SocketAddr],
         genesis: Block<N>,
-        cdn: Option<String>,
         storage_mode: StorageMode,
+        config: NodeConfig,
     ) -> Result<Self> {
         // Prepare the shutdown flag.
         let shutdown: Arc<AtomicBool> = Default::default();
@@ -95,10 +89,10 @@ impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
         // TODO: Remove me after Phase 3.
         let ledger = crate::phase_3_reset(ledger, storage_mode.clone())?;
         // Initialize the CDN.
-        if let Some(base_url) = cdn {
+        if let Some(base_url) = &config.cdn {
             // Sync the ledger with the CDN.
             if let Err((_, error)) =
-                snarkos_node_cdn::sync_ledger_with_cdn(&base_url, ledger.clone(), shutdown.clone()).await
+                snarkos_node_cdn::sync_ledger_with_cdn(base_url, ledger.clone(), shutdown.clone()).await
             {
                 crate::log_clean_error(&storage_mode);
                 return Err(error);
@@ -111,14 +105,10 @@ impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
         let sync = BlockSync::new(BlockSyncMode::Router, ledger_service.clone());
 
         // Initialize the node router.
-        let router = Router::new(
-            node_ip,
-            NodeType::Client,
-            account,
-            trusted_peers,
-            Self::MAXIMUM_NUMBER_OF_PEERS as u16,
-            matches!(storage_mode, StorageMode::Development(_)),
-        )
+        let router = Router::new(NodeType::Client, account, RouterConfig {
+            validator_listener: None,
+            ..config.router_config::<N>(&storage_mode, Self::MAXIMUM_NUMBER_OF_PEERS)
+        })
         .await?;
         // Load the coinbase puzzle.
         let coinbase_puzzle = CoinbasePuzzle::<N>::load()?;
@@ -135,13 +125,16 @@ impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
         };
 
         // Initialize the REST server.
-        if let Some(rest_ip) = rest_ip {
-            node.rest = Some(Rest::start(rest_ip, rest_rps, None, ledger.clone(), Arc::new(node.clone())).await?);
+        if let Some(rest_ip) = config.rest_ip {
+            node.rest =
+                Some(Rest::start(rest_ip, config.rest_rps, None, ledger.clone(), Arc::new(node.clone())).await?);
         }
         // Initialize the routing.
         node.initialize_routing().await;
         // Initialize the sync module.
         node.initialize_sync();
+        // Initialize the block announcements.
+        node.initialize_block_announcements();
         // Initialize the notification message loop.
         node.handles.lock().push(crate::start_notification_message_loop());
         // Pass the node to the signal handler.
@@ -180,6 +173,62 @@ impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
                 node.sync.try_block_sync(&node).await;
             }
         }));
+
+        // Start the block advancement pipeline, which advances the ledger with the block responses in order.
+        let node = self.clone();
+        self.handles.lock().push(tokio::spawn(async move {
+            loop {
+                // If the Ctrl-C handler registered the signal, stop the node.
+                if node.shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+                    info!("Shutting down the block advancement pipeline");
+                    break;
+                }
+
+                // Wait for new block responses.
+                node.sync.wait_for_block_responses().await;
+                // Advance the ledger with the block responses.
+                let sync = node.sync.clone();
+                if let Err(error) = tokio::task::spawn_blocking(move || sync.advance_with_block_responses()).await {
+                    error!("Failed to advance with the block responses - {error}");
+                }
+            }
+        }));
+    }
+
+    /// Initializes the block announcements, which announce the header of every new block to the peers.
+    fn initialize_block_announcements(&self) {
+        let node = self.clone();
+        self.handles.lock().push(tokio::spawn(async move {
+            // Retrieve the latest block height.
+            let mut latest_height = node.ledger.latest_height();
+            loop {
+                // If the Ctrl-C handler registered the signal, stop the node.
+                if node.shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+                    info!("Shutting down block announcements");
+                    break;
+                }
+
+                // Sleep briefly to check for a new block.
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                // Check if the ledger advanced since the last announcement.
+                let height = node.ledger.latest_height();
+                if height <= latest_height {
+                    continue;
+                }
+                latest_height = height;
+                // Skip the announcement while the node is syncing.
+                if !node.sync.is_block_synced() {
+                    continue;
+                }
+                // Announce the latest block to the peers.
+                match crate::get_block_headers(&node.ledger, height..height + 1) {
+                    Ok(headers) => headers.into_iter().for_each(|block_header| {
+                        node.propagate(Message::BlockAnnouncement(BlockAnnouncement { block_header }), &[])
+                    }),
+                    Err(error) => warn!("Failed to announce block {height} - {error}"),
+                }
+            }
+        }));
     }
 
     /// Spawns a task with the given future; it should only be used for long-running tasks.
@@ -15,16 +15,20 @@
 use super::*;
 use snarkos_node_router::{
     messages::{
+        BlockHeader,
         BlockRequest,
         BlockResponse,
         DataBlocks,
         DisconnectReason,
+        HeaderRequest,
+        HeaderResponse,
         MessageCodec,
         Ping,
         Pong,
         PuzzleResponse,
         UnconfirmedTransaction,
     },
+    Offense,
     Routing,
 };
 use snarkos_node_tcp::{Connection, ConnectionSide, Tcp};
@@ -34,7 +38,7 @@ use snarkvm::{
 };
 
 use snarkos_node_sync::communication_service::CommunicationService;
-use std::{io, net::SocketAddr, time::Duration};
+use std::{io, net::SocketAddr};
 
 impl<N: Network, C: ConsensusStorage<N>> P2P for Client<N, C> {
     /// Returns a reference to the TCP instance.
@@ -50,9 +54,21 @@ impl<N: Network, C: ConsensusStorage<N>> Handshake for Client<N, C> {
         // Perform the handshake.
         let peer_addr = connection.addr();
         let conn_side = connection.side();
-        let stream = self.borrow_stream(&mut connection);
+        let local_addr = connection.local_addr();
         let genesis_header = *self.genesis.header();
-        self.router.handshake(peer_addr, stream, conn_side, genesis_header).await?;
+        match self.router.tls() {
+            // If TLS is enabled, perform the handshake over the TLS stream.
+            Some(tls) => {
+                let stream = self.take_stream(&mut connection);
+                let mut stream = tls.wrap(peer_addr, stream, conn_side).await?;
+                self.router.handshake(peer_addr, &mut stream, conn_side, local_addr, genesis_header).await?;
+                self.return_stream(&mut connection, stream);
+            }
+            None => {
+                let stream = self.borrow_stream(&mut connection);
+                self.router.handshake(peer_addr, stream, conn_side, local_addr, genesis_header).await?;
+            }
+        }
 
         Ok(connection)
     }
@@ -97,8 +113,13 @@ impl<N: Network, C: ConsensusStorage<N>> Writing for Client<N, C> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(addr)
+    }
+
+    /// Returns `true` if the given message may be dropped for a peer that fails to keep up.
+    fn is_droppable(&self, message: &Self::Message) -> bool {
+        message.is_relay()
     }
 }
 
@@ -109,17 +130,30 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Client<N, C> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
     async fn process_message(&self, peer_addr: SocketAddr, message: Self::Message) -> io::Result<()> {
-        // Process the message. Disconnect if the peer violated the protocol.
-        if let Err(error) = self.inbound(peer_addr, message).await {
+        #[cfg(feature = "metrics")]
+        let (timer, name) = (std::time::Instant::now(), message.kind());
+        // Process the message.
+        let result = self.inbound(peer_addr, message).await;
+        #[cfg(feature = "metrics")]
+        metrics::histogram_with_labels(
+            metrics::router::MESSAGE_PROCESSING_TIME,
+            &[("message", name.to_string())],
+            timer.elapsed().as_micros() as f64,
+        );
+        // Disconnect if the peer violated the protocol.
+        if let Err(error) = result {
             if let Some(peer_ip) = self.router().resolve_to_listener(&peer_addr) {
                 warn!("Disconnecting from '{peer_ip}' - {error}");
-                Outbound::send(self, peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
+                // Lower the reputation of this peer, and inform it of the offense.
+                let offense = Offense::from_error(&error);
+                self.router().record_offense(peer_ip, offense);
+                Outbound::send(self, peer_ip, Message::Disconnect(offense.disconnect_reason().into()));
                 // Disconnect from this peer.
                 self.router().disconnect(peer_ip);
             }
@@ -139,6 +173,17 @@ impl<N: Network, C: ConsensusStorage<N>> CommunicationService for Client<N, C> {
         Message::BlockRequest(BlockRequest { start_height, end_height })
     }
 
+    /// Prepares a header request to be sent.
+    fn prepare_header_request(start_height: u32, end_height: u32) -> Option<Self::Message> {
+        debug_assert!(start_height < end_height, "Invalid header request format");
+        Some(Message::HeaderRequest(HeaderRequest { start_height, end_height }))
+    }
+
+    /// Returns `true` if the given peer supports the headers-first block sync.
+    fn supports_header_sync(&self, peer_ip: &SocketAddr) -> bool {
+        self.router.supports_headers_first(peer_ip)
+    }
+
     /// Sends the given message to specified peer.
     ///
     /// This function returns as soon as the message is queued to be sent,
@@ -167,6 +212,17 @@ impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Client<N, C> {
 
 #[async_trait]
 impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
+    /// Requests the announced block from the peer, if it extends the ledger.
+    fn block_announcement(&self, peer_ip: SocketAddr, block_header: BlockHeader<N>) -> bool {
+        let (height, hash, previous_hash) = (block_header.height(), block_header.hash(), block_header.previous_hash);
+        // If the announced block extends the ledger, send the `BlockRequest` message to the peer.
+        if self.sync.process_block_announcement(peer_ip, (height, hash, previous_hash)) {
+            let request = BlockRequest { start_height: height, end_height: height + 1 };
+            Outbound::send(self, peer_ip, Message::BlockRequest(request));
+        }
+        true
+    }
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, peer_ip: SocketAddr, message: BlockRequest) -> bool {
         let BlockRequest { start_height, end_height } = &message;
@@ -186,8 +242,39 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
 
     /// Handles a `BlockResponse` message.
     fn block_response(&self, peer_ip: SocketAddr, blocks: Vec<Block<N>>) -> bool {
-        // Tries to advance with blocks from the sync module.
-        match self.sync.advance_with_sync_blocks(peer_ip, blocks) {
+        // Inserts the blocks into the sync pool, to be advanced with by the block advancement pipeline.
+        match self.sync.process_block_response(peer_ip, blocks) {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("{error}");
+                false
+            }
+        }
+    }
+
+    /// Retrieves the block headers within the header request range, and returns the header response to the peer.
+    fn header_request(&self, peer_ip: SocketAddr, message: HeaderRequest) -> bool {
+        let HeaderRequest { start_height, end_height } = &message;
+
+        // Retrieve the block headers within the requested range.
+        let headers = match crate::get_block_headers(&self.ledger, *start_height..*end_height) {
+            Ok(headers) => headers,
+            Err(error) => {
+                error!("Failed to retrieve block headers {start_height} to {end_height} from the ledger - {error}");
+                return false;
+            }
+        };
+        // Send the `HeaderResponse` message to the peer.
+        Outbound::send(self, peer_ip, Message::HeaderResponse(HeaderResponse { request: message, headers }));
+        true
+    }
+
+    /// Handles a `HeaderResponse` message.
+    fn header_response(&self, peer_ip: SocketAddr, headers: Vec<BlockHeader<N>>) -> bool {
+        // Insert the block headers into the header chain of the sync module.
+        let headers =
+            headers.into_iter().map(|header| (header.height(), header.hash(), header.previous_hash)).collect();
+        match self.sync.insert_block_headers(peer_ip, headers) {
             Ok(()) => true,
             Err(error) => {
                 warn!("{error}");
@@ -208,10 +295,14 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
                     return false;
                 }
             }
+            // If metadata was provided, then update the lowest block height served by the peer in the sync pool.
+            if let Some(metadata) = message.metadata {
+                self.sync.update_peer_lowest_block_height(peer_ip, metadata.lowest_block_height);
+            }
         }
 
         // Send a `Pong` message to the peer.
-        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork: Some(false) }));
+        self.send_pong(peer_ip);
         true
     }
 
@@ -221,7 +312,7 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
         let self_ = self.clone();
         tokio::spawn(async move {
             // Sleep for the preset time before sending a `Ping` request.
-            tokio::time::sleep(Duration::from_secs(Self::PING_SLEEP_IN_SECS)).await;
+            tokio::time::sleep(self_.router().liveness_policy().ping_interval()).await;
             // Check that the peer is still connected.
             if self_.router().is_connected(&peer_ip) {
                 // Retrieve the block locators.
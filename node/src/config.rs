@@ -0,0 +1,67 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_router::RouterConfig;
+use snarkvm::prelude::Network;
+
+use aleo_std::StorageMode;
+use std::net::SocketAddr;
+
+/// The node's configuration. See the source of [`NodeConfig::default`] for the defaults.
+#[derive(Clone)]
+pub struct NodeConfig {
+    /// The configuration of the router of the node.
+    ///
+    /// note: The maximum number of peers, and the paths of the files persisted by the router, are set by the node.
+    pub router: RouterConfig,
+    /// The listening address of the BFT of a validator. If set to `None`, the default address is used.
+    pub bft_ip: Option<SocketAddr>,
+    /// The address of the REST server of a client or a validator. If set to `None`, the REST server is disabled.
+    pub rest_ip: Option<SocketAddr>,
+    /// The maximum number of requests per second per IP to the REST server.
+    pub rest_rps: u32,
+    /// The validators a validator always stays connected to.
+    pub trusted_validators: Vec<SocketAddr>,
+    /// The base URL of the CDN the ledger of a client or a validator is synced from. If set to `None`, it is not.
+    pub cdn: Option<String>,
+}
+
+impl NodeConfig {
+    /// Returns the configuration of the router of a node with the given storage mode and maximum number of peers.
+    /// The router persists its files in the ledger directory.
+    pub(crate) fn router_config<N: Network>(&self, storage_mode: &StorageMode, max_peers: usize) -> RouterConfig {
+        RouterConfig {
+            max_peers: max_peers as u16,
+            ban_list_path: Some(crate::ban_list_path(N::ID, storage_mode)),
+            identity_path: Some(crate::node_identity_path(N::ID, storage_mode)),
+            peer_book_path: Some(crate::peer_book_path(N::ID, storage_mode)),
+            is_dev: matches!(storage_mode, StorageMode::Development(_)),
+            ..self.router.clone()
+        }
+    }
+}
+
+impl Default for NodeConfig {
+    /// Initializes a new node configuration with the default values.
+    fn default() -> Self {
+        Self {
+            router: RouterConfig::default(),
+            bft_ip: None,
+            rest_ip: None,
+            rest_rps: 10,
+            trusted_validators: vec![],
+            cdn: None,
+        }
+    }
+}
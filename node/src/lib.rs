@@ -33,9 +33,15 @@ pub use snarkvm;
 mod client;
 pub use client::*;
 
+mod config;
+pub use config::*;
+
 mod prover;
 pub use prover::*;
 
+mod relay;
+pub use relay::*;
+
 mod validator;
 pub use validator::*;
 
@@ -46,6 +52,22 @@ mod traits;
 pub use traits::*;
 
 use aleo_std::StorageMode;
+use std::{ops::Range, path::PathBuf};
+
+/// Returns the path to the persistent list of banned peers, which is stored in the ledger directory.
+pub fn ban_list_path(network: u16, storage_mode: &StorageMode) -> PathBuf {
+    aleo_std::aleo_ledger_dir(network, storage_mode.clone()).join("bans.json")
+}
+
+/// Returns the path to the persistent identity of the node, which is stored in the ledger directory.
+pub fn node_identity_path(network: u16, storage_mode: &StorageMode) -> PathBuf {
+    aleo_std::aleo_ledger_dir(network, storage_mode.clone()).join("identity.key")
+}
+
+/// Returns the path to the persistent book of known good peers, which is stored in the ledger directory.
+pub fn peer_book_path(network: u16, storage_mode: &StorageMode) -> PathBuf {
+    aleo_std::aleo_ledger_dir(network, storage_mode.clone()).join("peers.json")
+}
 
 /// A helper to log instructions to recover.
 pub fn log_clean_error(storage_mode: &StorageMode) {
@@ -65,8 +87,28 @@ use snarkvm::{
     prelude::{const_assert, hrp2, AleoID, Field, Ledger, Network},
 };
 
+use snarkos_node_router::messages::BlockHeader;
+
 use anyhow::{bail, Result};
 
+/// Returns the block headers within the given range of heights, up to the latest block in the ledger.
+pub fn get_block_headers<N: Network, C: ConsensusStorage<N>>(
+    ledger: &Ledger<N, C>,
+    heights: Range<u32>,
+) -> Result<Vec<BlockHeader<N>>> {
+    let end_height = heights.end.min(ledger.latest_height().saturating_add(1));
+    (heights.start..end_height)
+        .map(|height| {
+            // Note: The genesis block has no previous block, so its previous block hash is the default hash.
+            let previous_hash = match height {
+                0 => N::BlockHash::default(),
+                _ => ledger.get_hash(height - 1)?,
+            };
+            BlockHeader::new(previous_hash, ledger.get_header(height)?)
+        })
+        .collect()
+}
+
 // TODO: Remove me after Phase 3.
 pub fn phase_3_reset<N: Network, C: ConsensusStorage<N>>(
     ledger: Ledger<N, C>,
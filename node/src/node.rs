@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{traits::NodeInterface, Client, Prover, Validator};
+use crate::{traits::NodeInterface, Client, NodeConfig, Prover, Relay, Validator};
 use snarkos_account::Account;
 use snarkos_node_router::messages::NodeType;
 use snarkvm::prelude::{
@@ -26,7 +26,7 @@ use snarkvm::prelude::{
 
 use aleo_std::StorageMode;
 use anyhow::Result;
-use std::{net::SocketAddr, sync::Arc};
+use std::sync::Arc;
 
 pub enum Node<N: Network> {
     /// A validator is a full node, capable of validating blocks.
@@ -35,64 +35,49 @@ pub enum Node<N: Network> {
     Prover(Arc<Prover<N, ConsensusMemory<N>>>),
     /// A client node is a full node, capable of querying with the network.
     Client(Arc<Client<N, ConsensusDB<N>>>),
+    /// A relay is an ultralight node, capable of relaying the gossip of the network without a ledger.
+    Relay(Arc<Relay<N, ConsensusMemory<N>>>),
 }
 
 impl<N: Network> Node<N> {
-    /// Initializes a new validator node.
+    /// Initializes a new validator node with the given configuration.
     pub async fn new_validator(
-        node_ip: SocketAddr,
-        bft_ip: Option<SocketAddr>,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
         account: Account<N>,
-        trusted_peers: &[SocketAddr],
-        trusted_validators: &[SocketAddr],
         genesis: Block<N>,
-        cdn: Option<String>,
         storage_mode: StorageMode,
+        config: NodeConfig,
     ) -> Result<Self> {
-        Ok(Self::Validator(Arc::new(
-            Validator::new(
-                node_ip,
-                bft_ip,
-                rest_ip,
-                rest_rps,
-                account,
-                trusted_peers,
-                trusted_validators,
-                genesis,
-                cdn,
-                storage_mode,
-            )
-            .await?,
-        )))
+        Ok(Self::Validator(Arc::new(Validator::new(account, genesis, storage_mode, config).await?)))
     }
 
-    /// Initializes a new prover node.
+    /// Initializes a new prover node with the given configuration.
     pub async fn new_prover(
-        node_ip: SocketAddr,
         account: Account<N>,
-        trusted_peers: &[SocketAddr],
         genesis: Block<N>,
         storage_mode: StorageMode,
+        config: NodeConfig,
     ) -> Result<Self> {
-        Ok(Self::Prover(Arc::new(Prover::new(node_ip, account, trusted_peers, genesis, storage_mode).await?)))
+        Ok(Self::Prover(Arc::new(Prover::new(account, genesis, storage_mode, config).await?)))
     }
 
-    /// Initializes a new client node.
+    /// Initializes a new client node with the given configuration.
     pub async fn new_client(
-        node_ip: SocketAddr,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
         account: Account<N>,
-        trusted_peers: &[SocketAddr],
         genesis: Block<N>,
-        cdn: Option<String>,
         storage_mode: StorageMode,
+        config: NodeConfig,
     ) -> Result<Self> {
-        Ok(Self::Client(Arc::new(
-            Client::new(node_ip, rest_ip, rest_rps, account, trusted_peers, genesis, cdn, storage_mode).await?,
-        )))
+        Ok(Self::Client(Arc::new(Client::new(account, genesis, storage_mode, config).await?)))
+    }
+
+    /// Initializes a new relay node with the given configuration.
+    pub async fn new_relay(
+        account: Account<N>,
+        genesis: Block<N>,
+        storage_mode: StorageMode,
+        config: NodeConfig,
+    ) -> Result<Self> {
+        Ok(Self::Relay(Arc::new(Relay::new(account, genesis, storage_mode, config).await?)))
     }
 
     /// Returns the node type.
@@ -101,6 +86,7 @@ impl<N: Network> Node<N> {
             Self::Validator(validator) => validator.node_type(),
             Self::Prover(prover) => prover.node_type(),
             Self::Client(client) => client.node_type(),
+            Self::Relay(relay) => relay.node_type(),
         }
     }
 
@@ -110,6 +96,7 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.private_key(),
             Self::Prover(node) => node.private_key(),
             Self::Client(node) => node.private_key(),
+            Self::Relay(node) => node.private_key(),
         }
     }
 
@@ -119,6 +106,7 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.view_key(),
             Self::Prover(node) => node.view_key(),
             Self::Client(node) => node.view_key(),
+            Self::Relay(node) => node.view_key(),
         }
     }
 
@@ -128,6 +116,7 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.address(),
             Self::Prover(node) => node.address(),
             Self::Client(node) => node.address(),
+            Self::Relay(node) => node.address(),
         }
     }
 
@@ -137,6 +126,7 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.is_dev(),
             Self::Prover(node) => node.is_dev(),
             Self::Client(node) => node.is_dev(),
+            Self::Relay(node) => node.is_dev(),
         }
     }
 }
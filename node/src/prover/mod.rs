@@ -14,7 +14,7 @@
 
 mod router;
 
-use crate::traits::NodeInterface;
+use crate::{traits::NodeInterface, NodeConfig};
 use snarkos_account::Account;
 use snarkos_node_bft::ledger_service::ProverLedgerService;
 use snarkos_node_router::{
@@ -23,6 +23,7 @@ use snarkos_node_router::{
     Inbound,
     Outbound,
     Router,
+    RouterConfig,
     Routing,
 };
 use snarkos_node_sync::{BlockSync, BlockSyncMode};
@@ -46,12 +47,9 @@ use colored::Colorize;
 use core::{marker::PhantomData, time::Duration};
 use parking_lot::{Mutex, RwLock};
 use rand::{rngs::OsRng, CryptoRng, Rng};
-use std::{
-    net::SocketAddr,
-    sync::{
-        atomic::{AtomicBool, AtomicU8, Ordering},
-        Arc,
-    },
+use std::sync::{
+    atomic::{AtomicBool, AtomicU8, Ordering},
+    Arc,
 };
 use tokio::task::JoinHandle;
 
@@ -85,11 +83,10 @@ pub struct Prover<N: Network, C: ConsensusStorage<N>> {
 impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
     /// Initializes a new prover node.
     pub async fn new(
-        node_ip: SocketAddr,
         account: Account<N>,
-        trusted_peers: &[SocketAddr],
         genesis: Block<N>,
         storage_mode: StorageMode,
+        config: NodeConfig,
     ) -> Result<Self> {
         // Prepare the shutdown flag.
         let shutdown: Arc<AtomicBool> = Default::default();
@@ -103,14 +100,10 @@ impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
         let sync = BlockSync::new(BlockSyncMode::Router, ledger_service.clone());
 
         // Initialize the node router.
-        let router = Router::new(
-            node_ip,
-            NodeType::Prover,
-            account,
-            trusted_peers,
-            Self::MAXIMUM_NUMBER_OF_PEERS as u16,
-            matches!(storage_mode, StorageMode::Development(_)),
-        )
+        let router = Router::new(NodeType::Prover, account, RouterConfig {
+            validator_listener: None,
+            ..config.router_config::<N>(&storage_mode, Self::MAXIMUM_NUMBER_OF_PEERS)
+        })
         .await?;
         // Load the coinbase puzzle.
         let coinbase_puzzle = CoinbasePuzzle::<N>::load()?;
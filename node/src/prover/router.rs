@@ -14,15 +14,20 @@
 
 use super::*;
 
-use snarkos_node_router::messages::{
-    BlockRequest,
-    DisconnectReason,
-    Message,
-    MessageCodec,
-    Ping,
-    Pong,
-    PuzzleRequest,
-    UnconfirmedTransaction,
+use snarkos_node_router::{
+    messages::{
+        BlockHeader,
+        BlockRequest,
+        DisconnectReason,
+        HeaderRequest,
+        Message,
+        MessageCodec,
+        Ping,
+        Pong,
+        PuzzleRequest,
+        UnconfirmedTransaction,
+    },
+    Offense,
 };
 use snarkos_node_tcp::{Connection, ConnectionSide, Tcp};
 use snarkvm::prelude::{block::Transaction, Network};
@@ -43,9 +48,21 @@ impl<N: Network, C: ConsensusStorage<N>> Handshake for Prover<N, C> {
         // Perform the handshake.
         let peer_addr = connection.addr();
         let conn_side = connection.side();
-        let stream = self.borrow_stream(&mut connection);
+        let local_addr = connection.local_addr();
         let genesis_header = *self.genesis.header();
-        self.router.handshake(peer_addr, stream, conn_side, genesis_header).await?;
+        match self.router.tls() {
+            // If TLS is enabled, perform the handshake over the TLS stream.
+            Some(tls) => {
+                let stream = self.take_stream(&mut connection);
+                let mut stream = tls.wrap(peer_addr, stream, conn_side).await?;
+                self.router.handshake(peer_addr, &mut stream, conn_side, local_addr, genesis_header).await?;
+                self.return_stream(&mut connection, stream);
+            }
+            None => {
+                let stream = self.borrow_stream(&mut connection);
+                self.router.handshake(peer_addr, stream, conn_side, local_addr, genesis_header).await?;
+            }
+        }
 
         Ok(connection)
     }
@@ -82,8 +99,13 @@ impl<N: Network, C: ConsensusStorage<N>> Writing for Prover<N, C> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(addr)
+    }
+
+    /// Returns `true` if the given message may be dropped for a peer that fails to keep up.
+    fn is_droppable(&self, message: &Self::Message) -> bool {
+        message.is_relay()
     }
 }
 
@@ -94,17 +116,30 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Prover<N, C> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
     async fn process_message(&self, peer_addr: SocketAddr, message: Self::Message) -> io::Result<()> {
-        // Process the message. Disconnect if the peer violated the protocol.
-        if let Err(error) = self.inbound(peer_addr, message).await {
+        #[cfg(feature = "metrics")]
+        let (timer, name) = (std::time::Instant::now(), message.kind());
+        // Process the message.
+        let result = self.inbound(peer_addr, message).await;
+        #[cfg(feature = "metrics")]
+        metrics::histogram_with_labels(
+            metrics::router::MESSAGE_PROCESSING_TIME,
+            &[("message", name.to_string())],
+            timer.elapsed().as_micros() as f64,
+        );
+        // Disconnect if the peer violated the protocol.
+        if let Err(error) = result {
             if let Some(peer_ip) = self.router().resolve_to_listener(&peer_addr) {
                 warn!("Disconnecting from '{peer_addr}' - {error}");
-                Outbound::send(self, peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
+                // Lower the reputation of this peer, and inform it of the offense.
+                let offense = Offense::from_error(&error);
+                self.router().record_offense(peer_ip, offense);
+                Outbound::send(self, peer_ip, Message::Disconnect(offense.disconnect_reason().into()));
                 // Disconnect from this peer.
                 self.router().disconnect(peer_ip);
             }
@@ -139,6 +174,12 @@ impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Prover<N, C> {
 
 #[async_trait]
 impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
+    /// Handles a `BlockAnnouncement` message.
+    fn block_announcement(&self, peer_ip: SocketAddr, _block_header: BlockHeader<N>) -> bool {
+        debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
+        false
+    }
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, peer_ip: SocketAddr, _message: BlockRequest) -> bool {
         debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
@@ -151,6 +192,18 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
         false
     }
 
+    /// Handles a `HeaderRequest` message.
+    fn header_request(&self, peer_ip: SocketAddr, _message: HeaderRequest) -> bool {
+        debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
+        false
+    }
+
+    /// Handles a `HeaderResponse` message.
+    fn header_response(&self, peer_ip: SocketAddr, _headers: Vec<BlockHeader<N>>) -> bool {
+        debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
+        false
+    }
+
     /// Processes the block locators and sends back a `Pong` message.
     fn ping(&self, peer_ip: SocketAddr, message: Ping<N>) -> bool {
         // Check if the sync module is in router mode.
@@ -163,10 +216,14 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
                     return false;
                 }
             }
+            // If metadata was provided, then update the lowest block height served by the peer in the sync pool.
+            if let Some(metadata) = message.metadata {
+                self.sync.update_peer_lowest_block_height(peer_ip, metadata.lowest_block_height);
+            }
         }
 
         // Send a `Pong` message to the peer.
-        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork: Some(false) }));
+        self.send_pong(peer_ip);
         true
     }
 
@@ -176,7 +233,7 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
         let self_clone = self.clone();
         tokio::spawn(async move {
             // Sleep for the preset time before sending a `Ping` request.
-            tokio::time::sleep(Duration::from_secs(Self::PING_SLEEP_IN_SECS)).await;
+            tokio::time::sleep(self_clone.router().liveness_policy().ping_interval()).await;
             // Check that the peer is still connected.
             if self_clone.router().is_connected(&peer_ip) {
                 // Send a `Ping` message to the peer.
@@ -0,0 +1,358 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod router;
+
+use crate::{traits::NodeInterface, NodeConfig};
+use snarkos_account::Account;
+use snarkos_node_bft::ledger_service::ProverLedgerService;
+use snarkos_node_router::{
+    messages::{BlockHeader, HeaderRequest, Message, NodeType},
+    Heartbeat,
+    Inbound,
+    Outbound,
+    Router,
+    RouterConfig,
+    Routing,
+};
+use snarkos_node_sync::{BlockSync, BlockSyncMode};
+use snarkos_node_tcp::{
+    protocols::{Disconnect, Handshake, OnConnect, Reading, Writing},
+    subnet,
+    P2P,
+};
+use snarkvm::prelude::{
+    block::{Block, Header, Transaction},
+    coinbase::{CoinbasePuzzle, EpochChallenge},
+    store::ConsensusStorage,
+    Network,
+    Process,
+};
+
+use aleo_std::StorageMode;
+use anyhow::{bail, ensure, Result};
+use core::{cmp::Reverse, marker::PhantomData, time::Duration};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tokio::task::JoinHandle;
+
+/// A relay is an ultralight node, capable of validating and relaying the gossip of the network
+/// (i.e. the block announcements, the unconfirmed solutions, and the unconfirmed transactions).
+/// It keeps no ledger beyond the recent block headers, so that it improves the connectivity of the network
+/// without the storage costs of a client. It presents itself to its peers as a client.
+///
+/// The recent block headers are linked to the genesis block: the node syncs the block headers from its peers,
+/// and only accepts a block header which extends the latest one. The block headers carry no proof of their authority,
+/// so a block header is only accepted once it is vouched for by a trusted peer, or by the peers of several distinct
+/// subnets; this way, a single peer cannot feed the node a forged chain of block headers. The epoch challenge and
+/// the proof target, against which the unconfirmed solutions are verified, are derived from these block headers.
+#[derive(Clone)]
+pub struct Relay<N: Network, C: ConsensusStorage<N>> {
+    /// The router of the node.
+    router: Router<N>,
+    /// The sync module, which only tracks the block locators of the peers.
+    sync: Arc<BlockSync<N>>,
+    /// The genesis block.
+    genesis: Block<N>,
+    /// The coinbase puzzle.
+    coinbase_puzzle: CoinbasePuzzle<N>,
+    /// The process, which verifies the proofs of the unconfirmed transactions.
+    process: Arc<Process<N>>,
+    /// The latest epoch challenge, derived from the recent block headers.
+    latest_epoch_challenge: Arc<RwLock<Arc<EpochChallenge<N>>>>,
+    /// The recent block headers, by height, which are linked to the genesis block.
+    recent_headers: Arc<RwLock<BTreeMap<u32, BlockHeader<N>>>>,
+    /// The block headers ahead of the recent block headers, by height and hash, along with the peers vouching for them.
+    pending_headers: Arc<RwLock<PendingHeaders<N>>>,
+    /// The timestamp of the pending round of header requests, and the peers yet to respond, if any.
+    header_request: Arc<Mutex<Option<(Instant, HashSet<SocketAddr>)>>>,
+    /// The spawned handles.
+    handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    /// The shutdown signal.
+    shutdown: Arc<AtomicBool>,
+    /// PhantomData.
+    _phantom: PhantomData<C>,
+}
+
+/// The block headers ahead of the recent block headers, by height and hash, along with the peers vouching for them.
+type PendingHeaders<N> = BTreeMap<u32, HashMap<<N as Network>::BlockHash, (BlockHeader<N>, HashSet<SocketAddr>)>>;
+
+impl<N: Network, C: ConsensusStorage<N>> Relay<N, C> {
+    /// The maximum number of recent block headers kept by the node.
+    pub const MAXIMUM_RECENT_HEADERS: u32 = 100;
+    /// The minimum number of distinct subnets of the peers that must vouch for a block header, before it is accepted,
+    /// unless a trusted peer vouches for it.
+    pub const MINIMUM_HEADER_CONFIRMATIONS: usize = 3;
+    /// The duration in seconds after which a pending header request is considered lost.
+    const HEADER_REQUEST_TIMEOUT_IN_SECS: u64 = 15;
+
+    /// Initializes a new relay node.
+    pub async fn new(
+        account: Account<N>,
+        genesis: Block<N>,
+        storage_mode: StorageMode,
+        config: NodeConfig,
+    ) -> Result<Self> {
+        // Prepare the shutdown flag.
+        let shutdown: Arc<AtomicBool> = Default::default();
+
+        // Initialize the signal handler.
+        let signal_node = Self::handle_signals(shutdown.clone());
+
+        // Initialize the ledger service.
+        let ledger_service = Arc::new(ProverLedgerService::new());
+        // Initialize the sync module.
+        let sync = BlockSync::new(BlockSyncMode::Router, ledger_service.clone());
+
+        // Initialize the node router.
+        let router = Router::new(NodeType::Client, account, RouterConfig {
+            validator_listener: None,
+            ..config.router_config::<N>(&storage_mode, Self::MAXIMUM_NUMBER_OF_PEERS)
+        })
+        .await?;
+        // Load the coinbase puzzle.
+        let coinbase_puzzle = CoinbasePuzzle::<N>::load()?;
+        // Load the process.
+        let process = Process::<N>::load()?;
+        // Initialize the recent block headers with the genesis block, from which the first epoch challenge is derived.
+        let genesis_header = BlockHeader::from_block(&genesis);
+        let epoch_challenge = Self::epoch_challenge(&genesis_header)?.expect("The genesis block starts an epoch");
+        // Initialize the node.
+        let node = Self {
+            router,
+            sync: Arc::new(sync),
+            genesis,
+            coinbase_puzzle,
+            process: Arc::new(process),
+            latest_epoch_challenge: Arc::new(RwLock::new(Arc::new(epoch_challenge))),
+            recent_headers: Arc::new(RwLock::new(BTreeMap::from([(0, genesis_header)]))),
+            pending_headers: Default::default(),
+            header_request: Default::default(),
+            handles: Default::default(),
+            shutdown,
+            _phantom: Default::default(),
+        };
+        // Initialize the routing.
+        node.initialize_routing().await;
+        // Initialize the notification message loop.
+        node.handles.lock().push(crate::start_notification_message_loop());
+        // Pass the node to the signal handler.
+        let _ = signal_node.set(node.clone());
+        // Return the node.
+        Ok(node)
+    }
+
+    /// Returns the recent block headers, in ascending order of height.
+    pub fn recent_headers(&self) -> Vec<BlockHeader<N>> {
+        self.recent_headers.read().values().cloned().collect()
+    }
+
+    /// Returns the height of the latest block header, which is linked to the genesis block.
+    pub fn latest_height(&self) -> u32 {
+        self.recent_headers.read().last_key_value().map_or(0, |(height, _)| *height)
+    }
+
+    /// Returns the latest block header, which is linked to the genesis block.
+    fn latest_header(&self) -> Header<N> {
+        self.recent_headers.read().last_key_value().map_or(*self.genesis.header(), |(_, header)| header.header)
+    }
+
+    /// Returns the epoch challenge of the epoch started by the given block, if the block starts an epoch.
+    /// Note: This is the epoch challenge as computed by the ledger, from the previous block hash of the epoch.
+    fn epoch_challenge(block_header: &BlockHeader<N>) -> Result<Option<EpochChallenge<N>>> {
+        let height = block_header.height();
+        if height % N::NUM_BLOCKS_PER_EPOCH != 0 {
+            return Ok(None);
+        }
+        let epoch_number = height / N::NUM_BLOCKS_PER_EPOCH;
+        EpochChallenge::new(epoch_number, block_header.previous_hash, N::COINBASE_PUZZLE_DEGREE).map(Some)
+    }
+
+    /// Returns `true` if the given peers, vouching for a block header, include a trusted peer,
+    /// or belong to at least `MINIMUM_HEADER_CONFIRMATIONS` distinct subnets.
+    fn is_confirmed(&self, peers: &HashSet<SocketAddr>) -> bool {
+        peers.iter().any(|peer_ip| self.router.is_trusted(peer_ip))
+            || peers.iter().map(|peer_ip| subnet(peer_ip.ip())).collect::<HashSet<_>>().len()
+                >= Self::MINIMUM_HEADER_CONFIRMATIONS
+    }
+
+    /// Records that the given peer vouches for the given block header, and returns the block headers accepted
+    /// as a result, in ascending order of height; these are new, so they are to be relayed.
+    ///
+    /// A block header is accepted into the recent block headers once it is confirmed (see [`Self::is_confirmed`])
+    /// and extends the latest block header, so that the recent block headers remain linked to the genesis block;
+    /// until then, it is pending. Returns an error if the block header conflicts with the recent block headers,
+    /// as the accepted blocks are final, or if the peer already vouched for another block at the same height.
+    fn vouch_for_header(&self, peer_ip: SocketAddr, block_header: BlockHeader<N>) -> Result<Vec<BlockHeader<N>>> {
+        let height = block_header.height();
+        let latest_height = self.latest_height();
+
+        // Ignore the known blocks, and reject the conflicting ones.
+        if height <= latest_height {
+            if let Some(known_header) = self.recent_headers.read().get(&height) {
+                ensure!(known_header.hash() == block_header.hash(), "Block {height} conflicts with the recent blocks");
+            }
+            return Ok(vec![]);
+        }
+        // Ignore the blocks too far ahead of the latest block header, until the block headers in between are synced.
+        if height - latest_height > HeaderRequest::MAXIMUM_NUMBER_OF_HEADERS {
+            return Ok(vec![]);
+        }
+
+        let mut pending_headers = self.pending_headers.write();
+        // Ensure the peer does not vouch for conflicting blocks, and record its vouch.
+        let candidates = pending_headers.entry(height).or_default();
+        let hash = block_header.hash();
+        if candidates.iter().any(|(other_hash, (_, peers))| *other_hash != hash && peers.contains(&peer_ip)) {
+            bail!("Block {height} conflicts with another block vouched for by the same peer");
+        }
+        candidates.entry(hash).or_insert_with(|| (block_header, Default::default())).1.insert(peer_ip);
+
+        // Accept the confirmed block headers which extend the latest block header, one height at a time.
+        let mut headers = self.recent_headers.write();
+        let mut accepted = Vec::new();
+        while let Some((latest_height, latest_hash)) =
+            headers.last_key_value().map(|(height, header)| (*height, header.hash()))
+        {
+            let next_height = latest_height + 1;
+            let Some(block_header) = pending_headers.get(&next_height).and_then(|candidates| {
+                candidates
+                    .values()
+                    .find(|(header, peers)| header.previous_hash == latest_hash && self.is_confirmed(peers))
+                    .map(|(header, _)| header.clone())
+            }) else {
+                break;
+            };
+
+            // Update the latest epoch challenge, if the block starts a new epoch.
+            if let Some(epoch_challenge) = Self::epoch_challenge(&block_header)? {
+                *self.latest_epoch_challenge.write() = Arc::new(epoch_challenge);
+            }
+            headers.insert(next_height, block_header.clone());
+            // Discard the pending block headers which are no longer ahead of the latest block header.
+            *pending_headers = pending_headers.split_off(&(next_height + 1));
+            // Discard the block headers in excess, starting with the oldest ones.
+            while headers.len() > Self::MAXIMUM_RECENT_HEADERS as usize {
+                headers.pop_first();
+            }
+            accepted.push(block_header);
+        }
+        Ok(accepted)
+    }
+
+    /// Removes the vouches of the given peer from the pending block headers.
+    fn remove_vouches(&self, peer_ip: SocketAddr) {
+        let mut pending_headers = self.pending_headers.write();
+        for candidates in pending_headers.values_mut() {
+            candidates.values_mut().for_each(|(_, peers)| {
+                peers.remove(&peer_ip);
+            });
+            candidates.retain(|_, (_, peers)| !peers.is_empty());
+        }
+        pending_headers.retain(|_, candidates| !candidates.is_empty());
+    }
+
+    /// Requests the block headers following the latest block header from the sync peers, one for each subnet,
+    /// so that the block headers can be confirmed by the peers of distinct subnets. The node syncs the block headers
+    /// one round of header requests at a time, so this is a no-op if a round of header requests is pending.
+    fn sync_headers(&self) {
+        let Some((sync_peers, _)) = self.sync.find_sync_peers() else {
+            return;
+        };
+        // Select the sync peers ahead of the latest block header, one for each subnet,
+        // starting with the trusted peers, and then with the highest peers.
+        let start_height = self.latest_height() + 1;
+        let mut sync_peers = sync_peers.into_iter().filter(|(_, height)| *height >= start_height).collect::<Vec<_>>();
+        sync_peers.sort_unstable_by_key(|(peer_ip, height)| (!self.router.is_trusted(peer_ip), Reverse(*height)));
+        let mut subnets = HashSet::new();
+        sync_peers.retain(|(peer_ip, _)| subnets.insert(subnet(peer_ip.ip())));
+        sync_peers.truncate(Self::MINIMUM_HEADER_CONFIRMATIONS);
+        // Compute the end height of the header requests, so that every selected peer has the requested block headers.
+        let Some(peer_height) = sync_peers.iter().map(|(_, height)| *height).min() else {
+            return;
+        };
+        let end_height = (peer_height + 1).min(start_height + HeaderRequest::MAXIMUM_NUMBER_OF_HEADERS);
+
+        // Ensure there is no pending round of header requests, unless it timed out.
+        {
+            let mut header_request = self.header_request.lock();
+            let timeout = Duration::from_secs(Self::HEADER_REQUEST_TIMEOUT_IN_SECS);
+            if header_request.as_ref().is_some_and(|(timestamp, _)| timestamp.elapsed() < timeout) {
+                return;
+            }
+            *header_request = Some((Instant::now(), sync_peers.iter().map(|(peer_ip, _)| *peer_ip).collect()));
+        }
+        // Send the `HeaderRequest` message to the selected peers.
+        for (peer_ip, _) in sync_peers {
+            Outbound::send(self, peer_ip, Message::HeaderRequest(HeaderRequest { start_height, end_height }));
+        }
+    }
+
+    /// Performs the checks of the given transaction which do not depend on the ledger, i.e. the owner signature
+    /// of a deployment, the proof of the fee, and the proofs of an execution of the programs known to the process
+    /// (i.e. `credits.aleo`). The other programs are deployed in the ledger, so their executions are not verified.
+    fn check_transaction(process: &Process<N>, transaction: &Transaction<N>) -> Result<()> {
+        match transaction {
+            Transaction::Deploy(id, owner, deployment, fee) => {
+                // Ensure the owner signed the deployment.
+                let deployment_id = deployment.to_deployment_id()?;
+                ensure!(owner.verify(deployment_id), "Invalid owner signature for deployment transaction '{id}'");
+                // Verify the fee.
+                process.verify_fee(fee, deployment_id)
+            }
+            Transaction::Execute(_, execution, fee) => {
+                let execution_id = execution.to_execution_id()?;
+                // Verify the execution, if the process knows all of the programs it executes.
+                if execution.transitions().all(|transition| process.contains_program(transition.program_id())) {
+                    process.verify_execution(execution)?;
+                }
+                // Verify the fee, if there is one.
+                match fee {
+                    Some(fee) => process.verify_fee(fee, execution_id),
+                    None => Ok(()),
+                }
+            }
+            Transaction::Fee(id, _) => bail!("Fee transaction '{id}' cannot be relayed"),
+        }
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> NodeInterface<N> for Relay<N, C> {
+    /// Shuts down the node.
+    async fn shut_down(&self) {
+        info!("Shutting down...");
+
+        // Shut down the node.
+        trace!("Shutting down the node...");
+        self.shutdown.store(true, Ordering::Relaxed);
+
+        // Abort the tasks.
+        trace!("Shutting down the relay...");
+        self.handles.lock().iter().for_each(|handle| handle.abort());
+
+        // Shut down the router.
+        self.router.shut_down().await;
+
+        info!("Node has shut down.");
+    }
+}
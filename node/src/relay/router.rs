@@ -0,0 +1,373 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkos_node_router::{
+    messages::{
+        BlockAnnouncement,
+        BlockRequest,
+        DisconnectReason,
+        HeaderRequest,
+        HeaderResponse,
+        Message,
+        MessageCodec,
+        Ping,
+        Pong,
+        PuzzleResponse,
+        UnconfirmedSolution,
+        UnconfirmedTransaction,
+    },
+    Offense,
+};
+use snarkos_node_tcp::{Connection, ConnectionSide, Tcp};
+use snarkvm::{
+    ledger::narwhal::Data,
+    prelude::{block::Transaction, coinbase::ProverSolution, Network},
+};
+
+use std::{io, net::SocketAddr};
+
+impl<N: Network, C: ConsensusStorage<N>> P2P for Relay<N, C> {
+    /// Returns a reference to the TCP instance.
+    fn tcp(&self) -> &Tcp {
+        self.router.tcp()
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Handshake for Relay<N, C> {
+    /// Performs the handshake protocol.
+    async fn perform_handshake(&self, mut connection: Connection) -> io::Result<Connection> {
+        // Perform the handshake.
+        let peer_addr = connection.addr();
+        let conn_side = connection.side();
+        let local_addr = connection.local_addr();
+        let genesis_header = *self.genesis.header();
+        match self.router.tls() {
+            // If TLS is enabled, perform the handshake over the TLS stream.
+            Some(tls) => {
+                let stream = self.take_stream(&mut connection);
+                let mut stream = tls.wrap(peer_addr, stream, conn_side).await?;
+                self.router.handshake(peer_addr, &mut stream, conn_side, local_addr, genesis_header).await?;
+                self.return_stream(&mut connection, stream);
+            }
+            None => {
+                let stream = self.borrow_stream(&mut connection);
+                self.router.handshake(peer_addr, stream, conn_side, local_addr, genesis_header).await?;
+            }
+        }
+
+        Ok(connection)
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> OnConnect for Relay<N, C>
+where
+    Self: Outbound<N>,
+{
+    async fn on_connect(&self, peer_addr: SocketAddr) {
+        // Resolve the peer address to the listener address.
+        let Some(peer_ip) = self.router.resolve_to_listener(&peer_addr) else { return };
+        // Send the first `Ping` message to the peer.
+        self.send_ping(peer_ip, None);
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Disconnect for Relay<N, C> {
+    /// Any extra operations to be performed during a disconnect.
+    async fn handle_disconnect(&self, peer_addr: SocketAddr) {
+        if let Some(peer_ip) = self.router.resolve_to_listener(&peer_addr) {
+            self.sync.remove_peer(&peer_ip);
+            self.remove_vouches(peer_ip);
+            self.router.remove_connected_peer(peer_ip);
+        }
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Writing for Relay<N, C> {
+    type Codec = MessageCodec<N>;
+    type Message = Message<N>;
+
+    /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
+    /// The `side` parameter indicates the connection side **from the node's perspective**.
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(addr)
+    }
+
+    /// Returns `true` if the given message may be dropped for a peer that fails to keep up.
+    fn is_droppable(&self, message: &Self::Message) -> bool {
+        message.is_relay()
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Reading for Relay<N, C> {
+    type Codec = MessageCodec<N>;
+    type Message = Message<N>;
+
+    /// Creates a [`Decoder`] used to interpret messages from the network.
+    /// The `side` param indicates the connection side **from the node's perspective**.
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(peer_addr)
+    }
+
+    /// Processes a message received from the network.
+    async fn process_message(&self, peer_addr: SocketAddr, message: Self::Message) -> io::Result<()> {
+        #[cfg(feature = "metrics")]
+        let (timer, name) = (std::time::Instant::now(), message.kind());
+        // Process the message.
+        let result = self.inbound(peer_addr, message).await;
+        #[cfg(feature = "metrics")]
+        metrics::histogram_with_labels(
+            metrics::router::MESSAGE_PROCESSING_TIME,
+            &[("message", name.to_string())],
+            timer.elapsed().as_micros() as f64,
+        );
+        // Disconnect if the peer violated the protocol.
+        if let Err(error) = result {
+            if let Some(peer_ip) = self.router().resolve_to_listener(&peer_addr) {
+                warn!("Disconnecting from '{peer_addr}' - {error}");
+                // Lower the reputation of this peer, and inform it of the offense.
+                let offense = Offense::from_error(&error);
+                self.router().record_offense(peer_ip, offense);
+                Outbound::send(self, peer_ip, Message::Disconnect(offense.disconnect_reason().into()));
+                // Disconnect from this peer.
+                self.router().disconnect(peer_ip);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Routing<N> for Relay<N, C> {}
+
+impl<N: Network, C: ConsensusStorage<N>> Heartbeat<N> for Relay<N, C> {
+    /// This function syncs the block headers from the peers, from which the coinbase puzzle is updated.
+    fn handle_puzzle_request(&self) {
+        self.sync_headers();
+    }
+}
+
+impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Relay<N, C> {
+    /// Returns a reference to the router.
+    fn router(&self) -> &Router<N> {
+        &self.router
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Relay<N, C> {
+    /// Relays the announced block to the peers, once it is confirmed and extends the latest block header.
+    fn block_announcement(&self, peer_ip: SocketAddr, block_header: BlockHeader<N>) -> bool {
+        let hash = block_header.hash();
+        match self.vouch_for_header(peer_ip, block_header) {
+            // If the announced block is accepted, propagate the `BlockAnnouncement`.
+            Ok(accepted) => {
+                match accepted.into_iter().find(|block_header| block_header.hash() == hash) {
+                    Some(block_header) => {
+                        let message = Message::BlockAnnouncement(BlockAnnouncement { block_header });
+                        self.propagate(message, &[peer_ip]);
+                    }
+                    // If the block is ahead of the latest block header, sync the missing block headers.
+                    None => self.sync_headers(),
+                }
+                true
+            }
+            Err(error) => {
+                warn!("Peer '{peer_ip}' announced an invalid block - {error}");
+                false
+            }
+        }
+    }
+
+    /// Handles a `BlockRequest` message.
+    fn block_request(&self, peer_ip: SocketAddr, _message: BlockRequest) -> bool {
+        debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
+        false
+    }
+
+    /// Handles a `BlockResponse` message.
+    fn block_response(&self, peer_ip: SocketAddr, _blocks: Vec<Block<N>>) -> bool {
+        debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
+        false
+    }
+
+    /// Returns the recent block headers within the header request range, if the node has all of them.
+    fn header_request(&self, peer_ip: SocketAddr, message: HeaderRequest) -> bool {
+        let HeaderRequest { start_height, end_height } = &message;
+
+        // Retrieve the recent block headers within the requested range.
+        let headers = self
+            .recent_headers
+            .read()
+            .range(*start_height..*end_height)
+            .map(|(_, header)| header.clone())
+            .collect::<Vec<_>>();
+        // Send the `HeaderResponse` message to the peer, if the node has all of the requested block headers.
+        match headers.len() == (end_height - start_height) as usize {
+            true => {
+                Outbound::send(self, peer_ip, Message::HeaderResponse(HeaderResponse { request: message, headers }));
+            }
+            false => debug!("Ignoring a header request from '{peer_ip}' beyond the recent block headers"),
+        }
+        true
+    }
+
+    /// Records the vouches of the peer for the requested block headers, and requests the next block headers
+    /// once all of the peers of the round of header requests responded.
+    fn header_response(&self, peer_ip: SocketAddr, headers: Vec<BlockHeader<N>>) -> bool {
+        // Complete the header request of the peer, and the round of header requests if it was the last one.
+        let is_round_complete = {
+            let mut header_request = self.header_request.lock();
+            if let Some((_, peers)) = header_request.as_mut() {
+                peers.remove(&peer_ip);
+                if peers.is_empty() {
+                    *header_request = None;
+                }
+            }
+            header_request.is_none()
+        };
+        // Vouch for the block headers, in ascending order of height.
+        for block_header in headers {
+            if let Err(error) = self.vouch_for_header(peer_ip, block_header) {
+                warn!("Peer '{peer_ip}' sent an invalid block header - {error}");
+                return false;
+            }
+        }
+        // Request the next block headers, if the sync peers are ahead.
+        if is_round_complete {
+            self.sync_headers();
+        }
+        true
+    }
+
+    /// Processes the block locators and sends back a `Pong` message.
+    fn ping(&self, peer_ip: SocketAddr, message: Ping<N>) -> bool {
+        // Check if the sync module is in router mode.
+        if self.sync.mode().is_router() {
+            // If block locators were provided, then update the peer in the sync pool.
+            if let Some(block_locators) = message.block_locators {
+                // Check the block locators are valid, and update the peer in the sync pool.
+                if let Err(error) = self.sync.update_peer_locators(peer_ip, block_locators) {
+                    warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
+                    return false;
+                }
+            }
+            // If metadata was provided, then update the lowest block height served by the peer in the sync pool.
+            if let Some(metadata) = message.metadata {
+                self.sync.update_peer_lowest_block_height(peer_ip, metadata.lowest_block_height);
+            }
+        }
+
+        // Send a `Pong` message to the peer.
+        self.send_pong(peer_ip);
+        true
+    }
+
+    /// Sleeps for a period and then sends a `Ping` message to the peer.
+    fn pong(&self, peer_ip: SocketAddr, _message: Pong) -> bool {
+        // Spawn an asynchronous task for the `Ping` request.
+        let self_ = self.clone();
+        tokio::spawn(async move {
+            // Sleep for the preset time before sending a `Ping` request.
+            tokio::time::sleep(self_.router().liveness_policy().ping_interval()).await;
+            // Check that the peer is still connected.
+            if self_.router().is_connected(&peer_ip) {
+                // Send a `Ping` message to the peer.
+                self_.send_ping(peer_ip, None);
+            }
+        });
+        true
+    }
+
+    /// Sends the latest epoch challenge and latest block header to the peer.
+    fn puzzle_request(&self, peer_ip: SocketAddr) -> bool {
+        let epoch_challenge = (**self.latest_epoch_challenge.read()).clone();
+        let message = PuzzleResponse { epoch_challenge, block_header: Data::Object(self.latest_header()) };
+        // Send the `PuzzleResponse` message to the peer.
+        Outbound::send(self, peer_ip, Message::PuzzleResponse(message));
+        true
+    }
+
+    /// Ignores the puzzle response, as the node derives the epoch challenge and the latest block header
+    /// from the recent block headers, instead.
+    fn puzzle_response(&self, peer_ip: SocketAddr, _epoch_challenge: EpochChallenge<N>, _header: Header<N>) -> bool {
+        debug!("Ignoring a puzzle response from '{peer_ip}'");
+        true
+    }
+
+    /// Propagates the unconfirmed solution to all connected peers, if it is valid for the latest epoch.
+    async fn unconfirmed_solution(
+        &self,
+        peer_ip: SocketAddr,
+        serialized: UnconfirmedSolution<N>,
+        solution: ProverSolution<N>,
+    ) -> bool {
+        // Retrieve the latest epoch challenge.
+        let epoch_challenge = self.latest_epoch_challenge.read().clone();
+        // Retrieve the latest proof target.
+        let proof_target = self.latest_header().proof_target();
+
+        // Ensure that the prover solution is valid for the given epoch.
+        let coinbase_puzzle = self.coinbase_puzzle.clone();
+        let is_valid = tokio::task::spawn_blocking(move || {
+            solution.verify(coinbase_puzzle.coinbase_verifying_key(), &epoch_challenge, proof_target)
+        })
+        .await;
+
+        match is_valid {
+            // If the solution is valid, propagate the `UnconfirmedSolution`.
+            Ok(Ok(true)) => {
+                let message = Message::UnconfirmedSolution(serialized);
+                // Propagate the "UnconfirmedSolution".
+                self.propagate(message, &[peer_ip]);
+            }
+            Ok(Ok(false)) | Ok(Err(_)) => {
+                trace!("Invalid prover solution '{}' for the proof target.", solution.commitment())
+            }
+            Err(error) => warn!("Failed to verify the prover solution: {error}"),
+        }
+        true
+    }
+
+    /// Propagates the unconfirmed transaction to all connected peers, if it passes the checks which do not
+    /// depend on the ledger. The node has no ledger to check the transaction against (e.g. its input records,
+    /// or the programs it executes), so the validators perform the remaining checks.
+    async fn unconfirmed_transaction(
+        &self,
+        peer_ip: SocketAddr,
+        serialized: UnconfirmedTransaction<N>,
+        transaction: Transaction<N>,
+    ) -> bool {
+        // Check that the transaction is not a fee transaction.
+        if transaction.is_fee() {
+            return true; // Maintain the connection.
+        }
+        // Check the transaction.
+        let process = self.process.clone();
+        let transaction_id = transaction.id();
+        match tokio::task::spawn_blocking(move || Self::check_transaction(&process, &transaction)).await {
+            // If the transaction is valid, propagate the `UnconfirmedTransaction`.
+            Ok(Ok(())) => self.propagate(Message::UnconfirmedTransaction(serialized), &[peer_ip]),
+            Ok(Err(error)) => trace!("Invalid transaction '{transaction_id}' - {error}"),
+            Err(error) => warn!("Failed to check the transaction: {error}"),
+        }
+        true
+    }
+}
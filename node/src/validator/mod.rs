@@ -14,17 +14,18 @@
 
 mod router;
 
-use crate::traits::NodeInterface;
+use crate::{traits::NodeInterface, NodeConfig};
 use snarkos_account::Account;
 use snarkos_node_bft::{helpers::init_primary_channels, ledger_service::CoreLedgerService};
 use snarkos_node_consensus::Consensus;
 use snarkos_node_rest::Rest;
 use snarkos_node_router::{
-    messages::{NodeType, PuzzleResponse, UnconfirmedSolution, UnconfirmedTransaction},
+    messages::{BlockAnnouncement, Message, NodeType, PuzzleResponse, UnconfirmedSolution, UnconfirmedTransaction},
     Heartbeat,
     Inbound,
     Outbound,
     Router,
+    RouterConfig,
     Routing,
 };
 use snarkos_node_sync::{BlockSync, BlockSyncMode};
@@ -73,16 +74,10 @@ pub struct Validator<N: Network, C: ConsensusStorage<N>> {
 impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
     /// Initializes a new validator node.
     pub async fn new(
-        node_ip: SocketAddr,
-        bft_ip: Option<SocketAddr>,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
         account: Account<N>,
-        trusted_peers: &[SocketAddr],
-        trusted_validators: &[SocketAddr],
         genesis: Block<N>,
-        cdn: Option<String>,
         storage_mode: StorageMode,
+        config: NodeConfig,
     ) -> Result<Self> {
         // Prepare the shutdown flag.
         let shutdown: Arc<AtomicBool> = Default::default();
@@ -95,10 +90,10 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         // TODO: Remove me after Phase 3.
         let ledger = crate::phase_3_reset(ledger, storage_mode.clone())?;
         // Initialize the CDN.
-        if let Some(base_url) = cdn {
+        if let Some(base_url) = &config.cdn {
             // Sync the ledger with the CDN.
             if let Err((_, error)) =
-                snarkos_node_cdn::sync_ledger_with_cdn(&base_url, ledger.clone(), shutdown.clone()).await
+                snarkos_node_cdn::sync_ledger_with_cdn(base_url, ledger.clone(), shutdown.clone()).await
             {
                 crate::log_clean_error(&storage_mode);
                 return Err(error);
@@ -111,23 +106,29 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         let sync = BlockSync::new(BlockSyncMode::Gateway, ledger_service.clone());
 
         // Initialize the consensus.
-        let mut consensus =
-            Consensus::new(account.clone(), ledger_service, bft_ip, trusted_validators, storage_mode.clone())?;
+        let mut consensus = Consensus::new(
+            account.clone(),
+            ledger_service,
+            config.bft_ip,
+            &config.trusted_validators,
+            storage_mode.clone(),
+        )?;
         // Initialize the primary channels.
         let (primary_sender, primary_receiver) = init_primary_channels::<N>();
         // Start the consensus.
         consensus.run(primary_sender, primary_receiver).await?;
 
         // Initialize the node router.
-        let router = Router::new(
-            node_ip,
-            NodeType::Validator,
-            account,
-            trusted_peers,
-            Self::MAXIMUM_NUMBER_OF_PEERS as u16,
-            matches!(storage_mode, StorageMode::Development(_)),
-        )
+        // note: The validator always listens for inbound connections, and connects directly, as its peers
+        // must be able to reach it.
+        let router = Router::new(NodeType::Validator, account, RouterConfig {
+            listen: true,
+            proxy: None,
+            ..config.router_config::<N>(&storage_mode, Self::MAXIMUM_NUMBER_OF_PEERS)
+        })
         .await?;
+        // Admit the members of the current committee through the validator listener.
+        router.set_validator_committee(ledger.latest_committee()?.members().keys().copied());
 
         // Initialize the node.
         let mut node = Self {
@@ -143,12 +144,15 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         node.initialize_transaction_pool(storage_mode)?;
 
         // Initialize the REST server.
-        if let Some(rest_ip) = rest_ip {
-            node.rest =
-                Some(Rest::start(rest_ip, rest_rps, Some(consensus), ledger.clone(), Arc::new(node.clone())).await?);
+        if let Some(rest_ip) = config.rest_ip {
+            node.rest = Some(
+                Rest::start(rest_ip, config.rest_rps, Some(consensus), ledger.clone(), Arc::new(node.clone())).await?,
+            );
         }
         // Initialize the routing.
         node.initialize_routing().await;
+        // Initialize the block announcements.
+        node.initialize_block_announcements();
         // Initialize the notification message loop.
         node.handles.lock().push(crate::start_notification_message_loop());
         // Pass the node to the signal handler.
@@ -418,6 +422,43 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         Ok(())
     }
 
+    /// Initializes the block announcements, which announce the header of every new block to the peers.
+    fn initialize_block_announcements(&self) {
+        let node = self.clone();
+        self.handles.lock().push(tokio::spawn(async move {
+            // Retrieve the latest block height.
+            let mut latest_height = node.ledger.latest_height();
+            loop {
+                // If the Ctrl-C handler registered the signal, stop the node.
+                if node.shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+                    info!("Shutting down block announcements");
+                    break;
+                }
+
+                // Sleep briefly to check for a new block.
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                // Check if the ledger advanced since the last announcement.
+                let height = node.ledger.latest_height();
+                if height <= latest_height {
+                    continue;
+                }
+                latest_height = height;
+                // Update the committee admitted through the validator listener.
+                match node.ledger.latest_committee() {
+                    Ok(committee) => node.router.set_validator_committee(committee.members().keys().copied()),
+                    Err(error) => warn!("Failed to retrieve the latest committee - {error}"),
+                }
+                // Announce the latest block to the peers.
+                match crate::get_block_headers(&node.ledger, height..height + 1) {
+                    Ok(headers) => headers.into_iter().for_each(|block_header| {
+                        node.propagate(Message::BlockAnnouncement(BlockAnnouncement { block_header }), &[])
+                    }),
+                    Err(error) => warn!("Failed to announce block {height} - {error}"),
+                }
+            }
+        }));
+    }
+
     /// Spawns a task with the given future; it should only be used for long-running tasks.
     pub fn spawn<T: Future<Output = ()> + Send + 'static>(&self, future: T) {
         self.handles.lock().push(tokio::spawn(future));
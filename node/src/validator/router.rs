@@ -13,16 +13,22 @@
 // limitations under the License.
 
 use super::*;
-use snarkos_node_router::messages::{
-    BlockRequest,
-    BlockResponse,
-    DataBlocks,
-    DisconnectReason,
-    Message,
-    MessageCodec,
-    Ping,
-    Pong,
-    UnconfirmedTransaction,
+use snarkos_node_router::{
+    messages::{
+        BlockHeader,
+        BlockRequest,
+        BlockResponse,
+        DataBlocks,
+        DisconnectReason,
+        HeaderRequest,
+        HeaderResponse,
+        Message,
+        MessageCodec,
+        Ping,
+        Pong,
+        UnconfirmedTransaction,
+    },
+    Offense,
 };
 use snarkos_node_tcp::{Connection, ConnectionSide, Tcp};
 use snarkvm::{
@@ -30,7 +36,7 @@ use snarkvm::{
     prelude::{block::Transaction, coinbase::EpochChallenge, error, Network},
 };
 
-use std::{io, net::SocketAddr, time::Duration};
+use std::{io, net::SocketAddr};
 
 impl<N: Network, C: ConsensusStorage<N>> P2P for Validator<N, C> {
     /// Returns a reference to the TCP instance.
@@ -46,9 +52,21 @@ impl<N: Network, C: ConsensusStorage<N>> Handshake for Validator<N, C> {
         // Perform the handshake.
         let peer_addr = connection.addr();
         let conn_side = connection.side();
-        let stream = self.borrow_stream(&mut connection);
+        let local_addr = connection.local_addr();
         let genesis_header = self.ledger.get_header(0).map_err(|e| error(format!("{e}")))?;
-        self.router.handshake(peer_addr, stream, conn_side, genesis_header).await?;
+        match self.router.tls() {
+            // If TLS is enabled, perform the handshake over the TLS stream.
+            Some(tls) => {
+                let stream = self.take_stream(&mut connection);
+                let mut stream = tls.wrap(peer_addr, stream, conn_side).await?;
+                self.router.handshake(peer_addr, &mut stream, conn_side, local_addr, genesis_header).await?;
+                self.return_stream(&mut connection, stream);
+            }
+            None => {
+                let stream = self.borrow_stream(&mut connection);
+                self.router.handshake(peer_addr, stream, conn_side, local_addr, genesis_header).await?;
+            }
+        }
 
         Ok(connection)
     }
@@ -93,8 +111,13 @@ impl<N: Network, C: ConsensusStorage<N>> Writing for Validator<N, C> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(addr)
+    }
+
+    /// Returns `true` if the given message may be dropped for a peer that fails to keep up.
+    fn is_droppable(&self, message: &Self::Message) -> bool {
+        message.is_relay()
     }
 }
 
@@ -105,17 +128,30 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Validator<N, C> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router.message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
     async fn process_message(&self, peer_addr: SocketAddr, message: Self::Message) -> io::Result<()> {
-        // Process the message. Disconnect if the peer violated the protocol.
-        if let Err(error) = self.inbound(peer_addr, message).await {
+        #[cfg(feature = "metrics")]
+        let (timer, name) = (std::time::Instant::now(), message.kind());
+        // Process the message.
+        let result = self.inbound(peer_addr, message).await;
+        #[cfg(feature = "metrics")]
+        metrics::histogram_with_labels(
+            metrics::router::MESSAGE_PROCESSING_TIME,
+            &[("message", name.to_string())],
+            timer.elapsed().as_micros() as f64,
+        );
+        // Disconnect if the peer violated the protocol.
+        if let Err(error) = result {
             if let Some(peer_ip) = self.router().resolve_to_listener(&peer_addr) {
                 warn!("Disconnecting from '{peer_ip}' - {error}");
-                Outbound::send(self, peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
+                // Lower the reputation of this peer, and inform it of the offense.
+                let offense = Offense::from_error(&error);
+                self.router().record_offense(peer_ip, offense);
+                Outbound::send(self, peer_ip, Message::Disconnect(offense.disconnect_reason().into()));
                 // Disconnect from this peer.
                 self.router().disconnect(peer_ip);
             }
@@ -141,6 +177,11 @@ impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Validator<N, C> {
 
 #[async_trait]
 impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
+    /// Ignores the `BlockAnnouncement` message, as the validator syncs blocks through the BFT.
+    fn block_announcement(&self, _peer_ip: SocketAddr, _block_header: BlockHeader<N>) -> bool {
+        true
+    }
+
     /// Retrieves the blocks within the block request range, and returns the block response to the peer.
     fn block_request(&self, peer_ip: SocketAddr, message: BlockRequest) -> bool {
         let BlockRequest { start_height, end_height } = &message;
@@ -170,6 +211,37 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
         }
     }
 
+    /// Retrieves the block headers within the header request range, and returns the header response to the peer.
+    fn header_request(&self, peer_ip: SocketAddr, message: HeaderRequest) -> bool {
+        let HeaderRequest { start_height, end_height } = &message;
+
+        // Retrieve the block headers within the requested range.
+        let headers = match crate::get_block_headers(&self.ledger, *start_height..*end_height) {
+            Ok(headers) => headers,
+            Err(error) => {
+                error!("Failed to retrieve block headers {start_height} to {end_height} from the ledger - {error}");
+                return false;
+            }
+        };
+        // Send the `HeaderResponse` message to the peer.
+        Outbound::send(self, peer_ip, Message::HeaderResponse(HeaderResponse { request: message, headers }));
+        true
+    }
+
+    /// Handles a `HeaderResponse` message.
+    fn header_response(&self, peer_ip: SocketAddr, headers: Vec<BlockHeader<N>>) -> bool {
+        // Insert the block headers into the header chain of the sync module.
+        let headers =
+            headers.into_iter().map(|header| (header.height(), header.hash(), header.previous_hash)).collect();
+        match self.sync.insert_block_headers(peer_ip, headers) {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("{error}");
+                false
+            }
+        }
+    }
+
     /// Processes the block locators and sends back a `Pong` message.
     fn ping(&self, peer_ip: SocketAddr, message: Ping<N>) -> bool {
         // Check if the sync module is in router mode.
@@ -182,10 +254,14 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
                     return false;
                 }
             }
+            // If metadata was provided, then update the lowest block height served by the peer in the sync pool.
+            if let Some(metadata) = message.metadata {
+                self.sync.update_peer_lowest_block_height(peer_ip, metadata.lowest_block_height);
+            }
         }
 
         // Send a `Pong` message to the peer.
-        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork: Some(false) }));
+        self.send_pong(peer_ip);
         true
     }
 
@@ -195,7 +271,7 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
         let self_ = self.clone();
         tokio::spawn(async move {
             // Sleep for the preset time before sending a `Ping` request.
-            tokio::time::sleep(Duration::from_secs(Self::PING_SLEEP_IN_SECS)).await;
+            tokio::time::sleep(self_.router().liveness_policy().ping_interval()).await;
             // Check that the peer is still connected.
             if self_.router().is_connected(&peer_ip) {
                 // Retrieve the block locators.
@@ -28,6 +28,16 @@ pub trait CommunicationService: Send + Sync {
     /// Prepares a block request to be sent.
     fn prepare_block_request(start: u32, end: u32) -> Self::Message;
 
+    /// Prepares a header request to be sent, if the headers-first block sync is supported.
+    fn prepare_header_request(_start: u32, _end: u32) -> Option<Self::Message> {
+        None
+    }
+
+    /// Returns `true` if the given peer supports the headers-first block sync.
+    fn supports_header_sync(&self, _peer_ip: &SocketAddr) -> bool {
+        false
+    }
+
     /// Sends the given message to specified peer.
     ///
     /// This function returns as soon as the message is queued to be sent,
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use crate::{
-    helpers::{PeerPair, SyncRequest},
+    helpers::{PeerPair, SyncHeader, SyncRequest},
     locators::BlockLocators,
 };
 use snarkos_node_bft_ledger_service::LedgerService;
@@ -25,9 +25,9 @@ use anyhow::{bail, ensure, Result};
 use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use parking_lot::{Mutex, RwLock};
-use rand::{prelude::IteratorRandom, CryptoRng, Rng};
+use rand::{prelude::SliceRandom, CryptoRng, Rng};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, VecDeque},
     net::{IpAddr, Ipv4Addr, SocketAddr},
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -35,6 +35,7 @@ use std::{
     },
     time::Instant,
 };
+use tokio::sync::Notify;
 
 pub const REDUNDANCY_FACTOR: usize = 3;
 const EXTRA_REDUNDANCY_FACTOR: usize = REDUNDANCY_FACTOR * 2;
@@ -42,7 +43,13 @@ const NUM_SYNC_CANDIDATE_PEERS: usize = REDUNDANCY_FACTOR * 5;
 
 const BLOCK_REQUEST_TIMEOUT_IN_SECS: u64 = 60; // 60 seconds
 const MAX_BLOCK_REQUESTS: usize = 50; // 50 requests
+const MIN_BLOCK_REQUESTS: usize = 10; // 10 requests
+const WRITE_THROUGHPUT_INTERVAL_IN_SECS: u64 = 10; // 10 seconds
 const MAX_BLOCK_REQUEST_TIMEOUTS: usize = 5; // 5 timeouts
+const MAX_BLOCK_REQUESTS_PER_PEER: usize = 10; // 10 requests
+const BLOCK_REQUEST_RANGE_SIZE: u32 = 5; // 5 blocks
+/// The maximum number of block headers in a header request; this matches the limit of the `HeaderRequest` message.
+const MAX_HEADER_REQUEST_SIZE: u32 = 500; // 500 headers
 
 /// The maximum number of blocks tolerated before the primary is considered behind its peers.
 pub const MAX_BLOCKS_BEHIND: u32 = 2; // blocks
@@ -92,6 +99,9 @@ pub struct BlockSync<N: Network> {
     /// The map of peer-to-peer to their common ancestor.
     /// This map is used to determine which peers to request blocks from.
     common_ancestors: Arc<RwLock<IndexMap<PeerPair, u32>>>,
+    /// The map of peer IP to the lowest block height they serve, if they pruned their history.
+    /// This map is used to avoid requesting the blocks below their lowest block height from the pruned peers.
+    lowest_block_heights: Arc<RwLock<IndexMap<SocketAddr, u32>>>,
     /// The map of block height to the expected block hash and peer IPs.
     /// Each entry is removed when its corresponding entry in the responses map is removed.
     requests: Arc<RwLock<BTreeMap<u32, SyncRequest<N>>>>,
@@ -104,10 +114,24 @@ pub struct BlockSync<N: Network> {
     /// The map of (timed out) peer IPs to their request timestamps.
     /// This map is used to determine which peers to remove if they have timed out too many times.
     request_timeouts: Arc<RwLock<IndexMap<SocketAddr, Vec<Instant>>>>,
+    /// The map of block height to the block hash and previous block hash, for the header chain above the canon height.
+    /// The header chain is linked to the canonical map, and is synced ahead of the blocks, which are then
+    /// requested by their block hashes in the header chain.
+    headers: Arc<RwLock<BTreeMap<u32, (N::BlockHash, N::BlockHash)>>>,
+    /// The peer IP and timestamp of the pending header request, if one exists.
+    header_request: Arc<Mutex<Option<(SocketAddr, Instant)>>>,
+    /// The timestamps and canon heights sampled by the sync loop, over the last `WRITE_THROUGHPUT_INTERVAL_IN_SECS`.
+    /// This queue is used to size the window of block requests to the write throughput of the ledger.
+    canon_samples: Arc<Mutex<VecDeque<(Instant, u32)>>>,
+    /// The notifier of new block responses, which wakes up the block advancement pipeline.
+    block_responses_notify: Arc<Notify>,
     /// The boolean indicator of whether the node is synced up to the latest block (within the given tolerance).
     is_block_synced: Arc<AtomicBool>,
     /// The lock to guarantee advance_with_sync_blocks() is called only once at a time.
     advance_with_sync_blocks_lock: Arc<Mutex<()>>,
+    /// The block locators of this node, as of the last call to `get_block_locators`.
+    /// The block locators are extended as the canonical ledger advances, instead of being rebuilt on every call.
+    block_locators: Arc<Mutex<Option<BlockLocators<N>>>>,
 }
 
 impl<N: Network> BlockSync<N> {
@@ -118,12 +142,18 @@ impl<N: Network> BlockSync<N> {
             canon: ledger,
             locators: Default::default(),
             common_ancestors: Default::default(),
+            lowest_block_heights: Default::default(),
             requests: Default::default(),
             responses: Default::default(),
             request_timestamps: Default::default(),
             request_timeouts: Default::default(),
+            headers: Default::default(),
+            header_request: Default::default(),
+            canon_samples: Default::default(),
+            block_responses_notify: Default::default(),
             is_block_synced: Default::default(),
             advance_with_sync_blocks_lock: Default::default(),
+            block_locators: Default::default(),
         }
     }
 
@@ -183,6 +213,11 @@ impl<N: Network> BlockSync<N> {
     fn get_block_request_timestamp(&self, height: u32) -> Option<Instant> {
         self.request_timestamps.read().get(&height).copied()
     }
+
+    /// Returns the block hash and previous block hash in the header chain for the given height, if it exists.
+    fn get_block_header(&self, height: u32) -> Option<(N::BlockHash, N::BlockHash)> {
+        self.headers.read().get(&height).copied()
+    }
 }
 
 impl<N: Network> BlockSync<N> {
@@ -192,6 +227,49 @@ impl<N: Network> BlockSync<N> {
         // Retrieve the latest block height.
         let latest_height = self.canon.latest_block_height();
 
+        // Acquire the lock, so that the cached block locators are updated by one caller at a time.
+        let mut cached_locators = self.block_locators.lock();
+        let block_locators = match cached_locators.take() {
+            // If the cached block locators are still canonical, extend them up to the latest height.
+            Some(locators) if self.is_canonical(&locators, latest_height) => {
+                self.extend_block_locators(locators, latest_height)?
+            }
+            // Otherwise, construct the block locators from scratch.
+            _ => self.construct_block_locators(latest_height)?,
+        };
+        *cached_locators = Some(block_locators.clone());
+        Ok(block_locators)
+    }
+
+    /// Returns `true` if the given block locators end at or below the given height, on the canonical chain.
+    fn is_canonical(&self, locators: &BlockLocators<N>, latest_height: u32) -> bool {
+        let height = locators.latest_locator_height();
+        height <= latest_height && self.canon.get_block_hash(height).ok() == locators.get_hash(height)
+    }
+
+    /// Extends the given canonical block locators up to the given height.
+    fn extend_block_locators(&self, locators: BlockLocators<N>, latest_height: u32) -> Result<BlockLocators<N>> {
+        let height = locators.latest_locator_height();
+        let BlockLocators { mut recents, mut checkpoints } = locators;
+
+        // Retrieve the new checkpoint block hashes.
+        let next_checkpoint = (height / CHECKPOINT_INTERVAL + 1) * CHECKPOINT_INTERVAL;
+        for height in (next_checkpoint..=latest_height).step_by(CHECKPOINT_INTERVAL as usize) {
+            checkpoints.insert(height, self.canon.get_block_hash(height)?);
+        }
+        // Retrieve the new recent block hashes, and remove the ones that are no longer recent.
+        let first_recent_height = latest_height.saturating_sub((NUM_RECENT_BLOCKS - 1) as u32);
+        for height in first_recent_height.max(height + 1)..=latest_height {
+            recents.insert(height, self.canon.get_block_hash(height)?);
+        }
+        recents.retain(|height, _| *height >= first_recent_height);
+
+        // Construct the block locators.
+        BlockLocators::new(recents, checkpoints)
+    }
+
+    /// Constructs the block locators up to the given height.
+    fn construct_block_locators(&self, latest_height: u32) -> Result<BlockLocators<N>> {
         // Initialize the recents map.
         let mut recents = IndexMap::with_capacity(NUM_RECENT_BLOCKS);
         // Retrieve the recent block hashes.
@@ -218,16 +296,20 @@ impl<N: Network> BlockSync<N> {
         let block_requests = self.prepare_block_requests();
         trace!("Prepared {} block requests", block_requests.len());
 
+        // Request the next block headers, if a sync peer supports the headers-first block sync.
+        // Until the header chain reaches past the canon height, the blocks are not requested.
+        if self.try_header_sync(communication).await {
+            trace!("Waiting for the header chain before requesting blocks");
+            return;
+        }
+
         // If there are no block requests, but there are pending block responses in the sync pool,
-        // then try to advance the ledger using these pending block responses.
+        // then wake up the block advancement pipeline to try to advance the ledger using these pending block responses.
         // Note: This condition is guarded by `mode.is_router()` because validators sync blocks
         // using another code path that updates both `storage` and `ledger` when advancing blocks.
         if block_requests.is_empty() && !self.responses.read().is_empty() && self.mode.is_router() {
-            // Retrieve the latest block height.
-            let current_height = self.canon.latest_block_height();
-            // Try to advance the ledger with the sync pool.
-            trace!("No block requests to send - try advancing with block responses (at block {current_height})");
-            self.try_advancing_with_block_responses(current_height);
+            trace!("No block requests to send - try advancing with block responses");
+            self.block_responses_notify.notify_one();
             // Return early.
             return;
         }
@@ -262,7 +344,151 @@ impl<N: Network> BlockSync<N> {
         }
     }
 
+    /// Sends a header request for the next block headers to a sync peer, if the header chain is behind the sync peers.
+    /// Returns `true` if the header chain does not yet reach past the canon height, and a header request is pending.
+    async fn try_header_sync<C: CommunicationService>(&self, communication: &C) -> bool {
+        // Retrieve the latest canon height.
+        let canon_height = self.canon.latest_block_height();
+        // Remove the block headers which are now canonical.
+        self.headers.write().retain(|height, _| *height > canon_height);
+        // Determine if the header chain reaches past the canon height.
+        let is_header_chain_ready = self.get_block_header(canon_height + 1).is_some();
+
+        // If a header request is pending, wait for the response, unless the request has timed out.
+        if let Some((peer_ip, timestamp)) = *self.header_request.lock() {
+            if timestamp.elapsed().as_secs() <= BLOCK_REQUEST_TIMEOUT_IN_SECS {
+                return !is_header_chain_ready;
+            }
+            // Add the timed out peer IP to the request timeouts map.
+            self.request_timeouts.write().entry(peer_ip).or_default().push(Instant::now());
+        }
+        *self.header_request.lock() = None;
+
+        // Retrieve the sync peers.
+        let Some((sync_peers, _)) = self.find_sync_peers_inner() else {
+            return false;
+        };
+        // Pick the highest sync peer which supports the headers-first block sync.
+        let Some((peer_ip, peer_height)) = sync_peers
+            .iter()
+            .filter(|(peer_ip, _)| communication.supports_header_sync(peer_ip))
+            .map(|(peer_ip, locators)| (*peer_ip, locators.latest_locator_height()))
+            .max_by_key(|(_, peer_height)| *peer_height)
+        else {
+            // If none of the sync peers support the headers-first block sync, fall back to the block locators.
+            self.headers.write().clear();
+            return false;
+        };
+        // Compute the start height for the header request.
+        let start_height = self.headers.read().last_key_value().map_or(canon_height + 1, |(height, _)| height + 1);
+        // If the header chain already reaches the height of the sync peer, return early.
+        if peer_height < start_height {
+            return false;
+        }
+        // Compute the end height for the header request.
+        let end_height = (peer_height + 1).min(start_height + MAX_HEADER_REQUEST_SIZE);
+
+        // Construct the message.
+        let Some(message) = C::prepare_header_request(start_height, end_height) else {
+            return false;
+        };
+        // Insert the pending header request, and send the message to the peer.
+        *self.header_request.lock() = Some((peer_ip, Instant::now()));
+        if communication.send(peer_ip, message).await.is_none() {
+            warn!("Failed to send header request to peer '{peer_ip}'");
+            *self.header_request.lock() = None;
+            return false;
+        }
+        !is_header_chain_ready
+    }
+
+    /// Inserts the block headers from the given peer IP into the header chain.
+    /// The block headers must be linked to the header chain (or the canonical map), and consistent with the
+    /// block locators of the peer; otherwise, this function returns an error.
+    pub fn insert_block_headers(&self, peer_ip: SocketAddr, headers: Vec<SyncHeader<N>>) -> Result<()> {
+        // Ensure the sync pool requested the block headers from the given peer, and complete the header request.
+        {
+            let mut header_request = self.header_request.lock();
+            match *header_request {
+                Some((request_ip, _)) if request_ip == peer_ip => *header_request = None,
+                _ => bail!("The sync pool did not request block headers from '{peer_ip}'"),
+            }
+        }
+        // Retrieve the block locators of the peer.
+        let Some(locators) = self.locators.read().get(&peer_ip).cloned() else {
+            bail!("The sync pool does not have block locators for '{peer_ip}'")
+        };
+
+        // Retrieve the latest canon height and hash.
+        let canon_height = self.canon.latest_block_height();
+        let canon_hash = self.canon.get_block_hash(canon_height)?;
+
+        // Acquire the write lock on the header chain.
+        let mut chain = self.headers.write();
+        for (height, hash, previous_hash) in headers {
+            // Skip the block headers which are already canonical.
+            if height <= canon_height {
+                continue;
+            }
+            // Ensure the block header is linked to the canonical map, or to the header chain.
+            let expected_previous_hash = match height == canon_height + 1 {
+                true => Some(canon_hash),
+                false => chain.get(&(height - 1)).map(|(hash, _)| *hash),
+            };
+            if expected_previous_hash != Some(previous_hash) {
+                bail!("Block header {height} from '{peer_ip}' does not extend the header chain")
+            }
+            // Ensure the block hash is consistent with the block locators of the peer.
+            if locators.get_hash(height).is_some_and(|expected_hash| expected_hash != hash) {
+                bail!("Block header {height} from '{peer_ip}' is inconsistent with its block locators")
+            }
+            // Ensure the block hash is consistent with the existing header chain.
+            if chain.get(&height).is_some_and(|(existing_hash, _)| *existing_hash != hash) {
+                bail!("Block header {height} from '{peer_ip}' conflicts with the header chain")
+            }
+            // Insert the block header into the header chain.
+            chain.insert(height, (hash, previous_hash));
+        }
+        Ok(())
+    }
+
+    /// Processes the block announcement from the given peer IP. If the announced block extends the canonical map,
+    /// its block header is inserted into the header chain, and a block request is inserted for the peer.
+    /// Returns `true` if the caller should send the block request to the peer.
+    pub fn process_block_announcement(&self, peer_ip: SocketAddr, header: SyncHeader<N>) -> bool {
+        let (height, hash, previous_hash) = header;
+        // Retrieve the latest canon height.
+        let canon_height = self.canon.latest_block_height();
+        // Ignore the announcement, unless the announced block is the next block.
+        // Note: The gaps are filled in by the block sync, instead.
+        if height != canon_height + 1 {
+            return false;
+        }
+        // Ignore the announcement, unless the announced block extends the canonical map (e.g. not a stale tip).
+        if self.canon.get_block_hash(canon_height).ok() != Some(previous_hash) {
+            return false;
+        }
+        // Insert the block header into the header chain, unless it conflicts with the header chain.
+        {
+            let mut chain = self.headers.write();
+            if chain.get(&height).is_some_and(|(existing_hash, _)| *existing_hash != hash) {
+                // Note: The conflicting announcements cannot both be honest, and the first one is not more trustworthy,
+                // so the header chain and the pending block request are discarded, and the block is requested by the
+                // block locators of the sync peers instead.
+                chain.retain(|header_height, _| *header_height < height);
+                drop(chain);
+                self.remove_block_request(height);
+                return false;
+            }
+            chain.insert(height, (hash, previous_hash));
+        }
+        // Insert the block request for the peer, unless the block is already requested.
+        self.insert_block_request(height, (Some(hash), Some(previous_hash), [peer_ip].into_iter().collect())).is_ok()
+    }
+
     /// Processes the block response from the given peer IP.
+    /// The candidate blocks are buffered in the sync pool, which may receive them out of order,
+    /// until the block advancement pipeline processes them in order of height.
     #[inline]
     pub fn process_block_response(&self, peer_ip: SocketAddr, blocks: Vec<Block<N>>) -> Result<()> {
         // Insert the candidate blocks into the sync pool.
@@ -271,9 +497,28 @@ impl<N: Network> BlockSync<N> {
                 bail!("{error}");
             }
         }
+        // Wake up the block advancement pipeline.
+        self.block_responses_notify.notify_one();
         Ok(())
     }
 
+    /// Waits until new block responses are inserted into the sync pool.
+    pub async fn wait_for_block_responses(&self) {
+        self.block_responses_notify.notified().await
+    }
+
+    /// Advances the ledger with the block responses in the sync pool, strictly in order of height,
+    /// until the next block has not been received yet. This is the block advancement pipeline,
+    /// which is decoupled from the receipt of the block responses.
+    pub fn advance_with_block_responses(&self) {
+        // Acquire the lock to ensure the ledger is advanced by one caller at a time.
+        let _lock = self.advance_with_sync_blocks_lock.lock();
+        // Retrieve the latest block height.
+        let current_height = self.canon.latest_block_height();
+        // Try to advance the ledger with the sync pool.
+        self.try_advancing_with_block_responses(current_height);
+    }
+
     /// Returns the next block to process, if one is ready.
     #[inline]
     pub fn process_next_block(&self, next_height: u32) -> Option<Block<N>> {
@@ -391,16 +636,32 @@ impl<N: Network> BlockSync<N> {
         Ok(())
     }
 
+    /// Updates the lowest block height served by the given peer IP, which is above the genesis block
+    /// if the peer pruned its history.
+    pub fn update_peer_lowest_block_height(&self, peer_ip: SocketAddr, lowest_block_height: u32) {
+        match lowest_block_height {
+            0 => self.lowest_block_heights.write().swap_remove(&peer_ip),
+            height => self.lowest_block_heights.write().insert(peer_ip, height),
+        };
+    }
+
     /// TODO (howardwu): Remove the `common_ancestor` entry. But check that this is safe
     ///  (that we don't rely upon it for safety when we re-connect with the same peer).
     /// Removes the peer from the sync pool, if they exist.
     pub fn remove_peer(&self, peer_ip: &SocketAddr) {
         // Remove the locators entry for the given peer IP.
         self.locators.write().swap_remove(peer_ip);
+        // Remove the lowest block height for the given peer IP.
+        self.lowest_block_heights.write().swap_remove(peer_ip);
         // Remove all block requests to the peer.
         self.remove_block_requests_to_peer(peer_ip);
         // Remove the timeouts for the peer.
         self.request_timeouts.write().swap_remove(peer_ip);
+        // Remove the pending header request to the peer.
+        let mut header_request = self.header_request.lock();
+        if header_request.is_some_and(|(request_ip, _)| request_ip == *peer_ip) {
+            *header_request = None;
+        }
     }
 }
 
@@ -409,6 +670,8 @@ impl<N: Network> BlockSync<N> {
     fn prepare_block_requests(&self) -> Vec<(u32, SyncRequest<N>)> {
         // Remove timed out block requests.
         self.remove_timed_out_block_requests();
+        // Sample the canon height, to measure the write throughput of the ledger.
+        self.sample_canon_height();
         // Prepare the block requests.
         if let Some((sync_peers, min_common_ancestor)) = self.find_sync_peers_inner() {
             // Retrieve the highest block height.
@@ -425,6 +688,32 @@ impl<N: Network> BlockSync<N> {
         }
     }
 
+    /// Records the latest canon height, and removes the samples older than `WRITE_THROUGHPUT_INTERVAL_IN_SECS`.
+    fn sample_canon_height(&self) {
+        let mut canon_samples = self.canon_samples.lock();
+        canon_samples.push_back((Instant::now(), self.canon.latest_block_height()));
+        while let Some((timestamp, _)) = canon_samples.front() {
+            match timestamp.elapsed().as_secs() > WRITE_THROUGHPUT_INTERVAL_IN_SECS {
+                true => canon_samples.pop_front(),
+                false => break,
+            };
+        }
+    }
+
+    /// Returns the size of the window of block requests above the canon height, which applies backpressure
+    /// to the block requests: the window is twice the number of blocks the ledger wrote over the last
+    /// `WRITE_THROUGHPUT_INTERVAL_IN_SECS`, between `MIN_BLOCK_REQUESTS` and `MAX_BLOCK_REQUESTS`.
+    /// As the window starts at the canon height, the buffered block responses which are yet to be written
+    /// to the ledger count towards the window.
+    fn request_window(&self) -> usize {
+        let canon_samples = self.canon_samples.lock();
+        let num_blocks_written = match (canon_samples.front(), canon_samples.back()) {
+            (Some((_, first_height)), Some((_, last_height))) => last_height.saturating_sub(*first_height) as usize,
+            _ => 0,
+        };
+        num_blocks_written.saturating_mul(2).clamp(MIN_BLOCK_REQUESTS, MAX_BLOCK_REQUESTS)
+    }
+
     /// Updates the state of `is_block_synced` for the sync module.
     fn update_is_block_synced(&self, greatest_peer_height: u32, max_blocks_behind: u32) {
         // Retrieve the latest block height.
@@ -464,6 +753,8 @@ impl<N: Network> BlockSync<N> {
         if let Err(error) = self.check_block_response(&peer_ip, &block) {
             // Remove all block requests to the peer.
             self.remove_block_requests_to_peer(&peer_ip);
+            // Remove the header chain from this height onwards, as it may have been synced from a stale tip.
+            self.headers.write().retain(|header_height, _| *header_height < height);
             return Err(error);
         }
 
@@ -681,14 +972,18 @@ impl<N: Network> BlockSync<N> {
             .iter()
             .map(|(peer_ip, timestamps)| (*peer_ip, timestamps.len()))
             .collect::<IndexMap<_, _>>();
+        // Retrieve the lowest block heights of the pruned peers.
+        let lowest_block_heights = self.lowest_block_heights.read().clone();
 
         // Pick a set of peers above the latest canon height, and include their locators.
+        // The pruned peers which no longer serve the next block are skipped.
         let candidate_locators: IndexMap<_, _> = self
             .locators
             .read()
             .iter()
             .filter(|(_, locators)| locators.latest_locator_height() > latest_canon_height)
             .filter(|(ip, _)| timeouts.get(*ip).map(|count| *count < MAX_BLOCK_REQUEST_TIMEOUTS).unwrap_or(true))
+            .filter(|(ip, _)| lowest_block_heights.get(*ip).map_or(true, |height| *height <= latest_canon_height + 1))
             .sorted_by(|(_, a), (_, b)| b.latest_locator_height().cmp(&a.latest_locator_height()))
             .take(NUM_SYNC_CANDIDATE_PEERS)
             .map(|(peer_ip, locators)| (*peer_ip, locators.clone()))
@@ -753,6 +1048,11 @@ impl<N: Network> BlockSync<N> {
     }
 
     /// Given the sync peers and their minimum common ancestor, return a list of block requests.
+    ///
+    /// The block requests are spread across the sync peers, so that the blocks are downloaded in parallel:
+    /// consecutive heights are requested from the same sync peer in disjoint ranges of `BLOCK_REQUEST_RANGE_SIZE`,
+    /// each sync peer has at most `MAX_BLOCK_REQUESTS_PER_PEER` pending block requests, and the sync peers with
+    /// fewer timeouts are preferred, so that the timed out requests are re-assigned to other sync peers.
     fn construct_requests<R: Rng + CryptoRng>(
         &self,
         sync_peers: IndexMap<SocketAddr, BlockLocators<N>>,
@@ -770,10 +1070,30 @@ impl<N: Network> BlockSync<N> {
         // Compute the start height for the block request.
         let start_height = latest_canon_height + 1;
         // Compute the end height for the block request.
-        let end_height = (min_common_ancestor + 1).min(start_height + MAX_BLOCK_REQUESTS as u32);
+        let end_height = (min_common_ancestor + 1).min(start_height + self.request_window() as u32);
+        // If the header chain is synced ahead of the canon height, only request the blocks which it covers.
+        let end_height = match self.headers.read().last_key_value() {
+            Some((header_height, _)) => end_height.min(header_height + 1),
+            None => end_height,
+        };
 
         let mut requests = Vec::with_capacity((start_height..end_height).len());
 
+        // Count the pending block requests to each sync peer.
+        let mut num_pending_requests: IndexMap<SocketAddr, usize> = sync_peers.keys().map(|ip| (*ip, 0)).collect();
+        for (_, _, sync_ips) in self.requests.read().values() {
+            for sync_ip in sync_ips {
+                if let Some(num_requests) = num_pending_requests.get_mut(sync_ip) {
+                    *num_requests += 1;
+                }
+            }
+        }
+        // Retrieve the number of timeouts of each sync peer.
+        let num_timeouts: IndexMap<SocketAddr, usize> =
+            self.request_timeouts.read().iter().map(|(ip, timestamps)| (*ip, timestamps.len())).collect();
+        // Track the sync peer of the current range of block requests.
+        let mut range_ip = None;
+
         for height in start_height..end_height {
             // Ensure the current height is not canonized or already requested.
             if self.check_block_request(height).is_err() {
@@ -781,7 +1101,22 @@ impl<N: Network> BlockSync<N> {
             }
 
             // Construct the block request.
-            let (hash, previous_hash, num_sync_ips, is_honest) = construct_request(height, &sync_peers);
+            // If the header chain covers the height, and the block locators of the sync peers agree with it,
+            // the block is requested by its hash in the header chain. The header chain may come from a single peer,
+            // so the number of sync peers is still determined by the redundancy of the block locators.
+            let (hash, previous_hash, num_sync_ips, is_honest) = match self.get_block_header(height) {
+                Some((hash, previous_hash)) if is_consistent_header(height, hash, previous_hash, &sync_peers) => {
+                    let (_, _, num_sync_ips, is_honest) = construct_request(height, &sync_peers);
+                    (Some(hash), Some(previous_hash), num_sync_ips, is_honest)
+                }
+                Some(_) => {
+                    // Discard the header chain from this height onwards, as the sync peers contradict it.
+                    warn!("Discarding the header chain from block {height} (inconsistent with the sync peers)");
+                    self.headers.write().retain(|header_height, _| *header_height < height);
+                    construct_request(height, &sync_peers)
+                }
+                None => construct_request(height, &sync_peers),
+            };
 
             // Handle the dishonest case.
             if !is_honest {
@@ -793,8 +1128,30 @@ impl<N: Network> BlockSync<N> {
                 }
             }
 
+            // Continue the current range with its sync peer, unless the range is complete or the peer is at capacity.
+            let is_range_start = (height - start_height) % BLOCK_REQUEST_RANGE_SIZE == 0;
+            let continued_ip = range_ip
+                .filter(|ip| !is_range_start && num_pending_requests[ip] < MAX_BLOCK_REQUESTS_PER_PEER)
+                .filter(|_| num_sync_ips == 1);
             // Pick the sync peers.
-            let sync_ips = sync_peers.keys().copied().choose_multiple(rng, num_sync_ips);
+            let sync_ips = match continued_ip {
+                Some(ip) => vec![ip],
+                None => select_sync_ips(&num_pending_requests, &num_timeouts, num_sync_ips, rng),
+            };
+            // If there are not enough sync peers with capacity, then the block requests are at capacity.
+            if sync_ips.len() < num_sync_ips.min(sync_peers.len()) {
+                break;
+            }
+
+            // Update the pending block requests to the sync peers.
+            for sync_ip in &sync_ips {
+                num_pending_requests[sync_ip] += 1;
+            }
+            // Update the sync peer of the current range.
+            range_ip = match num_sync_ips == 1 {
+                true => sync_ips.first().copied(),
+                false => None,
+            };
 
             // Append the request.
             requests.push((height, (hash, previous_hash, sync_ips.into_iter().collect())));
@@ -804,6 +1161,40 @@ impl<N: Network> BlockSync<N> {
     }
 }
 
+/// Returns up to `num_sync_ips` sync peers which are below the maximum number of pending block requests,
+/// preferring the sync peers with the fewest timeouts, and then the fewest pending block requests.
+fn select_sync_ips<R: Rng + CryptoRng>(
+    num_pending_requests: &IndexMap<SocketAddr, usize>,
+    num_timeouts: &IndexMap<SocketAddr, usize>,
+    num_sync_ips: usize,
+    rng: &mut R,
+) -> Vec<SocketAddr> {
+    // Shuffle the sync peers, to break the ties randomly.
+    let mut candidates = num_pending_requests
+        .iter()
+        .filter(|(_, num_requests)| **num_requests < MAX_BLOCK_REQUESTS_PER_PEER)
+        .map(|(ip, num_requests)| (*ip, *num_requests))
+        .collect::<Vec<_>>();
+    candidates.shuffle(rng);
+    // Sort the sync peers by their timeouts and pending block requests.
+    candidates.sort_by_key(|(ip, num_requests)| (num_timeouts.get(ip).copied().unwrap_or(0), *num_requests));
+    candidates.into_iter().take(num_sync_ips).map(|(ip, _)| ip).collect()
+}
+
+/// Returns `true` if the given block hash and previous block hash of the header chain are not contradicted
+/// by the block locators of any sync peer.
+fn is_consistent_header<N: Network>(
+    height: u32,
+    hash: N::BlockHash,
+    previous_hash: N::BlockHash,
+    sync_peers: &IndexMap<SocketAddr, BlockLocators<N>>,
+) -> bool {
+    sync_peers.values().all(|locators| {
+        locators.get_hash(height).map_or(true, |locator_hash| locator_hash == hash)
+            && height.checked_sub(1).and_then(|height| locators.get_hash(height)).map_or(true, |h| h == previous_hash)
+    })
+}
+
 /// If any peer is detected to be dishonest in this function, it will not set the hash or previous hash,
 /// in order to allow the caller to determine what to do.
 fn construct_request<N: Network>(
@@ -889,7 +1280,10 @@ mod tests {
 
     use indexmap::indexset;
     use snarkvm::ledger::committee::Committee;
-    use std::net::{IpAddr, Ipv4Addr};
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::Duration,
+    };
 
     type CurrentNetwork = snarkvm::prelude::Testnet3;
 
@@ -1195,6 +1589,248 @@ mod tests {
         sync.insert_block_request(10, (None, None, indexset![sample_peer_ip(1)])).unwrap();
     }
 
+    #[test]
+    fn test_prepare_block_requests_in_parallel() {
+        let sync = sample_sync_at_height(0);
+
+        // Add the peers.
+        for peer_id in 1..=3 {
+            sync.update_peer_locators(sample_peer_ip(peer_id), sample_block_locators(10)).unwrap();
+        }
+
+        // Prepare the block requests.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+
+        // Check that each range of heights is requested from a single sync peer.
+        let sync_ips = requests.iter().map(|(_, (_, _, sync_ips))| sync_ips[0]).collect::<Vec<_>>();
+        assert!(sync_ips[..5].iter().all(|ip| *ip == sync_ips[0]));
+        assert!(sync_ips[5..].iter().all(|ip| *ip == sync_ips[5]));
+        // Check that the ranges are requested from different sync peers.
+        assert_ne!(sync_ips[0], sync_ips[5]);
+    }
+
+    #[test]
+    fn test_prepare_block_requests_per_peer_limit() {
+        let sync = sample_sync_at_height(0);
+
+        // Add a peer.
+        sync.update_peer_locators(sample_peer_ip(1), sample_block_locators(30)).unwrap();
+
+        // Check that the number of block requests is limited for a single peer.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), MAX_BLOCK_REQUESTS_PER_PEER);
+
+        for (height, request) in requests {
+            sync.insert_block_request(height, request).unwrap();
+        }
+
+        // Check that no more block requests are prepared, while the peer is at capacity.
+        assert!(sync.prepare_block_requests().is_empty());
+    }
+
+    #[test]
+    fn test_prepare_block_requests_after_timeout() {
+        let sync = sample_sync_at_height(0);
+
+        // Add the peers.
+        for peer_id in 1..=3 {
+            sync.update_peer_locators(sample_peer_ip(peer_id), sample_block_locators(10)).unwrap();
+        }
+
+        // Insert the block requests.
+        let requests = sync.prepare_block_requests();
+        let timed_out_ips = requests.iter().map(|(_, (_, _, sync_ips))| sync_ips[0]).collect::<IndexSet<_>>();
+        assert_eq!(timed_out_ips.len(), 2);
+        for (height, request) in requests {
+            sync.insert_block_request(height, request).unwrap();
+        }
+
+        // Time out the block requests.
+        let timestamp = Instant::now() - Duration::from_secs(BLOCK_REQUEST_TIMEOUT_IN_SECS + 1);
+        sync.request_timestamps.write().values_mut().for_each(|request_timestamp| *request_timestamp = timestamp);
+        assert_eq!(sync.remove_timed_out_block_requests(), 10);
+
+        // Check that the timed out block requests are re-assigned to the peer without timeouts.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (_, (_, _, sync_ips)) in requests {
+            assert_eq!(sync_ips.len(), 1);
+            assert!(!timed_out_ips.contains(&sync_ips[0]));
+        }
+    }
+
+    #[test]
+    fn test_prepare_block_requests_skips_pruned_peers() {
+        let sync = sample_sync_at_height(0);
+
+        // Add the peers, where peer 1 pruned the blocks below height 5.
+        for peer_id in 1..=3 {
+            sync.update_peer_locators(sample_peer_ip(peer_id), sample_block_locators(10)).unwrap();
+        }
+        sync.update_peer_lowest_block_height(sample_peer_ip(1), 5);
+
+        // Check that the blocks are not requested from the pruned peer.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (_, (_, _, sync_ips)) in requests {
+            assert!(!sync_ips.contains(&sample_peer_ip(1)));
+        }
+
+        // Check that the peer is a sync peer again, once it serves the blocks from genesis.
+        sync.update_peer_lowest_block_height(sample_peer_ip(1), 0);
+        assert!(sync.find_sync_peers().unwrap().0.contains_key(&sample_peer_ip(1)));
+    }
+
+    #[test]
+    fn test_request_window() {
+        let sync = sample_sync_at_height(20);
+
+        // Without any writes to the ledger, the window is the minimum.
+        sync.sample_canon_height();
+        assert_eq!(sync.request_window(), MIN_BLOCK_REQUESTS);
+
+        // After the ledger wrote 20 blocks, the window is twice the write throughput.
+        sync.canon_samples.lock().push_front((Instant::now(), 0));
+        assert_eq!(sync.request_window(), 40);
+
+        // The window is capped at the maximum.
+        *sync.canon_samples.lock() = [(Instant::now(), 0), (Instant::now(), 100)].into_iter().collect();
+        assert_eq!(sync.request_window(), MAX_BLOCK_REQUESTS);
+
+        // The samples older than the interval are removed.
+        let timestamp = Instant::now() - Duration::from_secs(WRITE_THROUGHPUT_INTERVAL_IN_SECS + 1);
+        *sync.canon_samples.lock() = [(timestamp, 0)].into_iter().collect();
+        sync.sample_canon_height();
+        assert_eq!(sync.request_window(), MIN_BLOCK_REQUESTS);
+    }
+
+    /// Returns the block hash of the sample ledger and block locators at the given height.
+    fn sample_block_hash(height: u32) -> <CurrentNetwork as Network>::BlockHash {
+        Field::<CurrentNetwork>::from_u32(height).into()
+    }
+
+    #[test]
+    fn test_insert_block_headers() {
+        let sync = sample_sync_at_height(0);
+        let peer_ip = sample_peer_ip(1);
+
+        // Add a peer.
+        sync.update_peer_locators(peer_ip, sample_block_locators(10)).unwrap();
+
+        // Inserting block headers that were not requested should fail.
+        let headers = (1..=10).map(|height| (height, sample_block_hash(height), sample_block_hash(height - 1)));
+        sync.insert_block_headers(peer_ip, headers.clone().collect()).unwrap_err();
+
+        // Inserting the requested block headers should succeed.
+        *sync.header_request.lock() = Some((peer_ip, Instant::now()));
+        sync.insert_block_headers(peer_ip, headers.collect()).unwrap();
+        assert_eq!(sync.get_block_header(10), Some((sample_block_hash(10), sample_block_hash(9))));
+
+        // Check that the blocks of the header chain are requested by their hashes.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (height, (hash, previous_hash, sync_ips)) in requests {
+            assert_eq!(hash, Some(sample_block_hash(height)));
+            assert_eq!(previous_hash, Some(sample_block_hash(height - 1)));
+            assert_eq!(sync_ips, indexset![peer_ip]);
+        }
+    }
+
+    #[test]
+    fn test_header_chain_does_not_reduce_redundancy() {
+        let sync = sample_sync_at_height(0);
+        let peer_ips = (1..=REDUNDANCY_FACTOR as u16).map(sample_peer_ip).collect::<Vec<_>>();
+
+        // Add the peers, whose block locators do not cover the heights of the header chain.
+        for peer_ip in &peer_ips {
+            sync.update_peer_locators(*peer_ip, sample_block_locators(200)).unwrap();
+        }
+        // Insert a header chain from a single peer.
+        let headers = (1..=10).map(|height| (height, sample_block_hash(height), sample_block_hash(height - 1)));
+        *sync.header_request.lock() = Some((peer_ips[0], Instant::now()));
+        sync.insert_block_headers(peer_ips[0], headers.collect()).unwrap();
+
+        // Check that the blocks are requested by their hashes in the header chain, but still from redundant peers.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (height, (hash, previous_hash, sync_ips)) in requests {
+            assert_eq!(hash, Some(sample_block_hash(height)));
+            assert_eq!(previous_hash, Some(sample_block_hash(height - 1)));
+            assert_eq!(sync_ips.len(), REDUNDANCY_FACTOR);
+        }
+    }
+
+    #[test]
+    fn test_insert_block_headers_fails() {
+        let sync = sample_sync_at_height(0);
+        let peer_ip = sample_peer_ip(1);
+
+        // Add a peer.
+        sync.update_peer_locators(peer_ip, sample_block_locators(10)).unwrap();
+
+        // Inserting block headers that are not linked to the canonical map should fail.
+        *sync.header_request.lock() = Some((peer_ip, Instant::now()));
+        sync.insert_block_headers(peer_ip, vec![(2, sample_block_hash(2), sample_block_hash(1))]).unwrap_err();
+
+        // Inserting block headers that are inconsistent with the block locators of the peer should fail.
+        *sync.header_request.lock() = Some((peer_ip, Instant::now()));
+        sync.insert_block_headers(peer_ip, vec![(1, sample_block_hash(100), sample_block_hash(0))]).unwrap_err();
+
+        // Check that the header chain is empty.
+        assert!(sync.headers.read().is_empty());
+    }
+
+    #[test]
+    fn test_process_block_announcement() {
+        let sync = sample_sync_at_height(5);
+        let peer_ip = sample_peer_ip(1);
+
+        // A block announcement that does not extend the canonical map should be ignored.
+        assert!(!sync.process_block_announcement(peer_ip, (6, sample_block_hash(6), sample_block_hash(4))));
+        // A block announcement that is not the next block should be ignored.
+        assert!(!sync.process_block_announcement(peer_ip, (8, sample_block_hash(8), sample_block_hash(7))));
+
+        // A block announcement that extends the canonical map should insert a block request.
+        assert!(sync.process_block_announcement(peer_ip, (6, sample_block_hash(6), sample_block_hash(5))));
+        assert_eq!(
+            sync.get_block_request(6),
+            Some((Some(sample_block_hash(6)), Some(sample_block_hash(5)), indexset![peer_ip]))
+        );
+        // A repeated block announcement should not insert another block request.
+        assert!(!sync.process_block_announcement(sample_peer_ip(2), (6, sample_block_hash(6), sample_block_hash(5))));
+
+        // A conflicting block announcement should discard the header and the block request.
+        assert!(!sync.process_block_announcement(sample_peer_ip(2), (6, sample_block_hash(100), sample_block_hash(5))));
+        assert!(sync.get_block_header(6).is_none());
+        assert!(sync.get_block_request(6).is_none());
+    }
+
+    #[test]
+    fn test_header_chain_inconsistent_with_sync_peers() {
+        let sync = sample_sync_at_height(0);
+        let peer_ip = sample_peer_ip(1);
+
+        // Add a peer, and insert a header chain which contradicts its block locators from block 5 onwards.
+        sync.update_peer_locators(peer_ip, sample_block_locators(10)).unwrap();
+        sync.headers.write().extend((1..=10).map(|height| {
+            let hash = |height| match height < 5 {
+                true => sample_block_hash(height),
+                false => sample_block_hash(height + 100),
+            };
+            (height, (hash(height), hash(height - 1)))
+        }));
+
+        // Check that the header chain is discarded from block 5 onwards, and the blocks are requested by the locators.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (height, (hash, previous_hash, _)) in requests {
+            assert_eq!(hash, Some(sample_block_hash(height)));
+            assert_eq!(previous_hash, Some(sample_block_hash(height - 1)));
+        }
+        assert_eq!(sync.headers.read().keys().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    }
+
     #[test]
     fn test_update_peer_locators() {
         let sync = sample_sync_at_height(0);
@@ -1232,6 +1868,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_block_locators() {
+        let sync = sample_sync_at_height(25_000);
+        assert_eq!(sync.get_block_locators().unwrap(), sample_block_locators(25_000));
+        // The block locators are cached.
+        assert_eq!(sync.block_locators.lock().clone(), Some(sample_block_locators(25_000)));
+
+        // The cached block locators are extended up to the latest height.
+        for cached_height in [0, 1, 99, 100, 9_999, 10_000, 24_901, 24_999, 25_000] {
+            *sync.block_locators.lock() = Some(sample_block_locators(cached_height));
+            assert_eq!(sync.get_block_locators().unwrap(), sample_block_locators(25_000));
+        }
+
+        // The cached block locators are rebuilt if they are not canonical.
+        *sync.block_locators.lock() = Some(sample_block_locators_with_fork(24_990, 24_980));
+        assert_eq!(sync.get_block_locators().unwrap(), sample_block_locators(25_000));
+        *sync.block_locators.lock() = Some(sample_block_locators(25_001));
+        assert_eq!(sync.get_block_locators().unwrap(), sample_block_locators(25_000));
+    }
+
     #[test]
     fn test_remove_peer() {
         let sync = sample_sync_at_height(0);
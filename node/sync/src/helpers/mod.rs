@@ -21,6 +21,9 @@ use std::net::SocketAddr;
 /// A tuple of the block hash (optional), previous block hash (optional), and sync IPs.
 pub type SyncRequest<N> = (Option<<N as Network>::BlockHash>, Option<<N as Network>::BlockHash>, IndexSet<SocketAddr>);
 
+/// A tuple of the block height, block hash, and previous block hash, of a block header.
+pub type SyncHeader<N> = (u32, <N as Network>::BlockHash, <N as Network>::BlockHash);
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct PeerPair(pub SocketAddr, pub SocketAddr);
 
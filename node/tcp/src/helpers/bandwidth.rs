@@ -0,0 +1,97 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+/// A token bucket limiting the bandwidth to a number of bytes per second.
+///
+/// The bucket holds up to one second worth of tokens. A transfer larger than the available tokens puts
+/// the bucket into debt, which is paid off by waiting; this way, messages of any size can be transferred.
+#[derive(Debug)]
+pub struct TokenBucket {
+    /// The number of tokens (bytes) added per second, which is also the capacity of the bucket.
+    rate: u32,
+    /// The number of available tokens, and the time of the last refill.
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    /// Creates a full token bucket with the given rate in bytes per second, or `None` if the rate is zero.
+    pub fn new(rate: u32) -> Option<Self> {
+        match rate {
+            0 => None,
+            rate => Some(Self { rate, state: Mutex::new((rate as f64, Instant::now())) }),
+        }
+    }
+
+    /// Returns the rate of the bucket in bytes per second.
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// Takes the given number of tokens from the bucket, returning the duration to wait for before the
+    /// transfer is within the rate.
+    pub fn take(&self, amount: usize) -> Duration {
+        let rate = self.rate as f64;
+        let mut state = self.state.lock();
+        let (tokens, last_refill) = &mut *state;
+
+        // Refill the bucket.
+        let now = Instant::now();
+        *tokens = (*tokens + now.duration_since(*last_refill).as_secs_f64() * rate).min(rate);
+        *last_refill = now;
+
+        // Take the tokens, going into debt if necessary.
+        *tokens -= amount as f64;
+        match *tokens < 0.0 {
+            true => Duration::from_secs_f64(-*tokens / rate),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+/// Takes the given number of bytes from all the given token buckets, returning the longest duration to wait for
+/// before the transfer is within the rate of all of them.
+pub(crate) fn throttle_delay(buckets: [Option<&TokenBucket>; 2], amount: usize) -> Duration {
+    buckets.into_iter().flatten().map(|bucket| bucket.take(amount)).max().unwrap_or_default()
+}
+
+/// Waits until the given number of bytes is within the rate of all the given token buckets.
+pub(crate) async fn throttle(buckets: [Option<&TokenBucket>; 2], amount: usize) {
+    let delay = throttle_delay(buckets, amount);
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        assert!(TokenBucket::new(0).is_none());
+
+        let bucket = TokenBucket::new(1000).unwrap();
+        // The bucket starts full.
+        assert_eq!(bucket.take(600), Duration::ZERO);
+        // Exceeding the available tokens requires waiting for the deficit to be refilled.
+        let delay = bucket.take(600);
+        assert!(delay > Duration::from_millis(150) && delay <= Duration::from_millis(200), "{delay:?}");
+        // Transfers larger than the capacity are allowed, at the cost of a longer wait.
+        let delay = bucket.take(2000);
+        assert!(delay > Duration::from_millis(2000) && delay <= Duration::from_millis(2200), "{delay:?}");
+    }
+}
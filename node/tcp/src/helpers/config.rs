@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::ProxyConfig;
+
 use std::{
     io::{self, ErrorKind::*},
     net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -52,8 +54,27 @@ pub struct Config {
     /// note: This number can very briefly be breached by 1 in case of inbound connection attempts. It can never be
     /// breached by outbound connection attempts, though.
     pub max_connections: u16,
+    /// The maximum number of pending connections (i.e. ones that are still being established), above which
+    /// inbound connection attempts are rejected. This ensures that inbound connection attempts that never complete
+    /// their handshake cannot exhaust [`Config::max_connections`].
+    pub max_pending_connections: u16,
+    /// The maximum number of pending inbound connections from a single IP address. Loopback addresses are exempt.
+    pub max_pending_connections_per_ip: u16,
     /// The maximum time (in milliseconds) allowed to establish a raw (before the [`Handshake`] protocol) TCP connection.
     pub connection_timeout_ms: u16,
+    /// The maximum number of bytes per second read from all the connections combined, and likewise written.
+    /// If set to `None`, the bandwidth is not limited.
+    ///
+    /// note: Tcp needs to implement the [`Reading`] and/or [`Writing`] protocol in order for it to have any effect.
+    pub max_bandwidth: Option<u32>,
+    /// The maximum number of bytes per second read from each connection, and likewise written.
+    /// If set to `None`, the bandwidth is not limited.
+    ///
+    /// note: Tcp needs to implement the [`Reading`] and/or [`Writing`] protocol in order for it to have any effect.
+    pub max_peer_bandwidth: Option<u32>,
+    /// The SOCKS5 proxy through which the outbound connections are established.
+    /// If set to `None`, the outbound connections are established directly.
+    pub proxy: Option<ProxyConfig>,
 }
 
 impl Config {
@@ -89,7 +110,12 @@ impl Default for Config {
             allow_random_port: true,
             fatal_io_errors: vec![ConnectionReset, ConnectionAborted, BrokenPipe, InvalidData, UnexpectedEof],
             max_connections: 100,
+            max_pending_connections: 20,
+            max_pending_connections_per_ip: 2,
             connection_timeout_ms: 1_000,
+            max_bandwidth: None,
+            max_peer_bandwidth: None,
+            proxy: None,
         }
     }
 }
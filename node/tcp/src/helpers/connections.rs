@@ -19,11 +19,12 @@ use std::{collections::HashMap, net::SocketAddr, ops::Not};
 use parking_lot::RwLock;
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    net::TcpStream,
     sync::oneshot,
     task::JoinHandle,
 };
 
+use crate::Stream;
+
 #[cfg(doc)]
 use crate::protocols::{Handshake, Reading, Writing};
 
@@ -73,8 +74,10 @@ pub struct Connection {
     addr: SocketAddr,
     /// The connection's side in relation to Tcp.
     side: ConnectionSide,
+    /// The local address of the connection, which tells apart the listener that accepted it.
+    local_addr: Option<SocketAddr>,
     /// Available and used only in the [`Handshake`] protocol.
-    pub(crate) stream: Option<TcpStream>,
+    pub(crate) stream: Option<Stream>,
     /// Available and used only in the [`Reading`] protocol.
     pub(crate) reader: Option<Box<dyn AR>>,
     /// Available and used only in the [`Writing`] protocol.
@@ -87,9 +90,10 @@ pub struct Connection {
 
 impl Connection {
     /// Creates a [`Connection`] with placeholders for protocol-related objects.
-    pub(crate) fn new(addr: SocketAddr, stream: TcpStream, side: ConnectionSide) -> Self {
+    pub(crate) fn new(addr: SocketAddr, stream: Stream, side: ConnectionSide) -> Self {
         Self {
             addr,
+            local_addr: stream.local_addr().ok(),
             stream: Some(stream),
             reader: None,
             writer: None,
@@ -104,6 +108,12 @@ impl Connection {
         self.addr
     }
 
+    /// Returns the local address of the connection, if it is known. For an inbound connection,
+    /// its port is the port of the listener that accepted the connection.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
     /// Returns `ConnectionSide::Initiator` if the associated peer initiated the connection
     /// and `ConnectionSide::Responder` if the connection request was initiated by Tcp.
     pub fn side(&self) -> ConnectionSide {
@@ -0,0 +1,120 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Stream;
+
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    io,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+use tokio::sync::mpsc;
+
+/// The inbound connections of a listener of a [`MemoryNetwork`], along with the addresses of their initiators.
+pub(crate) type MemoryListener = mpsc::UnboundedReceiver<(Stream, SocketAddr)>;
+
+/// An in-process network, which connects the [`Tcp`](crate::Tcp) stacks of a simulation without any sockets.
+///
+/// The addresses are assigned sequentially, and the connections are established without involving the operating
+/// system, so that (on a single-threaded runtime with a paused clock) a simulated scenario is reproducible.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryNetwork(Arc<Mutex<InnerMemoryNetwork>>);
+
+#[derive(Debug, Default)]
+struct InnerMemoryNetwork {
+    /// The listeners of the network, by their listening address.
+    listeners: HashMap<SocketAddr, mpsc::UnboundedSender<(Stream, SocketAddr)>>,
+    /// The number of ports assigned by the network so far.
+    num_ports: u16,
+}
+
+impl MemoryNetwork {
+    /// The size in bytes of the buffer of each direction of a connection.
+    const BUFFER_SIZE: usize = 64 * 1024;
+    /// The first port assigned by the network.
+    const FIRST_PORT: u16 = 10_000;
+
+    /// Binds a listener to the given address, assigning the next port if the given port is `0`.
+    pub(crate) fn bind(&self, addr: SocketAddr) -> io::Result<(SocketAddr, MemoryListener)> {
+        let mut network = self.0.lock();
+        let addr = match addr.port() {
+            0 => SocketAddr::new(addr.ip(), network.next_port()?),
+            _ => addr,
+        };
+        // Ensure the address is not taken by a listener which is still running.
+        if network.listeners.get(&addr).is_some_and(|sender| !sender.is_closed()) {
+            return Err(io::ErrorKind::AddrInUse.into());
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        network.listeners.insert(addr, sender);
+        Ok((addr, receiver))
+    }
+
+    /// Connects from the given IP to the listener at the given address, and returns the stream of the initiator.
+    pub(crate) fn connect(&self, ip: IpAddr, addr: SocketAddr) -> io::Result<Stream> {
+        let mut network = self.0.lock();
+        let initiator_addr = SocketAddr::new(ip, network.next_port()?);
+        let Some(sender) = network.listeners.get(&addr) else {
+            return Err(io::ErrorKind::ConnectionRefused.into());
+        };
+        let (initiator, responder) = tokio::io::duplex(Self::BUFFER_SIZE);
+        sender
+            .send((Stream::Memory(responder, addr), initiator_addr))
+            .map_err(|_| io::Error::from(io::ErrorKind::ConnectionRefused))?;
+        Ok(Stream::Memory(initiator, initiator_addr))
+    }
+}
+
+impl InnerMemoryNetwork {
+    /// Returns the next port of the network.
+    fn next_port(&mut self) -> io::Result<u16> {
+        let port = MemoryNetwork::FIRST_PORT.checked_add(self.num_ports).ok_or(io::ErrorKind::AddrNotAvailable)?;
+        self.num_ports += 1;
+        Ok(port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_memory_network() {
+        let network = MemoryNetwork::default();
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        // The ports are assigned sequentially.
+        let (addr, mut listener) = network.bind(SocketAddr::new(ip, 0)).unwrap();
+        assert_eq!(addr.port(), MemoryNetwork::FIRST_PORT);
+        assert!(network.bind(addr).is_err());
+
+        // Connect to the listener, and exchange a message.
+        let mut initiator = network.connect(ip, addr).unwrap();
+        let (mut responder, initiator_addr) = listener.recv().await.unwrap();
+        assert_eq!(initiator.local_addr().unwrap(), initiator_addr);
+        assert_eq!(responder.local_addr().unwrap(), addr);
+        initiator.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        responder.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        // The connections to an unknown address, or a closed listener, are refused.
+        assert!(network.connect(ip, SocketAddr::new(ip, 1)).is_err());
+        drop(listener);
+        assert!(network.connect(ip, addr).is_err());
+    }
+}
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod bandwidth;
+pub use bandwidth::TokenBucket;
+pub(crate) use bandwidth::{throttle, throttle_delay};
+
 mod config;
 pub use config::Config;
 
@@ -21,9 +25,29 @@ pub use connections::{Connection, ConnectionSide};
 mod known_peers;
 pub use known_peers::KnownPeers;
 
+#[cfg(feature = "test")]
+mod memory;
+#[cfg(feature = "test")]
+pub use memory::MemoryNetwork;
+#[cfg(feature = "test")]
+pub(crate) use memory::MemoryListener;
+
+mod outbound_queue;
+pub(crate) use outbound_queue::OutboundQueue;
+
+mod proxy;
+pub use proxy::ProxyConfig;
+
+mod send_stats;
+pub use send_stats::IoHealth;
+pub(crate) use send_stats::SendStats;
+
 mod stats;
 pub use stats::Stats;
 
+mod stream;
+pub use stream::Stream;
+
 use tracing::{debug_span, error_span, info_span, trace_span, warn_span, Span};
 
 /// Creates the Tcp's tracing span based on its name.
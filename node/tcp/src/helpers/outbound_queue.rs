@@ -0,0 +1,163 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+/// The bounded queue of the outbound messages of a single connection.
+///
+/// Once the queue is full, the oldest droppable message is dropped to make room for a new message, so that a slow
+/// peer can neither grow the queue indefinitely, nor cause the messages which must not be dropped to be rejected.
+pub(crate) struct OutboundQueue<T> {
+    /// The queued messages, along with whether each of them may be dropped.
+    messages: Mutex<VecDeque<(T, bool)>>,
+    /// The maximum number of queued messages.
+    capacity: usize,
+    /// The number of messages dropped since the queue was last drained or throttled.
+    num_dropped: AtomicUsize,
+    /// Whether the consumer of the queue is held back by the bandwidth limits of the node.
+    is_throttled: AtomicBool,
+    /// Wakes up the consumer of the queue when a message is queued.
+    notify: Notify,
+}
+
+impl<T> OutboundQueue<T> {
+    /// Initializes a new queue with the given capacity.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            messages: Mutex::new(VecDeque::with_capacity(capacity.min(64))),
+            capacity,
+            num_dropped: Default::default(),
+            is_throttled: Default::default(),
+            notify: Default::default(),
+        }
+    }
+
+    /// Queues the given message, and returns the message that was dropped to make room for it, if any.
+    /// If the queue is full and none of the queued messages is droppable, the given message is returned as an error.
+    pub(crate) fn push(&self, message: T, is_droppable: bool) -> Result<Option<T>, T> {
+        let mut messages = self.messages.lock();
+        let mut dropped = None;
+        if messages.len() >= self.capacity {
+            // The messages dropped while the queue is throttled are due to the node's limits, not to the peer.
+            if !self.is_throttled.load(Ordering::Relaxed) {
+                self.num_dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            match messages.iter().position(|(_, is_droppable)| *is_droppable) {
+                Some(index) => dropped = messages.remove(index).map(|(message, _)| message),
+                None => return Err(message),
+            }
+        }
+        messages.push_back((message, is_droppable));
+        drop(messages);
+
+        self.notify.notify_one();
+        Ok(dropped)
+    }
+
+    /// Waits for a message to be queued, and removes the oldest queued message.
+    pub(crate) async fn pop(&self) -> T {
+        loop {
+            {
+                let mut messages = self.messages.lock();
+                if let Some((message, _)) = messages.pop_front() {
+                    // The peer has caught up, once the queue is drained.
+                    if messages.is_empty() {
+                        self.num_dropped.store(0, Ordering::Relaxed);
+                    }
+                    return message;
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Returns the number of queued messages.
+    pub(crate) fn len(&self) -> usize {
+        self.messages.lock().len()
+    }
+
+    /// Returns the number of messages dropped since the queue was last drained or throttled.
+    pub(crate) fn num_dropped(&self) -> usize {
+        self.num_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Marks whether the consumer of the queue is held back by the bandwidth limits of the node. A throttled
+    /// consumer means that the peer keeps up with the limits, so the number of dropped messages is reset.
+    pub(crate) fn set_throttled(&self, is_throttled: bool) {
+        if is_throttled {
+            self.num_dropped.store(0, Ordering::Relaxed);
+        }
+        self.is_throttled.store(is_throttled, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_outbound_queue() {
+        let queue = OutboundQueue::new(3);
+        assert_eq!(queue.push(1, true), Ok(None));
+        assert_eq!(queue.push(2, false), Ok(None));
+        assert_eq!(queue.push(3, true), Ok(None));
+
+        // Once the queue is full, the oldest droppable message is dropped, even for a message which is not droppable.
+        assert_eq!(queue.push(4, false), Ok(Some(1)));
+        assert_eq!(queue.push(5, true), Ok(Some(3)));
+        assert_eq!(queue.push(6, true), Ok(Some(5)));
+        assert_eq!(queue.num_dropped(), 3);
+        assert_eq!(queue.len(), 3);
+        // The messages which are not droppable are kept, even if they are older.
+        assert_eq!(queue.pop().await, 2);
+        assert_eq!(queue.pop().await, 4);
+        assert_eq!(queue.num_dropped(), 3);
+        // Draining the queue resets the number of dropped messages.
+        assert_eq!(queue.pop().await, 6);
+        assert_eq!(queue.num_dropped(), 0);
+
+        // If none of the queued messages is droppable, the new message is rejected.
+        for message in 7..10 {
+            assert_eq!(queue.push(message, false), Ok(None));
+        }
+        assert_eq!(queue.push(10, true), Err(10));
+        assert_eq!(queue.num_dropped(), 1);
+    }
+
+    #[test]
+    fn test_outbound_queue_throttled() {
+        let queue = OutboundQueue::new(1);
+        assert_eq!(queue.push(1, true), Ok(None));
+        assert_eq!(queue.push(2, true), Ok(Some(1)));
+        assert_eq!(queue.num_dropped(), 1);
+
+        // Throttling resets the number of dropped messages, and the messages dropped meanwhile aren't counted.
+        queue.set_throttled(true);
+        assert_eq!(queue.num_dropped(), 0);
+        assert_eq!(queue.push(3, true), Ok(Some(2)));
+        assert_eq!(queue.push(4, true), Ok(Some(3)));
+        assert_eq!(queue.num_dropped(), 0);
+
+        // Once the throttling is over, the dropped messages are counted again.
+        queue.set_throttled(false);
+        assert_eq!(queue.push(5, true), Ok(Some(4)));
+        assert_eq!(queue.num_dropped(), 1);
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{io, net::SocketAddr};
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+
+/// The configuration of a SOCKS5 proxy, through which the outbound connections are established.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// The address of the proxy.
+    addr: SocketAddr,
+    /// The username and password to authenticate with, if the proxy requires it.
+    credentials: Option<(String, String)>,
+}
+
+impl ProxyConfig {
+    /// Initializes a new proxy configuration, with optional username and password authentication.
+    pub fn new(addr: SocketAddr, credentials: Option<(String, String)>) -> Self {
+        Self { addr, credentials }
+    }
+
+    /// Returns the address of the proxy.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Connects to the given address through the proxy.
+    pub async fn connect(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let stream = match &self.credentials {
+            Some((username, password)) => {
+                Socks5Stream::connect_with_password(self.addr, addr, username, password).await
+            }
+            None => Socks5Stream::connect(self.addr, addr).await,
+        };
+        stream.map(Socks5Stream::into_inner).map_err(io::Error::other)
+    }
+}
+
+impl std::fmt::Debug for ProxyConfig {
+    /// Formats the proxy configuration, without revealing the password.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("addr", &self.addr)
+            .field("username", &self.credentials.as_ref().map(|(username, _)| username))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[tokio::test]
+    async fn test_connect_with_password() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = ProxyConfig::new(listener.local_addr().unwrap(), Some(("user".into(), "pass".into())));
+        let target: SocketAddr = "1.2.3.4:4133".parse().unwrap();
+
+        // Run a minimal SOCKS5 proxy, which accepts a single connection with the expected credentials.
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 32];
+            // Expect the greeting offering the username and password method, and select it.
+            stream.read_exact(&mut buffer[..2]).await.unwrap();
+            let num_methods = buffer[1] as usize;
+            stream.read_exact(&mut buffer[..num_methods]).await.unwrap();
+            assert!(buffer[..num_methods].contains(&0x02));
+            stream.write_all(&[0x05, 0x02]).await.unwrap();
+            // Expect the credentials.
+            stream.read_exact(&mut buffer[..11]).await.unwrap();
+            assert_eq!(&buffer[..11], b"\x01\x04user\x04pass");
+            stream.write_all(&[0x01, 0x00]).await.unwrap();
+            // Expect the connection request to the target.
+            stream.read_exact(&mut buffer[..10]).await.unwrap();
+            assert_eq!(&buffer[..10], &[0x05, 0x01, 0x00, 0x01, 1, 2, 3, 4, 0x10, 0x25]);
+            stream.write_all(&[0x05, 0x00, 0x00, 0x01, 1, 2, 3, 4, 0x10, 0x25]).await.unwrap();
+        });
+
+        proxy.connect(target).await.unwrap();
+        server.await.unwrap();
+    }
+}
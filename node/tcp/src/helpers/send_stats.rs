@@ -0,0 +1,145 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+/// Contains statistics related to the outbound messages of a single connection.
+#[derive(Default)]
+pub(crate) struct SendStats {
+    /// The number of bytes written to the stream, but not flushed yet.
+    pending_bytes: AtomicU64,
+    /// The number of messages taken from the outbound queue.
+    msgs_dequeued: AtomicU64,
+    /// The total time spent by the messages in the outbound queue, in microseconds.
+    total_queue_time: AtomicU64,
+    /// The longest time spent by a message in the outbound queue, in microseconds.
+    max_queue_time: AtomicU64,
+    /// The number of writes that took longer than the stall threshold.
+    num_stalls: AtomicU64,
+    /// The start of the write in progress, if any.
+    write_started_at: Mutex<Option<Instant>>,
+}
+
+impl SendStats {
+    /// Registers the start of a write of a message which was queued at the given time.
+    pub(crate) fn register_write_start(&self, queued_at: Instant) {
+        let now = Instant::now();
+        let queue_time = now.saturating_duration_since(queued_at).as_micros() as u64;
+        self.msgs_dequeued.fetch_add(1, Relaxed);
+        self.total_queue_time.fetch_add(queue_time, Relaxed);
+        self.max_queue_time.fetch_max(queue_time, Relaxed);
+        *self.write_started_at.lock() = Some(now);
+    }
+
+    /// Registers the given number of bytes written to the stream, but not flushed yet.
+    pub(crate) fn register_pending_bytes(&self, size: usize) {
+        self.pending_bytes.store(size as u64, Relaxed);
+    }
+
+    /// Registers the end of the write in progress, and returns its duration if it stalled,
+    /// i.e. if it took longer than the given threshold.
+    pub(crate) fn register_write_end(&self, stall_threshold: Duration) -> Option<Duration> {
+        self.pending_bytes.store(0, Relaxed);
+        let started_at = self.write_started_at.lock().take()?;
+        let duration = started_at.elapsed();
+        if duration > stall_threshold {
+            self.num_stalls.fetch_add(1, Relaxed);
+            return Some(duration);
+        }
+        None
+    }
+
+    /// Returns the I/O health of the connection, given the state of its outbound queue.
+    pub(crate) fn health(&self, queued_msgs: usize, dropped_msgs: usize, stall_threshold: Duration) -> IoHealth {
+        let msgs_dequeued = self.msgs_dequeued.load(Relaxed);
+        let total_queue_time = self.total_queue_time.load(Relaxed);
+        let current_write = self.write_started_at.lock().map(|started_at| started_at.elapsed());
+
+        IoHealth {
+            queued_msgs,
+            pending_bytes: self.pending_bytes.load(Relaxed),
+            dropped_msgs,
+            avg_queue_time: Duration::from_micros(total_queue_time.checked_div(msgs_dequeued).unwrap_or_default()),
+            max_queue_time: Duration::from_micros(self.max_queue_time.load(Relaxed)),
+            num_stalls: self.num_stalls.load(Relaxed),
+            is_stalled: current_write.is_some_and(|duration| duration > stall_threshold),
+        }
+    }
+}
+
+/// A summary of the health of the outbound messages of a single connection, which indicates whether
+/// the peer keeps up with the messages of the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IoHealth {
+    /// The number of messages waiting in the outbound queue.
+    pub queued_msgs: usize,
+    /// The number of bytes written to the stream, but not flushed yet.
+    pub pending_bytes: u64,
+    /// The number of messages dropped from the outbound queue since it was last drained.
+    pub dropped_msgs: usize,
+    /// The average time spent by a message in the outbound queue.
+    pub avg_queue_time: Duration,
+    /// The longest time spent by a message in the outbound queue.
+    pub max_queue_time: Duration,
+    /// The number of writes that stalled.
+    pub num_stalls: u64,
+    /// Whether the write in progress is stalled.
+    pub is_stalled: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_send_stats() {
+        let stats = SendStats::default();
+        let threshold = Duration::from_secs(5);
+        assert_eq!(stats.health(0, 0, threshold), IoHealth::default());
+
+        // A message which spent 2 seconds in the queue, and is written without stalling.
+        let queued_at = Instant::now();
+        tokio::time::advance(Duration::from_secs(2)).await;
+        stats.register_write_start(queued_at);
+        stats.register_pending_bytes(100);
+        let health = stats.health(3, 0, threshold);
+        assert_eq!(health.queued_msgs, 3);
+        assert_eq!(health.pending_bytes, 100);
+        assert!(!health.is_stalled);
+        assert_eq!(stats.register_write_end(threshold), None);
+
+        // A message which was queued immediately, and whose write stalls.
+        stats.register_write_start(Instant::now());
+        stats.register_pending_bytes(200);
+        tokio::time::advance(Duration::from_secs(6)).await;
+        let health = stats.health(0, 1, threshold);
+        assert!(health.is_stalled);
+        assert_eq!(health.pending_bytes, 200);
+        assert_eq!(stats.register_write_end(threshold), Some(Duration::from_secs(6)));
+
+        let health = stats.health(0, 1, threshold);
+        assert_eq!(health.pending_bytes, 0);
+        assert_eq!(health.dropped_msgs, 1);
+        assert_eq!(health.avg_queue_time, Duration::from_secs(1));
+        assert_eq!(health.max_queue_time, Duration::from_secs(2));
+        assert_eq!(health.num_stalls, 1);
+        assert!(!health.is_stalled);
+    }
+}
@@ -25,7 +25,7 @@ pub mod protocols;
 mod tcp;
 pub use tcp::Tcp;
 
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
 /// A trait for objects containing a [`Tcp`]; it is required to implement protocols.
 pub trait P2P {
@@ -36,11 +36,38 @@ pub trait P2P {
 /// Checks if the given IP address is a bogon address.
 ///
 /// A bogon address is an IP address that should not appear on the public Internet.
-/// This includes private addresses, loopback addresses, and link-local addresses.
+/// This includes private (and unique local) addresses, loopback addresses, and link-local addresses.
 pub fn is_bogon_ip(ip: IpAddr) -> bool {
-    match ip {
+    match canonical_ip(ip) {
         IpAddr::V4(ipv4) => ipv4.is_loopback() || ipv4.is_private() || ipv4.is_link_local(),
-        IpAddr::V6(ipv6) => ipv6.is_loopback(),
+        IpAddr::V6(ipv6) => {
+            ipv6.is_loopback() || ipv6.segments()[0] & 0xfe00 == 0xfc00 || ipv6.segments()[0] & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+/// Returns the given IP address, with an IPv4-mapped IPv6 address converted to the IPv4 address it maps to.
+///
+/// A dual-stack listener reports the IPv4 peers with IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`),
+/// so they are converted in order for every peer to have a single address.
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => ipv6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        ip => ip,
+    }
+}
+
+/// Returns the given socket address, with an IPv4-mapped IPv6 address converted to the IPv4 address it maps to.
+pub fn canonical_addr(addr: SocketAddr) -> SocketAddr {
+    SocketAddr::new(canonical_ip(addr.ip()), addr.port())
+}
+
+/// Returns the subnet of the given IP address, i.e. its /24 prefix for IPv4, and its /48 prefix for IPv6.
+pub fn subnet(ip: IpAddr) -> IpAddr {
+    // Treat IPv4-mapped IPv6 addresses as IPv4 addresses.
+    match canonical_ip(ip) {
+        IpAddr::V4(ipv4) => IpAddr::V4(Ipv4Addr::from(u32::from(ipv4) & !0xff)),
+        IpAddr::V6(ipv6) => IpAddr::V6(Ipv6Addr::from(u128::from(ipv6) & !((1u128 << 80) - 1))),
     }
 }
 
@@ -51,3 +78,43 @@ pub fn is_unspecified_or_broadcast_ip(ip: IpAddr) -> bool {
         ipv6 => ipv6.is_unspecified(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+        assert_eq!(subnet(ip("1.2.3.4")), ip("1.2.3.0"));
+        assert_eq!(subnet(ip("1.2.3.4")), subnet(ip("1.2.3.255")));
+        assert_ne!(subnet(ip("1.2.3.4")), subnet(ip("1.2.4.4")));
+        assert_eq!(subnet(ip("::ffff:1.2.3.4")), ip("1.2.3.0"));
+        assert_eq!(subnet(ip("2001:db8:1:2:3:4:5:6")), ip("2001:db8:1::"));
+        assert_ne!(subnet(ip("2001:db8:1::")), subnet(ip("2001:db8:2::")));
+    }
+
+    #[test]
+    fn test_canonical_addr() {
+        let addr = |s: &str| s.parse::<SocketAddr>().unwrap();
+
+        assert_eq!(canonical_addr(addr("[::ffff:1.2.3.4]:4133")), addr("1.2.3.4:4133"));
+        assert_eq!(canonical_addr(addr("1.2.3.4:4133")), addr("1.2.3.4:4133"));
+        assert_eq!(canonical_addr(addr("[2001:db8::1]:4133")), addr("[2001:db8::1]:4133"));
+    }
+
+    #[test]
+    fn test_is_bogon_ip() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+        assert!(is_bogon_ip(ip("10.0.0.1")));
+        assert!(is_bogon_ip(ip("::ffff:192.168.0.1")));
+        assert!(is_bogon_ip(ip("::1")));
+        assert!(is_bogon_ip(ip("fd00::1")));
+        assert!(is_bogon_ip(ip("fe80::1")));
+        assert!(!is_bogon_ip(ip("1.2.3.4")));
+        assert!(!is_bogon_ip(ip("::ffff:1.2.3.4")));
+        assert!(!is_bogon_ip(ip("2001:db8::1")));
+    }
+}
@@ -16,8 +16,10 @@
 use crate::{protocols::Handshake, Config};
 use crate::{
     protocols::{ProtocolHandler, ReturnableConnection},
+    throttle,
     ConnectionSide,
     Tcp,
+    TokenBucket,
     P2P,
 };
 
@@ -165,9 +167,16 @@ impl<R: Reading> ReadingInternal for R {
             // this task gets aborted, so there is no need for a dedicated timeout
             let _ = rx_conn_ready.await;
 
+            // the bandwidth limit of the connection
+            let peer_bandwidth = node.config().max_peer_bandwidth.and_then(TokenBucket::new);
+
             while let Some(bytes) = framed.next().await {
                 match bytes {
                     Ok(msg) => {
+                        // wait until the message is within the bandwidth limits, delaying further reads
+                        let len = framed.decoder().last_read_len;
+                        throttle([node.inbound_bandwidth(), peer_bandwidth.as_ref()], len).await;
+
                         // send the message for further processing
                         if let Err(e) = inbound_message_sender.try_send(msg) {
                             error!(parent: node.span(), "can't process a message from {addr}: {e}");
@@ -202,7 +211,7 @@ impl<R: Reading> ReadingInternal for R {
         framed: FramedRead<T, Self::Codec>,
         addr: SocketAddr,
     ) -> FramedRead<T, CountingCodec<Self::Codec>> {
-        framed.map_decoder(|codec| CountingCodec { codec, node: self.tcp().clone(), addr, acc: 0 })
+        framed.map_decoder(|codec| CountingCodec { codec, node: self.tcp().clone(), addr, acc: 0, last_read_len: 0 })
     }
 }
 
@@ -212,6 +221,8 @@ struct CountingCodec<D: Decoder> {
     node: Tcp,
     addr: SocketAddr,
     acc: usize,
+    /// The size of the last decoded message.
+    last_read_len: usize,
 }
 
 impl<D: Decoder> Decoder for CountingCodec<D> {
@@ -229,6 +240,7 @@ impl<D: Decoder> Decoder for CountingCodec<D> {
 
             if ret.is_some() {
                 self.acc = 0;
+                self.last_read_len = read_len;
                 self.node.known_peers().register_received_message(self.addr, read_len);
                 self.node.stats().register_received_message(read_len);
             } else {
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{any::Any, collections::HashMap, io, net::SocketAddr, sync::Arc};
+use std::{any::Any, collections::HashMap, io, net::SocketAddr, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use futures_util::sink::SinkExt;
@@ -20,6 +20,7 @@ use parking_lot::RwLock;
 use tokio::{
     io::AsyncWrite,
     sync::{mpsc, oneshot},
+    time::Instant,
 };
 use tokio_util::codec::{Encoder, FramedWrite};
 use tracing::*;
@@ -28,12 +29,17 @@ use tracing::*;
 use crate::{protocols::Handshake, Config, Tcp};
 use crate::{
     protocols::{Protocol, ProtocolHandler, ReturnableConnection},
+    throttle_delay,
     Connection,
     ConnectionSide,
+    IoHealth,
+    OutboundQueue,
+    SendStats,
+    TokenBucket,
     P2P,
 };
 
-type WritingSenders = Arc<RwLock<HashMap<SocketAddr, mpsc::Sender<WrappedMessage>>>>;
+type WritingSenders = Arc<RwLock<HashMap<SocketAddr, (Arc<OutboundQueue<WrappedMessage>>, Arc<SendStats>)>>>;
 
 /// Can be used to specify and enable writing, i.e. sending outbound messages. If the [`Handshake`]
 /// protocol is enabled too, it goes into force only after the handshake has been concluded.
@@ -45,10 +51,18 @@ where
     /// The depth of per-connection queues used to send outbound messages; the greater it is, the more outbound
     /// messages the node can enqueue. Setting it to a large value is not recommended, as doing it might
     /// obscure potential issues with your implementation (like slow serialization) or network.
+    /// Once a queue is full, its oldest droppable message is dropped to make room for a new one
+    /// (see [`Writing::is_droppable`]); if none of its messages is droppable, the new message is rejected.
     ///
     /// The default value is 1024.
     const MESSAGE_QUEUE_DEPTH: usize = 1024;
 
+    /// The duration after which a write to a stream is considered stalled, which indicates a peer that doesn't
+    /// read its messages, or a congested network (see [`Writing::io_health`]).
+    ///
+    /// The default value is 5 seconds.
+    const WRITE_STALL_THRESHOLD: Duration = Duration::from_secs(5);
+
     /// The type of the outbound messages; unless their serialization is expensive and the message
     /// is broadcasted (in which case it would get serialized multiple times), serialization should
     /// be done in the implementation of [`Self::Codec`].
@@ -92,6 +106,44 @@ where
     /// The `side` param indicates the connection side **from the node's perspective**.
     fn codec(&self, addr: SocketAddr, side: ConnectionSide) -> Self::Codec;
 
+    /// Returns `true` if the given message may be dropped in favor of newer messages, once the outbound
+    /// queue of its connection is full. By default, no message is droppable.
+    fn is_droppable(&self, _message: &Self::Message) -> bool {
+        false
+    }
+
+    /// Returns the number of messages dropped from the outbound queue of the given address since the queue
+    /// was last drained or the writes were last throttled by the bandwidth limits of the node; a growing number
+    /// indicates a peer that persistently fails to keep up with its messages.
+    fn num_dropped_messages(&self, addr: SocketAddr) -> usize {
+        match self.tcp().protocols.writing.get() {
+            Some(handler) => handler.senders.read().get(&addr).map_or(0, |(queue, _)| queue.num_dropped()),
+            None => 0,
+        }
+    }
+
+    /// Returns a summary of the health of the outbound messages of the given address, if it is connected.
+    fn io_health(&self, addr: SocketAddr) -> Option<IoHealth> {
+        let handler = self.tcp().protocols.writing.get()?;
+        let (queue, stats) = handler.senders.read().get(&addr).cloned()?;
+        Some(stats.health(queue.len(), queue.num_dropped(), Self::WRITE_STALL_THRESHOLD))
+    }
+
+    /// Returns a summary of the health of the outbound messages of all the connected addresses.
+    fn io_health_all(&self) -> HashMap<SocketAddr, IoHealth> {
+        match self.tcp().protocols.writing.get() {
+            Some(handler) => handler
+                .senders
+                .read()
+                .iter()
+                .map(|(addr, (queue, stats))| {
+                    (*addr, stats.health(queue.len(), queue.num_dropped(), Self::WRITE_STALL_THRESHOLD))
+                })
+                .collect(),
+            None => Default::default(),
+        }
+    }
+
     /// Sends the provided message to the specified [`SocketAddr`]. Returns as soon as the message is queued to
     /// be sent, without waiting for the actual delivery; instead, the caller is provided with a [`oneshot::Receiver`]
     /// which can be used to determine when and whether the message has been delivered.
@@ -100,22 +152,16 @@ where
     ///
     /// The following errors can be returned:
     /// - [`io::ErrorKind::NotConnected`] if the node is not connected to the provided address
-    /// - [`io::ErrorKind::Other`] if the outbound message queue for this address is full
+    /// - [`io::ErrorKind::Other`] if the outbound message queue for this address is full of non-droppable messages
     /// - [`io::ErrorKind::Unsupported`] if [`Writing::enable_writing`] hadn't been called yet
     fn unicast(&self, addr: SocketAddr, message: Self::Message) -> io::Result<oneshot::Receiver<io::Result<()>>> {
         // access the protocol handler
         if let Some(handler) = self.tcp().protocols.writing.get() {
-            // find the message sender for the given address
-            if let Some(sender) = handler.senders.read().get(&addr).cloned() {
+            // find the message queue for the given address
+            if let Some((queue, _)) = handler.senders.read().get(&addr).cloned() {
+                let is_droppable = self.is_droppable(&message);
                 let (msg, delivery) = WrappedMessage::new(Box::new(message));
-                sender
-                    .try_send(msg)
-                    .map_err(|e| {
-                        error!(parent: self.tcp().span(), "can't send a message to {}: {}", addr, e);
-                        self.tcp().stats().register_failure();
-                        io::ErrorKind::Other.into()
-                    })
-                    .map(|_| delivery)
+                self.enqueue(addr, &queue, msg, is_droppable).map(|_| delivery)
             } else {
                 Err(io::ErrorKind::NotConnected.into())
             }
@@ -138,13 +184,11 @@ where
     {
         // access the protocol handler
         if let Some(handler) = self.tcp().protocols.writing.get() {
+            let is_droppable = self.is_droppable(&message);
             let senders = handler.senders.read().clone();
-            for (addr, message_sender) in senders {
+            for (addr, (queue, _)) in senders {
                 let (msg, _delivery) = WrappedMessage::new(Box::new(message.clone()));
-                let _ = message_sender.try_send(msg).map_err(|e| {
-                    error!(parent: self.tcp().span(), "can't send a message to {}: {}", addr, e);
-                    self.tcp().stats().register_failure();
-                });
+                let _ = self.enqueue(addr, &queue, msg, is_droppable);
             }
 
             Ok(())
@@ -157,11 +201,21 @@ where
 /// This trait is used to restrict access to methods that would otherwise be public in [`Writing`].
 #[async_trait]
 trait WritingInternal: Writing {
+    /// Queues the given message in the outbound queue of the given address.
+    fn enqueue(
+        &self,
+        addr: SocketAddr,
+        queue: &OutboundQueue<WrappedMessage>,
+        msg: WrappedMessage,
+        is_droppable: bool,
+    ) -> io::Result<()>;
+
     /// Writes the given message to the network stream and returns the number of written bytes.
     async fn write_to_stream<W: AsyncWrite + Unpin + Send>(
         &self,
         message: Self::Message,
         writer: &mut FramedWrite<W, Self::Codec>,
+        stats: &SendStats,
     ) -> Result<usize, <Self::Codec as Encoder<Self::Message>>::Error>;
 
     /// Applies the [`Writing`] protocol to a single connection.
@@ -170,13 +224,41 @@ trait WritingInternal: Writing {
 
 #[async_trait]
 impl<W: Writing> WritingInternal for W {
+    fn enqueue(
+        &self,
+        addr: SocketAddr,
+        queue: &OutboundQueue<WrappedMessage>,
+        msg: WrappedMessage,
+        is_droppable: bool,
+    ) -> io::Result<()> {
+        match queue.push(msg, is_droppable) {
+            Ok(None) => Ok(()),
+            Ok(Some(dropped)) => {
+                debug!(parent: self.tcp().span(), "the outbound queue for {} is full; dropped the oldest message", addr);
+                self.tcp().stats().register_failure();
+                let _ = dropped.delivery_notification.send(Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "the message was dropped from the full outbound queue",
+                )));
+                Ok(())
+            }
+            Err(_rejected) => {
+                error!(parent: self.tcp().span(), "can't send a message to {}: the outbound queue is full", addr);
+                self.tcp().stats().register_failure();
+                Err(io::ErrorKind::Other.into())
+            }
+        }
+    }
+
     async fn write_to_stream<A: AsyncWrite + Unpin + Send>(
         &self,
         message: Self::Message,
         writer: &mut FramedWrite<A, Self::Codec>,
+        stats: &SendStats,
     ) -> Result<usize, <Self::Codec as Encoder<Self::Message>>::Error> {
         writer.feed(message).await?;
         let len = writer.write_buffer().len();
+        stats.register_pending_bytes(len);
         writer.flush().await?;
 
         Ok(len)
@@ -192,10 +274,11 @@ impl<W: Writing> WritingInternal for W {
         let writer = conn.writer.take().expect("missing connection writer!");
         let mut framed = FramedWrite::new(writer, codec);
 
-        let (outbound_message_sender, mut outbound_message_receiver) = mpsc::channel(Self::MESSAGE_QUEUE_DEPTH);
+        let outbound_queue = Arc::new(OutboundQueue::new(Self::MESSAGE_QUEUE_DEPTH));
+        let send_stats = Arc::new(SendStats::default());
 
-        // register the connection's message sender with the Writing protocol handler
-        conn_senders.write().insert(addr, outbound_message_sender);
+        // register the connection's message queue with the Writing protocol handler
+        conn_senders.write().insert(addr, (outbound_queue.clone(), send_stats.clone()));
 
         // this will automatically drop the sender upon a disconnect
         let auto_cleanup = SenderCleanup { addr, senders: Arc::clone(conn_senders) };
@@ -213,15 +296,33 @@ impl<W: Writing> WritingInternal for W {
             // move the cleanup into the task that gets aborted on disconnect
             let _auto_cleanup = auto_cleanup;
 
-            while let Some(wrapped_msg) = outbound_message_receiver.recv().await {
+            // the bandwidth limit of the connection
+            let peer_bandwidth = node.config().max_peer_bandwidth.and_then(TokenBucket::new);
+
+            loop {
+                let wrapped_msg = outbound_queue.pop().await;
                 let msg = wrapped_msg.msg.downcast().unwrap();
 
-                match self_clone.write_to_stream(*msg, &mut framed).await {
+                send_stats.register_write_start(wrapped_msg.queued_at);
+                let result = self_clone.write_to_stream(*msg, &mut framed, &send_stats).await;
+                if let Some(duration) = send_stats.register_write_end(Self::WRITE_STALL_THRESHOLD) {
+                    warn!(parent: node.span(), "the write to {} stalled for {}ms", addr, duration.as_millis());
+                }
+
+                match result {
                     Ok(len) => {
                         let _ = wrapped_msg.delivery_notification.send(Ok(()));
                         node.known_peers().register_sent_message(addr, len);
                         node.stats().register_sent_message(len);
                         trace!(parent: node.span(), "sent {}B to {}", len, addr);
+                        // wait until the message is within the bandwidth limits, delaying further writes; the peer
+                        // isn't to blame for the messages dropped meanwhile, as it's our own limits holding them back
+                        let delay = throttle_delay([node.outbound_bandwidth(), peer_bandwidth.as_ref()], len);
+                        if !delay.is_zero() {
+                            outbound_queue.set_throttled(true);
+                            tokio::time::sleep(delay).await;
+                            outbound_queue.set_throttled(false);
+                        }
                     }
                     Err(e) => {
                         node.known_peers().register_failure(addr);
@@ -251,12 +352,13 @@ impl<W: Writing> WritingInternal for W {
 struct WrappedMessage {
     msg: Box<dyn Any + Send>,
     delivery_notification: oneshot::Sender<io::Result<()>>,
+    queued_at: Instant,
 }
 
 impl WrappedMessage {
     fn new(msg: Box<dyn Any + Send>) -> (Self, oneshot::Receiver<io::Result<()>>) {
         let (tx, rx) = oneshot::channel();
-        let wrapped_msg = Self { msg, delivery_notification: tx };
+        let wrapped_msg = Self { msg, delivery_notification: tx, queued_at: Instant::now() };
 
         (wrapped_msg, rx)
     }
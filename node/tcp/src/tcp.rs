@@ -16,7 +16,7 @@ use std::{
     collections::HashSet,
     fmt,
     io,
-    net::{IpAddr, SocketAddr},
+    net::{IpAddr, Ipv6Addr, SocketAddr},
     ops::Deref,
     sync::{
         atomic::{AtomicUsize, Ordering::*},
@@ -27,6 +27,7 @@ use std::{
 
 use once_cell::sync::OnceCell;
 use parking_lot::Mutex;
+use socket2::{Domain, Socket, Type};
 use tokio::{
     io::split,
     net::{TcpListener, TcpStream},
@@ -37,12 +38,17 @@ use tokio::{
 use tracing::*;
 
 use crate::{
+    canonical_addr,
     connections::{Connection, ConnectionSide, Connections},
     protocols::{Protocol, Protocols},
     Config,
     KnownPeers,
     Stats,
+    Stream,
+    TokenBucket,
 };
+#[cfg(feature = "test")]
+use crate::{MemoryListener, MemoryNetwork};
 
 // A sequential numeric identifier assigned to `Tcp`s that were not provided with a name.
 static SEQUENTIAL_NODE_ID: AtomicUsize = AtomicUsize::new(0);
@@ -77,8 +83,15 @@ pub struct InnerTcp {
     known_peers: KnownPeers,
     /// Collects statistics related to the node itself.
     stats: Stats,
+    /// Limits the bandwidth of all the inbound messages combined.
+    inbound_bandwidth: Option<TokenBucket>,
+    /// Limits the bandwidth of all the outbound messages combined.
+    outbound_bandwidth: Option<TokenBucket>,
     /// The node's tasks.
     pub(crate) tasks: Mutex<Vec<JoinHandle<()>>>,
+    /// The in-process network the node is connected to instead of the operating system's, if one is set.
+    #[cfg(feature = "test")]
+    memory_network: OnceCell<MemoryNetwork>,
 }
 
 impl Tcp {
@@ -92,6 +105,10 @@ impl Tcp {
         // Create a tracing span containing the node's name.
         let span = crate::helpers::create_span(config.name.as_deref().unwrap());
 
+        // Initialize the global bandwidth limits.
+        let inbound_bandwidth = config.max_bandwidth.and_then(TokenBucket::new);
+        let outbound_bandwidth = config.max_bandwidth.and_then(TokenBucket::new);
+
         // Initialize the Tcp stack.
         let tcp = Tcp(Arc::new(InnerTcp {
             span,
@@ -102,7 +119,11 @@ impl Tcp {
             connections: Default::default(),
             known_peers: Default::default(),
             stats: Default::default(),
+            inbound_bandwidth,
+            outbound_bandwidth,
             tasks: Default::default(),
+            #[cfg(feature = "test")]
+            memory_network: Default::default(),
         }));
 
         debug!(parent: tcp.span(), "The node is ready");
@@ -123,6 +144,18 @@ impl Tcp {
         &self.config
     }
 
+    /// Returns the token bucket limiting the bandwidth of all the inbound messages combined, if one is configured.
+    #[inline]
+    pub(crate) fn inbound_bandwidth(&self) -> Option<&TokenBucket> {
+        self.inbound_bandwidth.as_ref()
+    }
+
+    /// Returns the token bucket limiting the bandwidth of all the outbound messages combined, if one is configured.
+    #[inline]
+    pub(crate) fn outbound_bandwidth(&self) -> Option<&TokenBucket> {
+        self.outbound_bandwidth.as_ref()
+    }
+
     /// Returns the listening address; returns an error if Tcp was not configured
     /// to listen for inbound connections.
     pub fn listening_addr(&self) -> io::Result<SocketAddr> {
@@ -225,21 +258,30 @@ impl Tcp {
             return Err(io::ErrorKind::AlreadyExists.into());
         }
 
-        let stream =
-            match timeout(Duration::from_millis(self.config().connection_timeout_ms.into()), TcpStream::connect(addr))
-                .await
-            {
-                Ok(Ok(stream)) => Ok(stream),
-                Ok(err) => {
-                    self.connecting.lock().remove(&addr);
-                    err
-                }
-                Err(err) => {
-                    self.connecting.lock().remove(&addr);
-                    error!("connection timeout error: {}", err);
-                    Err(io::ErrorKind::TimedOut.into())
-                }
-            }?;
+        // Connect through the proxy, if one is set.
+        let connect = async {
+            // Connect through the in-process network, if one is set.
+            #[cfg(feature = "test")]
+            if let Some(network) = self.memory_network.get() {
+                return network.connect(self.own_ip(), addr);
+            }
+            match &self.config().proxy {
+                Some(proxy) => proxy.connect(addr).await.map(Stream::from),
+                None => TcpStream::connect(addr).await.map(Stream::from),
+            }
+        };
+        let stream = match timeout(Duration::from_millis(self.config().connection_timeout_ms.into()), connect).await {
+            Ok(Ok(stream)) => Ok(stream),
+            Ok(err) => {
+                self.connecting.lock().remove(&addr);
+                err
+            }
+            Err(err) => {
+                self.connecting.lock().remove(&addr);
+                error!("connection timeout error: {}", err);
+                Err(io::ErrorKind::TimedOut.into())
+            }
+        }?;
 
         let ret = self.adapt_stream(stream, addr, ConnectionSide::Initiator).await;
 
@@ -295,6 +337,12 @@ impl Tcp {
         let listener_ip =
             self.config().listener_ip.expect("Tcp::enable_listener was called, but Config::listener_ip is not set");
 
+        // Listen on the in-process network, if one is set.
+        #[cfg(feature = "test")]
+        if let Some(network) = self.memory_network.get() {
+            return self.enable_memory_listener(network, listener_ip).await;
+        }
+
         // Initialize the TCP listener.
         let listener = self.create_listener(listener_ip).await?;
 
@@ -305,6 +353,39 @@ impl Tcp {
         let listening_addr = (listener_ip, port).into();
         self.listening_addr.set(listening_addr).expect("The node's listener was started more than once");
 
+        // Accept the inbound connections.
+        self.spawn_listening_task(listener).await;
+        debug!(parent: self.span(), "Listening on {listening_addr}");
+
+        Ok(listening_addr)
+    }
+
+    /// Starts an additional listener on the given address, which accepts connections like the main listener does.
+    /// The connections of the listeners can be told apart by their local address (see [`Connection::local_addr`]).
+    /// Returns the listening address, whose port is assigned randomly if the given port is `0`.
+    pub async fn enable_secondary_listener(&self, addr: SocketAddr) -> io::Result<SocketAddr> {
+        // Listen on the in-process network, if one is set.
+        #[cfg(feature = "test")]
+        if let Some(network) = self.memory_network.get() {
+            let (listening_addr, listener) = network.bind(addr)?;
+            self.spawn_memory_listening_task(listener).await;
+            debug!(parent: self.span(), "Listening on {listening_addr} (secondary listener, in-process network)");
+            return Ok(listening_addr);
+        }
+
+        // Initialize the TCP listener.
+        let listener = bind_listener(addr).await?;
+        let listening_addr = listener.local_addr()?;
+
+        // Accept the inbound connections.
+        self.spawn_listening_task(listener).await;
+        debug!(parent: self.span(), "Listening on {listening_addr} (secondary listener)");
+
+        Ok(listening_addr)
+    }
+
+    /// Spawns the task accepting the inbound connections of the given listener.
+    async fn spawn_listening_task(&self, listener: TcpListener) {
         // Use a channel to know when the listening task is ready.
         let (tx, rx) = oneshot::channel();
 
@@ -316,18 +397,63 @@ impl Tcp {
             loop {
                 // Await for a new connection.
                 match listener.accept().await {
-                    Ok((stream, addr)) => tcp.handle_connection(stream, addr),
+                    // A dual-stack listener reports the IPv4 peers with IPv4-mapped IPv6 addresses.
+                    Ok((stream, addr)) => tcp.handle_connection(stream.into(), canonical_addr(addr)),
                     Err(e) => error!(parent: tcp.span(), "Failed to accept a connection: {e}"),
                 }
             }
         });
         self.tasks.lock().push(listening_task);
         let _ = rx.await;
-        debug!(parent: self.span(), "Listening on {listening_addr}");
+    }
+
+    /// Connects the node to the given in-process network instead of the operating system's, so that the connections
+    /// of a simulation are reproducible. It must be called before the listener is enabled.
+    #[cfg(feature = "test")]
+    pub fn enable_memory_network(&self, network: MemoryNetwork) {
+        if self.memory_network.set(network).is_err() {
+            warn!(parent: self.span(), "The node was connected to an in-process network more than once");
+        }
+    }
+
+    /// Returns the IP address the node connects from.
+    #[cfg(feature = "test")]
+    fn own_ip(&self) -> IpAddr {
+        self.listening_addr().map(|addr| addr.ip()).ok().or(self.config().listener_ip).unwrap_or([127, 0, 0, 1].into())
+    }
+
+    /// Listens for incoming connections on the given in-process network.
+    #[cfg(feature = "test")]
+    async fn enable_memory_listener(&self, network: &MemoryNetwork, listener_ip: IpAddr) -> io::Result<SocketAddr> {
+        // Bind to the desired port, if one is set, or to the next available port otherwise.
+        let desired_port = self.config().desired_listening_port.unwrap_or(0);
+        let (listening_addr, listener) = match network.bind(SocketAddr::new(listener_ip, desired_port)) {
+            Ok(bound) => bound,
+            Err(_) if self.config().allow_random_port => network.bind(SocketAddr::new(listener_ip, 0))?,
+            Err(e) => return Err(e),
+        };
+        self.listening_addr.set(listening_addr).expect("The node's listener was started more than once");
+
+        // Accept the inbound connections.
+        self.spawn_memory_listening_task(listener).await;
+        debug!(parent: self.span(), "Listening on {listening_addr} (in-process network)");
 
         Ok(listening_addr)
     }
 
+    /// Spawns the task accepting the inbound connections of the given in-process listener.
+    #[cfg(feature = "test")]
+    async fn spawn_memory_listening_task(&self, mut listener: MemoryListener) {
+        let tcp = self.clone();
+        let listening_task = tokio::spawn(async move {
+            trace!(parent: tcp.span(), "Spawned the listening task");
+            while let Some((stream, addr)) = listener.recv().await {
+                tcp.handle_connection(stream, addr);
+            }
+        });
+        self.tasks.lock().push(listening_task);
+    }
+
     /// Creates an instance of `TcpListener` based on the node's configuration.
     async fn create_listener(&self, listener_ip: IpAddr) -> io::Result<TcpListener> {
         debug!("Creating a TCP listener on {listener_ip}...");
@@ -335,7 +461,7 @@ impl Tcp {
             // Construct the desired listening IP address.
             let desired_listening_addr = SocketAddr::new(listener_ip, port);
             // If a desired listening port is set, try to bind to it.
-            match TcpListener::bind(desired_listening_addr).await {
+            match bind_listener(desired_listening_addr).await {
                 Ok(listener) => listener,
                 Err(e) => {
                     if self.config().allow_random_port {
@@ -344,7 +470,7 @@ impl Tcp {
                             "Trying any listening port, as the desired port is unavailable: {e}"
                         );
                         let random_available_addr = SocketAddr::new(listener_ip, 0);
-                        TcpListener::bind(random_available_addr).await?
+                        bind_listener(random_available_addr).await?
                     } else {
                         error!(parent: self.span(), "The desired listening port is unavailable: {e}");
                         return Err(e);
@@ -353,7 +479,7 @@ impl Tcp {
             }
         } else if self.config().allow_random_port {
             let random_available_addr = SocketAddr::new(listener_ip, 0);
-            TcpListener::bind(random_available_addr).await?
+            bind_listener(random_available_addr).await?
         } else {
             panic!("As 'listener_ip' is set, either 'desired_listening_port' or 'allow_random_port' must be set");
         };
@@ -362,10 +488,10 @@ impl Tcp {
     }
 
     /// Handles a new inbound connection.
-    fn handle_connection(&self, stream: TcpStream, addr: SocketAddr) {
+    fn handle_connection(&self, stream: Stream, addr: SocketAddr) {
         debug!(parent: self.span(), "Received a connection from {addr}");
 
-        if !self.can_add_connection() || self.is_self_connect(addr) {
+        if !self.can_add_connection() || !self.can_add_pending_connection(addr) || self.is_self_connect(addr) {
             debug!(parent: self.span(), "Rejecting the connection from {addr}");
             return;
         }
@@ -414,8 +540,26 @@ impl Tcp {
         }
     }
 
+    /// Checks whether the `Tcp` can handle an additional pending inbound connection from the given address.
+    fn can_add_pending_connection(&self, addr: SocketAddr) -> bool {
+        let connecting = self.connecting.lock();
+
+        if connecting.len() >= self.config.max_pending_connections as usize {
+            warn!(parent: self.span(), "Maximum number of pending connections ({}) reached", connecting.len());
+            return false;
+        }
+        if !addr.ip().is_loopback() {
+            let num_pending = connecting.iter().filter(|pending_addr| pending_addr.ip() == addr.ip()).count();
+            if num_pending >= self.config.max_pending_connections_per_ip as usize {
+                warn!(parent: self.span(), "Maximum number of pending connections from {} reached", addr.ip());
+                return false;
+            }
+        }
+        true
+    }
+
     /// Prepares the freshly acquired connection to handle the protocols the Tcp implements.
-    async fn adapt_stream(&self, stream: TcpStream, peer_addr: SocketAddr, own_side: ConnectionSide) -> io::Result<()> {
+    async fn adapt_stream(&self, stream: Stream, peer_addr: SocketAddr, own_side: ConnectionSide) -> io::Result<()> {
         self.known_peers.add(peer_addr);
 
         // Register the port seen by the peer.
@@ -499,6 +643,24 @@ impl fmt::Debug for Tcp {
     }
 }
 
+/// Binds a listener to the given address. A listener on the unspecified IPv6 address is dual-stack, i.e. it accepts
+/// the IPv4 connections as well, regardless of the default of the platform.
+async fn bind_listener(addr: SocketAddr) -> io::Result<TcpListener> {
+    if addr.ip() != IpAddr::V6(Ipv6Addr::UNSPECIFIED) {
+        return TcpListener::bind(addr).await;
+    }
+
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(socket2::Protocol::TCP))?;
+    socket.set_only_v6(false)?;
+    // Allow the port to be rebound while the connections of a previous listener are lingering, like tokio does.
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    TcpListener::from_std(socket.into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -586,6 +748,58 @@ mod tests {
         assert!(!tcp.is_connecting(peer_ip));
     }
 
+    #[tokio::test]
+    async fn test_secondary_listener() {
+        let tcp = Tcp::new(Config::default());
+        let _node_ip = tcp.enable_listener().await.unwrap();
+
+        // Initialize the peer, with a secondary listener.
+        let peer = Tcp::new(Config {
+            listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            desired_listening_port: Some(0),
+            max_connections: 2,
+            ..Default::default()
+        });
+        let peer_ip = peer.enable_listener().await.unwrap();
+        let secondary_ip = peer.enable_secondary_listener(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).await.unwrap();
+        assert_ne!(peer_ip.port(), secondary_ip.port());
+
+        // Connect to the peer through the secondary listener.
+        tcp.connect(secondary_ip).await.unwrap();
+        assert!(tcp.is_connected(secondary_ip));
+        assert_eq!(tcp.num_connected(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dual_stack_listener() {
+        let tcp = Tcp::new(Config::default());
+        let _node_ip = tcp.enable_listener().await.unwrap();
+
+        // Initialize the peer, with a dual-stack listener.
+        let peer = Tcp::new(Config {
+            listener_ip: Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+            desired_listening_port: Some(0),
+            max_connections: 1,
+            ..Default::default()
+        });
+        // Skip the test if the host does not support IPv6.
+        let Ok(peer_ip) = peer.enable_listener().await else {
+            return;
+        };
+
+        // Connect to the peer over IPv4.
+        tcp.connect(SocketAddr::from((Ipv4Addr::LOCALHOST, peer_ip.port()))).await.unwrap();
+        // Wait for the peer to accept the connection.
+        let is_accepted = async {
+            while peer.num_connected() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        timeout(Duration::from_secs(1), is_accepted).await.unwrap();
+        // Check that the peer sees the IPv4 address, rather than the IPv4-mapped IPv6 address.
+        assert!(peer.connected_addrs().iter().all(|addr| addr.is_ipv4()));
+    }
+
     #[tokio::test]
     async fn test_can_add_connection() {
         let tcp = Tcp::new(Config { max_connections: 1, ..Default::default() });
@@ -603,7 +817,7 @@ mod tests {
 
         // Simulate an active connection.
         let stream = TcpStream::connect(peer_ip).await.unwrap();
-        tcp.connections.add(Connection::new(peer_ip, stream, ConnectionSide::Initiator));
+        tcp.connections.add(Connection::new(peer_ip, stream.into(), ConnectionSide::Initiator));
         assert!(!tcp.can_add_connection());
 
         // Remove the active connection.
@@ -620,7 +834,7 @@ mod tests {
 
         // Simulate an active and a pending connection (this case should never occur).
         let stream = TcpStream::connect(peer_ip).await.unwrap();
-        tcp.connections.add(Connection::new(peer_ip, stream, ConnectionSide::Responder));
+        tcp.connections.add(Connection::new(peer_ip, stream.into(), ConnectionSide::Responder));
         tcp.connecting.lock().insert(peer_ip);
         assert!(!tcp.can_add_connection());
 
@@ -630,6 +844,31 @@ mod tests {
         assert!(tcp.can_add_connection());
     }
 
+    #[tokio::test]
+    async fn test_can_add_pending_connection() {
+        let tcp =
+            Tcp::new(Config { max_pending_connections: 3, max_pending_connections_per_ip: 2, ..Default::default() });
+        let addr = |ip: [u8; 4], port: u16| SocketAddr::from((ip, port));
+
+        // Simulate pending connections from a single IP, up to the limit per IP.
+        tcp.connecting.lock().insert(addr([1, 2, 3, 4], 1));
+        assert!(tcp.can_add_pending_connection(addr([1, 2, 3, 4], 2)));
+        tcp.connecting.lock().insert(addr([1, 2, 3, 4], 2));
+        assert!(!tcp.can_add_pending_connection(addr([1, 2, 3, 4], 3)));
+
+        // Ensure other IPs can still connect, as well as loopback addresses.
+        assert!(tcp.can_add_pending_connection(addr([5, 6, 7, 8], 1)));
+        tcp.connecting.lock().insert(addr([127, 0, 0, 1], 1));
+        tcp.connecting.lock().remove(&addr([1, 2, 3, 4], 2));
+        tcp.connecting.lock().insert(addr([127, 0, 0, 1], 2));
+        assert!(!tcp.can_add_pending_connection(addr([127, 0, 0, 1], 3)));
+
+        // Ensure the total number of pending connections is capped.
+        assert!(!tcp.can_add_pending_connection(addr([5, 6, 7, 8], 1)));
+        tcp.connecting.lock().remove(&addr([1, 2, 3, 4], 1));
+        assert!(tcp.can_add_pending_connection(addr([127, 0, 0, 1], 3)));
+    }
+
     #[tokio::test]
     async fn test_handle_connection() {
         let tcp = Tcp::new(Config {
@@ -649,7 +888,7 @@ mod tests {
 
         // Simulate an active connection.
         let stream = TcpStream::connect(peer1_ip).await.unwrap();
-        tcp.connections.add(Connection::new(peer1_ip, stream, ConnectionSide::Responder));
+        tcp.connections.add(Connection::new(peer1_ip, stream.into(), ConnectionSide::Responder));
         assert!(!tcp.can_add_connection());
         assert_eq!(tcp.num_connected(), 1);
         assert_eq!(tcp.num_connecting(), 0);
@@ -667,7 +906,7 @@ mod tests {
 
         // Handle the connection.
         let stream = TcpStream::connect(peer2_ip).await.unwrap();
-        tcp.handle_connection(stream, peer2_ip);
+        tcp.handle_connection(stream.into(), peer2_ip);
         assert!(!tcp.can_add_connection());
         assert_eq!(tcp.num_connected(), 1);
         assert_eq!(tcp.num_connecting(), 0);
@@ -699,7 +938,7 @@ mod tests {
 
         // Simulate a new connection.
         let stream = TcpStream::connect(peer_ip).await.unwrap();
-        tcp.adapt_stream(stream, peer_ip, ConnectionSide::Responder).await.unwrap();
+        tcp.adapt_stream(stream.into(), peer_ip, ConnectionSide::Responder).await.unwrap();
         assert_eq!(tcp.num_connected(), 1);
         assert_eq!(tcp.num_connecting(), 0);
         assert!(tcp.is_connected(peer_ip));
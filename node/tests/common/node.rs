@@ -14,52 +14,45 @@
 
 use crate::common::test_peer::sample_genesis_block;
 use snarkos_account::Account;
-use snarkos_node::{Client, Prover, Validator};
+use snarkos_node::{router::RouterConfig, Client, NodeConfig, Prover, Relay, Validator};
 use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, Testnet3 as CurrentNetwork};
 
 use aleo_std::StorageMode;
 use std::str::FromStr;
 
+/// Returns a fixed account.
+fn sample_account() -> Account<CurrentNetwork> {
+    Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap()
+}
+
+/// Returns the configuration of a node, which listens on a random port, without a REST server.
+fn sample_node_config() -> NodeConfig {
+    NodeConfig {
+        router: RouterConfig { node_ip: "127.0.0.1:0".parse().unwrap(), ..Default::default() },
+        ..Default::default()
+    }
+}
+
 pub async fn client() -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
-    Client::new(
-        "127.0.0.1:0".parse().unwrap(),
-        None,
-        10,
-        Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
-        &[],
-        sample_genesis_block(),
-        None, // No CDN.
-        StorageMode::Production,
-    )
-    .await
-    .expect("couldn't create client instance")
+    Client::new(sample_account(), sample_genesis_block(), StorageMode::Production, sample_node_config())
+        .await
+        .expect("couldn't create client instance")
 }
 
 pub async fn prover() -> Prover<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
-    Prover::new(
-        "127.0.0.1:0".parse().unwrap(),
-        Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
-        &[],
-        sample_genesis_block(),
-        StorageMode::Production,
-    )
-    .await
-    .expect("couldn't create prover instance")
+    Prover::new(sample_account(), sample_genesis_block(), StorageMode::Production, sample_node_config())
+        .await
+        .expect("couldn't create prover instance")
+}
+
+pub async fn relay() -> Relay<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Relay::new(sample_account(), sample_genesis_block(), StorageMode::Production, sample_node_config())
+        .await
+        .expect("couldn't create relay instance")
 }
 
 pub async fn validator() -> Validator<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
-    Validator::new(
-        "127.0.0.1:0".parse().unwrap(),
-        None,
-        None,
-        10,
-        Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
-        &[],
-        &[],
-        sample_genesis_block(), // Should load the current network's genesis block.
-        None,                   // No CDN.
-        StorageMode::Production,
-    )
-    .await
-    .expect("couldn't create validator instance")
+    Validator::new(sample_account(), sample_genesis_block(), StorageMode::Production, sample_node_config())
+        .await
+        .expect("couldn't create validator instance")
 }
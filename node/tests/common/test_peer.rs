@@ -58,6 +58,7 @@ pub struct TestPeer {
     node: Node,
     node_type: NodeType,
     account: Account<CurrentNetwork>,
+    features: u32,
 }
 
 impl Pea2Pea for TestPeer {
@@ -80,6 +81,11 @@ impl TestPeer {
     }
 
     pub async fn new(node_type: NodeType, account: Account<CurrentNetwork>) -> Self {
+        Self::with_features(node_type, account, 0).await
+    }
+
+    /// Initializes a test peer which advertises the given features in its handshake.
+    pub async fn with_features(node_type: NodeType, account: Account<CurrentNetwork>, features: u32) -> Self {
         let peer = Self {
             node: Node::new(Config {
                 listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
@@ -88,6 +94,7 @@ impl TestPeer {
             }),
             node_type,
             account,
+            features,
         };
 
         peer.enable_handshake().await;
@@ -132,7 +139,14 @@ impl Handshake for TestPeer {
         match node_side {
             ConnectionSide::Initiator => {
                 // Send a challenge request to the peer.
-                let our_request = ChallengeRequest::new(local_ip.port(), self.node_type(), self.address(), rng.gen());
+                let our_request = ChallengeRequest::new(
+                    local_ip.port(),
+                    self.node_type(),
+                    self.address(),
+                    rng.gen(),
+                    self.features,
+                    None,
+                );
                 framed.send(Message::ChallengeRequest(our_request)).await?;
 
                 // Receive the peer's challenge bundle.
@@ -143,7 +157,8 @@ impl Handshake for TestPeer {
                 let signature = self.account().sign_bytes(&peer_request.nonce.to_le_bytes(), rng).unwrap();
 
                 // Send the challenge response.
-                let our_response = ChallengeResponse { genesis_header, signature: Data::Object(signature) };
+                let our_response =
+                    ChallengeResponse { genesis_header, signature: Data::Object(signature), node_signature: None };
                 framed.send(Message::ChallengeResponse(our_response)).await?;
             }
             ConnectionSide::Responder => {
@@ -154,9 +169,17 @@ impl Handshake for TestPeer {
                 let signature = self.account().sign_bytes(&peer_request.nonce.to_le_bytes(), rng).unwrap();
 
                 // Send our challenge bundle.
-                let our_response = ChallengeResponse { genesis_header, signature: Data::Object(signature) };
+                let our_response =
+                    ChallengeResponse { genesis_header, signature: Data::Object(signature), node_signature: None };
                 framed.send(Message::ChallengeResponse(our_response)).await?;
-                let our_request = ChallengeRequest::new(local_ip.port(), self.node_type(), self.address(), rng.gen());
+                let our_request = ChallengeRequest::new(
+                    local_ip.port(),
+                    self.node_type(),
+                    self.address(),
+                    rng.gen(),
+                    self.features,
+                    None,
+                );
                 framed.send(Message::ChallengeRequest(our_request)).await?;
 
                 // Listen for the challenge response.
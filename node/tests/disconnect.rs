@@ -128,6 +128,22 @@ mod prover {
     }
 }
 
+mod relay {
+    // Full node disconnects from synthetic peer.
+    test_disconnect! {
+        relay |> client,
+        relay |> validator,
+        relay |> prover
+    }
+
+    // Synthetic peer disconnects from the full node.
+    test_disconnect! {
+        relay <| client,
+        relay <| validator,
+        relay <| prover
+    }
+}
+
 mod validator {
     // Full node disconnects from synthetic peer.
     test_disconnect! {
@@ -18,7 +18,7 @@
 mod common;
 use common::{node::*, test_peer::TestPeer};
 
-use snarkos_node::{Client, Prover, Validator};
+use snarkos_node::{Client, Prover, Relay, Validator};
 use snarkos_node_router::Outbound;
 use snarkos_node_tcp::P2P;
 use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, Testnet3 as CurrentNetwork};
@@ -57,7 +57,7 @@ macro_rules! impl_connect {
     };
 }
 
-impl_connect!(Client, Prover, Validator);
+impl_connect!(Client, Prover, Relay, Validator);
 
 // Implement the `Connect` trait for the test peer.
 #[async_trait::async_trait]
@@ -166,6 +166,22 @@ mod prover {
     }
 }
 
+mod relay {
+    // Initiator side (full node connects to synthetic peer).
+    test_handshake! {
+        relay -> client,
+        relay -> validator,
+        relay -> prover
+    }
+
+    // Responder side (synthetic peer connects to full node).
+    test_handshake! {
+        relay <- client,
+        relay <- validator,
+        relay <- prover
+    }
+}
+
 mod validator {
     // Initiator side (full node connects to synthetic peer).
     test_handshake! {
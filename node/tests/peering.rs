@@ -79,4 +79,4 @@ macro_rules! test_reject_unsolicited_peer_response {
     };
 }
 
-test_reject_unsolicited_peer_response!(client, prover, validator);
+test_reject_unsolicited_peer_response!(client, prover, relay, validator);
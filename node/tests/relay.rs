@@ -0,0 +1,128 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{node::relay, sample_account, sample_genesis_block, test_peer::TestPeer};
+
+use snarkos_node::Relay;
+use snarkos_node_router::{
+    messages::{BlockAnnouncement, BlockHeader, ChallengeRequest, Message, NodeType},
+    Outbound,
+};
+use snarkvm::prelude::{
+    block::{Header, Metadata},
+    store::helpers::memory::ConsensusMemory,
+    Network,
+    Testnet3 as CurrentNetwork,
+};
+
+use deadline::deadline;
+use pea2pea::{protocols::Writing, Pea2Pea};
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Returns a chain of block headers of the given length, which is linked to the genesis block,
+/// but which was never produced by the validators; the chains of distinct seeds conflict with one another.
+fn forged_header_chain(length: u32, seed: i64) -> Vec<BlockHeader<CurrentNetwork>> {
+    let genesis = sample_genesis_block();
+    let mut previous = BlockHeader::from_block(&genesis);
+    let genesis = *genesis.header();
+
+    let mut headers = Vec::new();
+    for height in 1..=length {
+        let metadata = Metadata::new(
+            CurrentNetwork::ID,
+            height as u64,
+            height,
+            0,
+            0,
+            genesis.coinbase_target(),
+            genesis.proof_target(),
+            genesis.last_coinbase_target(),
+            genesis.last_coinbase_timestamp(),
+            genesis.timestamp() + seed + height as i64,
+        )
+        .unwrap();
+        let header = Header::from(
+            genesis.previous_state_root(),
+            genesis.transactions_root(),
+            genesis.finalize_root(),
+            genesis.ratifications_root(),
+            genesis.solutions_root(),
+            genesis.subdag_root(),
+            metadata,
+        )
+        .unwrap();
+        previous = BlockHeader::new(previous.hash(), header).unwrap();
+        headers.push(previous.clone());
+    }
+    headers
+}
+
+/// Spins up a test peer which supports the block announcements, and connects the relay to it.
+async fn connected_peer(relay: &Relay<CurrentNetwork, ConsensusMemory<CurrentNetwork>>, trusted: bool) -> TestPeer {
+    let features = ChallengeRequest::<CurrentNetwork>::FEATURE_HEADERS_FIRST;
+    let peer = TestPeer::with_features(NodeType::Client, sample_account(), features).await;
+    let peer_addr = peer.node().listening_addr().unwrap();
+    match trusted {
+        true => relay.router().connect_trusted(peer_addr).await.unwrap(),
+        false => relay.router().connect(peer_addr).unwrap().await.unwrap(),
+    }
+    let peer_clone = peer.clone();
+    deadline!(Duration::from_secs(5), move || peer_clone.node().num_connected() == 1);
+    peer
+}
+
+/// Announces the given block headers from the given test peer.
+fn announce(peer: &TestPeer, headers: &[BlockHeader<CurrentNetwork>]) {
+    let addr = *peer.node().connected_addrs().first().unwrap();
+    for block_header in headers {
+        let message = Message::BlockAnnouncement(BlockAnnouncement { block_header: block_header.clone() });
+        assert!(peer.unicast(addr, message).is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_relay_ignores_forged_header_chain() {
+    let relay = relay().await;
+
+    // A single peer announces a forged chain of block headers, which is linked to the genesis block.
+    let forger = connected_peer(&relay, false).await;
+    announce(&forger, &forged_header_chain(3, 0));
+    // Another peer announces a conflicting block header at the same height.
+    let other = connected_peer(&relay, false).await;
+    announce(&other, &forged_header_chain(1, 1));
+    sleep(Duration::from_secs(1)).await;
+
+    // The forged block headers are not confirmed by other subnets, so the puzzle is still derived from genesis.
+    assert_eq!(relay.latest_height(), 0);
+    assert_eq!(relay.recent_headers().len(), 1);
+    // The peers announcing the conflicting block headers are not disconnected, as neither block is accepted.
+    assert_eq!(relay.router().number_of_connected_peers(), 2);
+}
+
+#[tokio::test]
+async fn test_relay_accepts_header_chain_of_trusted_peer() {
+    let relay = relay().await;
+
+    // A trusted peer vouches for the block headers on its own.
+    let peer = connected_peer(&relay, true).await;
+    let headers = forged_header_chain(3, 0);
+    announce(&peer, &headers);
+
+    let relay_clone = relay.clone();
+    deadline!(Duration::from_secs(5), move || relay_clone.latest_height() == 3);
+    assert_eq!(relay.recent_headers().last(), headers.last());
+}